@@ -1,13 +1,21 @@
 //! 性能基准测试
 use criterion::{Criterion, criterion_group, criterion_main};
+use nanolog_rs::allocation::{self, CountingAllocator};
 use nanolog_rs::{
-    AsyncLogger, DefaultFormatter, Formatter, Level, MemorySink, Record,
+    AsyncLogger, AsyncLoggerBuilder, DefaultFormatter, Formatter, JsonFormatter, Level, MemorySink,
+    NullSink, Record, SimpleFormatter,
     buffer::{BufferPool, ByteBuffer},
+    escape::escape_json_into,
+    replay::{Pacing, Workload, WorkloadCapture},
 };
 use std::hint::black_box;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+/// 统计调用线程的分配次数，报告每次日志调用的分配数
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator::system();
+
 /// 测试ByteBuffer的性能
 fn bench_byte_buffer(c: &mut Criterion) {
     let mut group = c.benchmark_group("byte_buffer");
@@ -153,6 +161,70 @@ fn bench_logging(c: &mut Criterion) {
     group.finish();
 }
 
+/// 报告每次日志调用在调用线程上的分配数（消息由调用方预先构造时应为零）
+fn bench_allocations(_c: &mut Criterion) {
+    const CALLS: u64 = 10_000;
+    let logger = AsyncLogger::new(
+        Level::Info,
+        Arc::new(DefaultFormatter::new()),
+        Arc::new(MemorySink::new()),
+        1 << 15,
+        100,
+        Duration::from_millis(10),
+    );
+    let records: Vec<Record> = (0..CALLS)
+        .map(|i| {
+            Record::new(
+                Level::Info,
+                "benchmark",
+                file!(),
+                line!(),
+                format!("m {}", i),
+            )
+        })
+        .collect();
+    let (_, log_only) = allocation::measure(|| {
+        for record in records {
+            let _ = logger.log(record);
+        }
+    });
+    let (_, with_message) = allocation::measure(|| {
+        for i in 0..CALLS {
+            let record = Record::new(
+                Level::Info,
+                "benchmark",
+                file!(),
+                line!(),
+                format!("m {}", i),
+            );
+            let _ = logger.log(record);
+        }
+    });
+    let (_, disabled) = allocation::measure(|| {
+        for i in 0..CALLS {
+            if logger.should_log(Level::Debug) {
+                let record = Record::new(
+                    Level::Debug,
+                    "benchmark",
+                    file!(),
+                    line!(),
+                    format!("m {}", i),
+                );
+                let _ = logger.log(record);
+            }
+        }
+    });
+    let _ = logger.shutdown();
+
+    println!("allocations per log call:");
+    println!("  log (prebuilt record)   {:.2}", log_only.per_op(CALLS));
+    println!(
+        "  log (formatted message) {:.2}",
+        with_message.per_op(CALLS)
+    );
+    println!("  disabled level          {:.2}", disabled.per_op(CALLS));
+}
+
 /// 测试格式化性能
 fn bench_formatting(c: &mut Criterion) {
     let mut group = c.benchmark_group("formatting");
@@ -327,13 +399,176 @@ fn bench_publish_mutex_vs_concurrent(c: &mut Criterion) {
 
     group.finish();
 }
+
+/// 对比 JSON 转义：逐字符替换与批量扫描
+fn bench_json_escape(c: &mut Criterion) {
+    let mut group = c.benchmark_group("json_escape");
+    group.measurement_time(Duration::from_secs(5));
+    group.sample_size(100);
+
+    let inputs = [
+        (
+            "clean",
+            "order accepted id=42 px=101.25 qty=300 venue=XSHG ".repeat(4),
+        ),
+        ("sparse", "user said \"hello\" from C:\\path\n".repeat(4)),
+    ];
+    for (name, input) in &inputs {
+        group.bench_function(format!("per_char_{}", name), |b| {
+            b.iter(|| {
+                let mut out = String::with_capacity(input.len());
+                for ch in black_box(input.as_str()).chars() {
+                    match ch {
+                        '"' => out.push_str("\\\""),
+                        '\\' => out.push_str("\\\\"),
+                        '\n' => out.push_str("\\n"),
+                        c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+                        c => out.push(c),
+                    }
+                }
+                black_box(out);
+            });
+        });
+
+        group.bench_function(format!("swar_{}", name), |b| {
+            let mut out = Vec::with_capacity(input.len() * 2);
+            b.iter(|| {
+                out.clear();
+                escape_json_into(&mut out, black_box(input.as_str()));
+                black_box(&out);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// 对比多路输出：逐路复制记录并分配新缓冲区，与借用记录并复用缓冲区
+fn bench_fan_out(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fan_out");
+    group.measurement_time(Duration::from_secs(5));
+    group.sample_size(100);
+
+    let formatters: Vec<Arc<dyn Formatter>> = vec![
+        Arc::new(DefaultFormatter::new()),
+        Arc::new(JsonFormatter::new()),
+        Arc::new(SimpleFormatter::new()),
+    ];
+    let record = Record::new(
+        Level::Info,
+        "benchmark",
+        file!(),
+        line!(),
+        "order accepted id=42 px=101.25 qty=300 venue=XSHG".to_string(),
+    )
+    .with_field("user_id", 42u64);
+
+    for sinks in [2, 3] {
+        let formatters = &formatters[..sinks];
+        group.bench_function(format!("clone_and_allocate_{}_sinks", sinks), |b| {
+            b.iter(|| {
+                for formatter in formatters {
+                    let owned = black_box(&record).clone();
+                    let _ = black_box(formatter.format(&owned));
+                }
+            });
+        });
+
+        group.bench_function(format!("borrow_pooled_{}_sinks", sinks), |b| {
+            let mut buffers: Vec<Vec<u8>> = vec![Vec::new(); sinks];
+            b.iter(|| {
+                for (formatter, buf) in formatters.iter().zip(&mut buffers) {
+                    buf.clear();
+                    let _ = formatter.format_into(black_box(&record), buf);
+                    black_box(&buf);
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// 工作负载轨迹：`NANOLOG_WORKLOAD` 指向捕获的轨迹文件时使用真实流量，否则生成一段混合流量
+fn workload() -> Workload {
+    if let Ok(path) = std::env::var("NANOLOG_WORKLOAD") {
+        match Workload::load(&path) {
+            Ok(workload) => return workload,
+            Err(e) => eprintln!("failed to load workload {}: {}", path, e),
+        }
+    }
+    let capture = WorkloadCapture::new();
+    let mut trace = Vec::new();
+    let targets = ["orders", "risk", "gateway::fix", "md::feed"];
+    for i in 0..10_000usize {
+        let level = match i % 50 {
+            0 => Level::Warn,
+            1 => Level::Error,
+            n if n % 3 == 0 => Level::Debug,
+            _ => Level::Info,
+        };
+        let len = 24 + (i * 37) % 180;
+        let record = Record::new(
+            level,
+            targets[i % targets.len()],
+            file!(),
+            line!(),
+            "m".repeat(len),
+        );
+        let _ = capture.format_into(&record, &mut trace);
+    }
+    Workload::parse(&trace).unwrap_or_default()
+}
+
+/// 创建被测格式化器
+type MakeFormatter = fn() -> Arc<dyn Formatter>;
+
+/// 在不同配置下不限速回放同一工作负载
+fn bench_replay(c: &mut Criterion) {
+    let mut group = c.benchmark_group("replay");
+    group.measurement_time(Duration::from_secs(5));
+    group.sample_size(20);
+
+    let workload = workload();
+    let configs: [(&str, MakeFormatter); 3] = [
+        ("default", || Arc::new(DefaultFormatter::plain())),
+        ("json", || Arc::new(JsonFormatter::new())),
+        ("simple", || Arc::new(SimpleFormatter::new())),
+    ];
+    for (name, formatter) in configs {
+        for batch_size in [16, 256] {
+            group.bench_function(format!("{}_batch_{}", name, batch_size), |b| {
+                b.iter(|| {
+                    let built = AsyncLoggerBuilder::new()
+                        .level(Level::Debug)
+                        .formatter(formatter())
+                        .sink(Arc::new(NullSink::new()))
+                        .queue_capacity(8192)
+                        .batch_size(batch_size)
+                        .build();
+                    if let Ok(logger) = built {
+                        let _ = black_box(workload.replay(&logger, Pacing::Unpaced));
+                        let _ = logger.shutdown();
+                    }
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_byte_buffer,
     bench_buffer_pool,
     bench_logging,
+    bench_allocations,
     bench_formatting,
     bench_concurrent,
-    bench_publish_mutex_vs_concurrent
+    bench_publish_mutex_vs_concurrent,
+    bench_json_escape,
+    bench_fan_out,
+    bench_replay
 );
 criterion_main!(benches);
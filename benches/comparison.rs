@@ -0,0 +1,288 @@
+//! 与主流日志库的对比基准
+//!
+//! 以相同的工作负载分别驱动 nanolog、log + env_logger、tracing + tracing-subscriber 与
+//! slog（slog-async + slog-term）。各库的输出均丢弃，每个样本测量调用方记录
+//! [`DEFAULT_ITERATIONS`] 条日志并等待全部写出的总耗时，因此异步库的后台格式化与写入也计入结果。
+//!
+//! 结果打印为表格，并写入 JSON 报告（默认 `target/comparison-report.json`，
+//! 可通过 `NANOLOG_COMPARISON_REPORT` 指定路径），供 CI 比较历史数据。
+//!
+//! 运行：`cargo bench --bench comparison --features comparison`
+use nanolog_rs::{AsyncLogger, DefaultFormatter, Level, NullSink, init_global_logger};
+use std::fmt::Write as _;
+use std::hint::black_box;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 每个样本记录的日志条数（可通过 `NANOLOG_COMPARISON_ITERATIONS` 覆盖）
+const DEFAULT_ITERATIONS: u64 = 100_000;
+
+/// 每组测量的样本数（另有一次预热不计入结果）
+const SAMPLES: usize = 10;
+
+/// 异步库的队列容量，保证测量期间不因队列满而阻塞或丢弃
+const QUEUE_CAPACITY: usize = 1 << 17;
+
+/// 工作负载
+#[derive(Debug, Clone, Copy)]
+enum Workload {
+    /// 固定消息
+    StaticMessage,
+    /// 带两个整数参数的格式化消息
+    FormattedArgs,
+    /// 级别未启用的调用（只测量级别检查）
+    DisabledLevel,
+}
+
+impl Workload {
+    const ALL: [Workload; 3] = [
+        Workload::StaticMessage,
+        Workload::FormattedArgs,
+        Workload::DisabledLevel,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Workload::StaticMessage => "static_message",
+            Workload::FormattedArgs => "formatted_args",
+            Workload::DisabledLevel => "disabled_level",
+        }
+    }
+}
+
+/// 参与对比的日志库：`run` 返回记录 `iterations` 条日志并全部写出的耗时
+struct Contender {
+    name: &'static str,
+    run: fn(Workload, u64) -> Duration,
+}
+
+const CONTENDERS: [Contender; 4] = [
+    Contender {
+        name: "nanolog",
+        run: run_nanolog,
+    },
+    Contender {
+        name: "env_logger",
+        run: run_env_logger,
+    },
+    Contender {
+        name: "tracing",
+        run: run_tracing,
+    },
+    Contender {
+        name: "slog",
+        run: run_slog,
+    },
+];
+
+/// nanolog：全局日志器 + 级别宏，等待刷新屏障完成
+fn run_nanolog(workload: Workload, iterations: u64) -> Duration {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        let logger = AsyncLogger::new(
+            Level::Info,
+            Arc::new(DefaultFormatter::new()),
+            Arc::new(NullSink::new()),
+            QUEUE_CAPACITY,
+            1024,
+            Duration::from_millis(100),
+        );
+        let _ = init_global_logger(Arc::new(logger));
+    });
+    let Some(logger) = nanolog_rs::global_logger() else {
+        return Duration::ZERO;
+    };
+
+    let start = Instant::now();
+    for i in 0..iterations {
+        match workload {
+            Workload::StaticMessage => nanolog_rs::info!("request handled"),
+            Workload::FormattedArgs => {
+                nanolog_rs::info!("request {} handled in {}us", i, i % 1000)
+            }
+            Workload::DisabledLevel => nanolog_rs::debug!("request {} handled", i),
+        }
+    }
+    let _ = logger.flush();
+    start.elapsed()
+}
+
+/// log + env_logger：同步写入，输出到 `io::sink()`
+fn run_env_logger(workload: Workload, iterations: u64) -> Duration {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        let _ = env_logger::Builder::new()
+            .filter_level(log::LevelFilter::Info)
+            .target(env_logger::Target::Pipe(Box::new(io::sink())))
+            .try_init();
+    });
+
+    let start = Instant::now();
+    for i in 0..iterations {
+        match workload {
+            Workload::StaticMessage => log::info!("request handled"),
+            Workload::FormattedArgs => log::info!("request {} handled in {}us", i, i % 1000),
+            Workload::DisabledLevel => log::debug!("request {} handled", i),
+        }
+    }
+    log::logger().flush();
+    start.elapsed()
+}
+
+/// tracing + tracing-subscriber fmt：同步写入，输出到 `io::sink()`
+fn run_tracing(workload: Workload, iterations: u64) -> Duration {
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .with_writer(io::sink)
+        .finish();
+
+    tracing::subscriber::with_default(subscriber, || {
+        let start = Instant::now();
+        for i in 0..iterations {
+            match workload {
+                Workload::StaticMessage => tracing::info!("request handled"),
+                Workload::FormattedArgs => {
+                    tracing::info!("request {} handled in {}us", i, i % 1000)
+                }
+                Workload::DisabledLevel => tracing::debug!("request {} handled", i),
+            }
+        }
+        start.elapsed()
+    })
+}
+
+/// slog：slog-async 后台线程 + slog-term 格式化，丢弃守卫时等待队列写完
+fn run_slog(workload: Workload, iterations: u64) -> Duration {
+    use slog::Drain;
+
+    let decorator = slog_term::PlainSyncDecorator::new(io::sink());
+    let drain = slog_term::FullFormat::new(decorator).build().fuse();
+    let (drain, guard) = slog_async::Async::new(drain)
+        .chan_size(QUEUE_CAPACITY)
+        .overflow_strategy(slog_async::OverflowStrategy::Block)
+        .build_with_guard();
+    let drain = slog::LevelFilter::new(drain.fuse(), slog::Level::Info).fuse();
+    let logger = slog::Logger::root(drain, slog::o!());
+
+    let start = Instant::now();
+    for i in 0..iterations {
+        match workload {
+            Workload::StaticMessage => slog::info!(logger, "request handled"),
+            Workload::FormattedArgs => {
+                slog::info!(logger, "request {} handled in {}us", i, i % 1000)
+            }
+            Workload::DisabledLevel => slog::debug!(logger, "request {} handled", i),
+        }
+    }
+    drop(logger);
+    drop(guard);
+    start.elapsed()
+}
+
+/// 一组测量结果
+struct Measurement {
+    workload: Workload,
+    contender: &'static str,
+    /// 各样本的每条耗时（纳秒，升序）
+    ns_per_op: Vec<f64>,
+}
+
+impl Measurement {
+    fn median(&self) -> f64 {
+        let n = self.ns_per_op.len();
+        if n % 2 == 1 {
+            self.ns_per_op[n / 2]
+        } else {
+            (self.ns_per_op[n / 2 - 1] + self.ns_per_op[n / 2]) / 2.0
+        }
+    }
+
+    fn min(&self) -> f64 {
+        self.ns_per_op.first().copied().unwrap_or_default()
+    }
+
+    fn max(&self) -> f64 {
+        self.ns_per_op.last().copied().unwrap_or_default()
+    }
+}
+
+fn measure(contender: &Contender, workload: Workload, iterations: u64) -> Measurement {
+    black_box((contender.run)(workload, iterations));
+    let mut ns_per_op: Vec<f64> = (0..SAMPLES)
+        .map(|_| (contender.run)(workload, iterations).as_nanos() as f64 / iterations as f64)
+        .collect();
+    ns_per_op.sort_by(f64::total_cmp);
+    Measurement {
+        workload,
+        contender: contender.name,
+        ns_per_op,
+    }
+}
+
+/// 生成 JSON 报告
+fn report(iterations: u64, results: &[Measurement]) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "{{\"iterations\":{},\"samples\":{},\"results\":[",
+        iterations, SAMPLES
+    );
+    for (i, m) in results.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _ = write!(
+            out,
+            "{{\"workload\":\"{}\",\"logger\":\"{}\",\"ns_per_op_median\":{:.2},\
+             \"ns_per_op_min\":{:.2},\"ns_per_op_max\":{:.2},\"ops_per_sec\":{:.0}}}",
+            m.workload.as_str(),
+            m.contender,
+            m.median(),
+            m.min(),
+            m.max(),
+            1e9 / m.median().max(f64::MIN_POSITIVE)
+        );
+    }
+    out.push_str("]}\n");
+    out
+}
+
+fn main() -> io::Result<()> {
+    let iterations = std::env::var("NANOLOG_COMPARISON_ITERATIONS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS)
+        .max(1);
+
+    let mut results = Vec::new();
+    println!(
+        "{:<16} {:<12} {:>12} {:>12} {:>12}",
+        "workload", "logger", "median ns", "min ns", "max ns"
+    );
+    for workload in Workload::ALL {
+        for contender in &CONTENDERS {
+            let m = measure(contender, workload, iterations);
+            println!(
+                "{:<16} {:<12} {:>12.1} {:>12.1} {:>12.1}",
+                workload.as_str(),
+                m.contender,
+                m.median(),
+                m.min(),
+                m.max()
+            );
+            results.push(m);
+        }
+    }
+
+    let path = std::env::var("NANOLOG_COMPARISON_REPORT")
+        .unwrap_or_else(|_| "target/comparison-report.json".to_string());
+    if let Some(dir) = std::path::Path::new(&path).parent()
+        && !dir.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, report(iterations, &results))?;
+    println!("report written to {}", path);
+    Ok(())
+}
@@ -0,0 +1,96 @@
+/*!
+消费者线程的绑核与调度优先级。
+
+低延迟部署通常将消费者线程固定在隔离的核心上，并调整其调度优先级，避免写入路径
+与业务线程争抢 CPU。实际设置仅在 Linux 且启用 `core_affinity` 特性时生效，
+其他情况下相关操作返回 `Unsupported` 并优雅退化（见 [`AsyncLoggerBuilder::consumer_core`](crate::AsyncLoggerBuilder::consumer_core)）。
+*/
+
+use std::io;
+
+/// 消费者线程的默认名称
+pub const DEFAULT_THREAD_NAME: &str = "nanolog-writer";
+
+/// 检查当前构建是否支持绑核与调度优先级设置
+pub fn is_supported() -> bool {
+    cfg!(all(target_os = "linux", feature = "core_affinity"))
+}
+
+/// 将当前线程固定到指定核心
+#[cfg(all(target_os = "linux", feature = "core_affinity"))]
+pub fn pin_current_thread(core: usize) -> io::Result<()> {
+    if core >= libc::CPU_SETSIZE as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "core index exceeds CPU_SETSIZE",
+        ));
+    }
+    // SAFETY: cpu_set_t 是普通位图，全零初始化合法；传给内核的指针在调用期间有效。
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core, &mut set);
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// 将当前线程固定到指定核心
+#[cfg(not(all(target_os = "linux", feature = "core_affinity")))]
+pub fn pin_current_thread(_core: usize) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "core pinning requires Linux and the `core_affinity` feature",
+    ))
+}
+
+/// 设置当前线程的 nice 值（-20 最高，19 最低；调高优先级通常需要 `CAP_SYS_NICE`）
+///
+/// Linux 上 nice 值按线程生效，不影响进程内其他线程。
+#[cfg(all(target_os = "linux", feature = "core_affinity"))]
+pub fn set_current_thread_priority(nice: i32) -> io::Result<()> {
+    // SAFETY: setpriority 不涉及指针；who 为 0 表示调用线程。
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// 设置当前线程的 nice 值（-20 最高，19 最低；调高优先级通常需要 `CAP_SYS_NICE`）
+#[cfg(not(all(target_os = "linux", feature = "core_affinity")))]
+pub fn set_current_thread_priority(_nice: i32) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "thread priority requires Linux and the `core_affinity` feature",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsupported_builds_degrade() {
+        if is_supported() {
+            // 降低自身优先级无需特权
+            std::thread::spawn(|| set_current_thread_priority(1))
+                .join()
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                pin_current_thread(usize::MAX).unwrap_err().kind(),
+                io::ErrorKind::InvalidInput
+            );
+        } else {
+            assert_eq!(
+                pin_current_thread(0).unwrap_err().kind(),
+                io::ErrorKind::Unsupported
+            );
+            assert_eq!(
+                set_current_thread_priority(0).unwrap_err().kind(),
+                io::ErrorKind::Unsupported
+            );
+        }
+    }
+}
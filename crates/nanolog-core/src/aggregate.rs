@@ -0,0 +1,212 @@
+/*!
+错误聚合。
+
+消费者线程上可选的聚合阶段：同一调用点（目标、文件与行号）的 Error 记录在窗口内只写出首条，
+其余计数；窗口结束后写出一条汇总 `<首条消息> ×N in last 60s`（N 为窗口内的总次数），
+故障期间压低 Error 量的同时保留各调用点的发生频率。
+
+与 [去重](crate::AsyncLoggerBuilder::dedup) 不同，聚合按调用点而非连续的相同消息计数，
+不同调用点的错误交替出现时各自独立聚合。汇总在窗口结束后的批尾、或该调用点下一次出错时写出；
+同步写出的记录不参与聚合。
+*/
+
+use crate::{Level, Record};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// 调用点：目标、文件与行号
+type CallSite = (&'static str, &'static str, u32);
+
+/// 单个调用点在当前窗口内的聚合状态
+struct Group {
+    /// 窗口内首条记录的消息
+    message: String,
+    /// 窗口开始时刻
+    since: Instant,
+    /// 窗口开始的先后序号，汇总按此顺序写出
+    order: u64,
+    /// 窗口内的总次数（含已写出的首条）
+    count: u64,
+}
+
+/// 错误聚合状态（消费者线程独占）
+pub(crate) struct ErrorAggregator {
+    window: Duration,
+    groups: HashMap<CallSite, Group>,
+    /// 下一个窗口的序号
+    next_order: u64,
+    /// 到期汇总的排序缓冲区（复用）
+    expired: Vec<(u64, Record)>,
+}
+
+impl ErrorAggregator {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            groups: HashMap::new(),
+            next_order: 0,
+            expired: Vec::new(),
+        }
+    }
+
+    /// 检查记录：同一调用点在窗口内再次出错时计数并返回 `true`；
+    /// 否则开始新窗口，并通过 `summary` 交出上一窗口需在它之前写出的汇总
+    pub(crate) fn check(
+        &mut self,
+        record: &Record,
+        now: Instant,
+        summary: &mut Option<Record>,
+    ) -> bool {
+        if record.level() < Level::Error {
+            return false;
+        }
+        let site = (record.target(), record.file(), record.line());
+        let order = self.next_order;
+        match self.groups.get_mut(&site) {
+            Some(group) if now.duration_since(group.since) < self.window => {
+                group.count += 1;
+                true
+            }
+            Some(group) => {
+                *summary = group_summary(self.window, site, group);
+                group.message.clear();
+                group.message.push_str(record.message());
+                group.since = now;
+                group.order = order;
+                group.count = 1;
+                self.next_order += 1;
+                false
+            }
+            None => {
+                let group = Group {
+                    message: record.message().to_string(),
+                    since: now,
+                    order,
+                    count: 1,
+                };
+                self.groups.insert(site, group);
+                self.next_order += 1;
+                false
+            }
+        }
+    }
+
+    /// 按窗口开始的先后交出窗口已结束的调用点的汇总，并移除这些调用点
+    pub(crate) fn expire(&mut self, now: Instant, summaries: &mut Vec<Record>) {
+        if self.groups.is_empty() {
+            return;
+        }
+        let window = self.window;
+        let expired = &mut self.expired;
+        self.groups.retain(|&site, group| {
+            if now.duration_since(group.since) < window {
+                return true;
+            }
+            if let Some(summary) = group_summary(window, site, group) {
+                expired.push((group.order, summary));
+            }
+            false
+        });
+        expired.sort_unstable_by_key(|(order, _)| *order);
+        summaries.extend(expired.drain(..).map(|(_, summary)| summary));
+    }
+}
+
+/// 窗口内出现多次时生成汇总记录（只出现一次时首条已足够）
+fn group_summary(window: Duration, site: CallSite, group: &Group) -> Option<Record> {
+    if group.count < 2 {
+        return None;
+    }
+    let (target, file, line) = site;
+    let message = if window.subsec_nanos() == 0 {
+        format!(
+            "{} ×{} in last {}s",
+            group.message,
+            group.count,
+            window.as_secs()
+        )
+    } else {
+        format!(
+            "{} ×{} in last {}ms",
+            group.message,
+            group.count,
+            window.as_millis()
+        )
+    };
+    Some(Record::new(Level::Error, target, file, line, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(line: u32, message: &str) -> Record {
+        Record::new(Level::Error, "payment", "pay.rs", line, message.to_string())
+    }
+
+    #[test]
+    fn test_aggregates_per_call_site() {
+        let mut aggregator = ErrorAggregator::new(Duration::from_secs(60));
+        let now = Instant::now();
+        let mut summary = None;
+        assert!(!aggregator.check(&error(1, "payment timeout"), now, &mut summary));
+        assert!(!aggregator.check(&error(2, "card declined"), now, &mut summary));
+        for _ in 0..217 {
+            assert!(aggregator.check(&error(1, "payment timeout"), now, &mut summary));
+        }
+        // 低于 Error 的记录不参与聚合
+        let warn = Record::new(Level::Warn, "payment", "pay.rs", 1, "slow");
+        assert!(!aggregator.check(&warn, now, &mut summary));
+        assert!(summary.is_none());
+
+        let mut summaries = Vec::new();
+        aggregator.expire(now, &mut summaries);
+        assert!(summaries.is_empty());
+
+        // 只出现一次的调用点不产生汇总
+        aggregator.expire(now + Duration::from_secs(60), &mut summaries);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].message(), "payment timeout ×218 in last 60s");
+        assert_eq!(summaries[0].level(), Level::Error);
+        assert_eq!(summaries[0].line(), 1);
+        assert!(aggregator.groups.is_empty());
+    }
+
+    #[test]
+    fn test_next_error_after_window_flushes_summary() {
+        let window = Duration::from_millis(500);
+        let mut aggregator = ErrorAggregator::new(window);
+        let start = Instant::now();
+        let mut summary = None;
+        assert!(!aggregator.check(&error(1, "timeout"), start, &mut summary));
+        assert!(aggregator.check(&error(1, "timeout"), start, &mut summary));
+
+        let later = start + window;
+        assert!(!aggregator.check(&error(1, "timeout again"), later, &mut summary));
+        assert_eq!(summary.unwrap().message(), "timeout ×2 in last 500ms");
+        // 新窗口以本条消息开始
+        assert!(aggregator.check(&error(1, "timeout"), later, &mut None));
+        let mut summaries = Vec::new();
+        aggregator.expire(later + window, &mut summaries);
+        assert_eq!(summaries[0].message(), "timeout again ×2 in last 500ms");
+    }
+
+    #[test]
+    fn test_expired_summaries_follow_first_seen_order() {
+        let mut aggregator = ErrorAggregator::new(Duration::from_secs(1));
+        let now = Instant::now();
+        let lines = [7, 3, 12, 1, 9, 5, 20, 2];
+        for &line in &lines {
+            assert!(!aggregator.check(&error(line, "failed"), now, &mut None));
+        }
+        // 计数顺序与首次出现顺序相反，不影响汇总顺序
+        for &line in lines.iter().rev() {
+            assert!(aggregator.check(&error(line, "failed"), now, &mut None));
+        }
+
+        let mut summaries = Vec::new();
+        aggregator.expire(now + Duration::from_secs(1), &mut summaries);
+        let expired: Vec<u32> = summaries.iter().map(Record::line).collect();
+        assert_eq!(expired, lines);
+    }
+}
@@ -0,0 +1,180 @@
+/*!
+分配计数。
+
+[`CountingAllocator`] 包装任意全局分配器，按线程统计分配、释放与重分配次数，
+用于在测试与基准中验证日志调用路径上的分配数：
+
+```no_run
+use nanolog_rs::allocation::{self, CountingAllocator};
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator::system();
+
+let (_, stats) = allocation::measure(|| {
+    // 被测代码
+});
+assert_eq!(stats.allocations, 0);
+```
+
+计数只针对调用线程，消费者线程与其他线程的分配不计入；未安装为全局分配器时计数始终为零。
+*/
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 计数分配器是否已安装为全局分配器（发生过至少一次分配）
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// 本线程的计数（常量初始化，访问时不会分配）
+    static COUNTERS: Counters = const {
+        Counters {
+            allocations: Cell::new(0),
+            deallocations: Cell::new(0),
+            reallocations: Cell::new(0),
+            bytes: Cell::new(0),
+        }
+    };
+}
+
+struct Counters {
+    allocations: Cell<u64>,
+    deallocations: Cell<u64>,
+    reallocations: Cell<u64>,
+    bytes: Cell<u64>,
+}
+
+/// 分配统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocationStats {
+    /// 分配次数（含清零分配）
+    pub allocations: u64,
+    /// 释放次数
+    pub deallocations: u64,
+    /// 重分配次数
+    pub reallocations: u64,
+    /// 分配与重分配申请的字节数
+    pub bytes: u64,
+}
+
+impl AllocationStats {
+    /// 两次快照之间的增量
+    pub fn since(&self, earlier: &AllocationStats) -> AllocationStats {
+        AllocationStats {
+            allocations: self.allocations.wrapping_sub(earlier.allocations),
+            deallocations: self.deallocations.wrapping_sub(earlier.deallocations),
+            reallocations: self.reallocations.wrapping_sub(earlier.reallocations),
+            bytes: self.bytes.wrapping_sub(earlier.bytes),
+        }
+    }
+
+    /// 平均每次操作的分配次数（分配与重分配之和）
+    pub fn per_op(&self, ops: u64) -> f64 {
+        (self.allocations + self.reallocations) as f64 / ops.max(1) as f64
+    }
+}
+
+/// 计数分配器：转发给内部分配器并按线程计数
+pub struct CountingAllocator<A = System> {
+    inner: A,
+}
+
+impl CountingAllocator<System> {
+    /// 包装系统分配器
+    pub const fn system() -> Self {
+        Self { inner: System }
+    }
+}
+
+impl<A> CountingAllocator<A> {
+    /// 包装给定的分配器（如 jemalloc、mimalloc）
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+impl<A: GlobalAlloc> CountingAllocator<A> {
+    #[inline]
+    fn count(update: impl FnOnce(&Counters)) {
+        mark_installed();
+        // 线程退出后本地存储不可用，此时不计数
+        let _ = COUNTERS.try_with(update);
+    }
+}
+
+// SAFETY: 所有操作原样转发给内部分配器，计数不影响返回的内存
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::count(|c| {
+            c.allocations.set(c.allocations.get() + 1);
+            c.bytes.set(c.bytes.get() + layout.size() as u64);
+        });
+        // SAFETY: 调用方满足 `GlobalAlloc::alloc` 的约定
+        unsafe { self.inner.alloc(layout) }
+    }
+
+    #[inline]
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::count(|c| {
+            c.allocations.set(c.allocations.get() + 1);
+            c.bytes.set(c.bytes.get() + layout.size() as u64);
+        });
+        // SAFETY: 同上
+        unsafe { self.inner.alloc_zeroed(layout) }
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        Self::count(|c| c.deallocations.set(c.deallocations.get() + 1));
+        // SAFETY: `ptr` 由本分配器以相同布局分配
+        unsafe { self.inner.dealloc(ptr, layout) }
+    }
+
+    #[inline]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::count(|c| {
+            c.reallocations.set(c.reallocations.get() + 1);
+            c.bytes.set(c.bytes.get() + new_size as u64);
+        });
+        // SAFETY: `ptr` 由本分配器以相同布局分配，调用方保证 `new_size` 合法
+        unsafe { self.inner.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// 标记计数分配器已安装（只读检查，避免每次分配都写共享缓存行）
+#[inline]
+fn mark_installed() {
+    if !INSTALLED.load(Ordering::Relaxed) {
+        INSTALLED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 计数分配器是否已安装为全局分配器
+///
+/// 未安装时 [`thread_stats`] 与 [`measure`] 的结果始终为零，断言零分配前应先检查。
+pub fn is_installed() -> bool {
+    // 分配一次以触发计数
+    drop(std::hint::black_box(Box::new(0u8)));
+    INSTALLED.load(Ordering::Relaxed)
+}
+
+/// 本线程累计的分配统计
+pub fn thread_stats() -> AllocationStats {
+    COUNTERS
+        .try_with(|c| AllocationStats {
+            allocations: c.allocations.get(),
+            deallocations: c.deallocations.get(),
+            reallocations: c.reallocations.get(),
+            bytes: c.bytes.get(),
+        })
+        .unwrap_or_default()
+}
+
+/// 执行 `f` 并返回其在本线程上的分配统计
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, AllocationStats) {
+    let before = thread_stats();
+    let result = f();
+    (result, thread_stats().since(&before))
+}
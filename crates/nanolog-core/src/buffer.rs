@@ -8,6 +8,8 @@ use std::ptr::NonNull;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+use crate::memory::{MemoryArea, MemoryTracker};
+
 /// 字节缓冲区实现
 ///
 /// 提供高效的字节数据存储和管理，支持自动扩容和内存池优化。
@@ -74,6 +76,17 @@ impl ByteBuffer {
         self.len == 0
     }
 
+    /// 逐页写入一次，使物理页按当前内存策略立即分配
+    fn touch(&mut self) {
+        const PAGE: usize = 4096;
+        let mut offset = 0;
+        while offset < self.capacity {
+            // SAFETY: offset < capacity，写入位于本缓冲区的分配范围内。
+            unsafe { self.data.as_ptr().add(offset).write(0) };
+            offset += PAGE;
+        }
+    }
+
     /// 确保缓冲区有足够的空间
     pub fn reserve(&mut self, additional: usize) {
         let required = self.len.saturating_add(additional);
@@ -197,6 +210,9 @@ pub struct BufferPool {
     buffer_size: usize,
     max_pool_size: usize,
     current_size: AtomicUsize,
+    tracker: Option<Arc<MemoryTracker>>,
+    numa_node: Option<usize>,
+    huge_pages: bool,
 }
 
 impl BufferPool {
@@ -207,15 +223,51 @@ impl BufferPool {
             buffer_size,
             max_pool_size,
             current_size: AtomicUsize::new(0),
+            tracker: None,
+            numa_node: None,
+            huge_pages: false,
         }
     }
 
+    /// 使用透明大页支撑新分配的缓冲区（Linux 且启用 `hugepages` 特性时生效，失败时退化为普通页面）
+    ///
+    /// 建议在缓冲区首次写入前发出，对缓冲区的全部页面生效；缓冲区不小于大页（通常 2 MiB）时才有意义。
+    pub fn with_huge_pages(mut self) -> Self {
+        self.huge_pages = true;
+        self
+    }
+
+    /// 将新分配的缓冲区放置在指定 NUMA 节点（Linux 且启用 `numa` 特性时生效）
+    pub fn with_numa_node(mut self, node: usize) -> Self {
+        self.numa_node = Some(node);
+        self
+    }
+
+    /// 将池中空闲缓冲区的容量计入内存预算（超出预算的缓冲区归还时直接释放）
+    pub fn with_memory_tracker(mut self, tracker: Arc<MemoryTracker>) -> Self {
+        self.tracker = Some(tracker);
+        self
+    }
+
     /// 获取缓冲区（优先重用）
     pub fn acquire(&self) -> Arc<ByteBuffer> {
         if let Some(buffer) = self.buffers.pop() {
+            self.current_size.fetch_sub(1, Ordering::Relaxed);
+            if let Some(tracker) = &self.tracker {
+                tracker.release(MemoryArea::Pool, buffer.capacity());
+            }
             buffer
         } else {
-            Arc::new(ByteBuffer::new(self.buffer_size))
+            let mut buffer = ByteBuffer::new(self.buffer_size);
+            if self.huge_pages {
+                let _ = crate::hugepage::advise(buffer.data.as_ptr(), buffer.capacity());
+            }
+            if let Some(node) = self.numa_node {
+                let _ = crate::numa::bind_memory(buffer.data.as_ptr(), buffer.capacity(), node);
+                // 首次触碰在绑定之后，确保页面按节点策略分配
+                buffer.touch();
+            }
+            Arc::new(buffer)
         }
     }
 
@@ -224,6 +276,13 @@ impl BufferPool {
         // 检查当前池大小
         let current = self.current_size.load(Ordering::Relaxed);
         if current < self.max_pool_size {
+            if let Some(tracker) = &self.tracker
+                && !tracker.try_reserve(MemoryArea::Pool, buffer.capacity())
+            {
+                // 超出内存预算，缓冲区直接释放
+                return;
+            }
+
             // 尝试清空缓冲区内容以便重用
             // 注意：只有当没有其他强引用时，Arc::get_mut才能成功
             if let Some(buf) = Arc::get_mut(&mut Arc::clone(&buffer)) {
@@ -284,4 +343,25 @@ mod tests {
 
         assert_eq!(pool.size(), 2);
     }
+
+    #[test]
+    fn test_buffer_pool_respects_memory_budget() {
+        let tracker = Arc::new(MemoryTracker::new(
+            crate::memory::MemoryBudget::new().max_pool_bytes(1500),
+        ));
+        let pool = BufferPool::new(1024, 10).with_memory_tracker(tracker.clone());
+
+        let buffer1 = pool.acquire();
+        let buffer2 = pool.acquire();
+        pool.release(buffer1);
+        pool.release(buffer2);
+
+        assert_eq!(pool.size(), 1);
+        assert_eq!(tracker.usage().pool_bytes, 1024);
+        assert_eq!(tracker.usage().pool_dropped, 1);
+
+        let _reused = pool.acquire();
+        assert_eq!(pool.size(), 0);
+        assert_eq!(tracker.usage().pool_bytes, 0);
+    }
 }
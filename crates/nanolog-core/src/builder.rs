@@ -0,0 +1,877 @@
+/*!
+独立的Builder模块，用于构建AsyncLogger实例。
+
+该模块提供了现代化的Builder模式，使用户能够以流畅的方式配置和创建日志器实例。
+*/
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::Level;
+
+use crate::diagnostics::DiagnosticHandler;
+use crate::error::Error;
+use crate::field::FieldLimits;
+use crate::filter::Filter;
+use crate::format::TimestampStyle;
+use crate::format::{BatchFormatter, FormatStyle, Formatter, Multiline};
+use crate::logger::{AsyncLogger, LoggerOptions, OverflowPolicy};
+use crate::memory::{MemoryArea, MemoryBudget};
+use crate::metadata::ProcessMetadata;
+use crate::sink::{Sink, SinkErrorHandler, SinkErrorPolicy};
+use crate::trace::TraceContextProvider;
+use crate::transform::MessageTransformer;
+use crate::wait::WaitStrategy;
+use crate::watchdog::Watchdog;
+
+/// 启用丢失即 panic 的环境变量（值为 `1` 或 `true`），见 [`AsyncLoggerBuilder::panic_on_loss`]
+pub const PANIC_ON_LOSS_ENV: &str = "NANOLOG_PANIC_ON_LOSS";
+
+/// 相对时间戳基准的环境变量（值为 UNIX 纳秒），见 [`AsyncLoggerBuilder::timestamp_base`]
+pub const TIMESTAMP_BASE_ENV: &str = "NANOLOG_TIMESTAMP_BASE";
+
+/// 构建器模式配置
+#[derive(Clone)]
+pub struct AsyncLoggerBuilder {
+    level: Level,
+    formatter: Option<Arc<dyn Formatter>>,
+    sink: Option<Arc<dyn Sink>>,
+    queue_capacity: usize,
+    batch_size: usize,
+    flush_interval: Duration,
+    options: LoggerOptions,
+    env_filter: bool,
+    strict: bool,
+    timestamp_base: Option<SystemTime>,
+}
+
+impl Default for AsyncLoggerBuilder {
+    fn default() -> Self {
+        Self {
+            level: Level::Info,
+            formatter: None,
+            sink: None,
+            queue_capacity: 1000,
+            batch_size: 100,
+            flush_interval: Duration::from_millis(100),
+            options: LoggerOptions::default(),
+            env_filter: false,
+            strict: false,
+            timestamp_base: None,
+        }
+    }
+}
+
+impl AsyncLoggerBuilder {
+    /// 创建新的构建器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置日志级别
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// 设置格式化器
+    pub fn formatter(mut self, formatter: Arc<dyn Formatter>) -> Self {
+        self.formatter = Some(formatter);
+        self
+    }
+
+    /// 添加一路额外输出：同一记录以该格式化器格式化后写入该输出目标
+    ///
+    /// 各路格式化器借用同一条记录，不复制记录；格式化缓冲区在批次间复用。
+    pub fn output(mut self, formatter: Arc<dyn Formatter>, sink: Arc<dyn Sink>) -> Self {
+        self.options.outputs.push((formatter, sink));
+        self
+    }
+
+    /// 设置批量格式化器：整批记录序列化为一个载荷后写入输出目标，替代逐条格式化
+    pub fn batch_formatter(mut self, formatter: Arc<dyn BatchFormatter>) -> Self {
+        self.options.batch_formatter = Some(formatter);
+        self
+    }
+
+    /// 设置输出目标
+    pub fn sink(mut self, sink: Arc<dyn Sink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// 设置队列容量
+    pub fn queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity;
+        self
+    }
+
+    /// 设置批处理大小：消费者攒够该数量（或到达批尾、需要刷新）时通过 `write_batch` 一次写入
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size;
+        self
+    }
+
+    /// 设置刷新间隔
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// 设置消息转换钩子（在消费者线程格式化之前改写消息）
+    pub fn message_transformer(mut self, transformer: Arc<dyn MessageTransformer>) -> Self {
+        self.options.transformer = Some(transformer);
+        self
+    }
+
+    /// 设置刷新字节阈值（批内累计写入达到该值时立即刷新）
+    pub fn flush_threshold_bytes(mut self, bytes: usize) -> Self {
+        self.options.flush_threshold = Some(bytes);
+        self
+    }
+
+    /// 写入不低于 `level` 的记录后立即刷新输出目标（不等待批尾、合并窗口或刷新间隔），
+    /// 保证错误在大缓冲区与长刷新间隔下也能及时落盘
+    pub fn flush_on(mut self, level: Level) -> Self {
+        self.options.flush_on = Some(level);
+        self
+    }
+
+    /// 丢弃过期记录：级别不高于 `up_to` 的记录创建后超过 `age` 仍未写出时，消费者跳过而不写入
+    ///
+    /// 消费者严重积压时避免在过期的诊断日志上耗费 IO，优先写出新记录与高级别记录；
+    /// 丢弃数见 [`AsyncLogger::stale_dropped`](crate::AsyncLogger::stale_dropped)。同步写出的记录不受影响。
+    pub fn max_age(mut self, age: Duration, up_to: Level) -> Self {
+        self.options.max_age = Some((age, up_to));
+        self
+    }
+
+    /// 过载降级：队列积压超过容量的 3/4 时拒绝级别不高于 `up_to` 的新记录（计入丢失统计），
+    /// 剩余空间留给高级别记录
+    ///
+    /// 过载时先舍弃调试类日志而非随机丢弃，与任意溢出策略组合使用；同步写出的记录不受影响。
+    /// 拒绝数见 [`AsyncLogger::shed_dropped`](crate::AsyncLogger::shed_dropped)。
+    pub fn shed_on_overload(mut self, up_to: Level) -> Self {
+        self.options.shed_level = Some(up_to);
+        self
+    }
+
+    /// 日志器级别的令牌桶限流：平均每秒至多 `per_second` 条，突发至多 `burst` 条
+    ///
+    /// 作为调用点限流（[`log_every_n!`](crate::log_every_n)、[`log_at_most_every!`](crate::log_at_most_every)）
+    /// 之外的兜底，超出的记录直接拒绝；同步写出的记录不受影响。
+    /// 拒绝数见 [`AsyncLogger::rate_limited`](crate::AsyncLogger::rate_limited)。
+    pub fn rate_limit(mut self, per_second: u32, burst: u32) -> Self {
+        self.options.rate_limit = Some((per_second, burst));
+        self
+    }
+
+    /// 启用重复消息抑制：`window` 内连续出现的相同记录（级别、目标与消息均相同）只写出首条，
+    /// 之后写出一条 `last message repeated N times` 汇总
+    ///
+    /// 汇总在下一条不同的记录之前、或窗口结束后的批尾写出；同步写出的记录不参与去重。
+    /// 抑制数见 [`AsyncLogger::duplicates_suppressed`](crate::AsyncLogger::duplicates_suppressed)。
+    pub fn dedup(mut self, window: Duration) -> Self {
+        self.options.dedup_window = Some(window);
+        self
+    }
+
+    /// 启用错误聚合：同一调用点的 Error 记录在 `window` 内只写出首条，
+    /// 窗口结束后写出一条 `<消息> ×N in last 60s` 汇总
+    ///
+    /// 汇总在窗口结束后的批尾、或该调用点下一次出错时写出；同步写出的记录不参与聚合。
+    /// 计数见 [`AsyncLogger::errors_aggregated`](crate::AsyncLogger::errors_aggregated)。
+    pub fn aggregate_errors(mut self, window: Duration) -> Self {
+        self.options.error_window = Some(window);
+        self
+    }
+
+    /// 发布时为每条记录捕获调用线程的编号与名称（见 [`Record::thread`](crate::Record::thread)）
+    ///
+    /// 每条记录多一次线程本地存储读取，默认关闭。
+    pub fn capture_thread(mut self, enabled: bool) -> Self {
+        self.options.capture_thread = enabled;
+        self
+    }
+
+    /// 为每条记录附加进程元数据（进程号、主机名、服务名等）
+    ///
+    /// 元数据字段附加在调用线程的上下文字段之后，由各格式化器随其他结构化字段输出。
+    pub fn process_metadata(mut self, metadata: ProcessMetadata) -> Self {
+        self.options.metadata = Some(metadata);
+        self
+    }
+
+    /// 发布时从 `provider` 读取调用线程当前的追踪上下文，关联到尚未设置上下文的记录
+    ///
+    /// 见 [`trace`](crate::trace) 模块。
+    pub fn trace_context(mut self, provider: Arc<dyn TraceContextProvider>) -> Self {
+        self.options.trace_context = Some(provider);
+        self
+    }
+
+    /// 设置诊断处理器（默认输出到标准错误）
+    pub fn diagnostics(mut self, handler: Arc<dyn DiagnosticHandler>) -> Self {
+        self.options.diagnostics = Some(handler);
+        self
+    }
+
+    /// 设置输出目标写入失败时的处理策略（默认 [`SinkErrorPolicy::Ignore`]）
+    pub fn sink_error_policy(mut self, policy: SinkErrorPolicy) -> Self {
+        self.options.sink_error_policy = policy;
+        self
+    }
+
+    /// 设置输出目标操作失败回调
+    ///
+    /// 每次写入（按策略重试后）或刷新失败都会在消费者线程上调用，回调应尽快返回；
+    /// 诊断通道只在由正常转为失败时上报一次，回调则用于计数、告警等需要逐次观察的场景。
+    pub fn on_error(mut self, handler: SinkErrorHandler) -> Self {
+        self.options.on_error = Some(handler);
+        self
+    }
+
+    /// 设置输出目标写入的计时钩子（如 [`WriteTimings`](crate::stats::WriteTimings)），用于定位慢的输出目标
+    pub fn write_profiler(mut self, profiler: Arc<dyn crate::stats::WriteProfiler>) -> Self {
+        self.options.write_profiler = Some(profiler);
+        self
+    }
+
+    /// 在输出中为丢失的记录写入标记行（默认关闭）
+    ///
+    /// 溢出丢弃、过期丢弃、过载降级或格式化失败的记录在丢失位置留下一条 `WARN` 级别的
+    /// `... N messages dropped ...`，经各路格式化器渲染；标记写在之后的下一条记录之前或批尾，
+    /// 不计入 [`get_loss_stats`](crate::AsyncLogger::get_loss_stats) 的已写入数。
+    pub fn drop_markers(mut self, enabled: bool) -> Self {
+        self.options.drop_markers = enabled;
+        self
+    }
+
+    /// 设置单次 `log()` 调用的延迟预算
+    ///
+    /// 仅在调试构建中生效：超出预算时通过诊断通道告警，帮助在开发阶段发现热路径上的意外阻塞。
+    pub fn latency_budget(mut self, budget: Duration) -> Self {
+        self.options.latency_budget = Some(budget);
+        self
+    }
+
+    /// 启用空闲停靠：消费者持续空闲超过该时长后停靠，下一次发布时立即唤醒
+    ///
+    /// 以极小的唤醒延迟换取空闲时不再占满一个 CPU 核心，适合笔记本和共享主机。
+    pub fn idle_parking(mut self, idle_after: Duration) -> Self {
+        self.options.idle_park_after = Some(idle_after);
+        self
+    }
+
+    /// 设置消费者的等待策略（默认忙等）
+    ///
+    /// 忙等延迟最低但空闲时占满一个 CPU 核心；让出或休眠以更高的唤醒延迟换取 CPU。
+    pub fn wait_strategy(mut self, strategy: WaitStrategy) -> Self {
+        self.options.wait_strategy = strategy;
+        self
+    }
+
+    /// 拆分为格式化与写入两级流水线
+    ///
+    /// 格式化线程（`nanolog-format`）渲染、转换并格式化记录，写入线程只负责攒批与写出，
+    /// 代价较高的格式化（如带字段的 JSON）因此不再排在慢速磁盘写入之后。
+    /// 格式化结果保存在各队列槽位中复用的缓冲区里，稳定状态下不分配。
+    pub fn pipeline(mut self, enabled: bool) -> Self {
+        self.options.pipeline = enabled;
+        self
+    }
+
+    /// 设置内存预算（队列、缓冲池与溢出缓冲的字节上限）
+    pub fn memory_budget(mut self, budget: MemoryBudget) -> Self {
+        self.options.memory_budget = budget;
+        self
+    }
+
+    /// 将环形队列分配在指定 NUMA 节点，并把消费者线程固定到该节点的第一个核心
+    ///
+    /// 仅在 Linux 且启用 `numa` 特性时生效，其他情况下忽略该设置。
+    pub fn numa_node(mut self, node: usize) -> Self {
+        self.options.numa_node = Some(node);
+        self
+    }
+
+    /// 设置消费者线程的名称（默认 `nanolog-writer`），便于在 `top -H`、`perf` 等工具中识别
+    ///
+    /// 线程名需为 `'static`：传入自有的 `String` 时，每次 [`build`](Self::build) 泄漏一份名称，
+    /// 反复构建日志器时宜使用字符串字面量。
+    pub fn consumer_thread_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.options.consumer_name = Some(name.into());
+        self
+    }
+
+    /// 将消费者线程固定到指定核心（优先于 [`numa_node`](Self::numa_node) 选定的核心）
+    ///
+    /// 仅在 Linux 且启用 `core_affinity` 特性时生效，否则通过诊断通道报告后忽略该设置。
+    pub fn consumer_core(mut self, core: usize) -> Self {
+        self.options.consumer_core = Some(core);
+        self
+    }
+
+    /// 设置消费者线程的 nice 值（-20 最高，19 最低；调高优先级通常需要 `CAP_SYS_NICE`）
+    ///
+    /// 仅在 Linux 且启用 `core_affinity` 特性时生效，设置失败时通过诊断通道报告。
+    pub fn consumer_priority(mut self, nice: i32) -> Self {
+        self.options.consumer_priority = Some(nice);
+        self
+    }
+
+    /// 设置按目标过滤器（与全局级别同时生效）
+    ///
+    /// 例如 `Filter::parse("info,my_app::db=debug")?` 搭配 `.level(Level::Trace)`，
+    /// 由过滤器决定各目标的级别。
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.options.filter = Some(filter);
+        self
+    }
+
+    /// 构建时从环境变量 `NANOLOG_LEVEL` 读取目标过滤器（未设置时不过滤，解析失败时构建报错）
+    pub fn env_filter(mut self) -> Self {
+        self.env_filter = true;
+        self
+    }
+
+    /// 严格模式：构建时检查无效的配置组合
+    ///
+    /// 队列容量或批大小为零、批大小超过队列容量、刷新间隔为零（刷新计时器始终启用）、
+    /// 同一输出目标重复注册时，调试与测试构建直接 panic，发布构建返回 [`Error::Config`]。
+    pub fn strict(mut self, enabled: bool) -> Self {
+        self.strict = enabled;
+        self
+    }
+
+    /// 设置相对时间戳的基准（见 [`Record::relative_timestamp`](crate::Record::relative_timestamp)），
+    /// 构建时设置，进程内全局生效
+    ///
+    /// 未设置时依次取环境变量 [`TIMESTAMP_BASE_ENV`]、首个日志器的构建时刻；
+    /// 父进程可将基准写入该环境变量，使子进程的相对时间戳与之对齐。
+    pub fn timestamp_base(mut self, base: SystemTime) -> Self {
+        self.timestamp_base = Some(base);
+        self
+    }
+
+    /// 丢失即 panic：检测到记录丢失或输出目标失败时 panic，让 CI 集成测试捕获静默的日志回归
+    ///
+    /// 发布时被丢弃的记录在该次 `log()` 调用中 panic；消费者检测到的丢失（淘汰、过期、
+    /// 格式化失败、输出目标失败）在下一次 `log()`、`flush()` 或 `shutdown()` 时 panic。
+    /// 未启用时同样的检测只计入统计。也可通过环境变量 [`PANIC_ON_LOSS_ENV`] 启用。
+    pub fn panic_on_loss(mut self, enabled: bool) -> Self {
+        self.options.panic_on_loss = enabled;
+        self
+    }
+
+    /// 设置结构化字段的大小限制，超限值被截断并标记 `_truncated`
+    pub fn field_limits(mut self, limits: FieldLimits) -> Self {
+        self.options.field_limits = Some(limits);
+        self
+    }
+
+    /// 设置队列满时的处理策略（默认阻塞等待），丢弃的记录计入丢失统计
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.options.overflow_policy = policy;
+        self
+    }
+
+    /// 设置多行消息的折叠方式（转义换行或移入 `stack` 字段），避免采集管道拆分事件
+    pub fn multiline(mut self, mode: Multiline) -> Self {
+        self.options.multiline = mode;
+        self
+    }
+
+    /// 启用消费者存活看门狗：处理进度停滞时经诊断通道上报，可选切换到后备输出目标
+    pub fn watchdog(mut self, watchdog: Watchdog) -> Self {
+        self.options.watchdog = Some(watchdog);
+        self
+    }
+
+    /// 使用透明大页支撑环形队列与 [`AsyncLogger::buffer_pool`](crate::AsyncLogger::buffer_pool)
+    /// 创建的缓冲池，减少超大队列的 TLB 缺失
+    ///
+    /// 仅在 Linux 且启用 `hugepages` 特性时生效；不可用时退化为普通页面并通过诊断通道提示。
+    /// 环形队列由事件工厂初始化后才能取得地址，其已初始化的页面只能由内核的 khugepaged
+    /// 在后台合并为大页；缓冲池的缓冲区在首次写入前建议，全部页面立即生效。
+    pub fn huge_pages(mut self, enabled: bool) -> Self {
+        self.options.huge_pages = enabled;
+        self
+    }
+
+    /// 设置为调试级别 (便捷方法)
+    pub fn with_debug_level(mut self) -> Self {
+        self.level = Level::Debug;
+        self
+    }
+
+    /// 设置为跟踪级别 (便捷方法)
+    pub fn with_trace_level(mut self) -> Self {
+        self.level = Level::Trace;
+        self
+    }
+
+    /// 使用JSON格式化器 (便捷方法)
+    pub fn with_json_formatting(mut self) -> Self {
+        self.formatter = Some(Arc::new(crate::format::JsonFormatter::new()));
+        self
+    }
+
+    /// 使用简单格式化器 (便捷方法)
+    pub fn with_simple_formatting(mut self) -> Self {
+        self.formatter = Some(Arc::new(crate::format::SimpleFormatter::new()));
+        self
+    }
+
+    /// 使用混合格式化器：彩色文本前缀 + JSON 字段，适合本地开发 (便捷方法)
+    pub fn with_hybrid_formatting(mut self) -> Self {
+        self.formatter = Some(Arc::new(crate::format::HybridFormatter::new()));
+        self
+    }
+
+    /// 按风格名称选择内置格式化器，便于由配置切换（如 `"json"` / `"hybrid"`）
+    pub fn format_style(mut self, style: FormatStyle) -> Self {
+        self.formatter = Some(style.formatter());
+        self
+    }
+
+    /// 使用默认格式化器并配置为上海时区 ISO8601 (便捷方法)
+    pub fn with_iso8601_shanghai_formatting(mut self) -> Self {
+        self.formatter = Some(Arc::new(
+            crate::format::DefaultFormatter::with_iso8601_shanghai(),
+        ));
+        self
+    }
+
+    /// 使用默认格式化器并指定时间戳风格 (便捷方法)
+    pub fn with_default_timestamp_style(mut self, style: TimestampStyle) -> Self {
+        self.formatter = Some(Arc::new(
+            crate::format::DefaultFormatter::with_timestamp_style(style),
+        ));
+        self
+    }
+
+    /// Kubernetes 预设：JSON 单行输出到标准输出，RFC3339 时间戳，无颜色
+    pub fn for_kubernetes(mut self) -> Self {
+        self.formatter = Some(Arc::new(
+            crate::format::JsonFormatter::new().with_rfc3339_timestamps(),
+        ));
+        self.sink = Some(Arc::new(crate::sink::ConsoleSink::new()));
+        self
+    }
+
+    /// Docker 预设：每条记录为不超过 16KB 的单行 JSON，输出到标准输出，超长消息自动拆分
+    pub fn for_docker(mut self) -> Self {
+        self.formatter = Some(Arc::new(crate::format::JsonFormatter::docker()));
+        self.sink = Some(Arc::new(crate::sink::ConsoleSink::new()));
+        self
+    }
+
+    /// systemd 预设：运行在 journald 之下时使用原生协议，否则以 `<N>` 级别前缀输出到标准错误
+    pub fn for_systemd(mut self) -> Self {
+        #[cfg(unix)]
+        if crate::sink::JournaldSink::is_available()
+            && let Ok(sink) = crate::sink::JournaldSink::new()
+        {
+            self.formatter = Some(Arc::new(crate::format::JournaldFormatter::new()));
+            self.sink = Some(Arc::new(sink));
+            return self;
+        }
+        self.formatter = Some(Arc::new(crate::format::SystemdFormatter::new()));
+        self.sink = Some(Arc::new(crate::sink::ConsoleSink::stderr()));
+        self
+    }
+
+    /// 使用控制台输出 (便捷方法)
+    pub fn with_console_output(mut self) -> Self {
+        match &self.sink {
+            Some(existing) => {
+                let mut composite = crate::sink::CompositeSink::new();
+                composite.add_sink(existing.clone());
+                composite.add_sink(Arc::new(crate::sink::ConsoleSink::new()));
+                self.sink = Some(Arc::new(composite));
+            }
+            None => {
+                self.sink = Some(Arc::new(crate::sink::ConsoleSink::new()));
+            }
+        }
+        self
+    }
+
+    /// 使用文件输出 (便捷方法)
+    pub fn with_file_output<P: AsRef<Path>>(mut self, path: P) -> Self {
+        match crate::sink::FileSink::new(path) {
+            Ok(file_sink) => match &self.sink {
+                Some(existing) => {
+                    let mut composite = crate::sink::CompositeSink::new();
+                    composite.add_sink(existing.clone());
+                    composite.add_sink(Arc::new(file_sink));
+                    self.sink = Some(Arc::new(composite));
+                }
+                None => {
+                    self.sink = Some(Arc::new(file_sink));
+                }
+            },
+            Err(_) => match &self.sink {
+                Some(existing) => {
+                    let mut composite = crate::sink::CompositeSink::new();
+                    composite.add_sink(existing.clone());
+                    composite.add_sink(Arc::new(crate::sink::ConsoleSink::new()));
+                    self.sink = Some(Arc::new(composite));
+                }
+                None => {
+                    self.sink = Some(Arc::new(crate::sink::ConsoleSink::new()));
+                }
+            },
+        }
+        self
+    }
+
+    /// 使用默认HOME目录文件输出 ($HOME/.nanolog/$DATE.log)
+    pub fn with_default_home_file_output(mut self) -> Self {
+        let base = std::env::var("HOME")
+            .map(|h| std::path::PathBuf::from(h).join(".nanolog"))
+            .unwrap_or_else(|_| std::path::PathBuf::from(".nanolog"));
+        let now = time::OffsetDateTime::now_utc().date();
+        let filename = format!(
+            "{:04}-{:02}-{:02}.log",
+            now.year(),
+            now.month() as u8,
+            now.day()
+        );
+        let path = base.join(filename);
+        match crate::sink::FileSink::new(path) {
+            Ok(file_sink) => match &self.sink {
+                Some(existing) => {
+                    let mut composite = crate::sink::CompositeSink::new();
+                    composite.add_sink(existing.clone());
+                    composite.add_sink(Arc::new(file_sink));
+                    self.sink = Some(Arc::new(composite));
+                }
+                None => {
+                    self.sink = Some(Arc::new(file_sink));
+                }
+            },
+            Err(_) => match &self.sink {
+                Some(existing) => {
+                    let mut composite = crate::sink::CompositeSink::new();
+                    composite.add_sink(existing.clone());
+                    composite.add_sink(Arc::new(crate::sink::ConsoleSink::new()));
+                    self.sink = Some(Arc::new(composite));
+                }
+                None => {
+                    self.sink = Some(Arc::new(crate::sink::ConsoleSink::new()));
+                }
+            },
+        }
+        self
+    }
+
+    /// 构建AsyncLogger实例
+    pub fn build(mut self) -> Result<AsyncLogger, Error> {
+        if self.env_filter
+            && let Some(filter) = Filter::from_env()?
+        {
+            self.options.filter = Some(filter);
+        }
+
+        if matches!(
+            std::env::var(PANIC_ON_LOSS_ENV).as_deref(),
+            Ok("1" | "true")
+        ) {
+            self.options.panic_on_loss = true;
+        }
+
+        let base = self.timestamp_base.or_else(|| {
+            std::env::var(TIMESTAMP_BASE_ENV)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(|nanos| SystemTime::UNIX_EPOCH + Duration::from_nanos(nanos))
+        });
+        match base {
+            Some(base) => crate::record::set_timestamp_base(base),
+            None => {
+                crate::record::timestamp_base();
+            }
+        }
+
+        let formatter = self
+            .formatter
+            .unwrap_or_else(|| Arc::new(crate::format::DefaultFormatter::new()));
+        let sink = self
+            .sink
+            .unwrap_or_else(|| Arc::new(crate::sink::ConsoleSink::new()));
+
+        // 最小环形队列的槽位开销已占满队列预算时，每条记录都会被拒绝
+        if self
+            .options
+            .memory_budget
+            .limit(MemoryArea::Queue)
+            .is_some_and(|limit| limit <= crate::logger::min_queue_bytes())
+        {
+            return Err(Error::Config(
+                "queue memory budget must exceed the fixed cost of the minimum 64-slot ring",
+            ));
+        }
+
+        if self.strict
+            && let Some(problem) = misconfiguration(
+                self.queue_capacity,
+                self.batch_size,
+                self.flush_interval,
+                &sink,
+                &self.options,
+            )
+        {
+            #[cfg(debug_assertions)]
+            #[allow(clippy::panic)]
+            {
+                panic!("nanolog-rs strict mode: {}", problem);
+            }
+            #[cfg(not(debug_assertions))]
+            return Err(Error::Config(problem));
+        }
+
+        Ok(AsyncLogger::with_options(
+            self.level,
+            formatter,
+            sink,
+            self.queue_capacity,
+            self.batch_size,
+            self.flush_interval,
+            self.options,
+        ))
+    }
+}
+
+/// 严格模式检查：返回首个无效的配置组合
+fn misconfiguration(
+    queue_capacity: usize,
+    batch_size: usize,
+    flush_interval: Duration,
+    sink: &Arc<dyn Sink>,
+    options: &LoggerOptions,
+) -> Option<&'static str> {
+    if queue_capacity == 0 {
+        return Some("queue capacity must be non-zero");
+    }
+    if batch_size == 0 {
+        return Some("batch size must be non-zero");
+    }
+    if batch_size > queue_capacity {
+        return Some("batch size exceeds queue capacity");
+    }
+    if flush_interval.is_zero() {
+        return Some("flush interval must be non-zero");
+    }
+    let sinks: Vec<*const ()> = std::iter::once(sink)
+        .chain(options.outputs.iter().map(|(_, sink)| sink))
+        .map(|sink| Arc::as_ptr(sink) as *const ())
+        .collect();
+    if sinks
+        .iter()
+        .enumerate()
+        .any(|(i, sink)| sinks[..i].contains(sink))
+    {
+        return Some("the same sink is registered more than once");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_builder_creation() {
+        let builder = AsyncLoggerBuilder::new();
+        assert_eq!(builder.level, Level::Info);
+    }
+
+    #[test]
+    fn test_presets() {
+        let builder = AsyncLoggerBuilder::new().for_kubernetes();
+        assert!(builder.formatter.is_some() && builder.sink.is_some());
+        let builder = AsyncLoggerBuilder::new().for_systemd();
+        assert!(builder.formatter.is_some() && builder.sink.is_some());
+        let builder = AsyncLoggerBuilder::new().for_docker();
+        assert!(builder.formatter.is_some() && builder.sink.is_some());
+    }
+
+    #[test]
+    fn test_builder_with_level() {
+        let builder = AsyncLoggerBuilder::new().level(Level::Debug);
+        assert_eq!(builder.level, Level::Debug);
+    }
+
+    #[test]
+    fn test_builder_with_convenience_methods() {
+        let builder = AsyncLoggerBuilder::new()
+            .with_debug_level()
+            .with_console_output()
+            .with_simple_formatting();
+
+        assert_eq!(builder.level, Level::Debug);
+        assert!(builder.formatter.is_some());
+        assert!(builder.sink.is_some());
+    }
+
+    #[test]
+    fn test_builder_build() {
+        let result = AsyncLoggerBuilder::new().level(Level::Info).build();
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_queue_budget_below_minimum_ring_rejected() {
+        let minimum = crate::logger::min_queue_bytes();
+        let result = AsyncLoggerBuilder::new()
+            .memory_budget(MemoryBudget::new().max_queue_bytes(minimum))
+            .build();
+        assert!(matches!(result, Err(Error::Config(_))));
+
+        let logger = AsyncLoggerBuilder::new()
+            .sink(Arc::new(crate::sink::NullSink::new()))
+            .memory_budget(MemoryBudget::new().max_queue_bytes(minimum + 1024))
+            .build();
+        assert!(logger.is_ok_and(|logger| logger.shutdown().is_ok()));
+    }
+
+    #[test]
+    fn test_strict_accepts_valid_config() {
+        let sink: Arc<dyn Sink> = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLoggerBuilder::new()
+            .sink(sink)
+            .output(
+                Arc::new(crate::format::SimpleFormatter::new()),
+                Arc::new(crate::sink::MemorySink::new()),
+            )
+            .strict(true)
+            .build();
+        assert!(logger.is_ok_and(|logger| logger.shutdown().is_ok()));
+    }
+
+    #[test]
+    fn test_misconfiguration_checks() {
+        let sink: Arc<dyn Sink> = Arc::new(crate::sink::MemorySink::new());
+        let interval = Duration::from_millis(100);
+        let options = LoggerOptions::default();
+        assert_eq!(misconfiguration(1000, 100, interval, &sink, &options), None);
+        assert_eq!(
+            misconfiguration(64, 128, interval, &sink, &options),
+            Some("batch size exceeds queue capacity")
+        );
+        assert_eq!(
+            misconfiguration(64, 8, Duration::ZERO, &sink, &options),
+            Some("flush interval must be non-zero")
+        );
+        let options = LoggerOptions {
+            outputs: vec![(
+                Arc::new(crate::format::SimpleFormatter::new()),
+                sink.clone(),
+            )],
+            ..LoggerOptions::default()
+        };
+        assert_eq!(
+            misconfiguration(64, 8, interval, &sink, &options),
+            Some("the same sink is registered more than once")
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "strict mode: batch size exceeds queue capacity")]
+    fn test_strict_panics_in_debug_builds() {
+        let _ = AsyncLoggerBuilder::new()
+            .queue_capacity(64)
+            .batch_size(128)
+            .strict(true)
+            .build();
+    }
+
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn test_strict_returns_config_error_in_release_builds() {
+        let result = AsyncLoggerBuilder::new()
+            .queue_capacity(64)
+            .batch_size(128)
+            .strict(true)
+            .build();
+        assert!(matches!(
+            result,
+            Err(Error::Config("batch size exceeds queue capacity"))
+        ));
+    }
+
+    #[test]
+    fn test_builder_all_convenience_methods() {
+        let builder = AsyncLoggerBuilder::new()
+            .with_debug_level()
+            .with_trace_level() // This should override debug level
+            .with_json_formatting()
+            .with_simple_formatting() // This should override json formatting
+            .with_console_output();
+
+        assert_eq!(builder.level, Level::Trace);
+        assert!(builder.formatter.is_some());
+        assert!(builder.sink.is_some());
+    }
+
+    #[test]
+    fn test_builder_configuration_methods() {
+        let builder = AsyncLoggerBuilder::new()
+            .queue_capacity(2000)
+            .batch_size(50)
+            .flush_interval(Duration::from_millis(200));
+
+        // We can't directly access the fields, but we can verify the builder was configured
+        assert_eq!(builder.queue_capacity, 2000);
+        assert_eq!(builder.batch_size, 50);
+        assert_eq!(builder.flush_interval, Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_builder_message_transformer() {
+        let builder = AsyncLoggerBuilder::new().message_transformer(Arc::new(
+            crate::transform::MessageCatalog::new().with_entry("ID", "text"),
+        ));
+        assert!(builder.options.transformer.is_some());
+        assert!(builder.build().is_ok());
+    }
+
+    #[test]
+    fn test_builder_timestamp_base() {
+        // 基准进程内全局生效，沿用当前基准以免影响并行的测试
+        let base = crate::record::timestamp_base();
+        let logger = AsyncLoggerBuilder::new()
+            .timestamp_base(base)
+            .with_console_output()
+            .build()
+            .unwrap();
+        assert_eq!(crate::record::timestamp_base(), base);
+
+        let record = crate::Record::new(Level::Info, "t", "t.rs", 1, String::new());
+        let since_base = SystemTime::UNIX_EPOCH + Duration::from_nanos(record.timestamp() as u64)
+            - Duration::from_nanos(record.relative_timestamp());
+        assert_eq!(since_base, base);
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_builder_with_all_configurations() {
+        let result = AsyncLoggerBuilder::new()
+            .level(Level::Trace)
+            .with_json_formatting()
+            .with_console_output()
+            .queue_capacity(2000)
+            .batch_size(50)
+            .flush_interval(Duration::from_millis(200))
+            .build();
+
+        assert!(result.is_ok());
+    }
+}
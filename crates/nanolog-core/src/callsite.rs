@@ -0,0 +1,170 @@
+/*!
+调用点元数据缓存。
+
+每个日志宏展开处生成一个静态 [`Callsite`]，保存级别、模块、文件与行号，
+并缓存级别检查结果。全局日志器变更时递增全局代数使缓存失效，
+同一行的重复日志因此跳过全局日志器的加锁与级别判断。
+
+调用点首次使用时注册并分配进程内唯一的数字 ID，格式化器可输出该 ID，
+下游分析按 ID 聚合而无需解析 `file:line`，再通过 [`lookup`] 还原源码位置。
+*/
+
+use crate::Level;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// 全局缓存代数；从 1 开始，0 表示调用点尚未缓存
+static GENERATION: AtomicU64 = AtomicU64::new(1);
+
+/// 已注册的调用点，下标加一即为调用点 ID
+static REGISTRY: Mutex<Vec<&'static Callsite>> = Mutex::new(Vec::new());
+
+/// 按 ID 查找已注册的调用点
+pub fn lookup(id: u32) -> Option<&'static Callsite> {
+    let index = (id as usize).checked_sub(1)?;
+    REGISTRY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(index)
+        .copied()
+}
+
+/// 使所有调用点的级别缓存失效（全局日志器或其级别变更时调用）
+pub fn invalidate_all() {
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+/// 日志宏调用点
+pub struct Callsite {
+    level: Level,
+    module_path: &'static str,
+    file: &'static str,
+    line: u32,
+    /// 缓存的级别检查结果：`(代数 << 1) | 是否启用`
+    interest: AtomicU64,
+    /// 注册后分配的 ID（0 表示尚未注册）
+    id: AtomicU32,
+}
+
+impl Callsite {
+    /// 创建调用点（用于宏展开中的静态变量）
+    pub const fn new(
+        level: Level,
+        module_path: &'static str,
+        file: &'static str,
+        line: u32,
+    ) -> Self {
+        Self {
+            level,
+            module_path,
+            file,
+            line,
+            interest: AtomicU64::new(0),
+            id: AtomicU32::new(0),
+        }
+    }
+
+    /// 调用点的日志级别
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// 调用点所在模块
+    pub fn module_path(&self) -> &'static str {
+        self.module_path
+    }
+
+    /// 调用点所在文件
+    pub fn file(&self) -> &'static str {
+        self.file
+    }
+
+    /// 调用点所在行号
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// 调用点 ID（首次调用时注册）
+    #[inline]
+    pub fn id(&'static self) -> u32 {
+        match self.id.load(Ordering::Acquire) {
+            0 => self.register(),
+            id => id,
+        }
+    }
+
+    #[cold]
+    fn register(&'static self) -> u32 {
+        let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+        // 加锁后复查，避免并发重复注册
+        let id = self.id.load(Ordering::Acquire);
+        if id != 0 {
+            return id;
+        }
+        registry.push(self);
+        let id = registry.len() as u32;
+        self.id.store(id, Ordering::Release);
+        id
+    }
+
+    /// 检查全局日志器是否启用该调用点（命中缓存时无需访问全局日志器）
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled_with(|level| {
+            crate::global_logger()
+                .and_then(|global| global.get())
+                .is_some_and(|logger| logger.should_log(level))
+        })
+    }
+
+    #[inline]
+    fn is_enabled_with(&self, check: impl FnOnce(Level) -> bool) -> bool {
+        let generation = GENERATION.load(Ordering::Acquire);
+        let cached = self.interest.load(Ordering::Relaxed);
+        if cached >> 1 == generation {
+            return cached & 1 == 1;
+        }
+        let enabled = check(self.level);
+        self.interest
+            .store((generation << 1) | enabled as u64, Ordering::Relaxed);
+        enabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callsite_caches_until_invalidated() {
+        static CALLSITE: Callsite = Callsite::new(Level::Debug, module_path!(), file!(), line!());
+        let mut checks = 0;
+
+        // 其他测试可能并发使缓存失效，只断言首次检查与失效后必然重新检查
+        assert!(CALLSITE.is_enabled_with(|_| {
+            checks += 1;
+            true
+        }));
+        invalidate_all();
+        assert!(!CALLSITE.is_enabled_with(|level| {
+            checks += 1;
+            level > Level::Debug
+        }));
+        assert_eq!(checks, 2);
+        assert_eq!(CALLSITE.level(), Level::Debug);
+        assert_eq!(CALLSITE.file(), file!());
+    }
+
+    #[test]
+    fn test_callsite_id_is_stable_and_resolvable() {
+        static A: Callsite = Callsite::new(Level::Info, module_path!(), file!(), line!());
+        static B: Callsite = Callsite::new(Level::Warn, module_path!(), file!(), line!());
+
+        let a = A.id();
+        assert_ne!(a, 0);
+        assert_eq!(A.id(), a);
+        assert_ne!(B.id(), a);
+        assert!(lookup(a).is_some_and(|c| std::ptr::eq(c, &A)));
+        assert!(lookup(0).is_none());
+    }
+}
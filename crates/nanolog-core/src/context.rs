@@ -0,0 +1,272 @@
+/*!
+日志上下文。
+
+上下文是一组附加到当前线程所有日志记录上的结构化字段（如请求 ID）。
+[`wrap`] 捕获当前上下文并在每次轮询 future 时重新安装，
+启用 `tokio` 特性后 [`spawn`] 使上下文跨越 `tokio::spawn` 边界而无需手动复制。
+
+未接入分布式追踪的服务可用 [`push_request_id`] 一次调用生成可排序的请求 ID（ULID）
+并附加到当前上下文，使同一请求的日志得以关联。
+*/
+
+use crate::field::{Field, Value};
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 请求 ID 字段名
+pub const REQUEST_ID_KEY: &str = "request_id";
+
+/// ULID 使用的 Crockford Base32 字母表
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+thread_local! {
+    static CURRENT: RefCell<Context> = RefCell::new(Context::default());
+    /// ULID 生成状态：上次的毫秒时间戳与 80 位随机部分
+    static LAST_ULID: Cell<(u64, u128)> = const { Cell::new((0, 0)) };
+    /// 线程内随机数状态（splitmix64）
+    static RNG: Cell<u64> = Cell::new(RandomState::new().build_hasher().finish());
+}
+
+/// 上下文字段快照（克隆开销为一次引用计数）
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    fields: Arc<Vec<Field>>,
+}
+
+impl Context {
+    /// 获取当前线程的上下文
+    pub fn current() -> Self {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// 返回追加了字段的新上下文
+    pub fn with_field(&self, key: &'static str, value: impl Into<Value>) -> Self {
+        let mut fields = Vec::with_capacity(self.fields.len() + 1);
+        fields.extend(self.fields.iter().cloned());
+        fields.push(Field::new(key, value));
+        Self {
+            fields: Arc::new(fields),
+        }
+    }
+
+    /// 上下文字段
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// 在当前线程安装该上下文，守卫释放时恢复之前的上下文
+    pub fn enter(&self) -> ContextGuard {
+        let previous = CURRENT.with(|current| current.replace(self.clone()));
+        ContextGuard {
+            previous: Some(previous),
+            _not_send: PhantomData,
+        }
+    }
+}
+
+/// 上下文守卫；释放时恢复进入前的上下文
+#[must_use = "上下文在守卫释放时即被恢复"]
+pub struct ContextGuard {
+    previous: Option<Context>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            CURRENT.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+}
+
+/// 在当前上下文中追加字段，守卫释放时移除
+pub fn push(key: &'static str, value: impl Into<Value>) -> ContextGuard {
+    Context::current().with_field(key, value).enter()
+}
+
+/// 生成请求 ID（26 字符的 ULID）
+///
+/// 高 48 位为毫秒时间戳，低 80 位随机；同一线程同一毫秒内随机部分递增，
+/// 因此 ID 按字典序即按生成时间排序。
+pub fn new_request_id() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+        & ((1 << 48) - 1);
+    let random = LAST_ULID.with(|last| {
+        let (last_millis, last_random) = last.get();
+        let random = if millis == last_millis {
+            last_random.wrapping_add(1) & ((1 << 80) - 1)
+        } else {
+            ((u128::from(next_random()) << 64) | u128::from(next_random())) & ((1 << 80) - 1)
+        };
+        last.set((millis, random));
+        random
+    });
+
+    let mut value = (u128::from(millis) << 80) | random;
+    let mut encoded = [0u8; 26];
+    for slot in encoded.iter_mut().rev() {
+        *slot = CROCKFORD[(value & 31) as usize];
+        value >>= 5;
+    }
+    encoded.iter().map(|&b| b as char).collect()
+}
+
+/// 生成请求 ID 并附加到当前上下文（字段名 `request_id`），守卫释放时移除
+pub fn push_request_id() -> ContextGuard {
+    push(REQUEST_ID_KEY, new_request_id())
+}
+
+/// 线程内 splitmix64 随机数
+fn next_random() -> u64 {
+    RNG.with(|state| {
+        let next = state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        state.set(next);
+        let mut z = next;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    })
+}
+
+/// 将当前线程的上下文字段附加到记录上
+pub(crate) fn attach(fields: &mut Vec<Field>) {
+    CURRENT.with(|current| {
+        let current = current.borrow();
+        if !current.fields.is_empty() {
+            fields.extend(current.fields.iter().cloned());
+        }
+    });
+}
+
+/// 携带上下文的 future：每次轮询期间安装捕获的上下文
+pub struct WithContext<F> {
+    context: Context,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for WithContext<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let _guard = this.context.enter();
+        this.future.as_mut().poll(cx)
+    }
+}
+
+/// 捕获当前上下文并包装 future
+pub fn wrap<F: Future>(future: F) -> WithContext<F> {
+    WithContext {
+        context: Context::current(),
+        future: Box::pin(future),
+    }
+}
+
+/// 捕获当前上下文并在 tokio 任务中重新安装
+#[cfg(feature = "tokio")]
+pub fn spawn<F>(future: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(wrap(future))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::{RawWaker, RawWakerVTable, Waker};
+
+    fn noop_waker() -> Waker {
+        fn clone(_: *const ()) -> RawWaker {
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        // SAFETY: 所有回调均为空操作，不访问数据指针。
+        unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+    }
+
+    #[test]
+    fn test_push_and_restore() {
+        assert!(Context::current().fields().is_empty());
+        {
+            let _a = push("request_id", "r-1");
+            let _b = push("user", 7u64);
+            let keys: Vec<_> = Context::current()
+                .fields()
+                .iter()
+                .map(|f| f.key())
+                .collect();
+            assert_eq!(keys, ["request_id", "user"]);
+        }
+        assert!(Context::current().fields().is_empty());
+    }
+
+    #[test]
+    fn test_request_id_sortable() {
+        let ids: Vec<String> = (0..100).map(|_| new_request_id()).collect();
+        for id in &ids {
+            assert_eq!(id.len(), 26);
+            assert!(id.bytes().all(|b| CROCKFORD.contains(&b)));
+        }
+        let mut sorted = ids.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted, ids);
+    }
+
+    #[test]
+    fn test_push_request_id() {
+        let _guard = push_request_id();
+        let context = Context::current();
+        assert_eq!(context.fields()[0].key(), REQUEST_ID_KEY);
+        assert!(matches!(context.fields()[0].value(), Value::Str(id) if id.len() == 26));
+    }
+
+    #[test]
+    fn test_wrap_reinstalls_context_on_other_thread() {
+        let future = {
+            let _guard = push("request_id", "r-2");
+            wrap(async { Context::current().fields().to_vec() })
+        };
+
+        let fields = std::thread::spawn(move || {
+            let mut future = Box::pin(future);
+            let waker = noop_waker();
+            let mut cx = TaskContext::from_waker(&waker);
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(fields) => fields,
+                Poll::Pending => Vec::new(),
+            }
+        })
+        .join()
+        .unwrap();
+        assert_eq!(fields, vec![Field::new("request_id", "r-2")]);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_spawn_propagates_context() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let fields = runtime.block_on(async {
+            let _guard = push("request_id", "r-3");
+            spawn(async { Context::current().fields().to_vec() })
+                .await
+                .unwrap()
+        });
+        assert_eq!(fields, vec![Field::new("request_id", "r-3")]);
+    }
+}
@@ -0,0 +1,134 @@
+/*!
+重复消息抑制。
+
+消费者线程上可选的去重阶段：窗口内连续出现的相同记录（级别、目标与消息均相同）只写出首条，
+其余计入重复数；出现不同的记录或窗口结束后，先写出一条 syslog 风格的汇总
+`last message repeated N times`，避免失控的重试循环写满磁盘。
+
+汇总在下一条不同的记录之前、或窗口结束后的批尾写出；同步写出的记录不参与去重。
+*/
+
+use crate::{Level, Record};
+use std::time::{Duration, Instant};
+
+/// 去重状态（消费者线程独占）
+pub(crate) struct Dedup {
+    window: Duration,
+    level: Level,
+    target: &'static str,
+    /// 最近写出的消息（缓冲区复用）
+    message: String,
+    /// 最近写出的记录首次出现的时刻（`None` 表示尚无可比较的记录）
+    since: Option<Instant>,
+    /// 之后被抑制的重复次数
+    repeats: u64,
+}
+
+impl Dedup {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            level: Level::Info,
+            target: "",
+            message: String::new(),
+            since: None,
+            repeats: 0,
+        }
+    }
+
+    /// 检查记录：与上一条相同且在窗口内时抑制并返回 `true`；
+    /// 否则记下该记录，并通过 `summary` 交出需在它之前写出的汇总
+    pub(crate) fn check(
+        &mut self,
+        record: &Record,
+        now: Instant,
+        summary: &mut Option<Record>,
+    ) -> bool {
+        if let Some(since) = self.since
+            && now.duration_since(since) < self.window
+            && record.level() == self.level
+            && record.target() == self.target
+            && record.message() == self.message
+        {
+            self.repeats += 1;
+            return true;
+        }
+        *summary = self.take_summary();
+        self.level = record.level();
+        self.target = record.target();
+        self.message.clear();
+        self.message.push_str(record.message());
+        self.since = Some(now);
+        false
+    }
+
+    /// 窗口已结束时交出待写出的汇总，之后相同的记录重新写出
+    pub(crate) fn expire(&mut self, now: Instant) -> Option<Record> {
+        let since = self.since?;
+        if self.repeats == 0 || now.duration_since(since) < self.window {
+            return None;
+        }
+        self.since = None;
+        self.take_summary()
+    }
+
+    fn take_summary(&mut self) -> Option<Record> {
+        if self.repeats == 0 {
+            return None;
+        }
+        let repeats = std::mem::take(&mut self.repeats);
+        let noun = if repeats == 1 { "time" } else { "times" };
+        Some(Record::new(
+            self.level,
+            self.target,
+            "",
+            0,
+            format!("last message repeated {} {}", repeats, noun),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(message: &str) -> Record {
+        Record::new(Level::Warn, "retry", file!(), 1, message.to_string())
+    }
+
+    #[test]
+    fn test_collapses_repeats_until_different_message() {
+        let mut dedup = Dedup::new(Duration::from_secs(60));
+        let now = Instant::now();
+        let mut summary = None;
+        assert!(!dedup.check(&record("timeout"), now, &mut summary));
+        for _ in 0..3 {
+            assert!(dedup.check(&record("timeout"), now, &mut summary));
+        }
+        assert!(summary.is_none());
+
+        assert!(!dedup.check(&record("connected"), now, &mut summary));
+        let summary = summary.unwrap();
+        assert_eq!(summary.message(), "last message repeated 3 times");
+        assert_eq!(summary.level(), Level::Warn);
+        assert_eq!(summary.target(), "retry");
+    }
+
+    #[test]
+    fn test_window_expiry_flushes_summary() {
+        let window = Duration::from_millis(10);
+        let mut dedup = Dedup::new(window);
+        let start = Instant::now();
+        let mut summary = None;
+        assert!(!dedup.check(&record("timeout"), start, &mut summary));
+        assert!(dedup.check(&record("timeout"), start, &mut summary));
+        assert!(dedup.expire(start).is_none());
+
+        let later = start + window;
+        let expired = dedup.expire(later).unwrap();
+        assert_eq!(expired.message(), "last message repeated 1 time");
+        // 窗口结束后相同的记录重新写出
+        assert!(!dedup.check(&record("timeout"), later, &mut summary));
+        assert!(summary.is_none());
+    }
+}
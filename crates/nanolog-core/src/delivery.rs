@@ -0,0 +1,351 @@
+/*!
+网络输出目标的确认式投递统计。
+
+批量网络输出目标以 [`Transport`] 发送整批数据并等待接收方确认。[`DeliverySink`] 为每批分配
+批次 ID，否认或超时后按退避重发同一批次（ID 不变、尝试次数递增），接收方可据
+（发送方 ID，批次 ID）在去重窗口内丢弃重复批次，从而实现近似恰好一次的投递。
+批次名称（传输携带的文本形式 ID）由 [`IdGenerator`] 按批次 ID 生成，默认即十进制的批次 ID。
+已确认的投递数与本地写入数分开统计。
+*/
+
+use crate::naming::{Counter, IdGenerator};
+use crate::sink::Sink;
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// 接收方对一个批次的答复
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ack {
+    /// 已接收并持久化
+    Delivered,
+    /// 拒绝，需重发
+    Rejected,
+}
+
+/// 待发送的批次
+#[derive(Debug)]
+pub struct Batch<'a> {
+    /// 发送方 ID（每个 [`DeliverySink`] 实例唯一，跨进程重启不重复）
+    pub sender: &'a str,
+    /// 批次 ID（发送方内单调递增，重发时不变）
+    pub id: u64,
+    /// 批次名称（由批次 ID 生成，重发时不变）
+    pub name: &'a str,
+    /// 第几次尝试（从 1 开始）
+    pub attempt: u32,
+    /// 接收方应保留已见批次 ID 的最短时长，覆盖该批次所有可能的重发
+    pub dedup_window: Duration,
+    /// 批内各条已格式化的记录
+    pub records: &'a [Vec<u8>],
+}
+
+/// 批次传输接口
+pub trait Transport: Send + Sync {
+    /// 发送一个批次并等待确认；返回错误（含超时）视为未确认，将重发
+    fn send(&self, batch: &Batch<'_>) -> io::Result<Ack>;
+
+    /// 关闭传输
+    fn shutdown(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 投递统计快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeliveryStats {
+    /// 提交发送的批次数
+    pub batches_sent: u64,
+    /// 已确认的批次数
+    pub batches_delivered: u64,
+    /// 重试耗尽仍未确认的批次数
+    pub batches_failed: u64,
+    /// 重发次数
+    pub resends: u64,
+    /// 提交发送的记录数
+    pub records_sent: u64,
+    /// 已确认的记录数
+    pub records_delivered: u64,
+    /// 未确认而放弃的记录数
+    pub records_failed: u64,
+}
+
+/// 投递计数器
+#[derive(Default)]
+struct DeliveryCounters {
+    batches_sent: AtomicU64,
+    batches_delivered: AtomicU64,
+    batches_failed: AtomicU64,
+    resends: AtomicU64,
+    records_sent: AtomicU64,
+    records_delivered: AtomicU64,
+    records_failed: AtomicU64,
+}
+
+/// 确认式投递输出目标
+///
+/// 每次写入作为一个批次经 [`Transport`] 发送，未确认时在消费者线程上按退避重发，
+/// 达到最大尝试次数后放弃并返回错误。
+pub struct DeliverySink<T> {
+    transport: T,
+    sender: String,
+    max_attempts: u32,
+    retry_backoff: Duration,
+    next_id: AtomicU64,
+    batch_ids: Arc<dyn IdGenerator>,
+    counters: DeliveryCounters,
+}
+
+impl<T: Transport> DeliverySink<T> {
+    /// 包装批次传输（默认最多尝试 3 次，退避 100ms 起翻倍）
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            sender: crate::context::new_request_id(),
+            max_attempts: 3,
+            retry_backoff: Duration::from_millis(100),
+            next_id: AtomicU64::new(1),
+            batch_ids: Arc::new(Counter::new()),
+            counters: DeliveryCounters::default(),
+        }
+    }
+
+    /// 设置每批最大尝试次数（含首次发送）
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// 设置首次重发前的退避时长，之后逐次翻倍
+    pub fn with_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// 设置批次名称的生成器（默认 [`Counter`]），如 [`Ulid`](crate::naming::Ulid) 使名称跨进程唯一
+    pub fn with_batch_ids(mut self, generator: Arc<dyn IdGenerator>) -> Self {
+        self.batch_ids = generator;
+        self
+    }
+
+    /// 发送方 ID
+    pub fn sender(&self) -> &str {
+        &self.sender
+    }
+
+    /// 获取内部传输
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// 获取投递统计快照
+    pub fn stats(&self) -> DeliveryStats {
+        let c = &self.counters;
+        DeliveryStats {
+            batches_sent: c.batches_sent.load(Ordering::Relaxed),
+            batches_delivered: c.batches_delivered.load(Ordering::Relaxed),
+            batches_failed: c.batches_failed.load(Ordering::Relaxed),
+            resends: c.resends.load(Ordering::Relaxed),
+            records_sent: c.records_sent.load(Ordering::Relaxed),
+            records_delivered: c.records_delivered.load(Ordering::Relaxed),
+            records_failed: c.records_failed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 所有重发退避之和，即同一批次 ID 可能出现的最长时间跨度
+    fn dedup_window(&self) -> Duration {
+        (0..self.max_attempts.saturating_sub(1))
+            .map(|i| self.retry_backoff.saturating_mul(1 << i.min(16)))
+            .fold(Duration::ZERO, Duration::saturating_add)
+    }
+
+    fn deliver(&self, records: &[Vec<u8>]) -> io::Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        let count = records.len() as u64;
+        let c = &self.counters;
+        c.batches_sent.fetch_add(1, Ordering::Relaxed);
+        c.records_sent.fetch_add(count, Ordering::Relaxed);
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let name = self.batch_ids.id(id);
+        let mut batch = Batch {
+            sender: &self.sender,
+            id,
+            name: &name,
+            attempt: 0,
+            dedup_window: self.dedup_window(),
+            records,
+        };
+        let mut backoff = self.retry_backoff;
+        let mut last_err = None;
+        while batch.attempt < self.max_attempts {
+            if batch.attempt > 0 {
+                c.resends.fetch_add(1, Ordering::Relaxed);
+                std::thread::sleep(backoff);
+                backoff = backoff.saturating_mul(2);
+            }
+            batch.attempt += 1;
+            match self.transport.send(&batch) {
+                Ok(Ack::Delivered) => {
+                    c.batches_delivered.fetch_add(1, Ordering::Relaxed);
+                    c.records_delivered.fetch_add(count, Ordering::Relaxed);
+                    return Ok(());
+                }
+                Ok(Ack::Rejected) => last_err = None,
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        c.batches_failed.fetch_add(1, Ordering::Relaxed);
+        c.records_failed.fetch_add(count, Ordering::Relaxed);
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::other(format!(
+                "batch {} rejected after {} attempts",
+                batch.name, batch.attempt
+            ))
+        }))
+    }
+}
+
+impl<T: Transport> Sink for DeliverySink<T> {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        self.deliver(&[data.to_vec()])
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        self.deliver(data)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        // 每批在写入时已同步等待确认
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.transport.shutdown()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// 按预设答复序列应答并记录收到的批次
+    struct ScriptedTransport {
+        replies: Mutex<Vec<io::Result<Ack>>>,
+        seen: Mutex<Vec<(String, u64, u32, usize)>>,
+        names: Mutex<Vec<String>>,
+    }
+
+    impl ScriptedTransport {
+        fn new(mut replies: Vec<io::Result<Ack>>) -> Self {
+            replies.reverse();
+            Self {
+                replies: Mutex::new(replies),
+                seen: Mutex::new(Vec::new()),
+                names: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl Transport for ScriptedTransport {
+        fn send(&self, batch: &Batch<'_>) -> io::Result<Ack> {
+            self.seen
+                .lock()
+                .map_err(|_| io::Error::other("poisoned"))?
+                .push((
+                    batch.sender.to_string(),
+                    batch.id,
+                    batch.attempt,
+                    batch.records.len(),
+                ));
+            self.names
+                .lock()
+                .map_err(|_| io::Error::other("poisoned"))?
+                .push(batch.name.to_string());
+            self.replies
+                .lock()
+                .map_err(|_| io::Error::other("poisoned"))?
+                .pop()
+                .unwrap_or(Ok(Ack::Delivered))
+        }
+    }
+
+    #[test]
+    fn test_resends_same_batch_until_acknowledged() {
+        let sink = DeliverySink::new(ScriptedTransport::new(vec![
+            Ok(Ack::Rejected),
+            Err(io::Error::new(io::ErrorKind::TimedOut, "no ack")),
+        ]))
+        .with_retry_backoff(Duration::from_millis(1));
+
+        assert!(sink.write_batch(&[b"a".to_vec(), b"b".to_vec()]).is_ok());
+        assert!(sink.write(b"c").is_ok());
+
+        let sender = sink.sender().to_string();
+        let seen = sink.transport().seen.lock().map(|s| s.clone());
+        assert_eq!(
+            seen.ok(),
+            Some(vec![
+                (sender.clone(), 1, 1, 2),
+                (sender.clone(), 1, 2, 2),
+                (sender.clone(), 1, 3, 2),
+                (sender, 2, 1, 1),
+            ])
+        );
+        assert_eq!(
+            sink.stats(),
+            DeliveryStats {
+                batches_sent: 2,
+                batches_delivered: 2,
+                batches_failed: 0,
+                resends: 2,
+                records_sent: 3,
+                records_delivered: 3,
+                records_failed: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_gives_up_after_max_attempts() {
+        let sink = DeliverySink::new(ScriptedTransport::new(vec![
+            Ok(Ack::Rejected),
+            Ok(Ack::Rejected),
+        ]))
+        .with_max_attempts(2)
+        .with_retry_backoff(Duration::from_millis(1));
+
+        assert!(sink.write(b"x").is_err());
+        let stats = sink.stats();
+        assert_eq!(stats.records_sent, 1);
+        assert_eq!(stats.records_delivered, 0);
+        assert_eq!(stats.records_failed, 1);
+        assert_eq!(stats.batches_failed, 1);
+        assert_eq!(sink.dedup_window(), Duration::from_millis(1));
+    }
+
+    #[test]
+    fn test_batch_names_from_generator() {
+        let sink = DeliverySink::new(ScriptedTransport::new(vec![Ok(Ack::Rejected)]))
+            .with_retry_backoff(Duration::from_millis(1))
+            .with_batch_ids(Arc::new(Counter::zero_padded(4)));
+        assert!(sink.write(b"a").is_ok());
+        assert!(sink.write(b"b").is_ok());
+
+        // 重发沿用同一名称
+        let names = sink.transport().names.lock().map(|n| n.clone());
+        assert_eq!(
+            names.ok(),
+            Some(vec![
+                "0001".to_string(),
+                "0001".to_string(),
+                "0002".to_string()
+            ])
+        );
+    }
+}
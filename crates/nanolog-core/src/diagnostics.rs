@@ -0,0 +1,240 @@
+/*!
+诊断通道。
+
+日志库自身的异常情况（如热路径超出延迟预算）不能再通过日志器记录，
+因此通过独立的诊断处理器上报，默认输出到标准错误。
+*/
+
+use crate::Level;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// 保留的最近诊断事件条数（供调试快照使用）
+const RECENT_CAPACITY: usize = 8;
+
+/// 诊断事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// 单次 `log()` 调用耗时超出延迟预算
+    LatencyBudgetExceeded {
+        /// 实际耗时
+        elapsed: Duration,
+        /// 配置的预算
+        budget: Duration,
+        /// 日志记录的目标
+        target: &'static str,
+        /// 日志记录所在文件
+        file: &'static str,
+        /// 日志记录所在行号
+        line: u32,
+    },
+    /// 无法使用大页，已退化为普通页面
+    HugePagesUnavailable {
+        /// 失败原因
+        reason: String,
+    },
+    /// 输出目标操作失败（仅在由正常转为失败时上报一次）
+    SinkError {
+        /// 失败的操作（`write` / `flush`）
+        operation: &'static str,
+        /// 错误描述
+        error: String,
+    },
+    /// 有记录待处理但处理进度停滞超过看门狗时限（输出目标卡死或消费者线程退出）
+    ConsumerStalled {
+        /// 已停滞时长
+        stalled_for: Duration,
+        /// 待处理的记录数
+        pending: u64,
+        /// 是否已切换到后备输出目标
+        switched_to_fallback: bool,
+    },
+    /// 后备链降级到下一个输出目标
+    SinkDemoted {
+        /// 原活动目标在链中的位置
+        from: usize,
+        /// 新活动目标在链中的位置
+        to: usize,
+        /// 最后一次失败的错误描述
+        error: String,
+    },
+    /// 后备链健康检查通过，升级回更高优先级的输出目标
+    SinkPromoted {
+        /// 原活动目标在链中的位置
+        from: usize,
+        /// 新活动目标在链中的位置
+        to: usize,
+    },
+    /// 无法启动看门狗线程
+    WatchdogUnavailable {
+        /// 失败原因
+        reason: String,
+    },
+    /// 消费者线程的绑核或调度优先级设置未生效
+    ConsumerThreadSetupFailed {
+        /// 未生效的设置（`core` / `priority`）
+        setting: &'static str,
+        /// 错误描述
+        error: String,
+    },
+    /// 轮转文件压缩失败（原文件保留）
+    CompressionFailed {
+        /// 轮转文件路径
+        path: String,
+        /// 错误描述
+        error: String,
+    },
+    /// 运行时调整了日志级别（配置变更审计）
+    LevelChanged {
+        /// 原级别
+        from: Level,
+        /// 新级别
+        to: Level,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::LatencyBudgetExceeded {
+                elapsed,
+                budget,
+                target,
+                file,
+                line,
+            } => write!(
+                f,
+                "log() call took {:?}, exceeding budget {:?} ({} at {}:{})",
+                elapsed, budget, target, file, line
+            ),
+            Diagnostic::HugePagesUnavailable { reason } => {
+                write!(f, "huge pages unavailable, using regular pages: {}", reason)
+            }
+            Diagnostic::SinkError { operation, error } => {
+                write!(f, "sink {} failed: {}", operation, error)
+            }
+            Diagnostic::ConsumerStalled {
+                stalled_for,
+                pending,
+                switched_to_fallback,
+            } => {
+                write!(
+                    f,
+                    "consumer stalled for {:?} with {} pending records",
+                    stalled_for, pending
+                )?;
+                if *switched_to_fallback {
+                    f.write_str(", switched to fallback sink")?;
+                }
+                Ok(())
+            }
+            Diagnostic::SinkDemoted { from, to, error } => {
+                write!(f, "sink chain demoted from #{} to #{}: {}", from, to, error)
+            }
+            Diagnostic::SinkPromoted { from, to } => {
+                write!(f, "sink chain promoted from #{} to #{}", from, to)
+            }
+            Diagnostic::WatchdogUnavailable { reason } => {
+                write!(f, "watchdog thread unavailable: {}", reason)
+            }
+            Diagnostic::ConsumerThreadSetupFailed { setting, error } => {
+                write!(f, "consumer thread {} not applied: {}", setting, error)
+            }
+            Diagnostic::CompressionFailed { path, error } => {
+                write!(f, "failed to compress rotated file {}: {}", path, error)
+            }
+            Diagnostic::LevelChanged { from, to } => {
+                write!(f, "log level changed: {} -> {}", from.as_str(), to.as_str())
+            }
+        }
+    }
+}
+
+/// 诊断处理器接口
+pub trait DiagnosticHandler: Send + Sync {
+    /// 处理诊断事件
+    fn handle(&self, diagnostic: &Diagnostic);
+}
+
+impl<F> DiagnosticHandler for F
+where
+    F: Fn(&Diagnostic) + Send + Sync,
+{
+    fn handle(&self, diagnostic: &Diagnostic) {
+        self(diagnostic)
+    }
+}
+
+/// 输出到标准错误的诊断处理器（默认）
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StderrDiagnostics;
+
+impl DiagnosticHandler for StderrDiagnostics {
+    fn handle(&self, diagnostic: &Diagnostic) {
+        let _ = writeln!(std::io::stderr(), "[nanolog] {}", diagnostic);
+    }
+}
+
+/// 转发诊断事件并保留最近若干条（附发生时间）
+pub(crate) struct RecentDiagnostics {
+    inner: Arc<dyn DiagnosticHandler>,
+    recent: Mutex<VecDeque<(SystemTime, Diagnostic)>>,
+}
+
+impl RecentDiagnostics {
+    pub(crate) fn new(inner: Arc<dyn DiagnosticHandler>) -> Self {
+        Self {
+            inner,
+            recent: Mutex::new(VecDeque::with_capacity(RECENT_CAPACITY)),
+        }
+    }
+
+    /// 最近的诊断事件（由旧到新）
+    pub(crate) fn recent(&self) -> Vec<(SystemTime, Diagnostic)> {
+        self.recent
+            .lock()
+            .map(|recent| recent.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl DiagnosticHandler for RecentDiagnostics {
+    fn handle(&self, diagnostic: &Diagnostic) {
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() == RECENT_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back((SystemTime::now(), diagnostic.clone()));
+        }
+        self.inner.handle(diagnostic);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_display() {
+        let d = Diagnostic::LatencyBudgetExceeded {
+            elapsed: Duration::from_micros(12),
+            budget: Duration::from_micros(5),
+            target: "app",
+            file: "main.rs",
+            line: 3,
+        };
+        assert_eq!(
+            d.to_string(),
+            "log() call took 12µs, exceeding budget 5µs (app at main.rs:3)"
+        );
+
+        let d = Diagnostic::LevelChanged {
+            from: Level::Info,
+            to: Level::Debug,
+        };
+        assert_eq!(d.to_string(), "log level changed: INFO -> DEBUG");
+    }
+}
@@ -22,6 +22,15 @@ pub enum Error {
     /// 队列错误，如队列已满或为空
     Queue(&'static str),
 
+    /// 队列已满，记录未入队（非阻塞发布时返回）
+    QueueFull,
+
+    /// 过载降级拒绝了低级别记录（非阻塞发布时返回）
+    Overloaded,
+
+    /// 令牌桶限流拒绝了记录（非阻塞发布时返回）
+    RateLimited,
+
     /// 配置错误，如无效的配置值
     Config(&'static str),
 
@@ -51,6 +60,9 @@ impl fmt::Display for Error {
             Error::NotInitialized => write!(f, "logger not initialized"),
             Error::Io(err) => write!(f, "I/O error: {}", err),
             Error::Queue(msg) => write!(f, "queue error: {}", msg),
+            Error::QueueFull => write!(f, "queue full"),
+            Error::Overloaded => write!(f, "queue overloaded, record shed"),
+            Error::RateLimited => write!(f, "rate limited"),
             Error::Config(msg) => write!(f, "configuration error: {}", msg),
             Error::Memory(msg) => write!(f, "memory error: {}", msg),
             Error::Formatting(msg) => write!(f, "formatting error: {}", msg),
@@ -92,6 +104,15 @@ mod tests {
         let err = Error::NotInitialized;
         assert_eq!(err.to_string(), "logger not initialized");
 
+        let err = Error::QueueFull;
+        assert_eq!(err.to_string(), "queue full");
+
+        let err = Error::Overloaded;
+        assert_eq!(err.to_string(), "queue overloaded, record shed");
+
+        let err = Error::RateLimited;
+        assert_eq!(err.to_string(), "rate limited");
+
         let err = Error::Config("invalid level");
         assert_eq!(err.to_string(), "configuration error: invalid level");
 
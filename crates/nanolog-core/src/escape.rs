@@ -0,0 +1,154 @@
+/*!
+JSON 字符串转义。
+
+按 8 字节字（SWAR）批量扫描需要转义的字节（`"`、`\` 与控制字符），
+其间无特殊字符的连续片段整段拷贝，避免逐字符处理。
+*/
+
+const LO: u64 = 0x0101_0101_0101_0101;
+const HI: u64 = 0x8080_8080_8080_8080;
+
+/// 将字符串按 JSON 规则转义后追加到 `out`（不含两侧引号）
+pub fn escape_json_into(out: &mut Vec<u8>, s: &str) {
+    let bytes = s.as_bytes();
+    out.reserve(bytes.len());
+
+    let mut start = 0;
+    let mut pos = 0;
+    while let Some(offset) = find_special(&bytes[pos..]) {
+        let i = pos + offset;
+        out.extend_from_slice(&bytes[start..i]);
+        push_escaped(out, bytes[i]);
+        pos = i + 1;
+        start = pos;
+    }
+    out.extend_from_slice(&bytes[start..]);
+}
+
+/// 将字符串按 JSON 规则转义并返回新字符串（不含两侧引号）
+pub fn escape_json(s: &str) -> String {
+    let mut out = Vec::with_capacity(s.len());
+    escape_json_into(&mut out, s);
+    // 仅替换 ASCII 字节为 ASCII 序列，UTF-8 有效性保持不变
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// 单个字符转义后的字节长度
+#[inline]
+pub(crate) fn escaped_len(c: char) -> usize {
+    match c {
+        '"' | '\\' | '\n' | '\r' | '\t' | '\x08' | '\x0c' => 2,
+        c if (c as u32) < 0x20 => 6,
+        c => c.len_utf8(),
+    }
+}
+
+/// 查找第一个需要转义的字节位置
+#[inline]
+fn find_special(bytes: &[u8]) -> Option<usize> {
+    let mut chunks = bytes.chunks_exact(8);
+    let mut base = 0;
+    for chunk in &mut chunks {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(chunk);
+        let mask = special_mask(u64::from_le_bytes(word));
+        if mask != 0 {
+            return Some(base + (mask.trailing_zeros() / 8) as usize);
+        }
+        base += 8;
+    }
+    chunks
+        .remainder()
+        .iter()
+        .position(|&b| needs_escape(b))
+        .map(|i| base + i)
+}
+
+/// 每个需要转义的字节对应最高位被置位的掩码
+///
+/// 借位传播可能在真实命中之后的字节产生误报，但最低命中位置总是准确的。
+#[inline]
+fn special_mask(word: u64) -> u64 {
+    let quote = word ^ (LO * b'"' as u64);
+    let backslash = word ^ (LO * b'\\' as u64);
+    let zero = |x: u64| x.wrapping_sub(LO) & !x;
+    let control = word.wrapping_sub(LO * 0x20) & !word;
+    (zero(quote) | zero(backslash) | control) & HI
+}
+
+#[inline]
+fn needs_escape(b: u8) -> bool {
+    b == b'"' || b == b'\\' || b < 0x20
+}
+
+#[inline]
+fn push_escaped(out: &mut Vec<u8>, b: u8) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    match b {
+        b'"' => out.extend_from_slice(b"\\\""),
+        b'\\' => out.extend_from_slice(b"\\\\"),
+        b'\n' => out.extend_from_slice(b"\\n"),
+        b'\r' => out.extend_from_slice(b"\\r"),
+        b'\t' => out.extend_from_slice(b"\\t"),
+        0x08 => out.extend_from_slice(b"\\b"),
+        0x0c => out.extend_from_slice(b"\\f"),
+        _ => out.extend_from_slice(&[
+            b'\\',
+            b'u',
+            b'0',
+            b'0',
+            HEX[(b >> 4) as usize],
+            HEX[(b & 0xf) as usize],
+        ]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(s: &str) -> String {
+        let mut out = Vec::new();
+        for &b in s.as_bytes() {
+            if needs_escape(b) {
+                push_escaped(&mut out, b);
+            } else {
+                out.push(b);
+            }
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_escape_json_special_characters() {
+        assert_eq!(escape_json("plain text"), "plain text");
+        assert_eq!(escape_json(r#"say "hi"\n"#), r#"say \"hi\"\\n"#);
+        assert_eq!(escape_json("a\nb\tc\r\x08\x0c"), "a\\nb\\tc\\r\\b\\f");
+        assert_eq!(escape_json("\x01\x1f"), "\\u0001\\u001f");
+        assert_eq!(escape_json("日志 \"中文\""), "日志 \\\"中文\\\"");
+    }
+
+    #[test]
+    fn test_escaped_len_matches_escape() {
+        for c in ['a', '"', '\\', '\n', '\x01', '\x0c', 'é', '日'] {
+            assert_eq!(
+                escaped_len(c),
+                escape_json(c.encode_utf8(&mut [0; 4])).len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_escape_json_matches_scalar_at_every_offset() {
+        for special in ['"', '\\', '\n', '\x00', '\x1f'] {
+            for len in 0..24 {
+                for at in 0..=len {
+                    let mut s: String = "x".repeat(len);
+                    s.insert(at, special);
+                    s.push_str("é~\x7f");
+                    assert_eq!(escape_json(&s), scalar(&s), "{:?}", s);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,519 @@
+/*!
+结构化字段。
+
+字段值覆盖日志中最常见的类型（整数含 128 位 ID、浮点、字符串、时间、时长、
+网络地址，以及 `uuid` 特性下的 UUID），并直接序列化为 JSON 与 logfmt，
+不经过 `fmt` 中间字符串。
+
+值可嵌套为数组与映射（如请求头、配置快照），序列化时超过 [`MAX_DEPTH`] 的层级
+以 `"..."` 占位。
+
+[`FieldLimits`] 限制单个字段与单条记录的序列化字节数，超出的值被截断为字符串，
+并在记录上追加 `_truncated = true` 字段。
+*/
+
+use crate::escape::escape_json_into;
+use chrono::{DateTime, Utc};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 截断标记字段的键
+pub const TRUNCATED_KEY: &str = "_truncated";
+
+/// 嵌套值的最大序列化深度
+pub const MAX_DEPTH: usize = 8;
+
+/// 错误消息字段的键（见 [`Record::with_error`](crate::Record::with_error)）
+pub const ERROR_MESSAGE_KEY: &str = "error.message";
+
+/// 错误原因链字段的键，依次对应 `source()` 链上的第 n 层原因；更深的原因不再记录
+pub const ERROR_CAUSE_KEYS: [&str; 8] = [
+    "error.cause[0]",
+    "error.cause[1]",
+    "error.cause[2]",
+    "error.cause[3]",
+    "error.cause[4]",
+    "error.cause[5]",
+    "error.cause[6]",
+    "error.cause[7]",
+];
+
+/// 字段值
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// 布尔值
+    Bool(bool),
+    /// 有符号整数
+    I64(i64),
+    /// 无符号整数
+    U64(u64),
+    /// 128 位有符号整数
+    I128(i128),
+    /// 128 位无符号整数（如请求 ID）
+    U128(u128),
+    /// 浮点数（非有限值在 JSON 中输出为 `null`）
+    F64(f64),
+    /// 字符串
+    Str(Cow<'static, str>),
+    /// 系统时间（输出为 UTC RFC3339）
+    SystemTime(SystemTime),
+    /// 时长（JSON 中输出为纳秒整数）
+    Duration(Duration),
+    /// IP 地址
+    IpAddr(IpAddr),
+    /// 套接字地址
+    SocketAddr(SocketAddr),
+    /// UUID（连字符小写形式）
+    #[cfg(feature = "uuid")]
+    Uuid(uuid::Uuid),
+    /// 由 `serde` 序列化得到的紧凑 JSON（原样输出）
+    #[cfg(feature = "serde")]
+    Json(Box<str>),
+    /// 数组
+    Array(Vec<Value>),
+    /// 映射（保持插入顺序）
+    Map(Vec<(Cow<'static, str>, Value)>),
+}
+
+/// 结构化字段：键与值
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    key: &'static str,
+    value: Value,
+}
+
+impl Field {
+    /// 创建字段
+    pub fn new(key: &'static str, value: impl Into<Value>) -> Self {
+        Self {
+            key,
+            value: value.into(),
+        }
+    }
+
+    /// 字段键
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    /// 字段值
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+}
+
+/// 字段大小限制
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FieldLimits {
+    max_field_bytes: Option<usize>,
+    max_record_bytes: Option<usize>,
+}
+
+impl FieldLimits {
+    /// 创建不限制大小的配置
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置单个字段值的最大序列化字节数
+    pub fn max_field_bytes(mut self, bytes: usize) -> Self {
+        self.max_field_bytes = Some(bytes);
+        self
+    }
+
+    /// 设置单条记录全部字段值的最大序列化字节数
+    pub fn max_record_bytes(mut self, bytes: usize) -> Self {
+        self.max_record_bytes = Some(bytes);
+        self
+    }
+
+    /// 截断超限的字段值；发生截断时追加 `_truncated = true` 并返回 `true`
+    pub fn apply(&self, fields: &mut Vec<Field>) -> bool {
+        let mut remaining = self.max_record_bytes.unwrap_or(usize::MAX);
+        let mut truncated = false;
+        for field in fields.iter_mut() {
+            let cap = self.max_field_bytes.unwrap_or(usize::MAX).min(remaining);
+            let len = field.value.serialized_len();
+            if len > cap && field.value.truncate(cap) {
+                truncated = true;
+            }
+            remaining = remaining.saturating_sub(len.min(cap));
+        }
+        if truncated {
+            fields.push(Field::new(TRUNCATED_KEY, true));
+        }
+        truncated
+    }
+}
+
+impl Value {
+    /// 在调用线程上将任意 `Serialize` 值序列化为紧凑 JSON；失败时记录错误描述
+    #[cfg(feature = "serde")]
+    pub fn serialize<T: serde::Serialize + ?Sized>(value: &T) -> Self {
+        match serde_json::to_string(value) {
+            Ok(json) => Value::Json(json.into_boxed_str()),
+            Err(err) => Value::Str(format!("<serialize error: {}>", err).into()),
+        }
+    }
+
+    /// 序列化后的近似字节数（字符串按原始长度计，复合值按紧凑 JSON 计）
+    fn serialized_len(&self) -> usize {
+        match self {
+            Value::Str(v) => v.len(),
+            #[cfg(feature = "serde")]
+            Value::Json(v) => v.len(),
+            _ => {
+                let mut buf = Vec::new();
+                self.write_json(&mut buf);
+                buf.len()
+            }
+        }
+    }
+
+    /// 截断为不超过 `cap` 字节的字符串；标量值无法截断时返回 `false`
+    fn truncate(&mut self, cap: usize) -> bool {
+        let text = match self {
+            Value::Str(v) => std::mem::take(v).into_owned(),
+            #[cfg(feature = "serde")]
+            Value::Json(v) => std::mem::take(v).into_string(),
+            Value::Array(_) | Value::Map(_) => {
+                let mut buf = Vec::new();
+                self.write_json(&mut buf);
+                String::from_utf8_lossy(&buf).into_owned()
+            }
+            _ => return false,
+        };
+        let mut end = cap.min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let mut text = text;
+        text.truncate(end);
+        *self = Value::Str(text.into());
+        true
+    }
+
+    /// 以 JSON 值的形式写入
+    pub fn write_json(&self, out: &mut Vec<u8>) {
+        self.write_json_at(out, 0);
+    }
+
+    fn write_json_at(&self, out: &mut Vec<u8>, depth: usize) {
+        match self {
+            Value::Array(_) | Value::Map(_) if depth >= MAX_DEPTH => {
+                out.extend_from_slice(b"\"...\"")
+            }
+            Value::Array(items) => {
+                out.push(b'[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(b',');
+                    }
+                    item.write_json_at(out, depth + 1);
+                }
+                out.push(b']');
+            }
+            Value::Map(entries) => {
+                out.push(b'{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        out.push(b',');
+                    }
+                    out.push(b'"');
+                    escape_json_into(out, key);
+                    out.extend_from_slice(b"\":");
+                    value.write_json_at(out, depth + 1);
+                }
+                out.push(b'}');
+            }
+            Value::Bool(v) => out.extend_from_slice(if *v { b"true" } else { b"false" }),
+            Value::I64(v) => out.extend_from_slice(itoa::Buffer::new().format(*v).as_bytes()),
+            Value::U64(v) => out.extend_from_slice(itoa::Buffer::new().format(*v).as_bytes()),
+            Value::I128(v) => out.extend_from_slice(itoa::Buffer::new().format(*v).as_bytes()),
+            Value::U128(v) => out.extend_from_slice(itoa::Buffer::new().format(*v).as_bytes()),
+            Value::F64(v) if v.is_finite() => {
+                out.extend_from_slice(ryu::Buffer::new().format_finite(*v).as_bytes())
+            }
+            Value::F64(_) => out.extend_from_slice(b"null"),
+            Value::Str(v) => {
+                out.push(b'"');
+                escape_json_into(out, v);
+                out.push(b'"');
+            }
+            Value::Duration(v) => {
+                out.extend_from_slice(itoa::Buffer::new().format(v.as_nanos()).as_bytes())
+            }
+            #[cfg(feature = "serde")]
+            Value::Json(v) => out.extend_from_slice(v.as_bytes()),
+            _ => {
+                out.push(b'"');
+                self.write_plain(out);
+                out.push(b'"');
+            }
+        }
+    }
+
+    /// 以 logfmt 值的形式写入（含空白、引号或 `=` 的字符串加引号）
+    pub fn write_logfmt(&self, out: &mut Vec<u8>) {
+        match self {
+            Value::Str(v)
+                if v.is_empty() || v.bytes().any(|b| b <= b' ' || b == b'"' || b == b'=') =>
+            {
+                out.push(b'"');
+                escape_json_into(out, v);
+                out.push(b'"');
+            }
+            Value::F64(v) => out.extend_from_slice(ryu::Buffer::new().format(*v).as_bytes()),
+            Value::Duration(v) => {
+                out.extend_from_slice(itoa::Buffer::new().format(v.as_nanos()).as_bytes());
+                out.extend_from_slice(b"ns");
+            }
+            Value::I64(_) | Value::U64(_) | Value::I128(_) | Value::U128(_) | Value::Bool(_) => {
+                self.write_json(out)
+            }
+            // 嵌套值以带引号的紧凑 JSON 输出
+            #[cfg(feature = "serde")]
+            Value::Json(v) => {
+                out.push(b'"');
+                escape_json_into(out, v);
+                out.push(b'"');
+            }
+            Value::Array(_) | Value::Map(_) => {
+                let mut json = Vec::new();
+                self.write_json(&mut json);
+                out.push(b'"');
+                escape_json_into(out, &String::from_utf8_lossy(&json));
+                out.push(b'"');
+            }
+            _ => self.write_plain(out),
+        }
+    }
+
+    /// 写入不加引号的文本形式
+    pub fn write_plain(&self, out: &mut Vec<u8>) {
+        match self {
+            Value::Str(v) => out.extend_from_slice(v.as_bytes()),
+            Value::SystemTime(v) => {
+                let since_epoch = v.duration_since(UNIX_EPOCH).unwrap_or_default();
+                let dt = DateTime::<Utc>::from_timestamp(
+                    since_epoch.as_secs().min(i64::MAX as u64) as i64,
+                    since_epoch.subsec_nanos(),
+                )
+                .unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+                crate::format::write_iso8601(out, &dt.naive_utc());
+                out.push(b'Z');
+            }
+            Value::IpAddr(IpAddr::V4(ip)) => write_ipv4(out, ip),
+            Value::IpAddr(IpAddr::V6(ip)) => write_display(out, ip),
+            Value::SocketAddr(SocketAddr::V4(addr)) => {
+                write_ipv4(out, addr.ip());
+                out.push(b':');
+                out.extend_from_slice(itoa::Buffer::new().format(addr.port()).as_bytes());
+            }
+            Value::SocketAddr(addr) => write_display(out, addr),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(v) => {
+                let mut buf = uuid::Uuid::encode_buffer();
+                out.extend_from_slice(v.hyphenated().encode_lower(&mut buf).as_bytes());
+            }
+            _ => self.write_json(out),
+        }
+    }
+}
+
+/// 直接写入点分十进制 IPv4 地址
+fn write_ipv4(out: &mut Vec<u8>, ip: &Ipv4Addr) {
+    let mut buf = itoa::Buffer::new();
+    for (i, octet) in ip.octets().iter().enumerate() {
+        if i > 0 {
+            out.push(b'.');
+        }
+        out.extend_from_slice(buf.format(*octet).as_bytes());
+    }
+}
+
+/// IPv6 压缩规则较复杂，沿用标准库的 `Display`
+fn write_display(out: &mut Vec<u8>, value: &impl std::fmt::Display) {
+    use std::io::Write;
+    let _ = write!(out, "{}", value);
+}
+
+macro_rules! impl_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(v: $ty) -> Self {
+                    Value::$variant(v.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from! {
+    bool => Bool,
+    i8 => I64, i16 => I64, i32 => I64, i64 => I64,
+    u8 => U64, u16 => U64, u32 => U64, u64 => U64,
+    i128 => I128, u128 => U128,
+    f32 => F64, f64 => F64,
+    &'static str => Str, String => Str, Cow<'static, str> => Str,
+    SystemTime => SystemTime, Duration => Duration,
+    IpAddr => IpAddr, Ipv4Addr => IpAddr, Ipv6Addr => IpAddr,
+    SocketAddr => SocketAddr,
+}
+
+impl From<usize> for Value {
+    fn from(v: usize) -> Self {
+        Value::U64(v as u64)
+    }
+}
+
+impl From<isize> for Value {
+    fn from(v: isize) -> Self {
+        Value::I64(v as i64)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(v: Vec<T>) -> Self {
+        Value::Array(v.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<K, V> From<BTreeMap<K, V>> for Value
+where
+    K: Into<Cow<'static, str>>,
+    V: Into<Value>,
+{
+    fn from(v: BTreeMap<K, V>) -> Self {
+        Value::Map(v.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+impl<K, V, S> From<HashMap<K, V, S>> for Value
+where
+    K: Into<Cow<'static, str>>,
+    V: Into<Value>,
+{
+    fn from(v: HashMap<K, V, S>) -> Self {
+        Value::Map(v.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for Value {
+    fn from(v: uuid::Uuid) -> Self {
+        Value::Uuid(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(value: impl Into<Value>) -> String {
+        let mut out = Vec::new();
+        value.into().write_json(&mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    fn logfmt(value: impl Into<Value>) -> String {
+        let mut out = Vec::new();
+        value.into().write_logfmt(&mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_value_json_serialization() {
+        assert_eq!(json(u128::MAX), u128::MAX.to_string());
+        assert_eq!(json(-5i128), "-5");
+        assert_eq!(json(1.5f64), "1.5");
+        assert_eq!(json(f64::NAN), "null");
+        assert_eq!(json("a\"b"), r#""a\"b""#);
+        assert_eq!(json(Duration::from_micros(3)), "3000");
+        assert_eq!(
+            json(UNIX_EPOCH + Duration::from_nanos(1_700_000_000_000_000_001)),
+            r#""2023-11-14T22:13:20.000000001Z""#
+        );
+        assert_eq!(json(Ipv4Addr::new(10, 0, 0, 1)), r#""10.0.0.1""#);
+        assert_eq!(json(Ipv6Addr::LOCALHOST), r#""::1""#);
+        let addr: SocketAddr = "192.168.1.2:8080".parse().unwrap();
+        assert_eq!(json(addr), r#""192.168.1.2:8080""#);
+        let addr: SocketAddr = "[::1]:443".parse().unwrap();
+        assert_eq!(json(addr), r#""[::1]:443""#);
+    }
+
+    #[test]
+    fn test_value_logfmt_serialization() {
+        assert_eq!(logfmt("plain"), "plain");
+        assert_eq!(logfmt("two words"), r#""two words""#);
+        assert_eq!(logfmt(""), r#""""#);
+        assert_eq!(logfmt(Duration::from_millis(2)), "2000000ns");
+        assert_eq!(logfmt(true), "true");
+        assert_eq!(logfmt(Ipv4Addr::new(127, 0, 0, 1)), "127.0.0.1");
+    }
+
+    #[test]
+    fn test_nested_values() {
+        let mut headers = BTreeMap::new();
+        headers.insert("accept", "*/*");
+        headers.insert("x-id", "a\"b");
+        let value = Value::Map(vec![
+            ("headers".into(), headers.into()),
+            ("ports".into(), vec![80u16, 443].into()),
+        ]);
+        assert_eq!(
+            json(value.clone()),
+            r#"{"headers":{"accept":"*/*","x-id":"a\"b"},"ports":[80,443]}"#
+        );
+        assert_eq!(logfmt(vec![1, 2]), r#""[1,2]""#);
+
+        let mut deep = Value::from(1);
+        for _ in 0..MAX_DEPTH + 2 {
+            deep = Value::Array(vec![deep]);
+        }
+        let out = json(deep);
+        assert_eq!(out.matches('[').count(), MAX_DEPTH);
+        assert!(out.contains(r#""...""#));
+    }
+
+    #[test]
+    fn test_field_limits_truncate_with_marker() {
+        let mut fields = vec![
+            Field::new("body", "日志日志日志"),
+            Field::new("ids", vec![1000, 2000, 3000]),
+            Field::new("n", 123456u64),
+        ];
+        let limits = FieldLimits::new().max_field_bytes(7).max_record_bytes(12);
+        assert!(limits.apply(&mut fields));
+
+        assert_eq!(fields[0].value(), &Value::Str("日志".into()));
+        assert_eq!(fields[1].value(), &Value::Str("[1000".into()));
+        // 标量不截断
+        assert_eq!(fields[2].value(), &Value::U64(123456));
+        assert_eq!(fields[3].key(), TRUNCATED_KEY);
+        assert_eq!(fields[3].value(), &Value::Bool(true));
+
+        let mut small = vec![Field::new("k", "v")];
+        assert!(!limits.apply(&mut small));
+        assert_eq!(small.len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_value_capture() {
+        let mut payload = BTreeMap::new();
+        payload.insert("qty", vec![1, 2]);
+        assert_eq!(json(Value::serialize(&payload)), r#"{"qty":[1,2]}"#);
+        assert_eq!(logfmt(Value::serialize(&payload)), r#""{\"qty\":[1,2]}""#);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_value_uuid_serialization() {
+        let id = uuid::Uuid::from_u128(0x67e5504410b1426f9247bb680e5fe0c8);
+        assert_eq!(json(id), r#""67e55044-10b1-426f-9247-bb680e5fe0c8""#);
+    }
+}
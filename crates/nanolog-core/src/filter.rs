@@ -0,0 +1,171 @@
+/*!
+按目标过滤日志。
+
+指令字符串的语法与 `RUST_LOG` 相同，如 `"info,my_app::db=debug,hyper=warn"`：
+裸级别设置默认级别，`目标=级别` 按目标前缀（模块路径边界）覆盖，
+裸目标表示该目标启用全部级别，`off` 关闭对应目标。最长前缀优先。
+*/
+
+use crate::Level;
+use crate::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// 读取过滤指令的环境变量
+pub const ENV_VAR: &str = "NANOLOG_LEVEL";
+
+/// 目标过滤器
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    /// 默认级别（`None` 表示关闭）
+    default: Option<Level>,
+    /// 按目标长度降序排列的指令
+    directives: Vec<(String, Option<Level>)>,
+    /// 任一指令启用的最详细级别（缓存，供热路径快速判断）
+    max_level: Option<Level>,
+}
+
+impl Filter {
+    /// 创建只有默认级别的过滤器
+    pub fn new(default: Level) -> Self {
+        Self {
+            default: Some(default),
+            directives: Vec::new(),
+            max_level: Some(default),
+        }
+    }
+
+    /// 解析指令字符串
+    pub fn parse(spec: &str) -> Result<Self, Error> {
+        let mut filter = Self::new(Level::Error);
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    let target = target.trim();
+                    if target.is_empty() {
+                        return Err(Error::Config("empty target in filter directive"));
+                    }
+                    filter = filter.with_directive(target, parse_level(level.trim())?);
+                }
+                None => match parse_level(directive) {
+                    Ok(level) => filter.default = level,
+                    Err(_) => filter = filter.with_directive(directive, Some(Level::Trace)),
+                },
+            }
+        }
+        filter.refresh_max_level();
+        Ok(filter)
+    }
+
+    /// 从环境变量 [`ENV_VAR`] 读取过滤器；未设置时返回 `Ok(None)`
+    pub fn from_env() -> Result<Option<Self>, Error> {
+        match std::env::var(ENV_VAR) {
+            Ok(spec) => Self::parse(&spec).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// 为目标前缀设置级别（`None` 表示关闭该目标）
+    pub fn with_directive(mut self, target: &str, level: Option<Level>) -> Self {
+        self.directives.retain(|(t, _)| t != target);
+        self.directives.push((target.to_string(), level));
+        self.directives
+            .sort_by_key(|(t, _)| std::cmp::Reverse(t.len()));
+        self.refresh_max_level();
+        self
+    }
+
+    /// 检查指定目标的级别是否启用
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        let threshold = self
+            .directives
+            .iter()
+            .find(|(prefix, _)| matches_prefix(target, prefix))
+            .map_or(self.default, |(_, level)| *level);
+        threshold.is_some_and(|threshold| level >= threshold)
+    }
+
+    /// 任一指令启用的最详细级别（全部关闭时为 `None`）
+    #[inline]
+    pub fn max_level(&self) -> Option<Level> {
+        self.max_level
+    }
+
+    fn refresh_max_level(&mut self) {
+        self.max_level = self
+            .directives
+            .iter()
+            .filter_map(|(_, level)| *level)
+            .chain(self.default)
+            .min();
+    }
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Filter {
+    /// 输出可被 [`Filter::parse`] 还原的指令字符串
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = |level: Option<Level>| level.map_or("off", |l| l.as_str());
+        write!(f, "{}", level(self.default).to_lowercase())?;
+        for (target, target_level) in &self.directives {
+            write!(f, ",{}={}", target, level(*target_level).to_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+/// 解析级别名称，`off` 表示关闭
+fn parse_level(s: &str) -> Result<Option<Level>, Error> {
+    if s.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    Level::from_str(s)
+        .map(Some)
+        .map_err(|_| Error::Config("invalid level in filter directive"))
+}
+
+/// 目标等于前缀，或以前缀加 `::` 开头
+pub(crate) fn matches_prefix(target: &str, prefix: &str) -> bool {
+    target
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_parse_and_match() {
+        let filter = Filter::parse("info,my_app::db=debug,hyper=warn,noisy=off").unwrap();
+
+        assert!(filter.enabled(Level::Info, "my_app"));
+        assert!(!filter.enabled(Level::Debug, "my_app"));
+        assert!(filter.enabled(Level::Debug, "my_app::db"));
+        assert!(filter.enabled(Level::Debug, "my_app::db::pool"));
+        assert!(!filter.enabled(Level::Debug, "my_app::dbx"));
+        assert!(!filter.enabled(Level::Info, "hyper::client"));
+        assert!(filter.enabled(Level::Warn, "hyper::client"));
+        assert!(!filter.enabled(Level::Error, "noisy"));
+        assert_eq!(filter.max_level(), Some(Level::Debug));
+        assert_eq!(Filter::parse(&filter.to_string()).unwrap(), filter);
+    }
+
+    #[test]
+    fn test_filter_bare_target_and_errors() {
+        let filter: Filter = "warn,my_app".parse().unwrap();
+        assert!(filter.enabled(Level::Trace, "my_app::x"));
+        assert!(!filter.enabled(Level::Info, "other"));
+
+        assert!(Filter::parse("my_app=loud").is_err());
+        assert!(Filter::parse("=info").is_err());
+        assert_eq!(Filter::parse("off").unwrap().max_level(), None);
+    }
+}
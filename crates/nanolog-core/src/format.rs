@@ -0,0 +1,2127 @@
+/*!
+高性能日志格式化器。
+
+简化设计，专注于零拷贝和低延迟格式化。
+*/
+
+use crate::escape::escape_json_into;
+use crate::field::Value;
+use crate::record::ThreadInfo;
+use crate::trace::write_hex;
+use crate::{Level, Record};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDateTime, Timelike, Utc};
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// 高性能格式化器接口
+pub trait Formatter: Send + Sync {
+    /// 将日志记录格式化为字节数组（高性能版本）
+    fn format(&self, record: &Record) -> Result<Vec<u8>, fmt::Error>;
+
+    /// 将日志记录格式化后追加到 `out`，供消费者复用缓冲区
+    ///
+    /// 默认复制 [`format`](Self::format) 的结果；内置格式化器直接写入 `out`。
+    fn format_into(&self, record: &Record, out: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        out.extend_from_slice(&self.format(record)?);
+        Ok(())
+    }
+
+    /// 消费者处理完一个批次后调用，用于释放批次内的缓存状态
+    fn end_batch(&self) {}
+}
+
+/// 批量格式化器接口
+///
+/// 将整批记录序列化为单个载荷，用于需要批级信封的协议（如 Elasticsearch bulk、OTLP）。
+/// 配置后消费者攒批的是记录而非逐条格式化的字节，写入时输出目标收到一次 `write` 调用。
+pub trait BatchFormatter: Send + Sync {
+    /// 将一批记录序列化到 `out`
+    fn format_batch(&self, records: &[Record], out: &mut Vec<u8>) -> Result<(), fmt::Error>;
+}
+
+/// 默认高性能格式化器
+pub struct DefaultFormatter {
+    /// 是否使用彩色输出
+    colored: bool,
+    /// 时间戳显示风格
+    timestamp_style: TimestampStyle,
+    /// 软换行配置（`None` 表示不换行）
+    wrap: Option<LineWrap>,
+    /// 级别标记风格
+    level_marker: LevelMarker,
+    /// 时间戳精度
+    resolution: TimestampResolution,
+    /// 当前批次共享的时间戳：(基准纳秒, 已格式化文本)
+    batch_stamp: Mutex<Option<(u128, Vec<u8>)>>,
+    /// 是否输出调用点 ID
+    callsite_id: bool,
+    /// 是否输出线程
+    thread: bool,
+    /// 是否在时间戳之后输出相对时间戳
+    relative_timestamp: bool,
+}
+
+/// 时间戳精度
+///
+/// 极高吞吐场景下可让同一消费批次内的记录共用一次格式化的时间戳，以精度换取吞吐。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampResolution {
+    /// 每条记录单独格式化时间戳
+    #[default]
+    PerRecord,
+    /// 同一批次共用批内首条记录的时间戳；`with_delta` 为真时追加相对纳秒差，如 `+1520`
+    PerBatch {
+        /// 是否追加相对批次基准的纳秒差
+        with_delta: bool,
+    },
+}
+
+/// 级别标记风格
+///
+/// 紧凑标记可在信息密集的开发控制台中节省列宽。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LevelMarker {
+    /// 完整级别名称，如 `[INFO]`
+    #[default]
+    Text,
+    /// 单字符级别，如 `I` / `W` / `E`
+    Letter,
+    /// 表情符号标记，如 `ℹ️` / `⚠️` / `⛔`
+    Emoji,
+}
+
+impl LevelMarker {
+    /// 获取指定级别的紧凑标记（`Text` 风格返回完整级别名称）
+    pub fn marker(&self, level: crate::Level) -> &'static str {
+        match self {
+            LevelMarker::Text => level.as_str(),
+            LevelMarker::Letter => match level {
+                crate::Level::Trace => "T",
+                crate::Level::Debug => "D",
+                crate::Level::Info => "I",
+                crate::Level::Warn => "W",
+                crate::Level::Error => "E",
+            },
+            LevelMarker::Emoji => match level {
+                crate::Level::Trace => "🔍",
+                crate::Level::Debug => "✅",
+                crate::Level::Info => "ℹ️",
+                crate::Level::Warn => "⚠️",
+                crate::Level::Error => "⛔",
+            },
+        }
+    }
+}
+
+/// 多行消息折叠方式
+///
+/// 堆栈回溯等多行消息在文本输出中会被 Fluentd / Logstash 等采集管道拆成多条事件，
+/// 折叠后每条记录只占一行。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Multiline {
+    /// 原样输出换行
+    #[default]
+    Keep,
+    /// 将换行转义为字面量 `\n`（`\r` 转义为 `\r`）
+    Escape,
+    /// 首行作为消息，其余行移入 `stack` 字段
+    StackField,
+}
+
+/// [`Multiline::StackField`] 使用的字段名
+pub const STACK_KEY: &str = "stack";
+
+impl Multiline {
+    /// 折叠多行消息；无需折叠时返回 `None`
+    pub fn fold(&self, record: &Record) -> Option<Record> {
+        let message = record.message();
+        if *self == Multiline::Keep || !message.contains(['\n', '\r']) {
+            return None;
+        }
+        match self {
+            Multiline::Keep => None,
+            Multiline::Escape => {
+                let mut folded = String::with_capacity(message.len() + 16);
+                for c in message.chars() {
+                    match c {
+                        '\n' => folded.push_str("\\n"),
+                        '\r' => folded.push_str("\\r"),
+                        c => folded.push(c),
+                    }
+                }
+                Some(record.with_message(folded))
+            }
+            Multiline::StackField => {
+                let (first, rest) = message.split_once('\n').unwrap_or((message, ""));
+                Some(
+                    record
+                        .with_message(first.trim_end_matches('\r').to_string())
+                        .with_field(STACK_KEY, rest.to_string()),
+                )
+            }
+        }
+    }
+}
+
+/// 时间戳显示风格
+pub enum TimestampStyle {
+    /// 使用 UNIX 纳秒整型（十进制）
+    NumericNs,
+    /// 使用 ISO8601 字符串并带时区偏移（chrono）；`None` 表示使用 UTC
+    Iso8601(Option<FixedOffset>),
+}
+
+impl DefaultFormatter {
+    /// 创建新的默认格式化器
+    pub fn new() -> Self {
+        Self {
+            colored: Self::should_use_color(),
+            timestamp_style: TimestampStyle::NumericNs,
+            wrap: None,
+            level_marker: LevelMarker::Text,
+            resolution: TimestampResolution::PerRecord,
+            batch_stamp: Mutex::new(None),
+            callsite_id: false,
+            thread: false,
+            relative_timestamp: false,
+        }
+    }
+
+    /// 创建使用彩色输出的格式化器
+    pub fn colored() -> Self {
+        Self {
+            colored: true,
+            timestamp_style: TimestampStyle::NumericNs,
+            wrap: None,
+            level_marker: LevelMarker::Text,
+            resolution: TimestampResolution::PerRecord,
+            batch_stamp: Mutex::new(None),
+            callsite_id: false,
+            thread: false,
+            relative_timestamp: false,
+        }
+    }
+
+    /// 创建不使用彩色输出的格式化器
+    pub fn plain() -> Self {
+        Self {
+            colored: false,
+            timestamp_style: TimestampStyle::NumericNs,
+            wrap: None,
+            level_marker: LevelMarker::Text,
+            resolution: TimestampResolution::PerRecord,
+            batch_stamp: Mutex::new(None),
+            callsite_id: false,
+            thread: false,
+            relative_timestamp: false,
+        }
+    }
+
+    /// 使用 ISO8601 上海时区时间戳
+    pub fn with_iso8601_shanghai() -> Self {
+        let offset = FixedOffset::east_opt(8 * 3600);
+        Self {
+            colored: Self::should_use_color(),
+            timestamp_style: TimestampStyle::Iso8601(offset),
+            wrap: None,
+            level_marker: LevelMarker::Text,
+            resolution: TimestampResolution::PerRecord,
+            batch_stamp: Mutex::new(None),
+            callsite_id: false,
+            thread: false,
+            relative_timestamp: false,
+        }
+    }
+
+    /// 设置时间戳风格
+    pub fn with_timestamp_style(style: TimestampStyle) -> Self {
+        Self {
+            colored: Self::should_use_color(),
+            timestamp_style: style,
+            wrap: None,
+            level_marker: LevelMarker::Text,
+            resolution: TimestampResolution::PerRecord,
+            batch_stamp: Mutex::new(None),
+            callsite_id: false,
+            thread: false,
+            relative_timestamp: false,
+        }
+    }
+
+    /// 设置软换行（按列宽折行，续行带缩进）
+    pub fn with_line_wrap(mut self, wrap: LineWrap) -> Self {
+        self.wrap = Some(wrap);
+        self
+    }
+
+    /// 设置级别标记风格
+    pub fn with_level_marker(mut self, marker: LevelMarker) -> Self {
+        self.level_marker = marker;
+        self
+    }
+
+    /// 在模块名和行号之后输出调用点 ID（如 `#12`），便于下游按调用点聚合
+    pub fn with_callsite_id(mut self, enabled: bool) -> Self {
+        self.callsite_id = enabled;
+        self
+    }
+
+    /// 在模块名和行号之后输出记录所属的线程（如 `(worker-1)`），仅捕获了线程的记录输出
+    pub fn with_thread(mut self, enabled: bool) -> Self {
+        self.thread = enabled;
+        self
+    }
+
+    /// 在时间戳之后输出相对 [`timestamp_base`](crate::record::timestamp_base) 的秒数，
+    /// 如 `[1714550400000000000 +12.000345678]`
+    pub fn with_relative_timestamp(mut self, enabled: bool) -> Self {
+        self.relative_timestamp = enabled;
+        self
+    }
+
+    /// 设置时间戳精度
+    ///
+    /// `PerBatch` 依赖消费者在批尾调用 [`Formatter::end_batch`]，在日志器之外直接使用时
+    /// 需自行调用，否则时间戳不会更新。
+    pub fn with_timestamp_resolution(mut self, resolution: TimestampResolution) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// 检查是否应该使用彩色输出
+    fn should_use_color() -> bool {
+        // 在实际应用中，可以检查终端是否支持颜色
+        // 这里简化处理，默认在非Windows系统上使用彩色
+        #[cfg(not(windows))]
+        return true;
+        #[cfg(windows)]
+        return false;
+    }
+
+    /// 时间戳格式化（根据风格），直接写入输出缓冲区
+    fn write_timestamp(&self, out: &mut Vec<u8>, timestamp_ns: u128) {
+        write_timestamp(out, &self.timestamp_style, timestamp_ns);
+    }
+}
+
+/// 按风格写入时间戳
+fn write_timestamp(out: &mut Vec<u8>, style: &TimestampStyle, timestamp_ns: u128) {
+    match style {
+        TimestampStyle::NumericNs => {
+            out.extend_from_slice(itoa::Buffer::new().format(timestamp_ns).as_bytes())
+        }
+        TimestampStyle::Iso8601(offset_opt) => {
+            let utc_dt = utc_datetime(timestamp_ns);
+            match *offset_opt {
+                Some(offset) => {
+                    write_iso8601(out, &utc_dt.with_timezone(&offset).naive_local());
+                    write_offset(out, offset.local_minus_utc());
+                }
+                None => {
+                    write_iso8601(out, &utc_dt.naive_utc());
+                    out.push(b'Z');
+                }
+            }
+        }
+    }
+}
+
+/// 将纳秒时间戳转换为 UTC 时间（溢出时舍弃精度）
+pub(crate) fn utc_datetime(timestamp_ns: u128) -> DateTime<Utc> {
+    let secs_u128 = timestamp_ns / 1_000_000_000;
+    let nanos_u32 = (timestamp_ns % 1_000_000_000) as u32;
+    let (secs_i64, nanos_i32) = if secs_u128 > i64::MAX as u128 {
+        (i64::MAX, 0)
+    } else {
+        (secs_u128 as i64, nanos_u32)
+    };
+
+    DateTime::<Utc>::from_timestamp(secs_i64, nanos_i32).unwrap_or(DateTime::<Utc>::UNIX_EPOCH)
+}
+
+/// 写入线程名称，未命名时写入 `thread-<编号>`
+pub(crate) fn write_thread(out: &mut Vec<u8>, thread: &ThreadInfo) {
+    match thread.name() {
+        Some(name) => out.extend_from_slice(name.as_bytes()),
+        None => {
+            out.extend_from_slice(b"thread-");
+            out.extend_from_slice(itoa::Buffer::new().format(thread.id()).as_bytes());
+        }
+    }
+}
+
+/// 以 `秒.纳秒` 形式写入相对时间戳，如 `12.000345678`
+pub(crate) fn write_relative(out: &mut Vec<u8>, relative_ns: u64) {
+    out.extend_from_slice(
+        itoa::Buffer::new()
+            .format(relative_ns / 1_000_000_000)
+            .as_bytes(),
+    );
+    out.push(b'.');
+    write_padded(out, (relative_ns % 1_000_000_000) as u32, 9);
+}
+
+/// 写入零填充的十进制数
+pub(crate) fn write_padded(out: &mut Vec<u8>, value: u32, width: usize) {
+    let mut buf = itoa::Buffer::new();
+    let digits = buf.format(value);
+    for _ in digits.len()..width {
+        out.push(b'0');
+    }
+    out.extend_from_slice(digits.as_bytes());
+}
+
+/// 写入 `YYYY-MM-DDTHH:MM:SS.nnnnnnnnn`（与 chrono `%Y-%m-%dT%H:%M:%S%.9f` 一致）
+pub(crate) fn write_iso8601(out: &mut Vec<u8>, dt: &NaiveDateTime) {
+    let year = dt.year();
+    if year < 0 {
+        out.push(b'-');
+    } else if year > 9999 {
+        out.push(b'+');
+    }
+    write_padded(out, year.unsigned_abs(), 4);
+    out.push(b'-');
+    write_padded(out, dt.month(), 2);
+    out.push(b'-');
+    write_padded(out, dt.day(), 2);
+    out.push(b'T');
+    write_padded(out, dt.hour(), 2);
+    out.push(b':');
+    write_padded(out, dt.minute(), 2);
+    out.push(b':');
+    write_padded(out, dt.second(), 2);
+    out.push(b'.');
+    write_padded(out, dt.nanosecond(), 9);
+}
+
+/// 写入 `+HH:MM` 形式的时区偏移
+fn write_offset(out: &mut Vec<u8>, offset_secs: i32) {
+    out.push(if offset_secs < 0 { b'-' } else { b'+' });
+    let minutes = offset_secs.unsigned_abs() / 60;
+    write_padded(out, minutes / 60, 2);
+    out.push(b':');
+    write_padded(out, minutes % 60, 2);
+}
+
+impl Default for DefaultFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Formatter for DefaultFormatter {
+    fn format(&self, record: &Record) -> Result<Vec<u8>, fmt::Error> {
+        let mut result = Vec::with_capacity(64 + record.message().len());
+        self.format_into(record, &mut result)?;
+        Ok(result)
+    }
+
+    fn format_into(&self, record: &Record, result: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        let start = result.len();
+
+        // 格式化时间戳（可配置：数字或ISO8601）
+        result.extend_from_slice(b"[");
+        match self.resolution {
+            TimestampResolution::PerRecord => self.write_timestamp(result, record.timestamp()),
+            TimestampResolution::PerBatch { with_delta } => {
+                let ts = record.timestamp();
+                let mut stamp = self.batch_stamp.lock().unwrap_or_else(|e| e.into_inner());
+                let (base, text) = stamp.get_or_insert_with(|| {
+                    let mut text = Vec::with_capacity(40);
+                    self.write_timestamp(&mut text, ts);
+                    (ts, text)
+                });
+                result.extend_from_slice(text);
+                if with_delta {
+                    // 多生产者下批内记录可能早于基准，差值带符号
+                    let delta = ts as i128 - *base as i128;
+                    if delta >= 0 {
+                        result.push(b'+');
+                    }
+                    result.extend_from_slice(itoa::Buffer::new().format(delta).as_bytes());
+                }
+            }
+        }
+        if self.relative_timestamp {
+            result.extend_from_slice(b" +");
+            write_relative(result, record.relative_timestamp());
+        }
+        result.extend_from_slice(b"] ");
+
+        // 格式化级别（可选带颜色），直接拷贝预格式化的前缀
+        result.extend_from_slice(level_prefix(
+            self.level_marker,
+            self.colored,
+            record.level(),
+        ));
+
+        // 格式化模块名和行号
+        result.push(b'[');
+        result.extend_from_slice(record.target().as_bytes());
+        result.push(b':');
+        result.extend_from_slice(itoa::Buffer::new().format(record.line()).as_bytes());
+        result.extend_from_slice(b"] ");
+        if self.callsite_id
+            && let Some(id) = record.callsite_id()
+        {
+            result.push(b'#');
+            result.extend_from_slice(itoa::Buffer::new().format(id).as_bytes());
+            result.push(b' ');
+        }
+        if self.thread
+            && let Some(thread) = record.thread()
+        {
+            result.push(b'(');
+            write_thread(result, thread);
+            result.extend_from_slice(b") ");
+        }
+
+        // 格式化消息内容
+        result.extend_from_slice(record.message().as_bytes());
+
+        // 结构化字段以 logfmt 形式追加在消息之后
+        for field in record.fields() {
+            result.push(b' ');
+            result.extend_from_slice(field.key().as_bytes());
+            result.push(b'=');
+            field.value().write_logfmt(result);
+        }
+        if let Some(trace) = record.trace_context() {
+            result.extend_from_slice(b" trace_id=");
+            write_hex(result, &trace.trace_id());
+            result.extend_from_slice(b" span_id=");
+            write_hex(result, &trace.span_id());
+        }
+
+        if let Some(wrap) = &self.wrap {
+            let wrapped = wrap.apply(&result[start..]);
+            result.truncate(start);
+            result.extend_from_slice(&wrapped);
+        }
+
+        // 添加换行符
+        result.push(b'\n');
+
+        Ok(())
+    }
+
+    fn end_batch(&self) {
+        if self.resolution != TimestampResolution::PerRecord {
+            *self.batch_stamp.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        }
+    }
+}
+
+/// 预格式化的级别前缀，按 `[标记风格][是否彩色][级别]` 索引
+///
+/// 颜色：TRACE 灰色、DEBUG 青色、INFO 绿色、WARN 黄色、ERROR 红色；
+/// 表情符号自带语义颜色，无需额外的颜色转义。
+static LEVEL_PREFIXES: [[[&str; 5]; 2]; 3] = [
+    [
+        ["[TRACE] ", "[DEBUG] ", "[INFO] ", "[WARN] ", "[ERROR] "],
+        [
+            "\x1b[90m[TRACE]\x1b[0m ",
+            "\x1b[36m[DEBUG]\x1b[0m ",
+            "\x1b[32m[INFO]\x1b[0m ",
+            "\x1b[33m[WARN]\x1b[0m ",
+            "\x1b[31m[ERROR]\x1b[0m ",
+        ],
+    ],
+    [
+        ["T ", "D ", "I ", "W ", "E "],
+        [
+            "\x1b[90mT\x1b[0m ",
+            "\x1b[36mD\x1b[0m ",
+            "\x1b[32mI\x1b[0m ",
+            "\x1b[33mW\x1b[0m ",
+            "\x1b[31mE\x1b[0m ",
+        ],
+    ],
+    [
+        ["🔍 ", "✅ ", "ℹ️ ", "⚠️ ", "⛔ "],
+        ["🔍 ", "✅ ", "ℹ️ ", "⚠️ ", "⛔ "],
+    ],
+];
+
+/// 查表获取级别前缀（含尾随空格）
+#[inline]
+fn level_prefix(marker: LevelMarker, colored: bool, level: crate::Level) -> &'static [u8] {
+    LEVEL_PREFIXES[marker as usize][colored as usize][level as usize].as_bytes()
+}
+
+/// JSON格式化器（高性能版本）
+pub struct JsonFormatter {
+    /// 是否格式化输出（美化格式）
+    pretty: bool,
+    /// 时间戳输出为 RFC3339 字符串（否则为 UNIX 纳秒整数）
+    rfc3339: bool,
+    /// 单行最大字节数（含换行符），超出时拆分消息
+    max_line_bytes: Option<usize>,
+    /// 内置键的输出名（按 [`JsonKey`] 索引）
+    keys: [Cow<'static, str>; JsonKey::COUNT],
+    /// 级别的输出值
+    level_name: fn(Level) -> &'static str,
+    /// 每条记录附带的静态字段
+    static_fields: Vec<(Cow<'static, str>, Value)>,
+    /// 是否输出相对时间戳 `rel_ns`
+    relative_timestamp: bool,
+}
+
+/// [`JsonFormatter`] 输出的内置键，可通过 [`JsonFormatter::with_key`] 重命名
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonKey {
+    /// 时间戳（默认 `timestamp`）
+    Timestamp,
+    /// 级别（默认 `level`）
+    Level,
+    /// 目标（默认 `target`）
+    Target,
+    /// 源文件（默认 `file`）
+    File,
+    /// 行号（默认 `line`）
+    Line,
+    /// 消息（默认 `message`）
+    Message,
+    /// 结构化字段对象（默认 `fields`）
+    Fields,
+}
+
+impl JsonKey {
+    const COUNT: usize = 7;
+
+    /// 默认键名
+    pub fn default_name(&self) -> &'static str {
+        match self {
+            JsonKey::Timestamp => "timestamp",
+            JsonKey::Level => "level",
+            JsonKey::Target => "target",
+            JsonKey::File => "file",
+            JsonKey::Line => "line",
+            JsonKey::Message => "message",
+            JsonKey::Fields => "fields",
+        }
+    }
+}
+
+/// Docker / containerd 日志驱动的单行上限（16KB）
+pub const DOCKER_MAX_LINE_BYTES: usize = 16 * 1024;
+
+/// 拆分行的 `part`/`parts` 元数据按最大位数预留的长度
+const PART_RESERVE: (usize, usize) = (99_999, 99_999);
+
+impl JsonFormatter {
+    /// 创建新的JSON格式化器
+    pub fn new() -> Self {
+        Self {
+            pretty: false,
+            rfc3339: false,
+            max_line_bytes: None,
+            keys: [
+                JsonKey::Timestamp,
+                JsonKey::Level,
+                JsonKey::Target,
+                JsonKey::File,
+                JsonKey::Line,
+                JsonKey::Message,
+                JsonKey::Fields,
+            ]
+            .map(|key| Cow::Borrowed(key.default_name())),
+            level_name: |level| level.as_str(),
+            static_fields: Vec::new(),
+            relative_timestamp: false,
+        }
+    }
+
+    /// 创建美化格式的JSON格式化器
+    pub fn pretty() -> Self {
+        Self {
+            pretty: true,
+            ..Self::new()
+        }
+    }
+
+    /// Datadog Agent 约定的布局：级别为小写的 `status`，目标为 `logger.name`，RFC3339 时间戳
+    ///
+    /// 通常再以 [`with_static_field`](Self::with_static_field) 附加 `service`、`env`、`version`。
+    pub fn datadog() -> Self {
+        Self::new()
+            .with_rfc3339_timestamps()
+            .with_key(JsonKey::Level, "status")
+            .with_key(JsonKey::Target, "logger.name")
+            .with_level_names(|level| match level {
+                Level::Trace | Level::Debug => "debug",
+                Level::Info => "info",
+                Level::Warn => "warning",
+                Level::Error => "error",
+            })
+    }
+
+    /// Google Cloud Logging 约定的布局：`severity` 取 `LogSeverity` 名称，RFC3339 时间戳写入 `time`
+    pub fn google_cloud() -> Self {
+        Self::new()
+            .with_rfc3339_timestamps()
+            .with_key(JsonKey::Timestamp, "time")
+            .with_key(JsonKey::Level, "severity")
+            .with_level_names(|level| match level {
+                Level::Trace | Level::Debug => "DEBUG",
+                Level::Info => "INFO",
+                Level::Warn => "WARNING",
+                Level::Error => "ERROR",
+            })
+    }
+
+    /// 重命名内置键
+    pub fn with_key(mut self, key: JsonKey, name: impl Into<Cow<'static, str>>) -> Self {
+        self.keys[key as usize] = name.into();
+        self
+    }
+
+    /// 设置级别的输出值（默认为 [`Level::as_str`]）
+    pub fn with_level_names(mut self, names: fn(Level) -> &'static str) -> Self {
+        self.level_name = names;
+        self
+    }
+
+    /// 每条记录附带一个顶层静态字段（如 `service`、`env`、`version`），写在消息之后
+    pub fn with_static_field(
+        mut self,
+        key: impl Into<Cow<'static, str>>,
+        value: impl Into<Value>,
+    ) -> Self {
+        self.static_fields.push((key.into(), value.into()));
+        self
+    }
+
+    fn key(&self, key: JsonKey) -> &str {
+        &self.keys[key as usize]
+    }
+
+    /// Docker JSON 日志驱动友好模式：紧凑单行、RFC3339 时间戳、每行不超过 16KB
+    pub fn docker() -> Self {
+        Self::new()
+            .with_rfc3339_timestamps()
+            .with_max_line_bytes(DOCKER_MAX_LINE_BYTES)
+    }
+
+    /// 限制每行的最大字节数（含换行符）
+    ///
+    /// 超长记录的消息按字符边界拆分为多行，每行都是完整的 JSON 对象，
+    /// 并携带从 1 开始的 `part` 与总数 `parts`；其余字段在每行重复。
+    /// 仅对紧凑格式生效；元数据与字段本身超出上限时按原样输出单行。
+    pub fn with_max_line_bytes(mut self, max: usize) -> Self {
+        self.max_line_bytes = Some(max);
+        self
+    }
+
+    /// 时间戳输出为 UTC RFC3339 字符串，如 `"2024-05-01T08:00:00.000000000Z"`
+    pub fn with_rfc3339_timestamps(mut self) -> Self {
+        self.rfc3339 = true;
+        self
+    }
+
+    /// 在时间戳之后输出相对 [`timestamp_base`](crate::record::timestamp_base) 的纳秒数 `rel_ns`
+    pub fn with_relative_timestamp(mut self, enabled: bool) -> Self {
+        self.relative_timestamp = enabled;
+        self
+    }
+}
+
+impl Default for JsonFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonFormatter {
+    /// 写入一行 JSON 对象；`part` 为 `(序号, 总数)`，仅拆分时携带
+    fn write_object(
+        &self,
+        result: &mut Vec<u8>,
+        record: &Record,
+        message: &str,
+        part: Option<(usize, usize)>,
+    ) {
+        let (open, sep, close) = if self.pretty {
+            // 美化格式
+            ("{\n  \"", ",\n  \"", "\n}\n")
+        } else {
+            // 紧凑格式
+            ("{\"", ",\"", "}\n")
+        };
+        let colon: &[u8] = if self.pretty { b"\": " } else { b"\":" };
+
+        result.extend_from_slice(open.as_bytes());
+        escape_json_into(result, self.key(JsonKey::Timestamp));
+        result.extend_from_slice(colon);
+        if self.rfc3339 {
+            result.push(b'"');
+            write_iso8601(result, &utc_datetime(record.timestamp()).naive_utc());
+            result.extend_from_slice(b"Z\"");
+        } else {
+            result.extend_from_slice(itoa::Buffer::new().format(record.timestamp()).as_bytes());
+        }
+        if self.relative_timestamp {
+            result.extend_from_slice(sep.as_bytes());
+            result.extend_from_slice(b"rel_ns");
+            result.extend_from_slice(colon);
+            result.extend_from_slice(
+                itoa::Buffer::new()
+                    .format(record.relative_timestamp())
+                    .as_bytes(),
+            );
+        }
+        for (key, value) in [
+            (JsonKey::Level, (self.level_name)(record.level())),
+            (JsonKey::Target, record.target()),
+            (JsonKey::File, record.file()),
+        ] {
+            result.extend_from_slice(sep.as_bytes());
+            escape_json_into(result, self.key(key));
+            result.extend_from_slice(colon);
+            result.push(b'"');
+            escape_json_into(result, value);
+            result.push(b'"');
+        }
+        result.extend_from_slice(sep.as_bytes());
+        escape_json_into(result, self.key(JsonKey::Line));
+        result.extend_from_slice(colon);
+        result.extend_from_slice(itoa::Buffer::new().format(record.line()).as_bytes());
+        if let Some(id) = record.callsite_id() {
+            result.extend_from_slice(sep.as_bytes());
+            result.extend_from_slice(b"callsite");
+            result.extend_from_slice(colon);
+            result.extend_from_slice(itoa::Buffer::new().format(id).as_bytes());
+        }
+        if let Some(trace) = record.trace_context() {
+            for (key, id) in [
+                (&b"trace_id"[..], &trace.trace_id()[..]),
+                (b"span_id", &trace.span_id()),
+            ] {
+                result.extend_from_slice(sep.as_bytes());
+                result.extend_from_slice(key);
+                result.extend_from_slice(colon);
+                result.push(b'"');
+                write_hex(result, id);
+                result.push(b'"');
+            }
+        }
+        if let Some(thread) = record.thread() {
+            result.extend_from_slice(sep.as_bytes());
+            result.extend_from_slice(b"thread_id");
+            result.extend_from_slice(colon);
+            result.extend_from_slice(itoa::Buffer::new().format(thread.id()).as_bytes());
+            if let Some(name) = thread.name() {
+                result.extend_from_slice(sep.as_bytes());
+                result.extend_from_slice(b"thread");
+                result.extend_from_slice(colon);
+                result.push(b'"');
+                escape_json_into(result, name);
+                result.push(b'"');
+            }
+        }
+        result.extend_from_slice(sep.as_bytes());
+        escape_json_into(result, self.key(JsonKey::Message));
+        result.extend_from_slice(colon);
+        result.push(b'"');
+        escape_json_into(result, message);
+        result.push(b'"');
+        if let Some((index, total)) = part {
+            for (key, value) in [("part", index), ("parts", total)] {
+                result.extend_from_slice(sep.as_bytes());
+                result.extend_from_slice(key.as_bytes());
+                result.extend_from_slice(colon);
+                result.extend_from_slice(itoa::Buffer::new().format(value).as_bytes());
+            }
+        }
+        for (key, value) in &self.static_fields {
+            result.extend_from_slice(sep.as_bytes());
+            escape_json_into(result, key);
+            result.extend_from_slice(colon);
+            value.write_json(result);
+        }
+        if !record.fields().is_empty() {
+            result.extend_from_slice(sep.as_bytes());
+            escape_json_into(result, self.key(JsonKey::Fields));
+            result.extend_from_slice(colon);
+            for (i, field) in record.fields().iter().enumerate() {
+                result.extend_from_slice(if i == 0 { b"{\"" } else { b",\"" });
+                escape_json_into(result, field.key());
+                result.extend_from_slice(b"\":");
+                field.value().write_json(result);
+            }
+            result.push(b'}');
+        }
+        result.extend_from_slice(close.as_bytes());
+    }
+}
+
+/// 按转义后长度将消息拆分为不超过 `budget` 字节的片段（在字符边界处切分）
+fn split_escaped(message: &str, budget: usize) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut len = 0;
+    for (i, c) in message.char_indices() {
+        let width = crate::escape::escaped_len(c);
+        if len + width > budget && i > start {
+            parts.push(&message[start..i]);
+            start = i;
+            len = 0;
+        }
+        len += width;
+    }
+    parts.push(&message[start..]);
+    parts
+}
+
+impl Formatter for JsonFormatter {
+    fn format(&self, record: &Record) -> Result<Vec<u8>, fmt::Error> {
+        let mut result = Vec::with_capacity(128 + record.message().len());
+        self.format_into(record, &mut result)?;
+        Ok(result)
+    }
+
+    fn format_into(&self, record: &Record, result: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        let message = record.message();
+        let start = result.len();
+        self.write_object(result, record, message, None);
+
+        if let Some(max) = self.max_line_bytes
+            && !self.pretty
+            && result.len() - start > max
+        {
+            // 以空消息与最大位数的分片元数据估算每行的固定开销
+            let mut overhead = Vec::with_capacity(128);
+            self.write_object(&mut overhead, record, "", Some(PART_RESERVE));
+            if overhead.len() < max {
+                let parts = split_escaped(message, max - overhead.len());
+                let total = parts.len();
+                result.truncate(start);
+                for (i, part) in parts.into_iter().enumerate() {
+                    self.write_object(result, record, part, Some((i + 1, total)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// 简单格式化器（最高性能）
+pub struct SimpleFormatter {
+    /// 软换行配置（`None` 表示不换行）
+    wrap: Option<LineWrap>,
+}
+
+impl SimpleFormatter {
+    /// 创建新的简单格式化器
+    pub fn new() -> Self {
+        Self { wrap: None }
+    }
+
+    /// 设置软换行（按列宽折行，续行带缩进）
+    pub fn with_line_wrap(mut self, wrap: LineWrap) -> Self {
+        self.wrap = Some(wrap);
+        self
+    }
+}
+
+impl Default for SimpleFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Formatter for SimpleFormatter {
+    fn format(&self, record: &Record) -> Result<Vec<u8>, fmt::Error> {
+        let mut result = Vec::with_capacity(record.message().len() + 9);
+        self.format_into(record, &mut result)?;
+        Ok(result)
+    }
+
+    fn format_into(&self, record: &Record, result: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        // 最简单的格式化：级别 + 消息
+        let start = result.len();
+        result.push(b'[');
+        result.extend_from_slice(record.level().as_str().as_bytes());
+        result.extend_from_slice(b"] ");
+        result.extend_from_slice(record.message().as_bytes());
+        if let Some(wrap) = &self.wrap {
+            let wrapped = wrap.apply(&result[start..]);
+            result.truncate(start);
+            result.extend_from_slice(&wrapped);
+        }
+        result.push(b'\n');
+        Ok(())
+    }
+}
+
+/// 混合格式化器（开发模式）
+///
+/// 前半部分与 [`DefaultFormatter`] 相同（时间戳、彩色级别、模块名与行号、消息），
+/// 结构化字段以紧凑 JSON 对象追加在同一行末尾，如
+/// `[ts] [INFO] [app:3] login {"user":"alice","id":7}`，本地可读的同时字段仍可被机器解析。
+pub struct HybridFormatter {
+    /// 是否使用彩色输出
+    colored: bool,
+    /// 时间戳显示风格
+    timestamp_style: TimestampStyle,
+    /// 级别标记风格
+    level_marker: LevelMarker,
+}
+
+impl HybridFormatter {
+    /// 创建新的混合格式化器（非 Windows 系统默认彩色）
+    pub fn new() -> Self {
+        Self {
+            colored: DefaultFormatter::should_use_color(),
+            timestamp_style: TimestampStyle::NumericNs,
+            level_marker: LevelMarker::Text,
+        }
+    }
+
+    /// 设置是否使用彩色输出
+    pub fn with_color(mut self, colored: bool) -> Self {
+        self.colored = colored;
+        self
+    }
+
+    /// 设置时间戳风格
+    pub fn with_timestamp_style(mut self, style: TimestampStyle) -> Self {
+        self.timestamp_style = style;
+        self
+    }
+
+    /// 设置级别标记风格
+    pub fn with_level_marker(mut self, marker: LevelMarker) -> Self {
+        self.level_marker = marker;
+        self
+    }
+}
+
+impl Default for HybridFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Formatter for HybridFormatter {
+    fn format(&self, record: &Record) -> Result<Vec<u8>, fmt::Error> {
+        let mut result = Vec::with_capacity(96 + record.message().len());
+        self.format_into(record, &mut result)?;
+        Ok(result)
+    }
+
+    fn format_into(&self, record: &Record, result: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        result.push(b'[');
+        write_timestamp(result, &self.timestamp_style, record.timestamp());
+        result.extend_from_slice(b"] ");
+        result.extend_from_slice(level_prefix(
+            self.level_marker,
+            self.colored,
+            record.level(),
+        ));
+        result.push(b'[');
+        result.extend_from_slice(record.target().as_bytes());
+        result.push(b':');
+        result.extend_from_slice(itoa::Buffer::new().format(record.line()).as_bytes());
+        result.extend_from_slice(b"] ");
+        result.extend_from_slice(record.message().as_bytes());
+        for (i, field) in record.fields().iter().enumerate() {
+            result.extend_from_slice(if i == 0 { b" {\"" } else { b",\"" });
+            escape_json_into(result, field.key());
+            result.extend_from_slice(b"\":");
+            field.value().write_json(result);
+        }
+        if !record.fields().is_empty() {
+            result.push(b'}');
+        }
+        result.push(b'\n');
+        Ok(())
+    }
+}
+
+/// 按名称选择的内置格式化风格，便于通过配置文件或环境变量切换输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FormatStyle {
+    /// [`DefaultFormatter`]：文本行，字段为 logfmt
+    #[default]
+    Text,
+    /// [`JsonFormatter`]：单行 JSON
+    Json,
+    /// [`SimpleFormatter`]：级别 + 消息
+    Simple,
+    /// [`HybridFormatter`]：彩色文本前缀 + JSON 字段
+    Hybrid,
+}
+
+impl FormatStyle {
+    /// 风格名称（可被 [`FromStr`](std::str::FromStr) 还原）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FormatStyle::Text => "text",
+            FormatStyle::Json => "json",
+            FormatStyle::Simple => "simple",
+            FormatStyle::Hybrid => "hybrid",
+        }
+    }
+
+    /// 创建对应的格式化器
+    pub fn formatter(&self) -> Arc<dyn Formatter> {
+        match self {
+            FormatStyle::Text => Arc::new(DefaultFormatter::new()),
+            FormatStyle::Json => Arc::new(JsonFormatter::new()),
+            FormatStyle::Simple => Arc::new(SimpleFormatter::new()),
+            FormatStyle::Hybrid => Arc::new(HybridFormatter::new()),
+        }
+    }
+}
+
+impl std::str::FromStr for FormatStyle {
+    type Err = crate::error::Error;
+
+    /// 从名称解析（不区分大小写）；`dev` 为 `hybrid` 的别名
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" | "default" => Ok(FormatStyle::Text),
+            "json" => Ok(FormatStyle::Json),
+            "simple" => Ok(FormatStyle::Simple),
+            "hybrid" | "dev" => Ok(FormatStyle::Hybrid),
+            _ => Err(crate::error::Error::Config("unknown format style")),
+        }
+    }
+}
+
+/// systemd 级别前缀格式化器
+///
+/// 以 `<N>` 开头标注 syslog 优先级，输出到标准错误时 journald 据此识别严重级别。
+/// 默认布局不输出时间戳（由 journald 记录）；也可包装任意格式化器，为其输出加前缀。
+/// 多行输出的每一行都带前缀，续行因此不会退化为默认优先级。
+pub struct SystemdFormatter {
+    /// 被包装的格式化器（`None` 表示使用内置布局）
+    inner: Option<Arc<dyn Formatter>>,
+}
+
+impl SystemdFormatter {
+    /// 创建 systemd 级别前缀格式化器
+    pub fn new() -> Self {
+        Self { inner: None }
+    }
+
+    /// 包装已有格式化器，为其每一行输出加上 `<N>` 前缀
+    pub fn wrap(inner: Arc<dyn Formatter>) -> Self {
+        Self { inner: Some(inner) }
+    }
+
+    /// 内置布局：`[target:line] message key=value`
+    fn format_plain(record: &Record, result: &mut Vec<u8>) {
+        let message = record.message();
+        result.push(b'[');
+        result.extend_from_slice(record.target().as_bytes());
+        result.push(b':');
+        result.extend_from_slice(itoa::Buffer::new().format(record.line()).as_bytes());
+        result.extend_from_slice(b"] ");
+        result.extend_from_slice(message.as_bytes());
+        for field in record.fields() {
+            result.push(b' ');
+            result.extend_from_slice(field.key().as_bytes());
+            result.push(b'=');
+            field.value().write_logfmt(result);
+        }
+    }
+}
+
+impl Default for SystemdFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Formatter for SystemdFormatter {
+    fn format(&self, record: &Record) -> Result<Vec<u8>, fmt::Error> {
+        let mut result = Vec::with_capacity(record.message().len() + 40);
+        self.format_into(record, &mut result)?;
+        Ok(result)
+    }
+
+    fn format_into(&self, record: &Record, result: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        let start = result.len();
+        match &self.inner {
+            Some(inner) => inner.format_into(record, result)?,
+            None => Self::format_plain(record, result),
+        }
+        let prefix = [b'<', b'0' + record.level().syslog_priority(), b'>'];
+        prefix_lines(result, start, prefix);
+        Ok(())
+    }
+
+    fn end_batch(&self) {
+        if let Some(inner) = &self.inner {
+            inner.end_batch();
+        }
+    }
+}
+
+/// journald 原生协议格式化器
+///
+/// 每条记录输出为一组 `KEY=VALUE` 条目（`PRIORITY`、`MESSAGE`、`CODE_FILE` 等），
+/// 含换行的值使用长度前缀的二进制形式；结构化字段的键转换为大写。
+/// 与 [`JournaldSink`](crate::sink::JournaldSink) 搭配使用。
+pub struct JournaldFormatter;
+
+impl JournaldFormatter {
+    /// 创建 journald 原生协议格式化器
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for JournaldFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Formatter for JournaldFormatter {
+    fn format(&self, record: &Record) -> Result<Vec<u8>, fmt::Error> {
+        let mut result = Vec::with_capacity(record.message().len() + 128);
+        self.format_into(record, &mut result)?;
+        Ok(result)
+    }
+
+    fn format_into(&self, record: &Record, result: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        let message = record.message();
+        let mut buf = itoa::Buffer::new();
+        write_journald_entry(
+            result,
+            b"PRIORITY",
+            buf.format(record.level().syslog_priority()).as_bytes(),
+        );
+        write_journald_entry(result, b"MESSAGE", message.as_bytes());
+        write_journald_entry(result, b"TARGET", record.target().as_bytes());
+        write_journald_entry(result, b"CODE_FILE", record.file().as_bytes());
+        write_journald_entry(result, b"CODE_LINE", buf.format(record.line()).as_bytes());
+
+        let mut value = Vec::new();
+        for field in record.fields() {
+            // journald 字段名仅允许大写字母、数字与下划线，且不能以下划线开头
+            let key: Vec<u8> = field
+                .key()
+                .trim_start_matches('_')
+                .bytes()
+                .map(|b| {
+                    if b.is_ascii_alphanumeric() {
+                        b.to_ascii_uppercase()
+                    } else {
+                        b'_'
+                    }
+                })
+                .collect();
+            if key.is_empty() {
+                continue;
+            }
+            value.clear();
+            match field.value() {
+                crate::field::Value::Str(s) => value.extend_from_slice(s.as_bytes()),
+                other => other.write_logfmt(&mut value),
+            }
+            write_journald_entry(result, &key, &value);
+        }
+        Ok(())
+    }
+}
+
+/// Elasticsearch bulk API 批量格式化器
+///
+/// 每条记录输出一行 `index` 动作与一行 JSON 文档（由内部的 [`JsonFormatter`] 生成），
+/// 整批组成一个 `_bulk` 请求体。
+pub struct ElasticsearchBulkFormatter {
+    /// 目标索引
+    index: String,
+    /// 文档格式化器
+    document: JsonFormatter,
+}
+
+impl ElasticsearchBulkFormatter {
+    /// 创建写入指定索引的 bulk 格式化器（文档时间戳为 RFC3339）
+    pub fn new(index: impl Into<String>) -> Self {
+        Self {
+            index: index.into(),
+            document: JsonFormatter::new().with_rfc3339_timestamps(),
+        }
+    }
+}
+
+impl BatchFormatter for ElasticsearchBulkFormatter {
+    fn format_batch(&self, records: &[Record], out: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        for record in records {
+            out.extend_from_slice(b"{\"index\":{\"_index\":\"");
+            escape_json_into(out, &self.index);
+            out.extend_from_slice(b"\"}}\n");
+            out.extend_from_slice(&self.document.format(record)?);
+        }
+        Ok(())
+    }
+}
+
+/// InfluxDB 行协议格式化器
+///
+/// 每条记录输出一行 `measurement[,tag=v...] field=v[,field=v...] timestamp`：
+/// 度量名取自目标（空目标为 `log`），结构化字段作为字段集，时间戳为纳秒。
+/// 指定为标签的键写入标签集；没有字段的记录以 `message` 字符串字段写出。
+/// 搭配套接字输出可直接写入 Influx/Telegraf。
+pub struct LineProtocolFormatter {
+    /// 作为标签输出的字段键
+    tag_keys: Vec<&'static str>,
+}
+
+impl LineProtocolFormatter {
+    /// 创建行协议格式化器（全部字段作为字段集）
+    pub fn new() -> Self {
+        Self {
+            tag_keys: Vec::new(),
+        }
+    }
+
+    /// 将指定键的字段作为标签输出（值按文本写入）
+    pub fn with_tag_keys(mut self, keys: &[&'static str]) -> Self {
+        self.tag_keys = keys.to_vec();
+        self
+    }
+
+    /// 写入一个行协议字段值；非有限浮点数无法表示，返回 `false` 表示跳过
+    fn write_field_value(out: &mut Vec<u8>, value: &crate::field::Value) -> bool {
+        use crate::field::Value;
+        let mut buf = itoa::Buffer::new();
+        match value {
+            Value::Bool(v) => out.extend_from_slice(if *v { b"true" } else { b"false" }),
+            Value::I64(v) => {
+                out.extend_from_slice(buf.format(*v).as_bytes());
+                out.push(b'i');
+            }
+            Value::U64(v) => {
+                out.extend_from_slice(buf.format(*v).as_bytes());
+                out.push(b'u');
+            }
+            Value::I128(v) if i64::try_from(*v).is_ok() => {
+                out.extend_from_slice(buf.format(*v).as_bytes());
+                out.push(b'i');
+            }
+            Value::U128(v) if u64::try_from(*v).is_ok() => {
+                out.extend_from_slice(buf.format(*v).as_bytes());
+                out.push(b'u');
+            }
+            Value::F64(v) if v.is_finite() => {
+                out.extend_from_slice(ryu::Buffer::new().format_finite(*v).as_bytes())
+            }
+            Value::F64(_) => return false,
+            Value::Duration(v) => {
+                let nanos = i64::try_from(v.as_nanos()).unwrap_or(i64::MAX);
+                out.extend_from_slice(buf.format(nanos).as_bytes());
+                out.push(b'i');
+            }
+            Value::Str(v) => write_line_protocol_string(out, v),
+            // 嵌套值写为紧凑 JSON 字符串
+            Value::Array(_) | Value::Map(_) => {
+                let mut json = Vec::new();
+                value.write_json(&mut json);
+                write_line_protocol_string(out, &String::from_utf8_lossy(&json));
+            }
+            #[cfg(feature = "serde")]
+            Value::Json(v) => write_line_protocol_string(out, v),
+            other => {
+                let mut text = Vec::new();
+                other.write_plain(&mut text);
+                write_line_protocol_string(out, &String::from_utf8_lossy(&text));
+            }
+        }
+        true
+    }
+}
+
+impl Default for LineProtocolFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Formatter for LineProtocolFormatter {
+    fn format(&self, record: &Record) -> Result<Vec<u8>, fmt::Error> {
+        let mut result = Vec::with_capacity(64 + record.fields().len() * 24);
+        self.format_into(record, &mut result)?;
+        Ok(result)
+    }
+
+    fn format_into(&self, record: &Record, result: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        let measurement = match record.target() {
+            "" => "log",
+            target => target,
+        };
+        escape_line_protocol(result, measurement, b", ");
+
+        let is_tag = |key: &str| self.tag_keys.contains(&key);
+        let mut text = Vec::new();
+        for field in record.fields().iter().filter(|f| is_tag(f.key())) {
+            text.clear();
+            field.value().write_plain(&mut text);
+            if text.is_empty() {
+                // 行协议不允许空标签值
+                continue;
+            }
+            result.push(b',');
+            escape_line_protocol(result, field.key(), b",= ");
+            result.push(b'=');
+            escape_line_protocol(result, &String::from_utf8_lossy(&text), b",= ");
+        }
+
+        result.push(b' ');
+        let fields_start = result.len();
+        for field in record.fields().iter().filter(|f| !is_tag(f.key())) {
+            let start = result.len();
+            if start > fields_start {
+                result.push(b',');
+            }
+            escape_line_protocol(result, field.key(), b",= ");
+            result.push(b'=');
+            if !Self::write_field_value(result, field.value()) {
+                result.truncate(start);
+            }
+        }
+        if result.len() == fields_start {
+            result.extend_from_slice(b"message=");
+            write_line_protocol_string(result, record.message());
+        }
+
+        result.push(b' ');
+        result.extend_from_slice(itoa::Buffer::new().format(record.timestamp()).as_bytes());
+        result.push(b'\n');
+        Ok(())
+    }
+}
+
+/// 写入行协议标识符（度量名、标签与字段键、标签值），转义 `special` 中的字符与反斜杠；
+/// 换行不可出现在行内，写为 `\n`
+fn escape_line_protocol(out: &mut Vec<u8>, s: &str, special: &[u8]) {
+    for &b in s.as_bytes() {
+        match b {
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            b if special.contains(&b) => {
+                out.push(b'\\');
+                out.push(b);
+            }
+            b => out.push(b),
+        }
+    }
+}
+
+/// 写入带引号的行协议字符串字段值
+fn write_line_protocol_string(out: &mut Vec<u8>, s: &str) {
+    out.push(b'"');
+    escape_line_protocol(out, s, b"\"");
+    out.push(b'"');
+}
+
+/// 为 `out[start..]` 的每一行加上 `prefix`，结尾补齐换行（原地移动，不分配新缓冲区）
+fn prefix_lines(out: &mut Vec<u8>, start: usize, prefix: [u8; 3]) {
+    let mut end = out.len();
+    if end > start && out[end - 1] == b'\n' {
+        end -= 1;
+    }
+    let lines = out[start..end].iter().filter(|&&b| b == b'\n').count() + 1;
+    let new_len = end + lines * prefix.len() + 1;
+    out.resize(new_len, 0);
+
+    // 自后向前逐行搬移，写入位置始终不早于读取位置
+    let mut write = new_len - 1;
+    out[write] = b'\n';
+    let mut read = end;
+    loop {
+        let line_start = out[start..read]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(start, |i| start + i + 1);
+        write -= read - line_start;
+        out.copy_within(line_start..read, write);
+        write -= prefix.len();
+        out[write..write + prefix.len()].copy_from_slice(&prefix);
+        if line_start == start {
+            break;
+        }
+        write -= 1;
+        out[write] = b'\n';
+        read = line_start - 1;
+    }
+}
+
+/// 写入一条 journald 条目；值含换行时使用 `KEY\n<u64 小端长度><值>\n` 形式
+fn write_journald_entry(out: &mut Vec<u8>, key: &[u8], value: &[u8]) {
+    out.extend_from_slice(key);
+    if value.contains(&b'\n') {
+        out.push(b'\n');
+        out.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        out.push(b'=');
+    }
+    out.extend_from_slice(value);
+    out.push(b'\n');
+}
+
+/// 控制台软换行配置
+///
+/// 按可见列宽折行（忽略 ANSI 颜色转义序列，CJK 等宽字符按两列计算），
+/// 优先在空白处断行，续行以固定缩进开头，便于本地开发时在终端阅读长日志。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineWrap {
+    /// 最大列宽
+    width: usize,
+    /// 续行缩进列数
+    indent: usize,
+}
+
+impl LineWrap {
+    /// 默认续行缩进
+    pub const DEFAULT_INDENT: usize = 4;
+
+    /// 终端宽度无法检测时使用的列宽
+    pub const FALLBACK_WIDTH: usize = 80;
+
+    /// 使用指定列宽创建换行配置
+    pub fn new(width: usize) -> Self {
+        Self {
+            width: width.max(1),
+            indent: Self::DEFAULT_INDENT,
+        }
+    }
+
+    /// 根据终端宽度（`COLUMNS` 环境变量）创建换行配置，检测失败时使用 80 列
+    pub fn detect() -> Self {
+        let width = std::env::var("COLUMNS")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|w| *w > 0)
+            .unwrap_or(Self::FALLBACK_WIDTH);
+        Self::new(width)
+    }
+
+    /// 设置续行缩进列数
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// 获取最大列宽
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// 获取续行缩进列数
+    pub fn indent(&self) -> usize {
+        self.indent
+    }
+
+    /// 对一行格式化结果（不含结尾换行符）执行软换行
+    pub fn apply(&self, line: &[u8]) -> Vec<u8> {
+        let text = String::from_utf8_lossy(line);
+        // 缩进过大时退化为不缩进，保证续行至少能容纳一个字符
+        let indent = if self.indent < self.width {
+            self.indent
+        } else {
+            0
+        };
+
+        let mut out = String::with_capacity(text.len() + text.len() / self.width * (indent + 1));
+        for (i, logical) in text.split('\n').enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            self.wrap_logical_line(logical, indent, &mut out);
+        }
+        out.into_bytes()
+    }
+
+    /// 折行单个逻辑行
+    fn wrap_logical_line(&self, line: &str, indent: usize, out: &mut String) {
+        let mut col = 0;
+        // 当前行中最后一个可断行空白的位置（输出缓冲区偏移、该位置之后的列数起点）
+        let mut last_space: Option<(usize, usize)> = None;
+        let mut chars = line.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            // ANSI 转义序列原样输出且不占列宽
+            if c == '\x1b' {
+                out.push(c);
+                if chars.peek() == Some(&'[') {
+                    for esc in chars.by_ref() {
+                        out.push(esc);
+                        if esc.is_ascii_alphabetic() {
+                            break;
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let w = char_width(c);
+            if col + w > self.width && col > indent {
+                if c == ' ' {
+                    // 溢出位置恰好是空白：直接断行并丢弃该空白
+                    push_break(out, indent);
+                    col = indent;
+                    last_space = None;
+                    continue;
+                }
+                match last_space.take() {
+                    Some((pos, tail_col)) => {
+                        // 在最后一个空白处断行，空白本身被换行符替换
+                        let tail = out.split_off(pos + 1);
+                        out.truncate(pos);
+                        push_break(out, indent);
+                        out.push_str(&tail);
+                        col = indent + col - tail_col;
+                    }
+                    None => {
+                        push_break(out, indent);
+                        col = indent;
+                    }
+                }
+            }
+
+            if c == ' ' {
+                last_space = Some((out.len(), col + 1));
+            }
+            out.push(c);
+            col += w;
+        }
+    }
+}
+
+/// 写入换行符与续行缩进
+fn push_break(out: &mut String, indent: usize) {
+    out.push('\n');
+    for _ in 0..indent {
+        out.push(' ');
+    }
+}
+
+/// 估算字符在终端中的显示列宽（CJK/全角/表情按两列）
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    #[test]
+    fn test_line_wrap_breaks_at_whitespace_with_indent() {
+        let wrap = LineWrap::new(10).with_indent(2);
+        let out = wrap.apply(b"aaaa bbbb cccc dddd");
+        assert_eq!(String::from_utf8_lossy(&out), "aaaa bbbb\n  cccc\n  dddd");
+    }
+
+    #[test]
+    fn test_line_wrap_hard_breaks_long_words_and_ignores_ansi() {
+        let wrap = LineWrap::new(4).with_indent(1);
+        let out = wrap.apply(b"\x1b[31mabcdefg\x1b[0m");
+        assert_eq!(String::from_utf8_lossy(&out), "\x1b[31mabcd\n efg\x1b[0m");
+    }
+
+    #[test]
+    fn test_default_formatter_level_markers() {
+        let record = Record::new(Level::Warn, "app", "app.rs", 7, "disk low".to_string());
+
+        let letter = DefaultFormatter::plain().with_level_marker(LevelMarker::Letter);
+        let out = String::from_utf8(letter.format(&record).unwrap()).unwrap();
+        assert!(out.contains("] W [app:7] disk low"));
+
+        let emoji = DefaultFormatter::colored().with_level_marker(LevelMarker::Emoji);
+        let out = String::from_utf8(emoji.format(&record).unwrap()).unwrap();
+        assert!(out.contains("⚠️ [app:7] disk low"));
+        assert!(!out.contains("\x1b["));
+    }
+
+    #[test]
+    fn test_simple_formatter_with_line_wrap() {
+        let formatter = SimpleFormatter::new().with_line_wrap(LineWrap::new(20));
+        let record = Record::new(
+            Level::Info,
+            "test",
+            "test.rs",
+            1,
+            "hello wrapped world".to_string(),
+        );
+        let out = formatter.format(&record).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&out),
+            "[INFO] hello wrapped\n    world\n"
+        );
+    }
+
+    #[test]
+    fn test_json_formatter_escapes_strings() {
+        let record = Record::new(Level::Info, "app", "a.rs", 3, "q\"\\\n".to_string());
+        let out = String::from_utf8(JsonFormatter::new().format(&record).unwrap()).unwrap();
+        assert!(out.ends_with(
+            r#""level":"INFO","target":"app","file":"a.rs","line":3,"message":"q\"\\\n"}
+"#
+        ));
+
+        let pretty = String::from_utf8(JsonFormatter::pretty().format(&record).unwrap()).unwrap();
+        assert!(pretty.starts_with("{\n  \"timestamp\": "));
+        assert!(pretty.ends_with("  \"line\": 3,\n  \"message\": \"q\\\"\\\\\\n\"\n}\n"));
+    }
+
+    #[test]
+    fn test_iso8601_timestamp_matches_chrono() {
+        let shanghai = FixedOffset::east_opt(8 * 3600);
+        let west = FixedOffset::west_opt(3 * 3600 + 30 * 60);
+        for ns in [
+            0u128,
+            1_700_000_000_123_456_789,
+            253_402_300_799_999_999_999,
+        ] {
+            let secs = (ns / 1_000_000_000) as i64;
+            let dt = DateTime::<Utc>::from_timestamp(secs, (ns % 1_000_000_000) as u32).unwrap();
+
+            let utc = DefaultFormatter::with_timestamp_style(TimestampStyle::Iso8601(None));
+            let mut out = Vec::new();
+            utc.write_timestamp(&mut out, ns);
+            assert_eq!(
+                out,
+                dt.format("%Y-%m-%dT%H:%M:%S%.9fZ").to_string().into_bytes()
+            );
+
+            for offset in [shanghai, west] {
+                let local = DefaultFormatter::with_timestamp_style(TimestampStyle::Iso8601(offset));
+                let mut out = Vec::new();
+                local.write_timestamp(&mut out, ns);
+                let expected = dt
+                    .with_timezone(&offset.unwrap())
+                    .format("%Y-%m-%dT%H:%M:%S%.9f%:z")
+                    .to_string();
+                assert_eq!(String::from_utf8(out).unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_level_prefix_table_matches_markers() {
+        for level in [
+            Level::Trace,
+            Level::Debug,
+            Level::Info,
+            Level::Warn,
+            Level::Error,
+        ] {
+            let text = String::from_utf8_lossy(level_prefix(LevelMarker::Text, false, level));
+            assert_eq!(text, format!("[{}] ", level.as_str()));
+            for marker in [LevelMarker::Letter, LevelMarker::Emoji] {
+                let plain = String::from_utf8_lossy(level_prefix(marker, false, level));
+                assert_eq!(plain, format!("{} ", marker.marker(level)));
+            }
+            let colored = String::from_utf8_lossy(level_prefix(LevelMarker::Text, true, level));
+            assert!(
+                colored.starts_with("\x1b[") && colored.ends_with(&format!("[{}]\x1b[0m ", level))
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_timestamp_shared_until_end_batch() {
+        let formatter = DefaultFormatter::plain()
+            .with_timestamp_resolution(TimestampResolution::PerBatch { with_delta: true });
+        let first = Record::new(Level::Info, "app", "a.rs", 1, "a".to_string());
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        let second = Record::new(Level::Info, "app", "a.rs", 1, "b".to_string());
+        let base = first.timestamp().to_string();
+        let delta = second.timestamp() - first.timestamp();
+
+        let out1 = String::from_utf8(formatter.format(&first).unwrap()).unwrap();
+        let out2 = String::from_utf8(formatter.format(&second).unwrap()).unwrap();
+        assert!(out1.starts_with(&format!("[{}+0] ", base)));
+        assert!(out2.starts_with(&format!("[{}+{}] ", base, delta)));
+
+        formatter.end_batch();
+        let out3 = String::from_utf8(formatter.format(&second).unwrap()).unwrap();
+        assert!(out3.starts_with(&format!("[{}+0] ", second.timestamp())));
+    }
+
+    #[test]
+    fn test_callsite_id_in_output() {
+        static CALLSITE: crate::callsite::Callsite =
+            crate::callsite::Callsite::new(Level::Info, module_path!(), file!(), line!());
+        let record =
+            Record::new(Level::Info, "app", "a.rs", 3, "m".to_string()).with_callsite(&CALLSITE);
+        let id = CALLSITE.id();
+
+        let text = DefaultFormatter::plain().with_callsite_id(true);
+        let out = String::from_utf8(text.format(&record).unwrap()).unwrap();
+        assert!(out.ends_with(&format!("[app:3] #{} m\n", id)));
+
+        let out = String::from_utf8(JsonFormatter::new().format(&record).unwrap()).unwrap();
+        assert!(out.contains(&format!("\"line\":3,\"callsite\":{},\"message\"", id)));
+    }
+
+    #[test]
+    fn test_relative_timestamp_in_output() {
+        let record = Record::new(Level::Info, "app", "a.rs", 3, "m".to_string());
+        let relative = record.relative_timestamp();
+        let base = crate::record::timestamp_base()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        assert_eq!(u128::from(relative), record.timestamp() - base);
+
+        let mut expected = Vec::new();
+        write_relative(&mut expected, relative);
+        let expected = String::from_utf8(expected).unwrap();
+        let text = DefaultFormatter::plain().with_relative_timestamp(true);
+        let out = String::from_utf8(text.format(&record).unwrap()).unwrap();
+        assert!(out.starts_with(&format!("[{} +{}] ", record.timestamp(), expected)));
+
+        let json = JsonFormatter::new().with_relative_timestamp(true);
+        let out = String::from_utf8(json.format(&record).unwrap()).unwrap();
+        assert!(out.contains(&format!(",\"rel_ns\":{},\"level\"", relative)));
+
+        let mut out = Vec::new();
+        write_relative(&mut out, 12_000_345_678);
+        assert_eq!(out, b"12.000345678");
+    }
+
+    #[test]
+    fn test_trace_context_in_output() {
+        let context = crate::trace::TraceContext::from_hex(
+            "4bf92f3577b34da6a3ce929d0e0e4736",
+            "00f067aa0ba902b7",
+        )
+        .unwrap();
+        let record = Record::new(Level::Info, "app", "a.rs", 3, "m".to_string())
+            .with_field("user", 7u64)
+            .with_trace_context(context);
+
+        let out = String::from_utf8(DefaultFormatter::plain().format(&record).unwrap()).unwrap();
+        assert!(out.ends_with(
+            "m user=7 trace_id=4bf92f3577b34da6a3ce929d0e0e4736 span_id=00f067aa0ba902b7\n"
+        ));
+        let out = String::from_utf8(JsonFormatter::new().format(&record).unwrap()).unwrap();
+        assert!(out.contains(
+            "\"line\":3,\"trace_id\":\"4bf92f3577b34da6a3ce929d0e0e4736\",\"span_id\":\"00f067aa0ba902b7\",\"message\""
+        ));
+    }
+
+    #[test]
+    fn test_thread_in_output() {
+        let record = std::thread::Builder::new()
+            .name("worker-1".into())
+            .spawn(|| Record::new(Level::Info, "app", "a.rs", 3, "m".to_string()).with_thread())
+            .unwrap()
+            .join()
+            .unwrap();
+        let id = record.thread().unwrap().id();
+        assert_eq!(record.thread().unwrap().name(), Some("worker-1"));
+
+        let text = DefaultFormatter::plain().with_thread(true);
+        let out = String::from_utf8(text.format(&record).unwrap()).unwrap();
+        assert!(out.ends_with("[app:3] (worker-1) m\n"));
+        // 未启用或未捕获线程时不输出
+        let out = String::from_utf8(DefaultFormatter::plain().format(&record).unwrap()).unwrap();
+        assert!(out.ends_with("[app:3] m\n"));
+
+        let out = String::from_utf8(JsonFormatter::new().format(&record).unwrap()).unwrap();
+        assert!(out.contains(&format!(
+            "\"line\":3,\"thread_id\":{},\"thread\":\"worker-1\",\"message\"",
+            id
+        )));
+
+        let unnamed = std::thread::spawn(|| {
+            Record::new(Level::Info, "app", "a.rs", 3, "m".to_string()).with_thread()
+        })
+        .join()
+        .unwrap();
+        let mut out = Vec::new();
+        write_thread(&mut out, unnamed.thread().unwrap());
+        assert_eq!(
+            out,
+            format!("thread-{}", unnamed.thread().unwrap().id()).as_bytes()
+        );
+        assert_ne!(unnamed.thread().unwrap().id(), id);
+    }
+
+    #[test]
+    fn test_formatters_render_fields() {
+        let record = Record::new(Level::Info, "app", "a.rs", 3, "login".to_string())
+            .with_field("user", "alice smith")
+            .with_field("id", 7u128);
+
+        let out = String::from_utf8(DefaultFormatter::plain().format(&record).unwrap()).unwrap();
+        assert!(out.ends_with("login user=\"alice smith\" id=7\n"));
+
+        let out = String::from_utf8(JsonFormatter::new().format(&record).unwrap()).unwrap();
+        assert!(
+            out.ends_with(
+                "\"message\":\"login\",\"fields\":{\"user\":\"alice smith\",\"id\":7}}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_hybrid_formatter_appends_json_fields() {
+        let record = Record::new(Level::Warn, "app", "a.rs", 3, "login".to_string())
+            .with_field("user", "alice \"a\"")
+            .with_field("id", 7u128);
+
+        let plain = HybridFormatter::new().with_color(false);
+        let out = String::from_utf8(plain.format(&record).unwrap()).unwrap();
+        assert!(out.ends_with("] [WARN] [app:3] login {\"user\":\"alice \\\"a\\\"\",\"id\":7}\n"));
+
+        let colored = HybridFormatter::new().with_color(true);
+        let out = String::from_utf8(colored.format(&record).unwrap()).unwrap();
+        assert!(out.contains("\x1b[33m[WARN]\x1b[0m [app:3] login {"));
+
+        let bare = Record::new(Level::Info, "app", "a.rs", 3, "ready".to_string());
+        let out = String::from_utf8(plain.format(&bare).unwrap()).unwrap();
+        assert!(out.ends_with("[app:3] ready\n"));
+    }
+
+    #[test]
+    fn test_format_style_from_str() {
+        assert_eq!("dev".parse::<FormatStyle>().unwrap(), FormatStyle::Hybrid);
+        assert_eq!("JSON".parse::<FormatStyle>().unwrap(), FormatStyle::Json);
+        for style in [
+            FormatStyle::Text,
+            FormatStyle::Json,
+            FormatStyle::Simple,
+            FormatStyle::Hybrid,
+        ] {
+            assert_eq!(style.as_str().parse::<FormatStyle>().unwrap(), style);
+        }
+        assert!("yaml".parse::<FormatStyle>().is_err());
+    }
+
+    #[test]
+    fn test_json_max_line_splits_message() {
+        let message = "日志\"x\n".repeat(300);
+        let record = Record::new(Level::Info, "app", "a.rs", 3, message.clone()).with_field("k", 1);
+        let out = String::from_utf8(
+            JsonFormatter::new()
+                .with_max_line_bytes(512)
+                .format(&record)
+                .unwrap(),
+        )
+        .unwrap();
+
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines.len() > 1);
+        let mut rebuilt = String::new();
+        for (i, line) in lines.iter().enumerate() {
+            assert!(line.len() < 512);
+            let part = format!(
+                ",\"part\":{},\"parts\":{},\"fields\":{{\"k\":1}}}}",
+                i + 1,
+                lines.len()
+            );
+            assert!(line.ends_with(&part));
+            let start = line.find("\"message\":\"").unwrap() + 11;
+            let end = line.rfind("\",\"part\"").unwrap();
+            rebuilt.push_str(&line[start..end]);
+        }
+        assert_eq!(rebuilt, crate::escape::escape_json(&message));
+
+        let short = Record::new(Level::Info, "app", "a.rs", 3, "ok".to_string());
+        let out = JsonFormatter::docker().format(&short).unwrap();
+        assert!(!out.windows(6).any(|w| w == b"\"part\""));
+    }
+
+    #[test]
+    fn test_multiline_fold() {
+        let record = Record::new(
+            Level::Error,
+            "app",
+            "a.rs",
+            3,
+            "panic: boom\r\n  at main.rs:1\n  at lib.rs:2".to_string(),
+        );
+        assert!(Multiline::Keep.fold(&record).is_none());
+
+        let escaped = Multiline::Escape.fold(&record).unwrap();
+        assert_eq!(
+            escaped.message(),
+            "panic: boom\\r\\n  at main.rs:1\\n  at lib.rs:2"
+        );
+
+        let stacked = Multiline::StackField.fold(&record).unwrap();
+        assert_eq!(stacked.message(), "panic: boom");
+        let out = String::from_utf8(DefaultFormatter::plain().format(&stacked).unwrap()).unwrap();
+        assert!(out.ends_with("panic: boom stack=\"  at main.rs:1\\n  at lib.rs:2\"\n"));
+
+        let single = Record::new(Level::Info, "app", "a.rs", 3, "ok".to_string());
+        assert!(Multiline::StackField.fold(&single).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_output_parses_with_serde_json() {
+        let message: String = (0u8..0x20)
+            .map(char::from)
+            .chain("\"\\/\u{7f}\u{2028}日志🚀".chars())
+            .collect();
+        let record = Record::new(Level::Error, "app\n", "a\\b.rs", 9, message.clone())
+            .with_field("path\"key", "C:\\tmp\t")
+            .with_field("ratio", f64::INFINITY)
+            .with_field(
+                "nested",
+                crate::Value::Map(vec![("k\u{1}".into(), crate::Value::Str("v\r".into()))]),
+            );
+
+        for formatter in [JsonFormatter::new(), JsonFormatter::pretty()] {
+            let out = formatter.format(&record).unwrap();
+            let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+            assert_eq!(parsed["message"], message.as_str());
+            assert_eq!(parsed["target"], "app\n");
+            assert_eq!(parsed["file"], "a\\b.rs");
+            assert_eq!(parsed["fields"]["path\"key"], "C:\\tmp\t");
+            assert!(parsed["fields"]["ratio"].is_null());
+            assert_eq!(parsed["fields"]["nested"]["k\u{1}"], "v\r");
+        }
+    }
+
+    #[test]
+    fn test_json_key_mapping_and_static_fields() {
+        let record = Record::new(Level::Warn, "db", "a.rs", 3, "slow".to_string());
+        let time = {
+            let mut out = Vec::new();
+            write_iso8601(&mut out, &utc_datetime(record.timestamp()).naive_utc());
+            String::from_utf8(out).unwrap()
+        };
+
+        let out = JsonFormatter::datadog()
+            .with_static_field("service", "checkout")
+            .with_static_field("env", "prod")
+            .format(&record)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{{\"timestamp\":\"{}Z\",\"status\":\"warning\",\"logger.name\":\"db\",\
+                 \"file\":\"a.rs\",\"line\":3,\"message\":\"slow\",\"service\":\"checkout\",\
+                 \"env\":\"prod\"}}\n",
+                time
+            )
+        );
+
+        let out = JsonFormatter::google_cloud()
+            .with_key(JsonKey::Message, "msg")
+            .format(&record.with_field("rows", 2u64))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{{\"time\":\"{}Z\",\"severity\":\"WARNING\",\"target\":\"db\",\
+                 \"file\":\"a.rs\",\"line\":3,\"msg\":\"slow\",\"fields\":{{\"rows\":2}}}}\n",
+                time
+            )
+        );
+    }
+
+    #[test]
+    fn test_json_rfc3339_timestamp() {
+        let record = Record::new(Level::Info, "app", "a.rs", 3, "m".to_string());
+        let out = String::from_utf8(
+            JsonFormatter::new()
+                .with_rfc3339_timestamps()
+                .format(&record)
+                .unwrap(),
+        )
+        .unwrap();
+        let expected = utc_datetime(record.timestamp())
+            .format("{\"timestamp\":\"%Y-%m-%dT%H:%M:%S%.9fZ\",")
+            .to_string();
+        assert!(out.starts_with(&expected));
+    }
+
+    #[test]
+    fn test_systemd_and_journald_formatters() {
+        let record = Record::new(Level::Warn, "app", "a.rs", 3, "disk\nfull".to_string())
+            .with_field("mount-point", "/var");
+
+        let out = SystemdFormatter::new().format(&record).unwrap();
+        assert_eq!(out, b"<4>[app:3] disk\n<4>full mount-point=/var\n");
+
+        let out = SystemdFormatter::wrap(Arc::new(SimpleFormatter::new()))
+            .format(&record)
+            .unwrap();
+        assert_eq!(out, b"<4>[WARN] disk\n<4>full\n");
+
+        // 追加写入时不改动缓冲区中已有的内容
+        let mut out = b"keep".to_vec();
+        SystemdFormatter::new()
+            .format_into(&record, &mut out)
+            .unwrap();
+        assert_eq!(out, b"keep<4>[app:3] disk\n<4>full mount-point=/var\n");
+        let mut out = b"x\n".to_vec();
+        SystemdFormatter::wrap(Arc::new(SimpleFormatter::new()))
+            .format_into(
+                &Record::new(Level::Info, "app", "a.rs", 3, String::new()),
+                &mut out,
+            )
+            .unwrap();
+        assert_eq!(out, b"x\n<6>[INFO] \n");
+
+        let out = JournaldFormatter::new().format(&record).unwrap();
+        let mut expected = b"PRIORITY=4\nMESSAGE\n".to_vec();
+        expected.extend_from_slice(&9u64.to_le_bytes());
+        expected.extend_from_slice(b"disk\nfull\nTARGET=app\nCODE_FILE=a.rs\nCODE_LINE=3\n");
+        expected.extend_from_slice(b"MOUNT_POINT=/var\n");
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_line_protocol_formatter() {
+        let record = Record::new(
+            Level::Info,
+            "http requests",
+            "a.rs",
+            3,
+            "served".to_string(),
+        )
+        .with_field("route", "/api,v1")
+        .with_field("latency", 1.5)
+        .with_field("status", 200i64)
+        .with_field("bytes", 512u64)
+        .with_field("ok", true)
+        .with_field("nan", f64::NAN)
+        .with_field("note", "say \"hi\"");
+        let out = LineProtocolFormatter::new()
+            .with_tag_keys(&["route"])
+            .format(&record)
+            .unwrap();
+        let expected = format!(
+            "http\\ requests,route=/api\\,v1 latency=1.5,status=200i,bytes=512u,ok=true,\
+             note=\"say \\\"hi\\\"\" {}\n",
+            record.timestamp()
+        );
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+        // 没有字段的记录以消息作为字段
+        let record = Record::new(Level::Info, "", "a.rs", 3, "up".to_string());
+        let out = LineProtocolFormatter::new().format(&record).unwrap();
+        assert_eq!(
+            out,
+            format!("log message=\"up\" {}\n", record.timestamp()).into_bytes()
+        );
+    }
+}
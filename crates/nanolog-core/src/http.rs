@@ -0,0 +1,461 @@
+/*!
+HTTP 批量输出目标。
+
+将已格式化的记录攒成批，以单个 POST 请求发送到 HTTP 端点（Loki、Elasticsearch、Vector 等的接收接口）。
+基于标准库的 `TcpStream` 实现最小的 HTTP/1.1 客户端，仅支持 `http://`；需要 TLS 时可经本地代理转发。
+*/
+
+use crate::delivery::{Ack, Batch, Transport};
+use crate::sink::{BatchCompression, Sink};
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// 批量载荷格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HttpPayload {
+    /// 每行一条记录（`application/x-ndjson`）
+    #[default]
+    NewlineDelimited,
+    /// JSON 数组，每条记录为一个元素（`application/json`），要求记录本身是 JSON
+    JsonArray,
+    /// 原样拼接的 protobuf 消息（`application/x-protobuf`），要求每条记录是同类型的完整消息，
+    /// 拼接即合并（如 `nanolog_rs::otlp::OtlpFormatter` 的输出）
+    Protobuf,
+}
+
+impl HttpPayload {
+    fn content_type(self) -> &'static str {
+        match self {
+            HttpPayload::NewlineDelimited => "application/x-ndjson",
+            HttpPayload::JsonArray => "application/json",
+            HttpPayload::Protobuf => "application/x-protobuf",
+        }
+    }
+
+    /// 将一条记录追加到载荷
+    fn append(self, body: &mut Vec<u8>, record: &[u8]) {
+        match self {
+            HttpPayload::NewlineDelimited => {
+                body.extend_from_slice(record);
+                if !record.ends_with(b"\n") {
+                    body.push(b'\n');
+                }
+            }
+            HttpPayload::JsonArray => {
+                body.push(if body.is_empty() { b'[' } else { b',' });
+                let end = record
+                    .iter()
+                    .rposition(|b| !b.is_ascii_whitespace())
+                    .map_or(0, |i| i + 1);
+                body.extend_from_slice(&record[..end]);
+            }
+            HttpPayload::Protobuf => body.extend_from_slice(record),
+        }
+    }
+
+    /// 完成载荷
+    fn finish(self, body: &mut Vec<u8>) {
+        if self == HttpPayload::JsonArray && !body.is_empty() {
+            body.push(b']');
+        }
+    }
+}
+
+/// 解析后的 `http://host[:port]/path` 端点
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Endpoint {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl Endpoint {
+    fn parse(url: &str) -> io::Result<Self> {
+        let invalid =
+            |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, format!("{}: {}", msg, url));
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| invalid("only http:// endpoints are supported"))?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        // IPv6 字面量地址带方括号，如 `[::1]:8080`
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => {
+                (host, port.parse().map_err(|_| invalid("invalid port"))?)
+            }
+            _ => (authority, 80),
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.is_empty() {
+            return Err(invalid("missing host"));
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    /// `Host` 请求头的值
+    fn host_header(&self) -> String {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        if self.port == 80 {
+            host
+        } else {
+            format!("{}:{}", host, self.port)
+        }
+    }
+}
+
+/// 待发送批次
+#[derive(Default)]
+struct HttpBatch {
+    body: Vec<u8>,
+    records: usize,
+}
+
+/// HTTP 批量输出目标
+///
+/// 写入的记录追加到当前批次，批次字节数达到上限或刷新时以一个 POST 请求发送。
+/// 连接失败、超时、`429` 与 `5xx` 响应按退避重试；重试耗尽或收到其他非 `2xx` 响应时丢弃该批并返回错误。
+/// 也实现了 [`Transport`]，可交给 [`DeliverySink`](crate::delivery::DeliverySink) 做确认式投递。
+pub struct HttpSink {
+    endpoint: Endpoint,
+    payload: HttpPayload,
+    headers: Vec<(String, String)>,
+    timeout: Duration,
+    max_batch_bytes: usize,
+    max_retries: u32,
+    retry_backoff: Duration,
+    compression: Option<BatchCompression>,
+    batch: Mutex<HttpBatch>,
+    dropped_records: AtomicU64,
+}
+
+impl HttpSink {
+    /// 创建发送到 `http://host[:port]/path` 的输出目标
+    pub fn new(url: &str) -> io::Result<Self> {
+        Ok(Self {
+            endpoint: Endpoint::parse(url)?,
+            payload: HttpPayload::default(),
+            headers: Vec::new(),
+            timeout: Duration::from_secs(5),
+            max_batch_bytes: 1024 * 1024,
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(200),
+            compression: None,
+            batch: Mutex::new(HttpBatch::default()),
+            dropped_records: AtomicU64::new(0),
+        })
+    }
+
+    /// 设置载荷格式（默认按行分隔）
+    pub fn with_payload(mut self, payload: HttpPayload) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// 添加请求头（如认证令牌）
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// 设置连接与读写超时（默认 5 秒）
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置单个请求体的最大字节数（默认 1MB），单条超限的记录单独发送
+    pub fn with_max_batch_bytes(mut self, bytes: usize) -> Self {
+        self.max_batch_bytes = bytes.max(1);
+        self
+    }
+
+    /// 设置失败后的重试次数与首次重试前的退避（之后逐次翻倍）
+    pub fn with_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.max_retries = retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// 设置请求体压缩（附带 `Content-Encoding`）
+    ///
+    /// 压缩在消费者线程上按批进行，每个消费者批次单独以一个请求发送，不再攒到请求体上限。
+    pub fn with_compression(mut self, compression: BatchCompression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// 重试耗尽后丢弃的记录数
+    pub fn dropped_records(&self) -> u64 {
+        self.dropped_records.load(Ordering::Relaxed)
+    }
+
+    fn lock_batch(&self) -> io::Result<std::sync::MutexGuard<'_, HttpBatch>> {
+        self.batch
+            .lock()
+            .map_err(|_| io::Error::other("http sink batch poisoned"))
+    }
+
+    /// 追加一条记录，批次将超出上限时先发送当前批次
+    fn append(&self, batch: &mut HttpBatch, record: &[u8]) -> io::Result<()> {
+        let mut result = Ok(());
+        if batch.records > 0 && batch.body.len() + record.len() + 1 > self.max_batch_bytes {
+            result = self.send_batch(batch);
+        }
+        self.payload.append(&mut batch.body, record);
+        batch.records += 1;
+        if batch.body.len() >= self.max_batch_bytes {
+            result = result.and(self.send_batch(batch));
+        }
+        result
+    }
+
+    /// 发送并清空当前批次，失败时丢弃
+    fn send_batch(&self, batch: &mut HttpBatch) -> io::Result<()> {
+        if batch.records == 0 {
+            return Ok(());
+        }
+        self.payload.finish(&mut batch.body);
+        let result = self.post_with_retries(&batch.body, &[]);
+        if result.is_err() {
+            self.dropped_records
+                .fetch_add(batch.records as u64, Ordering::Relaxed);
+        }
+        batch.body.clear();
+        batch.records = 0;
+        result
+    }
+
+    fn post_with_retries(&self, body: &[u8], extra_headers: &[(&str, String)]) -> io::Result<()> {
+        let mut backoff = self.retry_backoff;
+        let mut attempt = 0;
+        loop {
+            let err = match self.post(body, extra_headers) {
+                Ok(status) if (200..300).contains(&status) => return Ok(()),
+                Ok(status) => {
+                    let err = io::Error::other(format!("http sink received status {}", status));
+                    if status != 429 && status < 500 {
+                        return Err(err);
+                    }
+                    err
+                }
+                Err(err) => err,
+            };
+            if attempt >= self.max_retries {
+                return Err(err);
+            }
+            attempt += 1;
+            std::thread::sleep(backoff);
+            backoff = backoff.saturating_mul(2);
+        }
+    }
+
+    /// 发送一个 POST 请求并返回响应状态码
+    fn post(&self, body: &[u8], extra_headers: &[(&str, String)]) -> io::Result<u16> {
+        let mut stream = self.connect()?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let mut request = Vec::with_capacity(256 + body.len());
+        write!(
+            request,
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.endpoint.path,
+            self.endpoint.host_header(),
+            self.payload.content_type(),
+            body.len()
+        )?;
+        for (name, value) in &self.headers {
+            write!(request, "{}: {}\r\n", name, value)?;
+        }
+        for (name, value) in extra_headers {
+            write!(request, "{}: {}\r\n", name, value)?;
+        }
+        request.extend_from_slice(b"\r\n");
+        request.extend_from_slice(body);
+        stream.write_all(&request)?;
+        stream.flush()?;
+
+        read_status(&mut stream)
+    }
+
+    fn connect(&self) -> io::Result<TcpStream> {
+        let mut last_err = io::Error::new(io::ErrorKind::NotFound, "address resolved to nothing");
+        for addr in (self.endpoint.host.as_str(), self.endpoint.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_err = err,
+            }
+        }
+        Err(last_err)
+    }
+}
+
+/// 读取响应状态行并解析状态码
+fn read_status(stream: &mut impl Read) -> io::Result<u16> {
+    let mut head = Vec::with_capacity(64);
+    let mut chunk = [0u8; 64];
+    while !head.windows(2).any(|w| w == b"\r\n") {
+        let n = stream.read(&mut chunk)?;
+        if n == 0 || head.len() > 8 * 1024 {
+            break;
+        }
+        head.extend_from_slice(&chunk[..n]);
+    }
+    std::str::from_utf8(&head)
+        .ok()
+        .and_then(|head| head.strip_prefix("HTTP/1."))
+        .and_then(|rest| rest.get(2..5))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed http response"))
+}
+
+impl Sink for HttpSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        let mut batch = self.lock_batch()?;
+        self.append(&mut batch, data)
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        let mut batch = self.lock_batch()?;
+        let mut result = Ok(());
+        for record in data {
+            result = result.and(self.append(&mut batch, record));
+        }
+        result
+    }
+
+    fn batch_compression(&self) -> Option<BatchCompression> {
+        self.compression
+    }
+
+    fn encode_batch(&self, data: &[Vec<u8>], out: &mut Vec<u8>) {
+        for record in data {
+            self.payload.append(out, record);
+        }
+        self.payload.finish(out);
+    }
+
+    fn write_compressed(&self, frame: &[u8], records: usize) -> io::Result<()> {
+        let Some(compression) = self.compression else {
+            return self.write(frame);
+        };
+        let headers = [(
+            "Content-Encoding",
+            compression.content_encoding().to_string(),
+        )];
+        let result = self.post_with_retries(frame, &headers);
+        if result.is_err() {
+            self.dropped_records
+                .fetch_add(records as u64, Ordering::Relaxed);
+        }
+        result
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        let mut batch = self.lock_batch()?;
+        self.send_batch(&mut batch)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+impl Transport for HttpSink {
+    /// 以单个请求发送批次，并通过请求头携带批次 ID 供接收方去重；
+    /// `2xx` 视为确认，其余响应视为否认
+    fn send(&self, batch: &Batch<'_>) -> io::Result<Ack> {
+        let mut body = Vec::new();
+        for record in batch.records {
+            self.payload.append(&mut body, record);
+        }
+        self.payload.finish(&mut body);
+        let headers = [
+            ("X-Batch-Sender", batch.sender.to_string()),
+            ("X-Batch-Id", batch.name.to_string()),
+            ("X-Batch-Attempt", batch.attempt.to_string()),
+            (
+                "X-Dedup-Window-Ms",
+                batch.dedup_window.as_millis().to_string(),
+            ),
+        ];
+        let status = self.post(&body, &headers)?;
+        Ok(if (200..300).contains(&status) {
+            Ack::Delivered
+        } else {
+            Ack::Rejected
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_endpoint() {
+        let e = Endpoint::parse("http://localhost:3100/loki/api/v1/push").unwrap();
+        assert_eq!(
+            e,
+            Endpoint {
+                host: "localhost".to_string(),
+                port: 3100,
+                path: "/loki/api/v1/push".to_string()
+            }
+        );
+        assert_eq!(e.host_header(), "localhost:3100");
+
+        let e = Endpoint::parse("http://vector").unwrap();
+        assert_eq!((e.port, e.path.as_str()), (80, "/"));
+        assert_eq!(e.host_header(), "vector");
+
+        let e = Endpoint::parse("http://[::1]:8080/ingest").unwrap();
+        assert_eq!((e.host.as_str(), e.port), ("::1", 8080));
+        assert_eq!(e.host_header(), "[::1]:8080");
+
+        assert!(Endpoint::parse("https://example.com").is_err());
+        assert!(Endpoint::parse("http://host:notaport/").is_err());
+    }
+
+    #[test]
+    fn test_payload_formats() {
+        let mut body = Vec::new();
+        HttpPayload::JsonArray.append(&mut body, b"{\"a\":1}\n");
+        HttpPayload::JsonArray.append(&mut body, b"{\"b\":2}\n");
+        HttpPayload::JsonArray.finish(&mut body);
+        assert_eq!(body, b"[{\"a\":1},{\"b\":2}]");
+
+        let mut body = Vec::new();
+        HttpPayload::NewlineDelimited.append(&mut body, b"a");
+        HttpPayload::NewlineDelimited.append(&mut body, b"b\n");
+        HttpPayload::NewlineDelimited.finish(&mut body);
+        assert_eq!(body, b"a\nb\n");
+
+        let mut body = Vec::new();
+        HttpPayload::Protobuf.append(&mut body, &[0x0A, 0x00]);
+        HttpPayload::Protobuf.append(&mut body, &[0x0A, 0x00]);
+        HttpPayload::Protobuf.finish(&mut body);
+        assert_eq!(body, [0x0A, 0x00, 0x0A, 0x00]);
+    }
+
+    #[test]
+    fn test_read_status() {
+        let mut response: &[u8] = b"HTTP/1.1 204 No Content\r\n\r\n";
+        assert_eq!(read_status(&mut response).unwrap(), 204);
+        let mut garbage: &[u8] = b"nope";
+        assert!(read_status(&mut garbage).is_err());
+    }
+}
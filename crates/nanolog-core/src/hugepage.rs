@@ -0,0 +1,118 @@
+/*!
+大页内存支持。
+
+对超大队列和缓冲池使用透明大页（`madvise(MADV_HUGEPAGE)`）以减少 TLB 缺失。
+仅在 Linux 且启用 `hugepages` 特性时生效；内核不支持或未开启透明大页时返回错误，
+调用方退化为普通页面继续运行。
+*/
+
+use std::io;
+
+/// 检查当前构建是否支持大页建议
+pub fn is_supported() -> bool {
+    cfg!(all(target_os = "linux", feature = "hugepages"))
+}
+
+/// 建议内核以大页支撑指定内存区域（按页对齐，区域内不足一页的部分忽略）
+#[cfg(all(target_os = "linux", feature = "hugepages"))]
+pub fn advise(ptr: *const u8, len: usize) -> io::Result<()> {
+    // SAFETY: sysconf 无副作用。
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = (ptr as usize).div_ceil(page) * page;
+    let end = (ptr as usize + len) / page * page;
+    if end <= start {
+        return Ok(());
+    }
+
+    // SAFETY: [start, end) 位于调用方拥有的分配内，madvise 只是提示，不改变内存内容。
+    let ret =
+        unsafe { libc::madvise(start as *mut libc::c_void, end - start, libc::MADV_HUGEPAGE) };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// 建议内核以大页支撑指定内存区域（按页对齐，区域内不足一页的部分忽略）
+#[cfg(not(all(target_os = "linux", feature = "hugepages")))]
+pub fn advise(_ptr: *const u8, _len: usize) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "huge pages require Linux and the `hugepages` feature",
+    ))
+}
+
+/// 环形队列大页建议器
+///
+/// 环形队列由 Disruptor 内部分配为连续的槽位数组，无法直接取得其地址；消费者以序号 0 的槽位
+/// 作为队列起始地址，并确认序号 1 的槽位紧随其后（步长等于槽位类型的大小），然后对整个队列区域
+/// 发出一次大页建议。
+///
+/// 建议发出时队列已由事件工厂初始化，已触碰的页面不会立即换成大页，
+/// 只能由内核的 khugepaged 在后台合并。
+pub(crate) struct RingAdvisor {
+    slots: usize,
+    base: Option<usize>,
+}
+
+impl RingAdvisor {
+    /// 为包含 `slots` 个槽位的队列创建建议器
+    pub(crate) fn new(slots: usize) -> Self {
+        Self { slots, base: None }
+    }
+
+    /// 观察一个槽位；取得足够信息并完成建议后返回 `Some(结果)`
+    pub(crate) fn observe<T>(&mut self, sequence: i64, slot: *const T) -> Option<io::Result<()>> {
+        let addr = slot as usize;
+        let stride = std::mem::size_of::<T>();
+        match (sequence, self.base) {
+            (0, _) => {
+                self.base = Some(addr);
+                None
+            }
+            (1, Some(base)) if addr == base + stride => {
+                Some(advise(base as *const u8, stride * self.slots))
+            }
+            // 未能按顺序观察到前两个槽位，或槽位不连续，放弃建议
+            _ => Some(Err(io::Error::other("ring layout not observable"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_advisor_derives_region_from_first_slots() {
+        let ring = vec![0u64; 1024];
+        let mut advisor = RingAdvisor::new(ring.len());
+        assert!(advisor.observe(0, &ring[0]).is_none());
+        let result = advisor.observe(1, &ring[1]).unwrap();
+        // 布局确认后才发出建议：不支持的构建在建议时失败，而不是在推算布局时失败
+        if let Err(e) = result {
+            assert_ne!(e.kind(), io::ErrorKind::Other);
+            assert!(!is_supported() || e.kind() != io::ErrorKind::Unsupported);
+        }
+
+        // 槽位不连续时不猜测步长
+        let mut advisor = RingAdvisor::new(ring.len());
+        assert!(advisor.observe(0, &ring[0]).is_none());
+        let result = advisor.observe(1, &ring[2]).unwrap();
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::Other);
+    }
+
+    #[test]
+    fn test_advise_before_first_touch() {
+        // 未触碰的 4 MiB 区域：支持的构建上建议在缺页前生效，或因内核关闭透明大页而报告错误
+        let mut region: Vec<u8> = Vec::with_capacity(4 << 20);
+        let result = advise(region.as_ptr(), region.capacity());
+        if is_supported() {
+            assert!(result.is_ok() || result.unwrap_err().raw_os_error().is_some());
+        } else {
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::Unsupported);
+        }
+        region.resize(4 << 20, 1);
+        assert!(region.iter().all(|&b| b == 1));
+    }
+}
@@ -0,0 +1,203 @@
+/*!
+tracing 集成。
+
+[`NanologLayer`] 实现 `tracing_subscriber::Layer`，将 tracing 事件（连同所在 span 的字段）
+转换为 [`Record`] 发布到 [`AsyncLogger`]，已使用 tracing 的应用因此可以把 nanolog 作为高性能写入后端。
+*/
+
+use crate::field::{Field, Value};
+use crate::{AsyncLogger, Level, Record};
+use std::fmt::{self, Write};
+use std::sync::Arc;
+use tracing_core::field::Visit;
+use tracing_core::span::{Attributes, Id, Record as SpanRecord};
+use tracing_core::{Event, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// 将 tracing 事件写入 [`AsyncLogger`] 的 Layer
+pub struct NanologLayer {
+    logger: Arc<AsyncLogger>,
+}
+
+impl NanologLayer {
+    /// 创建写入指定日志器的 Layer
+    pub fn new(logger: Arc<AsyncLogger>) -> Self {
+        Self { logger }
+    }
+}
+
+/// 保存在 span 扩展中的字段
+struct SpanFields(Vec<Field>);
+
+/// 收集事件消息与字段的访问器
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    fields: Vec<Field>,
+}
+
+impl FieldVisitor {
+    fn push(&mut self, field: &tracing_core::Field, value: Value) {
+        self.fields.push(Field::new(field.name(), value));
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &tracing_core::Field, value: &dyn fmt::Debug) {
+        let mut text = String::new();
+        let _ = write!(text, "{:?}", value);
+        if field.name() == "message" {
+            self.message = Some(text);
+        } else {
+            self.push(field, Value::from(text));
+        }
+    }
+
+    fn record_str(&mut self, field: &tracing_core::Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_string());
+        } else {
+            self.push(field, Value::from(value.to_string()));
+        }
+    }
+
+    fn record_i64(&mut self, field: &tracing_core::Field, value: i64) {
+        self.push(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &tracing_core::Field, value: u64) {
+        self.push(field, Value::from(value));
+    }
+
+    fn record_i128(&mut self, field: &tracing_core::Field, value: i128) {
+        self.push(field, Value::from(value));
+    }
+
+    fn record_u128(&mut self, field: &tracing_core::Field, value: u128) {
+        self.push(field, Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &tracing_core::Field, value: f64) {
+        self.push(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &tracing_core::Field, value: bool) {
+        self.push(field, Value::from(value));
+    }
+
+    fn record_error(
+        &mut self,
+        field: &tracing_core::Field,
+        value: &(dyn std::error::Error + 'static),
+    ) {
+        self.push(field, Value::from(value.to_string()));
+    }
+}
+
+/// 转换 tracing 级别
+fn level(metadata: &Metadata<'_>) -> Level {
+    match *metadata.level() {
+        tracing_core::Level::ERROR => Level::Error,
+        tracing_core::Level::WARN => Level::Warn,
+        tracing_core::Level::INFO => Level::Info,
+        tracing_core::Level::DEBUG => Level::Debug,
+        tracing_core::Level::TRACE => Level::Trace,
+    }
+}
+
+impl<S> Layer<S> for NanologLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        // span 需要记录字段供后续事件使用，只按级别过滤事件
+        !metadata.is_event() || self.logger.enabled(level(metadata), metadata.target())
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        span.extensions_mut().insert(SpanFields(visitor.fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &SpanRecord<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = FieldVisitor::default();
+        values.record(&mut visitor);
+        let mut extensions = span.extensions_mut();
+        match extensions.get_mut::<SpanFields>() {
+            Some(fields) => fields.0.extend(visitor.fields),
+            None => extensions.insert(SpanFields(visitor.fields)),
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = level(metadata);
+        if !self.logger.enabled(level, metadata.target()) {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let mut record = Record::new(
+            level,
+            metadata.target(),
+            metadata.file().unwrap_or(""),
+            metadata.line().unwrap_or(0),
+            visitor.message.unwrap_or_default(),
+        );
+        // span 字段按从外到内的顺序排在事件字段之前
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(fields) = span.extensions().get::<SpanFields>() {
+                    record.fields_mut().extend(fields.0.iter().cloned());
+                }
+            }
+        }
+        record.fields_mut().extend(visitor.fields);
+        let _ = self.logger.log(record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::MemorySink;
+    use std::time::Duration;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_layer_writes_events_with_span_fields() {
+        let sink = Arc::new(MemorySink::new());
+        let logger = Arc::new(AsyncLogger::new(
+            Level::Info,
+            Arc::new(crate::format::JsonFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+        ));
+        let subscriber = tracing_subscriber::registry().with(NanologLayer::new(logger.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", request_id = "r-1");
+            let _entered = span.enter();
+            tracing::info!(user = 7u64, "handled {}", "ok");
+            tracing::debug!("filtered");
+        });
+        assert!(logger.flush().is_ok());
+
+        let out = String::from_utf8(sink.get_content()).unwrap();
+        assert_eq!(out.lines().count(), 1);
+        assert!(out.contains(r#""message":"handled ok","fields":{"request_id":"r-1","user":7}}"#));
+        assert!(logger.shutdown().is_ok());
+    }
+}
@@ -20,6 +20,17 @@ pub enum Level {
 }
 
 impl Level {
+    /// 从判别值还原级别（超出范围时取最高级别）
+    pub(crate) fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Level::Trace,
+            1 => Level::Debug,
+            2 => Level::Info,
+            3 => Level::Warn,
+            _ => Level::Error,
+        }
+    }
+
     /// 获取级别的字符串表示
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -30,6 +41,16 @@ impl Level {
             Level::Error => "ERROR",
         }
     }
+
+    /// 对应的 syslog 优先级（`<N>` 前缀与 journald `PRIORITY` 使用）
+    pub fn syslog_priority(&self) -> u8 {
+        match self {
+            Level::Trace | Level::Debug => 7,
+            Level::Info => 6,
+            Level::Warn => 4,
+            Level::Error => 3,
+        }
+    }
 }
 
 impl FromStr for Level {
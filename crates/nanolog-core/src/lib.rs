@@ -0,0 +1,161 @@
+/*!
+高性能异步日志库的核心实现。
+
+包含日志器、格式化器与内置输出目标；通常通过门面 crate `nanolog-rs` 使用，
+依赖外部库的输出目标（Kafka、OTLP、插件）位于各自的 `nanolog-sinks-*` crate。
+*/
+
+#![warn(missing_docs)]
+
+use std::sync::Arc;
+
+pub mod affinity;
+mod aggregate;
+pub mod allocation;
+pub mod buffer;
+pub mod builder;
+pub mod callsite;
+pub mod context;
+mod dedup;
+pub mod delivery;
+pub mod diagnostics;
+pub mod error;
+pub mod escape;
+pub mod field;
+pub mod filter;
+pub mod format;
+pub mod http;
+pub mod hugepage;
+#[cfg(feature = "tracing")]
+pub mod layer;
+pub mod level;
+pub mod logger;
+pub mod macros;
+pub mod memory;
+pub mod metadata;
+pub mod naming;
+pub mod numa;
+mod output;
+pub mod pattern;
+pub mod ratelimit;
+pub mod record;
+pub mod replay;
+pub mod rotation;
+pub mod sink;
+pub mod slot;
+pub mod stats;
+pub mod subscribe;
+#[cfg(feature = "tail")]
+pub mod tail;
+pub mod trace;
+pub mod transform;
+mod wait;
+pub mod watchdog;
+
+// 公共API导出
+pub use crate::builder::AsyncLoggerBuilder;
+pub use crate::context::Context;
+pub use crate::field::{Field, FieldLimits, Value};
+pub use crate::filter::Filter;
+pub use crate::format::{
+    BatchFormatter, DefaultFormatter, ElasticsearchBulkFormatter, FormatStyle, Formatter,
+    HybridFormatter, JournaldFormatter, JsonFormatter, JsonKey, LineProtocolFormatter, Multiline,
+    SimpleFormatter, SystemdFormatter,
+};
+pub use crate::http::{HttpPayload, HttpSink};
+#[cfg(feature = "tracing")]
+pub use crate::layer::NanologLayer;
+pub use crate::level::Level;
+pub use crate::logger::{
+    AsyncLogger, FlushBarrier, GlobalLogger, OverflowPolicy, global_logger, init_global_logger,
+};
+pub use crate::memory::{MemoryBudget, MemoryUsage};
+pub use crate::metadata::ProcessMetadata;
+pub use crate::pattern::{LayoutError, PatternFormatter, PatternLayout};
+// 注意：宏通过#[macro_export]自动导出，无需在此处重新导出
+// pub use crate::macros::*;
+pub use crate::record::{Record, ThreadInfo};
+pub use crate::sink::{
+    BatchCompression, CompositeSink, ConsoleSink, Durability, FailoverSink, FallbackSink, FileSink,
+    LevelRoutingSink, MemorySink, NullSink, RecordMeta, RouterSink, Sink, SinkErrorPolicy,
+    SinkFailure, TcpSink, TimeoutSink,
+};
+pub use crate::slot::RecordSlot;
+pub use crate::transform::{MessageCatalog, MessageTransformer};
+pub use crate::wait::WaitStrategy;
+pub use crate::watchdog::Watchdog;
+
+/// 初始化全局日志器
+///
+/// # 示例
+/// ```no_run
+/// use nanolog_rs::{init_global_logger, Level, AsyncLogger, DefaultFormatter, ConsoleSink};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let formatter = Arc::new(DefaultFormatter::new());
+/// let sink = Arc::new(ConsoleSink::new());
+/// let logger = Arc::new(AsyncLogger::new(
+///     Level::Debug,
+///     formatter,
+///     sink,
+///     1000,
+///     10,
+///     Duration::from_millis(100),
+/// ));
+///
+/// init_global_logger(logger).unwrap();
+/// ```
+pub fn init(logger: Arc<AsyncLogger>) -> Result<(), crate::error::Error> {
+    init_global_logger(logger)
+}
+
+/// 获取全局日志器实例
+///
+/// # 示例
+/// ```
+/// use nanolog_rs::global_logger;
+///
+/// if let Some(logger) = global_logger() {
+///     // 使用logger
+/// }
+/// ```
+pub fn get_logger() -> Option<&'static GlobalLogger> {
+    global_logger()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_api_compilation() {
+        // 测试API是否能正常编译
+        let formatter = Arc::new(DefaultFormatter::new());
+        let sink = Arc::new(ConsoleSink::new());
+
+        let logger = Arc::new(AsyncLogger::new(
+            Level::Debug,
+            formatter,
+            sink,
+            1000,
+            10,
+            Duration::from_millis(100),
+        ));
+
+        // 验证基本类型可以正常编译
+        let record = Record::new(
+            Level::Info,
+            "test",
+            "test.rs",
+            1,
+            "Test message".to_string(),
+        );
+
+        // 测试日志记录功能
+        assert!(logger.log(record).is_ok());
+        assert!(logger.flush().is_ok());
+        assert!(logger.shutdown().is_ok());
+    }
+}
@@ -0,0 +1,3683 @@
+/*!
+高性能非阻塞日志系统实现。
+
+基于 Disruptor 环形缓冲与零拷贝技术，提供低延迟、高吞吐量的日志记录能力。
+
+## 特性
+
+- 非阻塞发布：调用方快速发布日志记录到环形缓冲，不等待 I/O
+- 零拷贝记录：`&'static str` 元数据和高效字节格式化，减少分配
+- 批量处理：消费者闭包在批尾统一刷新，支持批量写入接口
+- 线程安全：`Arc` 与原子计数统计发送/写入/丢失
+- 优雅关闭：等待已发送日志全部写出后关闭输出目标
+
+## 使用示例
+
+```rust
+use nanolog_rs::{AsyncLogger, Level, Record, DefaultFormatter, ConsoleSink};
+use std::sync::Arc;
+use std::time::Duration;
+
+let logger = AsyncLogger::new(
+    Level::Info,
+    Arc::new(DefaultFormatter::new()),
+    Arc::new(ConsoleSink::new()),
+    1024,
+    100,
+    Duration::from_millis(100),
+);
+
+let record = Record::new(
+    Level::Info,
+    "example",
+    file!(),
+    line!(),
+    "Hello, world!".to_string()
+);
+
+logger.log(record).unwrap();
+logger.shutdown().unwrap();
+```
+*/
+
+use disruptor::*;
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::Level;
+use crate::Record;
+use crate::aggregate::ErrorAggregator;
+use crate::buffer::BufferPool;
+use crate::dedup::Dedup;
+use crate::diagnostics::{Diagnostic, DiagnosticHandler, RecentDiagnostics, StderrDiagnostics};
+use crate::error::Error;
+use crate::field::FieldLimits;
+use crate::filter::Filter;
+use crate::format::{BatchFormatter, Formatter, Multiline};
+use crate::hugepage::RingAdvisor;
+use crate::memory::{MemoryArea, MemoryBudget, MemoryTracker, MemoryUsage};
+use crate::metadata::ProcessMetadata;
+use crate::output::{Outputs, Preformatted, SinkHealth};
+use crate::ratelimit::TokenBucket;
+use crate::sink::{Sink, SinkErrorHandler, SinkErrorPolicy};
+use crate::stats::{FlushReason, FlushStats, FlushStatsSnapshot, WakeStatsSnapshot, WriteProfiler};
+use crate::subscribe::{Receiver, Subscribers};
+use crate::trace::TraceContextProvider;
+use crate::transform::MessageTransformer;
+use crate::wait::{HybridWait, IdleParker, SleepingWait, WaitStrategy, YieldingWait};
+use crate::watchdog::Watchdog;
+
+/// 工作线程配置
+struct Event {
+    record: Record,
+    /// 两级流水线中格式化阶段的产出（首次使用时分配，单级消费者不使用）
+    staged: Mutex<Option<Box<Staged>>>,
+}
+
+/// 格式化阶段为槽位中的记录准备的结果，由写入阶段取用
+#[derive(Default)]
+struct Staged {
+    /// 渲染或转换后的记录（与槽位中的记录相同时为 `None`）
+    record: Option<Record>,
+    /// 多行折叠后用于输出的记录
+    folded: Option<Record>,
+    /// 各路输出预先格式化的结果
+    formatted: Preformatted,
+}
+
+/// 格式化阶段的事件处理函数
+type FormatStage = Box<dyn FnMut(&Event, Sequence, bool) + Send>;
+
+/// 两级流水线中格式化线程的名称
+const FORMAT_THREAD_NAME: &str = "nanolog-format";
+
+/// 渲染延迟格式化的消息并应用消息转换；记录不变时返回 `None`
+fn prepare(record: &Record, transformer: Option<&Arc<dyn MessageTransformer>>) -> Option<Record> {
+    let resolved = record.is_lazy().then(|| record.resolve());
+    let current = resolved.as_ref().unwrap_or(record);
+    transformer
+        .and_then(|t| t.transform(current))
+        .map(|message| current.with_message(message))
+        .or(resolved)
+}
+
+/// 消费者线程的配置
+struct ConsumerThread {
+    /// 线程名称
+    name: &'static str,
+    /// 由 NUMA 节点确定的核心（在线程创建时固定）
+    pin_core: Option<usize>,
+    /// 显式指定的核心（在消费者线程上固定）
+    core: Option<usize>,
+    /// nice 值
+    priority: Option<i32>,
+    /// 空闲停靠器（启用空闲停靠时）
+    parker: Option<Arc<IdleParker>>,
+    diagnostics: Arc<dyn DiagnosticHandler>,
+}
+
+impl ConsumerThread {
+    /// 在消费者线程上调用：安装空闲停靠器，应用绑核与优先级设置，失败时通过诊断通道上报
+    fn setup(&self) {
+        if let Some(parker) = &self.parker {
+            parker.install();
+        }
+        let results = [
+            ("core", self.core.map(crate::affinity::pin_current_thread)),
+            (
+                "priority",
+                self.priority
+                    .map(crate::affinity::set_current_thread_priority),
+            ),
+        ];
+        for (setting, result) in results {
+            if let Some(Err(e)) = result {
+                self.diagnostics
+                    .handle(&Diagnostic::ConsumerThreadSetupFailed {
+                        setting,
+                        error: e.to_string(),
+                    });
+            }
+        }
+    }
+}
+
+/// 日志器的可选配置（由构建器填充）
+#[derive(Clone, Default)]
+pub(crate) struct LoggerOptions {
+    /// 消费者线程上的消息转换钩子
+    pub(crate) transformer: Option<Arc<dyn MessageTransformer>>,
+    /// 批内待刷新字节数达到该阈值时立即刷新
+    pub(crate) flush_threshold: Option<usize>,
+    /// 写入不低于该级别的记录后立即刷新
+    pub(crate) flush_on: Option<Level>,
+    /// 过期丢弃：不高于该级别的记录创建后超过该时长仍未写出时丢弃
+    pub(crate) max_age: Option<(Duration, Level)>,
+    /// 过载降级：队列积压超过水位时拒绝不高于该级别的新记录
+    pub(crate) shed_level: Option<Level>,
+    /// 令牌桶限流：每秒速率与突发容量
+    pub(crate) rate_limit: Option<(u32, u32)>,
+    /// 诊断处理器（`None` 表示输出到标准错误）
+    pub(crate) diagnostics: Option<Arc<dyn DiagnosticHandler>>,
+    /// 单次 `log()` 调用的延迟预算（仅调试构建检查）
+    pub(crate) latency_budget: Option<Duration>,
+    /// 消费者持续空闲多久后停靠（`None` 表示始终忙等）
+    pub(crate) idle_park_after: Option<Duration>,
+    /// 消费者等待策略（未启用空闲停靠时生效）
+    pub(crate) wait_strategy: WaitStrategy,
+    /// 是否拆分为格式化与写入两级流水线
+    pub(crate) pipeline: bool,
+    /// 内存预算
+    pub(crate) memory_budget: MemoryBudget,
+    /// 环形队列与消费者线程所在的 NUMA 节点
+    pub(crate) numa_node: Option<usize>,
+    /// 消费者线程名称（`None` 表示默认的 `nanolog-writer`）
+    pub(crate) consumer_name: Option<Cow<'static, str>>,
+    /// 消费者线程固定到的核心
+    pub(crate) consumer_core: Option<usize>,
+    /// 消费者线程的 nice 值
+    pub(crate) consumer_priority: Option<i32>,
+    /// 使用透明大页支撑环形队列
+    pub(crate) huge_pages: bool,
+    /// 按目标过滤
+    pub(crate) filter: Option<Filter>,
+    /// 字段大小限制
+    pub(crate) field_limits: Option<FieldLimits>,
+    /// 队列满时的处理策略
+    pub(crate) overflow_policy: OverflowPolicy,
+    /// 多行消息折叠方式
+    pub(crate) multiline: Multiline,
+    /// 消费者存活看门狗
+    pub(crate) watchdog: Option<Watchdog>,
+    /// 批量格式化器（设置后替代主输出的逐条格式化）
+    pub(crate) batch_formatter: Option<Arc<dyn BatchFormatter>>,
+    /// 额外输出：同一记录以各自的格式化器写入各自的输出目标
+    pub(crate) outputs: Vec<(Arc<dyn Formatter>, Arc<dyn Sink>)>,
+    /// 输出目标写入失败时的处理策略
+    pub(crate) sink_error_policy: SinkErrorPolicy,
+    /// 输出目标操作失败回调
+    pub(crate) on_error: Option<SinkErrorHandler>,
+    /// 在输出中为丢失的记录写入标记行
+    pub(crate) drop_markers: bool,
+    /// 重复消息抑制窗口
+    pub(crate) dedup_window: Option<Duration>,
+    /// 错误聚合窗口
+    pub(crate) error_window: Option<Duration>,
+    /// 发布时捕获调用线程
+    pub(crate) capture_thread: bool,
+    /// 附加到每条记录的进程元数据
+    pub(crate) metadata: Option<ProcessMetadata>,
+    /// 发布时读取追踪上下文
+    pub(crate) trace_context: Option<Arc<dyn TraceContextProvider>>,
+    /// 检测到记录丢失或输出目标失败时 panic
+    pub(crate) panic_on_loss: bool,
+    /// 输出目标写入的计时钩子
+    pub(crate) write_profiler: Option<Arc<dyn WriteProfiler>>,
+}
+
+/// 环形队列满时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// 等待消费者腾出空间（默认，不丢失记录）
+    #[default]
+    Block,
+    /// 丢弃新记录，调用方立即返回
+    DropNewest,
+    /// 队列满时丢弃一条较旧的记录，让新记录入队
+    ///
+    /// 发布者请求消费者跳过一条记录，随后仍等待消费者腾出槽位：若消费者正在写入输出目标，
+    /// 发布会等到该次写入完成，等待时间通常短于 [`Block`](Self::Block) 但并非与写入耗时无关。
+    /// 被跳过的是消费者接下来读取的记录（已读入当前批次的记录与同步记录不会被跳过），
+    /// 不一定是环形队列中最早发布的一条。被跳过的记录计入丢失统计。
+    DropOldest,
+}
+
+/// 记录入队的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Enqueued {
+    /// 已入队（被过滤的记录视为已处理）
+    Accepted,
+    /// 队列满，按溢出策略丢弃
+    Full,
+    /// 过载降级拒绝
+    Shed,
+    /// 令牌桶限流拒绝
+    RateLimited,
+    /// 超出队列内存预算被拒绝
+    OverBudget,
+}
+
+/// 发布函数类型（按给定的溢出策略发布，返回记录是否入队）
+type Publisher = Arc<dyn Fn(Record, OverflowPolicy) -> bool + Send + Sync>;
+
+/// 批内检查刷新计时器的间隔（事件数），避免每条记录都读取时钟
+const TIMER_CHECK_INTERVAL: u32 = 32;
+
+/// 过载降级水位（队列容量的四分之几），超过后拒绝低级别记录，剩余空间留给高级别记录
+const SHED_WATERMARK_QUARTERS: usize = 3;
+
+/// 环形队列的最少槽位数（队列预算也不会缩小到此以下）
+const MIN_RING_SLOTS: usize = 64;
+
+/// 最小环形队列的固定槽位开销；队列预算须超过该值才能容纳排队中的消息
+pub(crate) fn min_queue_bytes() -> usize {
+    MIN_RING_SLOTS * std::mem::size_of::<Event>()
+}
+
+/// 高性能异步日志器
+pub struct AsyncLogger {
+    level: AtomicU8,
+    /// 全部输出目标（首个为主输出）
+    sinks: Vec<Arc<dyn Sink>>,
+    shutdown: Arc<AtomicBool>,
+    sent_count: Arc<AtomicUsize>,
+    written_count: Arc<AtomicUsize>,
+    lost_count: Arc<AtomicUsize>,
+    /// 因过期被消费者丢弃的记录数
+    stale_dropped: Arc<AtomicU64>,
+    /// 因过载降级被拒绝的记录数
+    shed_dropped: AtomicU64,
+    /// 日志器级别的令牌桶
+    rate_limiter: Option<TokenBucket>,
+    /// 因限流被拒绝的记录数
+    rate_limited: AtomicU64,
+    /// 被去重抑制的重复记录数
+    duplicates: Arc<AtomicU64>,
+    /// 被错误聚合计数的记录数
+    aggregated: Arc<AtomicU64>,
+    loss_detection_enabled: bool,
+    /// 检测到丢失时 panic
+    panic_on_loss: bool,
+    /// 发布时捕获调用线程
+    capture_thread: bool,
+    /// 附加到每条记录的进程元数据
+    metadata: Option<ProcessMetadata>,
+    /// 发布时读取追踪上下文
+    trace_context: Option<Arc<dyn TraceContextProvider>>,
+    flush_stats: Arc<FlushStats>,
+    diagnostics: Arc<dyn DiagnosticHandler>,
+    /// 最近的诊断事件（调试快照中的最近错误）
+    recent_diagnostics: Arc<RecentDiagnostics>,
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    latency_budget: Option<Duration>,
+    parker: Option<Arc<IdleParker>>,
+    /// 写入线程的停靠器（单级消费者时与 `parker` 相同）
+    writer_parker: Option<Arc<IdleParker>>,
+    /// 消费者等待策略
+    wait_strategy: WaitStrategy,
+    /// 消费者线程名称
+    consumer_name: &'static str,
+    /// 是否拆分为格式化与写入两级流水线
+    pipeline: bool,
+    memory: Arc<MemoryTracker>,
+    /// 环形队列与缓冲池是否使用透明大页
+    huge_pages: bool,
+    /// 环形队列与缓冲池所在的 NUMA 节点
+    numa_node: Option<usize>,
+    progress: Arc<Progress>,
+    filter: Option<Filter>,
+    field_limits: Option<FieldLimits>,
+    overflow_policy: OverflowPolicy,
+    shed_level: Option<Level>,
+    subscribers: Arc<Subscribers>,
+    publisher: Publisher,
+    /// 各路输出的健康状态（与 `sinks` 顺序一致）
+    sink_health: Vec<Arc<SinkHealth>>,
+    /// 环形队列实际容量
+    queue_capacity: usize,
+    batch_size: usize,
+    flush_interval: Duration,
+    started_at: SystemTime,
+    started: Instant,
+}
+
+/// 发布与消费进度
+#[derive(Default)]
+struct Progress {
+    /// 已发布的记录数
+    published: AtomicU64,
+    /// 消费者已处理的记录数
+    processed: AtomicU64,
+    /// 待执行的异步刷新请求（目标处理数，0 表示无请求）
+    flush_request: AtomicU64,
+    /// 待消费者跳过的最旧记录数（[`OverflowPolicy::DropOldest`]）
+    evict: AtomicU64,
+    /// 发布端丢弃、尚未交给消费者标记的记录数
+    dropped: AtomicU64,
+    /// 丢失即 panic 模式下首次检测到的丢失原因
+    loss: OnceLock<String>,
+}
+
+impl Progress {
+    /// 记下首次检测到的丢失原因
+    fn report_loss(&self, reason: impl FnOnce() -> String) {
+        if self.loss.get().is_none() {
+            let _ = self.loss.set(reason());
+        }
+    }
+}
+
+/// 刷新屏障令牌
+///
+/// 由 [`AsyncLogger::flush_barrier`] 返回，在调用前已发布的全部记录写入输出目标后完成。
+pub struct FlushBarrier {
+    target: u64,
+    progress: Arc<Progress>,
+}
+
+impl FlushBarrier {
+    /// 屏障是否已完成
+    ///
+    /// 尝试发布失败的记录会回退已发布计数，目标因此可能多算；处理进度追上当前的已发布计数时，
+    /// 调用前申请到序号的记录也已全部处理。
+    pub fn is_complete(&self) -> bool {
+        let published = self.progress.published.load(Ordering::Acquire);
+        let processed = self.progress.processed.load(Ordering::Acquire);
+        processed >= self.target || processed >= published
+    }
+
+    /// 阻塞等待屏障完成
+    pub fn wait(&self) {
+        while !self.is_complete() {
+            std::thread::yield_now();
+        }
+    }
+
+    /// 在超时时间内等待屏障完成，返回是否已完成
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while !self.is_complete() {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::yield_now();
+        }
+        true
+    }
+}
+
+impl AsyncLogger {
+    /// 创建新的异步日志器构建器
+    pub fn builder() -> crate::builder::AsyncLoggerBuilder {
+        crate::builder::AsyncLoggerBuilder::new()
+    }
+
+    /// 创建新的异步日志器
+    pub fn new(
+        level: Level,
+        formatter: Arc<dyn Formatter>,
+        sink: Arc<dyn Sink>,
+        queue_capacity: usize,
+        batch_size: usize,
+        flush_interval: Duration,
+    ) -> Self {
+        Self::with_options(
+            level,
+            formatter,
+            sink,
+            queue_capacity,
+            batch_size,
+            flush_interval,
+            LoggerOptions::default(),
+        )
+    }
+
+    /// 使用可选配置创建异步日志器
+    pub(crate) fn with_options(
+        level: Level,
+        formatter: Arc<dyn Formatter>,
+        sink: Arc<dyn Sink>,
+        queue_capacity: usize,
+        batch_size: usize,
+        flush_interval: Duration,
+        options: LoggerOptions,
+    ) -> Self {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let sent_count = Arc::new(AtomicUsize::new(0));
+        let written_count = Arc::new(AtomicUsize::new(0));
+        let lost_count = Arc::new(AtomicUsize::new(0));
+        let stale_dropped = Arc::new(AtomicU64::new(0));
+        let flush_stats = Arc::new(FlushStats::new());
+        let recent_diagnostics = Arc::new(RecentDiagnostics::new(
+            options
+                .diagnostics
+                .clone()
+                .unwrap_or_else(|| Arc::new(StderrDiagnostics)),
+        ));
+        let progress = Arc::new(Progress::default());
+        // 丢失即 panic：输出目标失败记为丢失
+        let diagnostics: Arc<dyn DiagnosticHandler> = if options.panic_on_loss {
+            let recent = recent_diagnostics.clone();
+            let progress = progress.clone();
+            Arc::new(move |diagnostic: &Diagnostic| {
+                if let Diagnostic::SinkError { .. } = diagnostic {
+                    progress.report_loss(|| diagnostic.to_string());
+                }
+                recent.handle(diagnostic);
+            })
+        } else {
+            recent_diagnostics.clone()
+        };
+
+        let memory = Arc::new(MemoryTracker::new(options.memory_budget));
+
+        // 看门狗配置了后备目标时，经可切换的输出目标写入
+        let switch = options
+            .watchdog
+            .as_ref()
+            .and_then(|watchdog| watchdog.wrap_sink(sink.clone()));
+        let sink: Arc<dyn Sink> = match &switch {
+            Some(switch) => switch.clone(),
+            None => sink,
+        };
+
+        // 环形队列的固定槽位开销计入队列预算，超出时缩小队列容量
+        let slot_bytes = std::mem::size_of::<Event>();
+        let mut size = queue_capacity.next_power_of_two().max(MIN_RING_SLOTS);
+        if let Some(limit) = options.memory_budget.limit(MemoryArea::Queue) {
+            while size > MIN_RING_SLOTS && size * slot_bytes > limit {
+                size /= 2;
+            }
+        }
+        memory.reserve(MemoryArea::Queue, size * slot_bytes);
+
+        let subscribers = Arc::new(Subscribers::default());
+        let written_c = written_count.clone();
+        let stats_c = flush_stats.clone();
+        let memory_c = memory.clone();
+        let diagnostics_c = diagnostics.clone();
+        let mut ring_advisor = options.huge_pages.then(|| RingAdvisor::new(size));
+        let transformer = options.transformer;
+        let multiline = options.multiline;
+        let flush_threshold = options.flush_threshold.map(|t| t as u64);
+        let flush_on = options.flush_on;
+        let max_age = options.max_age;
+        let stale_c = stale_dropped.clone();
+        let drop_markers = options.drop_markers;
+        let panic_on_loss = options.panic_on_loss;
+        let mut dedup = options.dedup_window.map(Dedup::new);
+        let duplicates = Arc::new(AtomicU64::new(0));
+        let duplicates_c = duplicates.clone();
+        let mut aggregator = options.error_window.map(ErrorAggregator::new);
+        let mut aggregated_summaries = Vec::new();
+        let aggregated = Arc::new(AtomicU64::new(0));
+        let aggregated_c = aggregated.clone();
+        let mut urgent: Option<FlushReason> = None;
+        let mut last_flush = Instant::now();
+        let mut since_timer_check = 0u32;
+
+        // 主输出与额外输出：同一记录依次借给各路格式化器，结果攒批后通过 write_batch 一次写入
+        let mut outputs = Outputs::new(
+            std::iter::once((formatter.clone(), sink.clone())).chain(options.outputs.clone()),
+            options.batch_formatter,
+            subscribers.clone(),
+            options.sink_error_policy,
+            options.on_error,
+            drop_markers,
+            options.write_profiler.clone(),
+        );
+        let sink_health = outputs.health();
+        let batch_size = batch_size.max(1);
+
+        // 合并写入：批尾在窗口内等待后续事件，有新事件到达时推迟写入与刷新
+        let coalesce_window = outputs.coalesce_window();
+        let mut coalesce_started: Option<Instant> = None;
+        let progress_c = progress.clone();
+        let mut processed = 0u64;
+
+        let factory = || Event {
+            record: Record::new(Level::Info, "nanolog_rs", "", 0, ""),
+            staged: Mutex::default(),
+        };
+
+        // 两级流水线：格式化线程渲染、转换并格式化记录，写入线程只负责攒批与写出
+        let pipeline = options.pipeline;
+        // 发布者唤醒第一级消费者；两级流水线中写入线程等待的是格式化线程，
+        // 因此使用独立的停靠器，由格式化线程在每批之后唤醒
+        let parker = options.idle_park_after.map(IdleParker::new);
+        let writer_parker = match &parker {
+            Some(parker) if pipeline => Some(parker.next_stage()),
+            _ => parker.clone(),
+        };
+        let format_stage = pipeline.then(|| {
+            let transformer = transformer.clone();
+            let formatters = outputs.formatters();
+            let skip_primary = outputs.batches_primary();
+            let wake_writer = writer_parker.clone();
+            let mut formatted = 0u64;
+            Box::new(move |e: &Event, _: Sequence, end_of_batch: bool| {
+                let mut staged = e.staged.lock().unwrap_or_else(|e| e.into_inner());
+                let staged = staged.get_or_insert_with(Box::default);
+                staged.record = prepare(&e.record, transformer.as_ref());
+                let record = staged.record.as_ref().unwrap_or(&e.record);
+                staged.folded = multiline.fold(record);
+                let folded = staged.folded.as_ref().unwrap_or(record);
+                staged.formatted.format(&formatters, skip_primary, folded);
+                formatted += 1;
+                if end_of_batch {
+                    formatters
+                        .iter()
+                        .for_each(|formatter| formatter.end_batch());
+                    if let Some(parker) = &wake_writer {
+                        parker.notify(formatted);
+                    }
+                    formatted = 0;
+                }
+            }) as FormatStage
+        });
+
+        // 合并窗口内按配置的等待方式等待新事件：启用空闲停靠时停靠在写入线程的停靠器上
+        let coalesce_wait = options.wait_strategy;
+        let coalesce_parker = writer_parker.clone();
+
+        let processor = move |e: &Event, sequence: Sequence, end_of_batch: bool| {
+            if let Some(advisor) = ring_advisor.as_mut()
+                && let Some(result) = advisor.observe(sequence, e)
+            {
+                ring_advisor = None;
+                if let Err(err) = result {
+                    diagnostics_c.handle(&crate::diagnostics::Diagnostic::HugePagesUnavailable {
+                        reason: err.to_string(),
+                    });
+                }
+            }
+
+            // 生产者因队列满请求淘汰时，跳过当前（最旧的）记录；同步记录不跳过，淘汰顺延到其后的记录
+            let evicted = !e.record.is_immediate()
+                && progress_c.evict.load(Ordering::Acquire) != 0
+                && progress_c
+                    .evict
+                    .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+                    .is_ok();
+
+            // 积压恢复期间跳过过期的低级别记录，优先写出新记录与高级别记录；同步记录不丢弃
+            let stale = !evicted
+                && max_age.is_some_and(|(age, level)| {
+                    e.record.level() <= level && !e.record.is_immediate() && e.record.age() > age
+                });
+            if stale {
+                stale_c.fetch_add(1, Ordering::Relaxed);
+            }
+            if panic_on_loss && (evicted || stale) {
+                progress_c.report_loss(|| {
+                    let cause = if evicted {
+                        "evicted from a full queue"
+                    } else {
+                        "stale"
+                    };
+                    format!("record dropped ({})", cause)
+                });
+            }
+
+            // 丢失标记：本条被跳过，或发布端在此之前丢弃了记录
+            if drop_markers {
+                let mut dropped = u64::from(evicted || stale);
+                if progress_c.dropped.load(Ordering::Relaxed) != 0 {
+                    dropped += progress_c.dropped.swap(0, Ordering::AcqRel);
+                }
+                outputs.note_dropped(dropped);
+            }
+
+            if !evicted && !stale {
+                // 延迟格式化的记录在消费者线程上渲染消息；两级流水线中已由格式化阶段完成
+                let mut staged =
+                    pipeline.then(|| e.staged.lock().unwrap_or_else(|e| e.into_inner()));
+                let (prepared, staged) = match staged.as_deref_mut().and_then(Option::as_deref_mut)
+                {
+                    Some(Staged {
+                        record,
+                        folded,
+                        formatted,
+                    }) => (record.as_ref(), Some((folded.as_ref(), formatted))),
+                    None => (None, None),
+                };
+                let resolved = match staged {
+                    Some(_) => None,
+                    None => prepare(&e.record, transformer.as_ref()),
+                };
+                let record = prepared.or(resolved.as_ref()).unwrap_or(&e.record);
+
+                // 错误聚合：同一调用点窗口内再次出错只计数（视为已写出），新窗口之前先写出上一窗口的汇总
+                let mut summary = None;
+                let aggregate = !record.is_immediate()
+                    && aggregator
+                        .as_mut()
+                        .is_some_and(|a| a.check(record, Instant::now(), &mut summary));
+                if let Some(summary) = summary {
+                    outputs.push_synthetic(&summary, &stats_c);
+                }
+
+                // 去重：窗口内与上一条相同的记录只计数（视为已写出），不同的记录之前先写出重复汇总
+                let mut summary = None;
+                let duplicate = !aggregate
+                    && !record.is_immediate()
+                    && dedup
+                        .as_mut()
+                        .is_some_and(|d| d.check(record, Instant::now(), &mut summary));
+                if let Some(summary) = summary {
+                    outputs.push_synthetic(&summary, &stats_c);
+                }
+                if aggregate {
+                    aggregated_c.fetch_add(1, Ordering::Relaxed);
+                    written_c.fetch_add(1, Ordering::Relaxed);
+                } else if duplicate {
+                    duplicates_c.fetch_add(1, Ordering::Relaxed);
+                    written_c.fetch_add(1, Ordering::Relaxed);
+                } else {
+                    match staged {
+                        Some((folded, formatted)) => {
+                            outputs.push(folded.unwrap_or(record), &stats_c, Some(formatted))
+                        }
+                        None => {
+                            let folded = multiline.fold(record);
+                            outputs.push(folded.as_ref().unwrap_or(record), &stats_c, None);
+                        }
+                    }
+                    if record.is_immediate() {
+                        urgent = Some(FlushReason::Explicit);
+                    } else if flush_on.is_some_and(|level| record.level() >= level) {
+                        urgent.get_or_insert(FlushReason::LevelThreshold);
+                    }
+                }
+            }
+            memory_c.release(MemoryArea::Queue, e.record.message().len());
+            if end_of_batch {
+                if let Some(summary) = dedup.as_mut().and_then(|d| d.expire(Instant::now())) {
+                    outputs.push_synthetic(&summary, &stats_c);
+                }
+                if let Some(aggregator) = aggregator.as_mut() {
+                    aggregator.expire(Instant::now(), &mut aggregated_summaries);
+                    for summary in aggregated_summaries.drain(..) {
+                        outputs.push_synthetic(&summary, &stats_c);
+                    }
+                }
+                // 两级流水线中由格式化线程通知格式化器批次结束
+                if !pipeline {
+                    outputs.end_batch();
+                }
+            }
+
+            // 批尾或攒满一批时写入；处理进度在写入后发布，刷新屏障因此覆盖已写入的记录。
+            // 设置了合并窗口时批尾推迟写入，与窗口内后续到达的事件合并为一次写系统调用
+            processed += 1;
+            let coalescing = end_of_batch
+                && !coalesce_window.is_zero()
+                && urgent.is_none()
+                && outputs.pending() > 0;
+            if (end_of_batch && !coalescing) || outputs.pending() >= batch_size {
+                outputs.write(&written_c, &stats_c, diagnostics_c.as_ref());
+                if panic_on_loss {
+                    note_format_failures(&mut outputs, &progress_c);
+                }
+                progress_c.processed.store(processed, Ordering::Release);
+            }
+
+            // 异步刷新请求在其目标记录写入后立即执行；
+            // 批尾刷新（可按合并窗口推迟）；长批次内按字节阈值或刷新间隔提前刷新
+            let requested = progress_c.flush_request.load(Ordering::Acquire);
+            let reason = if requested != 0 && processed >= requested {
+                let _ = progress_c.flush_request.compare_exchange(
+                    requested,
+                    0,
+                    Ordering::AcqRel,
+                    Ordering::Relaxed,
+                );
+                Some(FlushReason::Explicit)
+            } else if urgent.is_some() {
+                // 同步记录与高级别记录不等待批尾或合并窗口
+                urgent
+            } else if end_of_batch {
+                if !coalescing {
+                    Some(FlushReason::BatchEnd)
+                } else {
+                    // 窗口到期时写入并刷新；窗口内有新事件到达则返回处理，已攒的记录留待合并
+                    let deadline =
+                        *coalesce_started.get_or_insert_with(Instant::now) + coalesce_window;
+                    loop {
+                        if Instant::now() >= deadline {
+                            break Some(FlushReason::BatchEnd);
+                        }
+                        let requested = progress_c.flush_request.load(Ordering::Acquire);
+                        if requested != 0 && processed >= requested {
+                            let _ = progress_c.flush_request.compare_exchange(
+                                requested,
+                                0,
+                                Ordering::AcqRel,
+                                Ordering::Relaxed,
+                            );
+                            break Some(FlushReason::Explicit);
+                        }
+                        if progress_c.published.load(Ordering::Acquire) > processed {
+                            break None;
+                        }
+                        match &coalesce_parker {
+                            Some(parker) => parker.park_until(processed, deadline),
+                            None => coalesce_wait.pause_until(deadline),
+                        }
+                    }
+                }
+            } else if flush_threshold.is_some_and(|t| stats_c.pending_bytes() >= t) {
+                Some(FlushReason::SizeThreshold)
+            } else {
+                since_timer_check += 1;
+                if since_timer_check >= TIMER_CHECK_INTERVAL {
+                    since_timer_check = 0;
+                    (last_flush.elapsed() >= flush_interval).then_some(FlushReason::Timer)
+                } else {
+                    None
+                }
+            };
+            if let Some(reason) = reason {
+                if outputs.pending() > 0 {
+                    outputs.write(&written_c, &stats_c, diagnostics_c.as_ref());
+                    if panic_on_loss {
+                        note_format_failures(&mut outputs, &progress_c);
+                    }
+                    progress_c.processed.store(processed, Ordering::Release);
+                }
+                outputs.flush(diagnostics_c.as_ref());
+                stats_c.record_flush(reason);
+                last_flush = Instant::now();
+                since_timer_check = 0;
+                coalesce_started = None;
+                urgent = None;
+            }
+        };
+
+        let wait_strategy = options.wait_strategy;
+        let progress_p = progress.clone();
+        // disruptor 要求 `'static` 的线程名：自有的名称在此泄漏一次（每个日志器一次）
+        let consumer_name: &'static str = match options.consumer_name {
+            Some(Cow::Borrowed(name)) => name,
+            Some(Cow::Owned(name)) => Box::leak(name.into_boxed_str()),
+            None => crate::affinity::DEFAULT_THREAD_NAME,
+        };
+        let consumer = ConsumerThread {
+            name: consumer_name,
+            pin_core: None,
+            core: options.consumer_core,
+            priority: options.consumer_priority,
+            parker: writer_parker.clone(),
+            diagnostics: diagnostics.clone(),
+        };
+        let parker_p = parker.clone();
+        let start = move |pin_core: Option<usize>| {
+            let thread = ConsumerThread {
+                pin_core,
+                ..consumer
+            };
+            match (parker_p, wait_strategy) {
+                (Some(parker), _) => Self::start_consumer(
+                    size,
+                    factory,
+                    HybridWait,
+                    processor,
+                    format_stage,
+                    Some(parker),
+                    thread,
+                    progress_p,
+                ),
+                (None, WaitStrategy::BusySpin) => Self::start_consumer(
+                    size,
+                    factory,
+                    BusySpin,
+                    processor,
+                    format_stage,
+                    None,
+                    thread,
+                    progress_p,
+                ),
+                (None, WaitStrategy::BusySpinWithSpinLoopHint) => Self::start_consumer(
+                    size,
+                    factory,
+                    BusySpinWithSpinLoopHint,
+                    processor,
+                    format_stage,
+                    None,
+                    thread,
+                    progress_p,
+                ),
+                (None, WaitStrategy::Yielding) => Self::start_consumer(
+                    size,
+                    factory,
+                    YieldingWait,
+                    processor,
+                    format_stage,
+                    None,
+                    thread,
+                    progress_p,
+                ),
+                (None, WaitStrategy::Sleeping(interval)) => Self::start_consumer(
+                    size,
+                    factory,
+                    SleepingWait(interval),
+                    processor,
+                    format_stage,
+                    None,
+                    thread,
+                    progress_p,
+                ),
+            }
+        };
+
+        // 指定 NUMA 节点时，在绑定到该节点的线程上分配环形队列，并将消费者固定到节点内的核心
+        let publisher = match options.numa_node {
+            Some(node) => {
+                let pin_core = crate::numa::is_supported()
+                    .then(|| crate::numa::node_cpus(node).ok())
+                    .flatten()
+                    .and_then(|cpus| cpus.first().copied());
+                crate::numa::run_on_node(node, move || start(pin_core))
+            }
+            None => start(None),
+        };
+
+        if let Some(watchdog) = &options.watchdog {
+            let progress_w = progress.clone();
+            let counters = move || {
+                (
+                    progress_w.published.load(Ordering::Acquire),
+                    progress_w.processed.load(Ordering::Acquire),
+                )
+            };
+            if let Err(e) = watchdog.spawn(counters, shutdown.clone(), diagnostics.clone(), switch)
+            {
+                diagnostics.handle(&Diagnostic::WatchdogUnavailable {
+                    reason: e.to_string(),
+                });
+            }
+        }
+
+        Self {
+            level: AtomicU8::new(level as u8),
+            sinks: std::iter::once(sink)
+                .chain(options.outputs.into_iter().map(|(_, sink)| sink))
+                .collect(),
+            shutdown,
+            sent_count,
+            written_count,
+            lost_count,
+            stale_dropped,
+            shed_dropped: AtomicU64::new(0),
+            rate_limiter: options
+                .rate_limit
+                .map(|(rate, burst)| TokenBucket::new(rate, burst)),
+            rate_limited: AtomicU64::new(0),
+            duplicates,
+            aggregated,
+            loss_detection_enabled: true,
+            panic_on_loss: options.panic_on_loss,
+            capture_thread: options.capture_thread,
+            metadata: options.metadata,
+            trace_context: options.trace_context,
+            flush_stats,
+            diagnostics,
+            recent_diagnostics,
+            latency_budget: options.latency_budget,
+            parker,
+            writer_parker,
+            wait_strategy,
+            consumer_name,
+            pipeline,
+            memory,
+            huge_pages: options.huge_pages,
+            numa_node: options.numa_node,
+            progress,
+            filter: options.filter,
+            field_limits: options.field_limits,
+            overflow_policy: options.overflow_policy,
+            shed_level: options.shed_level,
+            subscribers,
+            publisher,
+            sink_health,
+            queue_capacity: size,
+            batch_size,
+            flush_interval,
+            started_at: SystemTime::now(),
+            started: Instant::now(),
+        }
+    }
+
+    /// 启动消费者线程并返回发布函数
+    ///
+    /// 给出 `format_stage` 时先由格式化线程处理每个事件，写入线程（`processor`）在其后处理。
+    #[allow(clippy::too_many_arguments)]
+    fn start_consumer<F, W, P>(
+        size: usize,
+        factory: F,
+        wait_strategy: W,
+        mut processor: P,
+        format_stage: Option<FormatStage>,
+        parker: Option<Arc<IdleParker>>,
+        thread: ConsumerThread,
+        progress: Arc<Progress>,
+    ) -> Publisher
+    where
+        F: FnMut() -> Event,
+        W: disruptor::wait_strategies::WaitStrategy + 'static,
+        P: FnMut(&Event, Sequence, bool) + Send + 'static,
+    {
+        let (name, pin_core) = (thread.name, thread.pin_core);
+        let writer = move |_: &mut (), e: &Event, sequence: Sequence, end_of_batch: bool| {
+            processor(e, sequence, end_of_batch)
+        };
+        let setup = move || thread.setup();
+        let builder = build_multi_producer(size, factory, wait_strategy);
+        match format_stage {
+            Some(mut format) => {
+                let format_parker = parker.clone();
+                let builder = builder
+                    .thread_name(FORMAT_THREAD_NAME)
+                    .handle_events_and_state_with(
+                        move |_: &mut (), e: &Event, sequence: Sequence, end_of_batch: bool| {
+                            format(e, sequence, end_of_batch)
+                        },
+                        move || {
+                            if let Some(parker) = format_parker {
+                                parker.install();
+                            }
+                        },
+                    )
+                    .and_then();
+                let builder = match pin_core {
+                    Some(core) => builder.pin_at_core(core),
+                    None => builder,
+                };
+                let prod = builder
+                    .thread_name(name)
+                    .handle_events_and_state_with(writer, setup)
+                    .build();
+                Self::publisher(prod, parker, progress)
+            }
+            None => {
+                let builder = match pin_core {
+                    Some(core) => builder.pin_at_core(core),
+                    None => builder,
+                };
+                let prod = builder
+                    .thread_name(name)
+                    .handle_events_and_state_with(writer, setup)
+                    .build();
+                Self::publisher(prod, parker, progress)
+            }
+        }
+    }
+
+    /// 以生产者句柄创建发布函数
+    fn publisher<Prod>(
+        prod: Prod,
+        parker: Option<Arc<IdleParker>>,
+        progress: Arc<Progress>,
+    ) -> Publisher
+    where
+        Prod: Producer<Event> + Clone + Send + Sync + 'static,
+    {
+        Arc::new(move |record: Record, overflow_policy: OverflowPolicy| {
+            let mut p = prod.clone();
+            // 记录移入槽位而非克隆，发布路径不分配；尝试发布失败时记录保留在原处。
+            // 槽位中已写出记录的消息缓冲区归还本线程的缓冲区池，供 `write_with` 复用
+            let mut record = Some(record);
+            let mut fill = |e: &mut Event| {
+                if let Some(record) = record.take() {
+                    let old = std::mem::replace(&mut e.record, record);
+                    if let Cow::Owned(message) = old.into_message() {
+                        crate::record::recycle_message(message);
+                    }
+                }
+            };
+            // 各路径都先计数再申请序号（尝试发布失败时回退），已申请序号的记录数因此不超过已发布计数，
+            // 屏障目标覆盖调用前已发布的全部记录
+            let accepted = match overflow_policy {
+                OverflowPolicy::Block => {
+                    progress.published.fetch_add(1, Ordering::Release);
+                    p.publish(fill);
+                    true
+                }
+                OverflowPolicy::DropNewest => {
+                    progress.published.fetch_add(1, Ordering::Release);
+                    let accepted = p.try_publish(&mut fill).is_ok();
+                    if !accepted {
+                        progress.published.fetch_sub(1, Ordering::Release);
+                    }
+                    accepted
+                }
+                OverflowPolicy::DropOldest => {
+                    progress.published.fetch_add(1, Ordering::Release);
+                    if p.try_publish(&mut fill).is_err() {
+                        progress.evict.fetch_add(1, Ordering::AcqRel);
+                        p.publish(fill);
+                    }
+                    true
+                }
+            };
+            if accepted && let Some(parker) = &parker {
+                parker.notify(1);
+            }
+            accepted
+        })
+    }
+
+    /// 记录日志（非阻塞）
+    ///
+    /// 标记为 [`immediate`](Record::immediate) 的记录同步写出：队列满时等待空间而不按溢出策略丢弃，
+    /// 消费者不等待批尾即写入并刷新，调用在记录写入且各输出目标刷新后返回。
+    pub fn log(&self, record: Record) -> Result<(), Error> {
+        if self.panic_on_loss {
+            self.check_loss();
+        }
+        if record.is_immediate() {
+            return self.log_immediate(record);
+        }
+        match self.enqueue(record, self.overflow_policy)? {
+            Enqueued::Accepted => return Ok(()),
+            Enqueued::Full => {}
+            Enqueued::Shed => self.note_rejected(&self.shed_dropped),
+            Enqueued::RateLimited => self.note_rejected(&self.rate_limited),
+            Enqueued::OverBudget => return Err(self.note_over_budget()),
+        }
+        // 按溢出策略、过载降级或限流丢弃，交给消费者在输出中标记
+        self.progress.dropped.fetch_add(1, Ordering::Relaxed);
+        if self.panic_on_loss {
+            self.progress.report_loss(|| {
+                "record dropped on publish (queue full, shed or rate limited)".to_string()
+            });
+            self.check_loss();
+        }
+        Ok(())
+    }
+
+    /// 记下被过载降级或限流拒绝的记录：计入对应的拒绝数与丢失统计
+    fn note_rejected(&self, counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+        if self.loss_detection_enabled {
+            self.sent_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 记下超出队列内存预算的记录：与溢出丢弃一样计入丢失统计并在输出中标记
+    fn note_over_budget(&self) -> Error {
+        if self.loss_detection_enabled {
+            self.sent_count.fetch_add(1, Ordering::Relaxed);
+        }
+        self.progress.dropped.fetch_add(1, Ordering::Relaxed);
+        if self.panic_on_loss {
+            self.progress
+                .report_loss(|| "record rejected by the queue memory budget".to_string());
+            self.check_loss();
+        }
+        Error::Memory("queue memory budget exceeded")
+    }
+
+    /// 丢失即 panic 模式下，已检测到丢失时 panic
+    ///
+    /// 当前线程已在 panic（panic 钩子或展开中的析构）时跳过，避免二次 panic 导致进程中止。
+    #[allow(clippy::panic)]
+    fn check_loss(&self) {
+        if self.panic_on_loss
+            && !std::thread::panicking()
+            && let Some(reason) = self.progress.loss.get()
+        {
+            panic!("nanolog-rs detected record loss: {}", reason);
+        }
+    }
+
+    /// 同步写出单条记录
+    fn log_immediate(&self, record: Record) -> Result<(), Error> {
+        if !self.enabled(record.level(), record.target()) {
+            return Ok(());
+        }
+        if self.enqueue(record, OverflowPolicy::Block)? == Enqueued::OverBudget {
+            return Err(self.note_over_budget());
+        }
+        self.flush_barrier().wait();
+        for sink in &self.sinks {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 尝试记录日志，队列满时立即返回 [`Error::QueueFull`] 而不等待
+    ///
+    /// 不受配置的溢出策略影响，供实时线程检测队列饱和。过载降级与限流拒绝分别返回
+    /// [`Error::Overloaded`] 与 [`Error::RateLimited`]。被拒绝的记录由调用方处理，
+    /// 不计入丢失统计，也不计入 [`shed_dropped`](Self::shed_dropped) 与 [`rate_limited`](Self::rate_limited)。
+    pub fn try_log(&self, record: Record) -> Result<(), Error> {
+        match self.enqueue(record, OverflowPolicy::DropNewest)? {
+            Enqueued::Accepted => Ok(()),
+            Enqueued::Full => {
+                if self.loss_detection_enabled {
+                    self.sent_count.fetch_sub(1, Ordering::Relaxed);
+                }
+                Err(Error::QueueFull)
+            }
+            Enqueued::Shed => Err(Error::Overloaded),
+            Enqueued::RateLimited => Err(Error::RateLimited),
+            Enqueued::OverBudget => Err(Error::Memory("queue memory budget exceeded")),
+        }
+    }
+
+    /// 过滤并按溢出策略发布记录，返回入队结果（被过滤的记录视为已处理）
+    ///
+    /// 只有尝试发布的记录计入已发送数；被拒绝的记录由调用方决定是否计入统计。
+    fn enqueue(
+        &self,
+        mut record: Record,
+        overflow_policy: OverflowPolicy,
+    ) -> Result<Enqueued, Error> {
+        if !self.enabled(record.level(), record.target()) {
+            return Ok(Enqueued::Accepted);
+        }
+
+        // 附加调用线程的上下文字段（如请求 ID）、进程元数据与线程信息
+        crate::context::attach(record.fields_mut());
+        if let Some(metadata) = &self.metadata {
+            metadata.attach(record.fields_mut());
+        }
+        if self.capture_thread {
+            record.capture_thread();
+        }
+        if let Some(provider) = &self.trace_context
+            && record.trace_context().is_none()
+        {
+            record.set_trace_context(provider.current());
+        }
+
+        // 入队前截断超限字段，避免单个巨大字段拖垮管道
+        if let Some(limits) = &self.field_limits
+            && !record.fields().is_empty()
+        {
+            limits.apply(record.fields_mut());
+        }
+
+        #[cfg(debug_assertions)]
+        let started = self.latency_budget.map(|budget| {
+            (
+                budget,
+                Instant::now(),
+                record.target(),
+                record.file(),
+                record.line(),
+            )
+        });
+
+        // 过载时先拒绝低级别记录，队列剩余空间留给高级别记录；同步记录不拒绝
+        if self.shed_level.is_some_and(|level| record.level() <= level)
+            && !record.is_immediate()
+            && self.queue_depth() >= self.queue_capacity / 4 * SHED_WATERMARK_QUARTERS
+        {
+            return Ok(Enqueued::Shed);
+        }
+
+        // 令牌桶兜底限流；同步记录不受限
+        if let Some(limiter) = &self.rate_limiter
+            && !record.is_immediate()
+            && !limiter.try_acquire()
+        {
+            return Ok(Enqueued::RateLimited);
+        }
+
+        // 排队中记录的消息字节计入队列预算，超出时明确拒绝
+        if !self
+            .memory
+            .try_reserve(MemoryArea::Queue, record.message().len())
+        {
+            return Ok(Enqueued::OverBudget);
+        }
+
+        if self.loss_detection_enabled {
+            self.sent_count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let message_len = record.message().len();
+        let accepted = (self.publisher)(record, overflow_policy);
+        if !accepted {
+            // 按 DropNewest 策略丢弃：未写入的记录计入丢失统计
+            self.memory.release(MemoryArea::Queue, message_len);
+        }
+
+        // 调试构建下检查热路径延迟预算，捕获意外阻塞
+        #[cfg(debug_assertions)]
+        if let Some((budget, started, target, file, line)) = started {
+            let elapsed = started.elapsed();
+            if elapsed > budget {
+                self.diagnostics
+                    .handle(&crate::diagnostics::Diagnostic::LatencyBudgetExceeded {
+                        elapsed,
+                        budget,
+                        target,
+                        file,
+                        line,
+                    });
+            }
+        }
+
+        Ok(if accepted {
+            Enqueued::Accepted
+        } else {
+            Enqueued::Full
+        })
+    }
+
+    /// 获取日志丢失统计信息
+    pub fn get_loss_stats(&self) -> (usize, usize, usize) {
+        let sent = self.sent_count.load(Ordering::Relaxed);
+        let written = self.written_count.load(Ordering::Relaxed);
+        let lost = self.lost_count.load(Ordering::Relaxed);
+
+        // 计算当前丢失的日志数量
+        let current_lost = sent.saturating_sub(written);
+
+        // 更新丢失计数器
+        if self.loss_detection_enabled && current_lost > lost {
+            self.lost_count.store(current_lost, Ordering::Relaxed);
+        }
+
+        (sent, written, current_lost)
+    }
+
+    /// 重置日志丢失统计信息
+    pub fn reset_loss_stats(&self) {
+        self.sent_count.store(0, Ordering::Relaxed);
+        self.written_count.store(0, Ordering::Relaxed);
+        self.lost_count.store(0, Ordering::Relaxed);
+    }
+
+    /// 启用或禁用日志丢失检测
+    pub fn set_loss_detection(&mut self, enabled: bool) {
+        self.loss_detection_enabled = enabled;
+        if !enabled {
+            self.reset_loss_stats();
+        }
+    }
+
+    /// 因过期被丢弃的记录数（见 [`AsyncLoggerBuilder::max_age`](crate::AsyncLoggerBuilder::max_age)）
+    ///
+    /// 过期丢弃的记录同样计入 [`get_loss_stats`](Self::get_loss_stats) 的丢失数。
+    pub fn stale_dropped(&self) -> u64 {
+        self.stale_dropped.load(Ordering::Relaxed)
+    }
+
+    /// 因过载降级被拒绝的记录数（见 [`AsyncLoggerBuilder::shed_on_overload`](crate::AsyncLoggerBuilder::shed_on_overload)）
+    ///
+    /// 被拒绝的记录同样计入 [`get_loss_stats`](Self::get_loss_stats) 的丢失数。
+    pub fn shed_dropped(&self) -> u64 {
+        self.shed_dropped.load(Ordering::Relaxed)
+    }
+
+    /// 因限流被拒绝的记录数（见 [`AsyncLoggerBuilder::rate_limit`](crate::AsyncLoggerBuilder::rate_limit)）
+    ///
+    /// 被拒绝的记录同样计入 [`get_loss_stats`](Self::get_loss_stats) 的丢失数。
+    pub fn rate_limited(&self) -> u64 {
+        self.rate_limited.load(Ordering::Relaxed)
+    }
+
+    /// 被去重抑制的重复记录数（见 [`AsyncLoggerBuilder::dedup`](crate::AsyncLoggerBuilder::dedup)）
+    ///
+    /// 被抑制的记录由汇总行代表，计入 [`get_loss_stats`](Self::get_loss_stats) 的已写入数。
+    pub fn duplicates_suppressed(&self) -> u64 {
+        self.duplicates.load(Ordering::Relaxed)
+    }
+
+    /// 被错误聚合计数、未单独写出的记录数（见 [`AsyncLoggerBuilder::aggregate_errors`](crate::AsyncLoggerBuilder::aggregate_errors)）
+    ///
+    /// 被计数的记录由汇总行代表，计入 [`get_loss_stats`](Self::get_loss_stats) 的已写入数。
+    pub fn errors_aggregated(&self) -> u64 {
+        self.aggregated.load(Ordering::Relaxed)
+    }
+
+    /// 已发布、尚未写出的记录数
+    fn queue_depth(&self) -> usize {
+        let published = self.progress.published.load(Ordering::Acquire);
+        let processed = self.progress.processed.load(Ordering::Acquire);
+        published.saturating_sub(processed) as usize
+    }
+
+    /// 获取刷新统计快照（刷新原因与刷新字节数）
+    pub fn flush_stats(&self) -> FlushStatsSnapshot {
+        self.flush_stats.snapshot()
+    }
+
+    /// 重置刷新统计
+    pub fn reset_flush_stats(&self) {
+        self.flush_stats.reset();
+    }
+
+    /// 获取当前内存用量（队列、缓冲池与溢出缓冲）
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.memory.usage()
+    }
+
+    /// 获取内存计量器，可交给缓冲池或带溢出缓冲的输出目标共享同一预算
+    pub fn memory_tracker(&self) -> Arc<MemoryTracker> {
+        self.memory.clone()
+    }
+
+    /// 创建与日志器共享内存预算的缓冲池，沿用构建器上的大页与 NUMA 节点设置
+    pub fn buffer_pool(&self, buffer_size: usize, max_pool_size: usize) -> BufferPool {
+        let mut pool =
+            BufferPool::new(buffer_size, max_pool_size).with_memory_tracker(self.memory.clone());
+        if self.huge_pages {
+            pool = pool.with_huge_pages();
+        }
+        if let Some(node) = self.numa_node {
+            pool = pool.with_numa_node(node);
+        }
+        pool
+    }
+
+    /// 获取消费者停靠/唤醒统计（未启用空闲停靠时全部为零）
+    pub fn wake_stats(&self) -> WakeStatsSnapshot {
+        self.parker.as_ref().map(|p| p.stats()).unwrap_or_default()
+    }
+
+    //
+
+    /// 检查是否应该记录指定级别的日志
+    ///
+    /// 设置了目标过滤器时，级别还须达到过滤器中最详细的级别；按目标的精确判断见 [`enabled`](Self::enabled)。
+    pub fn should_log(&self, level: Level) -> bool {
+        level >= self.level()
+            && self.filter.as_ref().map_or(true, |filter| {
+                filter.max_level().is_some_and(|max| level >= max)
+            })
+    }
+
+    /// 检查指定目标的级别是否启用（全局级别与目标过滤器同时生效）
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        level >= self.level()
+            && self
+                .filter
+                .as_ref()
+                .map_or(true, |filter| filter.enabled(level, target))
+    }
+
+    /// 获取日志级别
+    pub fn level(&self) -> Level {
+        Level::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    /// 运行时调整日志级别，并使调用点的级别缓存失效
+    ///
+    /// 级别实际变化时通过诊断通道上报 [`Diagnostic::LevelChanged`]，作为配置变更的审计记录。
+    pub fn set_level(&self, level: Level) {
+        let from = Level::from_u8(self.level.swap(level as u8, Ordering::Relaxed));
+        crate::callsite::invalidate_all();
+        if from != level {
+            self.diagnostics
+                .handle(&Diagnostic::LevelChanged { from, to: level });
+        }
+    }
+
+    /// 刷新日志（等待调用前已发布的日志写入后刷新输出目标）
+    pub fn flush(&self) -> Result<(), Error> {
+        self.flush_barrier().wait();
+        for sink in &self.sinks {
+            let _ = sink.flush();
+        }
+        self.flush_stats.record_flush(FlushReason::Explicit);
+        self.check_loss();
+        Ok(())
+    }
+
+    /// 请求刷新并立即返回
+    ///
+    /// 消费者写入调用前已发布的记录后执行一次刷新；若这些记录均已处理（已随批尾刷新），则无需额外刷新。
+    pub fn flush_async(&self) {
+        let target = self.progress.published.load(Ordering::Acquire);
+        if self.progress.processed.load(Ordering::Acquire) < target {
+            self.progress
+                .flush_request
+                .fetch_max(target, Ordering::AcqRel);
+        }
+    }
+
+    /// 获取刷新屏障：令牌在调用前已发布的全部记录写入输出目标后完成
+    ///
+    /// 与 [`flush`](Self::flush) 不同，屏障不等待调用之后其他生产者发布的记录。
+    pub fn flush_barrier(&self) -> FlushBarrier {
+        FlushBarrier {
+            target: self.progress.published.load(Ordering::Acquire),
+            progress: self.progress.clone(),
+        }
+    }
+
+    /// 订阅匹配过滤器的已格式化记录（主输出的格式），用于实时追踪
+    ///
+    /// 订阅队列容量为 [`DEFAULT_CAPACITY`](crate::subscribe::DEFAULT_CAPACITY)，满时丢弃最旧的记录。
+    /// 仅接收订阅之后写入的记录；日志器关闭后接收端返回 `None`。
+    pub fn subscribe(&self, filter: Filter) -> Receiver<Arc<[u8]>> {
+        self.subscribe_with_capacity(filter, crate::subscribe::DEFAULT_CAPACITY)
+    }
+
+    /// 以指定的队列容量订阅
+    pub fn subscribe_with_capacity(&self, filter: Filter, capacity: usize) -> Receiver<Arc<[u8]>> {
+        self.subscribers.subscribe(filter, capacity)
+    }
+
+    /// 生成状态快照（配置、统计、各输出目标健康状态、队列进度与最近错误）
+    ///
+    /// 输出为 `/proc` 风格的分节 `键: 值` 文本，可直接放入支持包或崩溃报告。
+    /// 只读取计数器与已缓存的状态，不会阻塞消费者线程或访问输出目标。
+    pub fn debug_dump(&self) -> String {
+        let mut out = String::new();
+        let _ = self.write_debug_dump(&mut out);
+        out
+    }
+
+    fn write_debug_dump(&self, out: &mut String) -> std::fmt::Result {
+        writeln!(out, "nanolog debug dump")?;
+        writeln!(out, "generated_at: {}", rfc3339(SystemTime::now()))?;
+        writeln!(out, "started_at: {}", rfc3339(self.started_at))?;
+        writeln!(out, "uptime: {:?}", self.started.elapsed())?;
+        let state = if self.shutdown.load(Ordering::Acquire) {
+            "shut down"
+        } else {
+            "running"
+        };
+        writeln!(out, "state: {}", state)?;
+
+        writeln!(out, "\n[config]")?;
+        writeln!(out, "level: {}", self.level())?;
+        match &self.filter {
+            Some(filter) => writeln!(out, "filter: {}", filter)?,
+            None => writeln!(out, "filter: none")?,
+        }
+        writeln!(out, "queue_capacity: {}", self.queue_capacity)?;
+        writeln!(out, "batch_size: {}", self.batch_size)?;
+        writeln!(out, "flush_interval: {:?}", self.flush_interval)?;
+        writeln!(out, "overflow_policy: {:?}", self.overflow_policy)?;
+        writeln!(out, "loss_detection: {}", self.loss_detection_enabled)?;
+        writeln!(out, "idle_park: {}", self.parker.is_some())?;
+        writeln!(out, "wait_strategy: {:?}", self.wait_strategy)?;
+        writeln!(out, "consumer_thread: {}", self.consumer_name)?;
+        writeln!(out, "pipeline: {}", self.pipeline)?;
+
+        let published = self.progress.published.load(Ordering::Acquire);
+        let processed = self.progress.processed.load(Ordering::Acquire);
+        writeln!(out, "\n[queue]")?;
+        writeln!(out, "published: {}", published)?;
+        writeln!(out, "processed: {}", processed)?;
+        writeln!(out, "in_flight: {}", published.saturating_sub(processed))?;
+        writeln!(
+            out,
+            "pending_evictions: {}",
+            self.progress.evict.load(Ordering::Acquire)
+        )?;
+        writeln!(
+            out,
+            "flush_request: {}",
+            self.progress.flush_request.load(Ordering::Acquire)
+        )?;
+        writeln!(out, "subscribers_active: {}", self.subscribers.is_active())?;
+
+        let (sent, written, lost) = self.get_loss_stats();
+        writeln!(out, "\n[records]")?;
+        writeln!(out, "sent: {}", sent)?;
+        writeln!(out, "written: {}", written)?;
+        writeln!(out, "lost: {}", lost)?;
+        writeln!(out, "stale_dropped: {}", self.stale_dropped())?;
+        writeln!(out, "shed_dropped: {}", self.shed_dropped())?;
+        writeln!(out, "rate_limited: {}", self.rate_limited())?;
+        writeln!(
+            out,
+            "duplicates_suppressed: {}",
+            self.duplicates_suppressed()
+        )?;
+        writeln!(out, "errors_aggregated: {}", self.errors_aggregated())?;
+
+        let flush = self.flush_stats();
+        writeln!(out, "\n[flush]")?;
+        writeln!(out, "written_bytes: {}", flush.written_bytes)?;
+        writeln!(out, "pending_bytes: {}", flush.pending_bytes)?;
+        for reason in FlushReason::ALL {
+            let stats = flush.reason(reason);
+            writeln!(
+                out,
+                "{}: {} flushes, {} bytes",
+                reason.as_str(),
+                stats.count,
+                stats.bytes
+            )?;
+        }
+
+        let memory = self.memory_usage();
+        writeln!(out, "\n[memory]")?;
+        writeln!(out, "queue_bytes: {}", memory.queue_bytes)?;
+        writeln!(out, "pool_bytes: {}", memory.pool_bytes)?;
+        writeln!(out, "spill_bytes: {}", memory.spill_bytes)?;
+        writeln!(out, "queue_dropped: {}", memory.queue_dropped)?;
+        writeln!(out, "pool_dropped: {}", memory.pool_dropped)?;
+        writeln!(out, "spill_dropped: {}", memory.spill_dropped)?;
+
+        let wake = self.wake_stats();
+        writeln!(out, "\n[wake]")?;
+        writeln!(out, "parks: {}", wake.parks)?;
+        writeln!(out, "wakeups: {}", wake.wakeups)?;
+        writeln!(out, "avg_wake_latency: {:?}", wake.avg_wake_latency)?;
+        writeln!(out, "max_wake_latency: {:?}", wake.max_wake_latency)?;
+
+        writeln!(out, "\n[sinks]")?;
+        for (index, health) in self.sink_health.iter().enumerate() {
+            let role = if index == 0 { "primary" } else { "output" };
+            let status = match (
+                health.write_failing.load(Ordering::Relaxed),
+                health.flush_failing.load(Ordering::Relaxed),
+            ) {
+                (false, false) => "ok",
+                (true, false) => "write failing",
+                (false, true) => "flush failing",
+                (true, true) => "write and flush failing",
+            };
+            writeln!(
+                out,
+                "sink[{}] ({}): {}, {} writes, {} write errors, {} flush errors",
+                index,
+                role,
+                status,
+                health.writes.load(Ordering::Relaxed),
+                health.write_errors.load(Ordering::Relaxed),
+                health.flush_errors.load(Ordering::Relaxed)
+            )?;
+        }
+
+        writeln!(out, "\n[recent_diagnostics]")?;
+        let recent = self.recent_diagnostics.recent();
+        if recent.is_empty() {
+            writeln!(out, "none")?;
+        }
+        for (at, diagnostic) in recent {
+            writeln!(out, "{} {}", rfc3339(at), diagnostic)?;
+        }
+        Ok(())
+    }
+
+    /// 优雅关闭日志器
+    pub fn shutdown(&self) -> Result<(), Error> {
+        self.shutdown.store(true, Ordering::Release);
+
+        // 等待已入队的记录全部处理（按溢出策略丢弃的记录不会写入）
+        self.flush_barrier().wait();
+        for sink in &self.sinks {
+            let _ = sink.shutdown();
+        }
+        self.subscribers.close();
+        self.flush_stats.record_flush(FlushReason::Shutdown);
+        self.check_loss();
+        Ok(())
+    }
+}
+
+impl Drop for AsyncLogger {
+    fn drop(&mut self) {
+        // 消费者线程随发布者一起退出，退出前不再停靠
+        for parker in self.parker.iter().chain(&self.writer_parker) {
+            parker.close();
+        }
+        if !self.shutdown.load(Ordering::Acquire) {
+            self.shutdown.store(true, Ordering::Release);
+            self.flush_barrier().wait();
+            for sink in &self.sinks {
+                let _ = sink.flush();
+                let _ = sink.shutdown();
+            }
+            self.subscribers.close();
+        }
+    }
+}
+
+/// 丢失即 panic 模式下，将格式化失败记为丢失
+fn note_format_failures(outputs: &mut Outputs, progress: &Progress) {
+    let failures = outputs.take_format_failures();
+    if failures > 0 {
+        progress.report_loss(|| format!("{} record(s) failed to format", failures));
+    }
+}
+
+/// 以 RFC 3339（UTC，毫秒精度）格式化时间
+fn rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    crate::format::utc_datetime(since_epoch.as_nanos())
+        .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+        .to_string()
+}
+
+/// 全局日志器管理
+pub struct GlobalLogger {
+    logger: Mutex<Option<Arc<AsyncLogger>>>,
+}
+
+impl GlobalLogger {
+    /// 创建新的全局日志器
+    pub fn new() -> Self {
+        Self {
+            logger: Mutex::new(None),
+        }
+    }
+
+    /// 初始化全局日志器
+    pub fn init(&self, logger: Arc<AsyncLogger>) -> Result<(), Error> {
+        let mut guard = self
+            .logger
+            .lock()
+            .map_err(|_| Error::Concurrent("global logger lock poisoned"))?;
+
+        // 在测试环境中允许重新初始化
+        #[cfg(test)]
+        {
+            *guard = Some(logger);
+            crate::callsite::invalidate_all();
+            Ok(())
+        }
+
+        // 在生产环境中只允许初始化一次
+        #[cfg(not(test))]
+        {
+            if guard.is_some() {
+                return Err(Error::AlreadyInitialized);
+            }
+
+            *guard = Some(logger);
+            crate::callsite::invalidate_all();
+            Ok(())
+        }
+    }
+
+    /// 获取全局日志器实例
+    pub fn get(&self) -> Option<Arc<AsyncLogger>> {
+        self.logger
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// 取出日志器后释放锁再调用：丢失即 panic 模式下的 panic 不会毒化锁
+    fn current(&self) -> Result<Arc<AsyncLogger>, Error> {
+        self.get().ok_or(Error::NotInitialized)
+    }
+
+    /// 记录日志
+    pub fn log(&self, record: Record) -> Result<(), Error> {
+        self.current()?.log(record)
+    }
+
+    /// 运行时调整全局日志器的级别
+    pub fn set_level(&self, level: Level) -> Result<(), Error> {
+        self.current()?.set_level(level);
+        Ok(())
+    }
+
+    /// 刷新日志
+    pub fn flush(&self) -> Result<(), Error> {
+        self.current()?.flush()
+    }
+
+    /// 关闭日志器
+    pub fn shutdown(&self) -> Result<(), Error> {
+        self.current()?.shutdown()
+    }
+}
+
+impl Default for GlobalLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 全局日志器实例 - 使用OnceLock确保线程安全
+use std::sync::OnceLock;
+
+static GLOBAL_LOGGER: OnceLock<GlobalLogger> = OnceLock::new();
+
+/// 初始化全局日志器
+pub fn init_global_logger(logger: Arc<AsyncLogger>) -> Result<(), Error> {
+    // 获取或创建全局日志器实例
+    let global_logger = GLOBAL_LOGGER.get_or_init(GlobalLogger::new);
+
+    // 调用实例的init方法来设置日志器
+    let result = global_logger.init(logger);
+
+    // 注册panic钩子，确保异常退出时仍尽力刷写缓冲区（钩子内不检查丢失，见 check_loss）
+    static PANIC_HOOK_INSTALLED: std::sync::OnceLock<()> = std::sync::OnceLock::new();
+    let _ = PANIC_HOOK_INSTALLED.get_or_init(|| {
+        std::panic::set_hook(Box::new(|_info| {
+            if let Some(gl) = crate::global_logger() {
+                let _ = gl.flush().ok();
+                let _ = gl.shutdown().ok();
+            }
+        }));
+    });
+
+    result
+}
+
+/// 获取全局日志器
+pub fn global_logger() -> Option<&'static GlobalLogger> {
+    GLOBAL_LOGGER.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Diagnostic;
+    use crate::format::DefaultFormatter;
+    use crate::sink::ConsoleSink;
+
+    #[test]
+    fn test_async_logger_basic() {
+        let formatter = Arc::new(DefaultFormatter::new());
+        let sink = Arc::new(ConsoleSink::new());
+
+        let logger = AsyncLogger::new(
+            Level::Debug,
+            formatter,
+            sink,
+            1000,                       // queue capacity
+            10,                         // batch size
+            Duration::from_millis(100), // flush interval
+        );
+
+        let record = Record::new(
+            Level::Info,
+            "test",
+            "test.rs",
+            1,
+            "Test message".to_string(),
+        );
+
+        assert!(logger.log(record).is_ok());
+        assert!(logger.flush().is_ok());
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_global_logger() {
+        let formatter = Arc::new(DefaultFormatter::new());
+        let sink = Arc::new(ConsoleSink::new());
+
+        let logger = Arc::new(AsyncLogger::new(
+            Level::Debug,
+            formatter,
+            sink,
+            1000,
+            10,
+            Duration::from_millis(100),
+        ));
+
+        assert!(init_global_logger(logger).is_ok());
+        assert!(global_logger().is_some());
+    }
+
+    #[test]
+    fn test_concurrent_publish_without_mutex() {
+        let formatter = Arc::new(DefaultFormatter::new());
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = Arc::new(AsyncLogger::new(
+            Level::Info,
+            formatter,
+            sink,
+            2048,
+            64,
+            Duration::from_millis(10),
+        ));
+
+        let threads = 4;
+        let per_thread = 250;
+        let mut handles = Vec::new();
+        for _ in 0..threads {
+            let lg = logger.clone();
+            let handle = std::thread::spawn(move || {
+                for i in 0..per_thread {
+                    let _ = lg.log(Record::new(
+                        Level::Info,
+                        "test::concurrent",
+                        file!(),
+                        line!(),
+                        format!("msg {}", i),
+                    ));
+                }
+            });
+            handles.push(handle);
+        }
+        for h in handles {
+            let _ = h.join();
+        }
+
+        assert!(logger.flush().is_ok());
+        let (sent, written, lost) = logger.get_loss_stats();
+        assert_eq!(sent, threads * per_thread);
+        assert!(written >= sent);
+        assert_eq!(lost, 0);
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_message_transformer_runs_on_consumer() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let catalog = crate::transform::MessageCatalog::new().with_entry("E1001", "库存不足");
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                transformer: Some(Arc::new(catalog)),
+                ..LoggerOptions::default()
+            },
+        );
+
+        let _ = logger.log(Record::new(
+            Level::Warn,
+            "shop",
+            file!(),
+            line!(),
+            "E1001".to_string(),
+        ));
+        let _ = logger.log(Record::new(
+            Level::Info,
+            "shop",
+            file!(),
+            line!(),
+            "ok".to_string(),
+        ));
+        assert!(logger.flush().is_ok());
+
+        let content = String::from_utf8(sink.get_content()).unwrap();
+        assert_eq!(content, "[WARN] 库存不足\n[INFO] ok\n");
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_flush_stats_reasons() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::new(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+        );
+
+        let _ = logger.log(Record::new(
+            Level::Info,
+            "t",
+            file!(),
+            line!(),
+            "abc".to_string(),
+        ));
+        assert!(logger.flush().is_ok());
+
+        let stats = logger.flush_stats();
+        assert_eq!(stats.written_bytes, "[INFO] abc\n".len() as u64);
+        assert_eq!(stats.reason(FlushReason::Explicit).count, 1);
+        // 批尾刷新与显式刷新共同覆盖全部写入字节
+        let flushed =
+            stats.reason(FlushReason::BatchEnd).bytes + stats.reason(FlushReason::Explicit).bytes;
+        assert_eq!(flushed, stats.written_bytes);
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_latency_budget_reports_diagnostics() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let reports_c = reports.clone();
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(DefaultFormatter::new()),
+            Arc::new(crate::sink::NullSink::new()),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                diagnostics: Some(Arc::new(move |d: &Diagnostic| {
+                    reports_c.lock().unwrap().push(d.clone());
+                })),
+                latency_budget: Some(Duration::ZERO),
+                ..LoggerOptions::default()
+            },
+        );
+
+        let _ = logger.log(Record::new(
+            Level::Info,
+            "budget",
+            "b.rs",
+            9,
+            "x".to_string(),
+        ));
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert!(matches!(
+            reports[0],
+            Diagnostic::LatencyBudgetExceeded {
+                target: "budget",
+                line: 9,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_consumer_thread_name_and_setup_diagnostics() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let reports_c = reports.clone();
+        let transformer = |_: &Record| std::thread::current().name().map(str::to_string);
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                diagnostics: Some(Arc::new(move |d: &Diagnostic| {
+                    reports_c.lock().unwrap().push(d.clone());
+                })),
+                transformer: Some(Arc::new(transformer)),
+                consumer_name: Some(format!("audit-{}", "writer").into()),
+                // 超出范围的核心在任何构建下都无法固定
+                consumer_core: Some(usize::MAX),
+                ..LoggerOptions::default()
+            },
+        );
+
+        let _ = logger.log(Record::new(Level::Info, "t", file!(), line!(), "x"));
+        assert!(logger.flush().is_ok());
+        assert_eq!(sink.get_content(), b"[INFO] audit-writer\n");
+        assert!(
+            logger
+                .debug_dump()
+                .contains("consumer_thread: audit-writer")
+        );
+        assert!(logger.shutdown().is_ok());
+        let reports = reports.lock().unwrap();
+        assert!(matches!(
+            reports.as_slice(),
+            [Diagnostic::ConsumerThreadSetupFailed {
+                setting: "core",
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_idle_parking_wakes_on_publish() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                idle_park_after: Some(Duration::from_millis(1)),
+                ..LoggerOptions::default()
+            },
+        );
+
+        // 等待消费者进入停靠
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while logger.wake_stats().parks == 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        assert!(logger.wake_stats().parks >= 1);
+
+        let _ = logger.log(Record::new(
+            Level::Info,
+            "t",
+            file!(),
+            line!(),
+            "wake".to_string(),
+        ));
+        assert!(logger.flush().is_ok());
+        assert_eq!(sink.get_content(), b"[INFO] wake\n");
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_pipeline_idle_parking_wakes_both_stages() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                pipeline: true,
+                idle_park_after: Some(Duration::ZERO),
+                ..LoggerOptions::default()
+            },
+        );
+
+        // 两级线程都停靠后发布：写出不应等到停靠超时
+        let mut slowest = Duration::ZERO;
+        for _ in 0..10 {
+            std::thread::sleep(Duration::from_millis(2));
+            let start = Instant::now();
+            let _ = logger.log(Record::new(Level::Info, "t", file!(), line!(), "wake"));
+            assert!(logger.flush().is_ok());
+            slowest = slowest.max(start.elapsed());
+        }
+        assert!(
+            slowest < Duration::from_millis(50),
+            "slowest: {:?}",
+            slowest
+        );
+        assert_eq!(sink.get_content().split(|&b| b == b'\n').count(), 11);
+        assert!(logger.wake_stats().parks >= 1);
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_wait_strategies_deliver_records() {
+        for strategy in [
+            WaitStrategy::BusySpin,
+            WaitStrategy::BusySpinWithSpinLoopHint,
+            WaitStrategy::Yielding,
+            WaitStrategy::Sleeping(Duration::from_millis(1)),
+        ] {
+            let sink = Arc::new(crate::sink::MemorySink::new());
+            let logger = AsyncLogger::with_options(
+                Level::Info,
+                Arc::new(crate::format::SimpleFormatter::new()),
+                sink.clone(),
+                64,
+                8,
+                Duration::from_millis(10),
+                LoggerOptions {
+                    wait_strategy: strategy,
+                    ..LoggerOptions::default()
+                },
+            );
+            for message in ["a", "b"] {
+                let _ = logger.log(Record::new(Level::Info, "t", file!(), line!(), message));
+            }
+            assert!(logger.flush().is_ok());
+            assert_eq!(
+                sink.get_content(),
+                b"[INFO] a\n[INFO] b\n",
+                "{:?}",
+                strategy
+            );
+            assert!(
+                logger
+                    .debug_dump()
+                    .contains(&format!("wait_strategy: {:?}", strategy))
+            );
+            assert!(logger.shutdown().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_memory_budget_rejections_count_as_losses() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                memory_budget: MemoryBudget::new().max_queue_bytes(min_queue_bytes() + 16),
+                drop_markers: true,
+                ..LoggerOptions::default()
+            },
+        );
+
+        let big = Record::new(Level::Info, "m", file!(), line!(), "x".repeat(32));
+        assert!(matches!(logger.log(big), Err(Error::Memory(_))));
+        let small = Record::new(Level::Info, "m", file!(), line!(), "ok".to_string());
+        assert!(logger.log(small).is_ok());
+        assert!(logger.flush().is_ok());
+
+        assert_eq!(logger.get_loss_stats(), (2, 1, 1));
+        let content = String::from_utf8(sink.get_content()).unwrap();
+        assert_eq!(marked_drops(&content), 1);
+        assert!(content.ends_with("[INFO] ok\n"));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_memory_budget_rejects_records_over_queue_limit() {
+        let slot_bytes = std::mem::size_of::<Event>();
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(DefaultFormatter::new()),
+            Arc::new(crate::sink::NullSink::new()),
+            4096,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                memory_budget: MemoryBudget::new().max_queue_bytes(64 * slot_bytes + 16),
+                ..LoggerOptions::default()
+            },
+        );
+
+        // 队列容量被缩小到预算允许的 64 个槽位
+        assert_eq!(logger.memory_usage().queue_bytes, 64 * slot_bytes);
+
+        let big = Record::new(Level::Info, "m", file!(), line!(), "x".repeat(32));
+        assert!(matches!(logger.log(big), Err(Error::Memory(_))));
+        assert_eq!(logger.memory_usage().queue_dropped, 1);
+
+        let small = Record::new(Level::Info, "m", file!(), line!(), "ok".to_string());
+        assert!(logger.log(small).is_ok());
+        assert!(logger.flush().is_ok());
+        assert_eq!(logger.memory_usage().queue_bytes, 64 * slot_bytes);
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_numa_node_placement_falls_back_gracefully() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                numa_node: Some(0),
+                ..LoggerOptions::default()
+            },
+        );
+
+        let _ = logger.log(Record::new(
+            Level::Info,
+            "n",
+            file!(),
+            line!(),
+            "numa".to_string(),
+        ));
+        assert!(logger.flush().is_ok());
+        assert_eq!(sink.get_content(), b"[INFO] numa\n");
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_huge_pages_fall_back_gracefully() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let reports_c = reports.clone();
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                huge_pages: true,
+                diagnostics: Some(Arc::new(move |d: &Diagnostic| {
+                    reports_c.lock().unwrap().push(d.clone());
+                })),
+                ..LoggerOptions::default()
+            },
+        );
+
+        for i in 0..3 {
+            let _ = logger.log(Record::new(
+                Level::Info,
+                "h",
+                file!(),
+                line!(),
+                format!("{}", i),
+            ));
+        }
+        assert!(logger.flush().is_ok());
+        assert_eq!(sink.get_content(), b"[INFO] 0\n[INFO] 1\n[INFO] 2\n");
+
+        // 缓冲池沿用日志器的内存预算
+        let pool = logger.buffer_pool(4096, 4);
+        pool.release(pool.acquire());
+        assert_eq!(logger.memory_usage().pool_bytes, 4096);
+        if !crate::hugepage::is_supported() {
+            assert!(matches!(
+                reports.lock().unwrap().as_slice(),
+                [Diagnostic::HugePagesUnavailable { .. }]
+            ));
+        }
+        assert!(logger.shutdown().is_ok());
+    }
+
+    struct CoalescingSink {
+        inner: crate::sink::MemorySink,
+        window: Duration,
+        /// 写入调用次数
+        writes: AtomicUsize,
+    }
+
+    impl CoalescingSink {
+        fn new(window: Duration) -> Self {
+            Self {
+                inner: crate::sink::MemorySink::new(),
+                window,
+                writes: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl Sink for CoalescingSink {
+        fn write(&self, data: &[u8]) -> std::io::Result<()> {
+            self.writes.fetch_add(1, Ordering::Relaxed);
+            self.inner.write(data)
+        }
+
+        fn write_batch(&self, data: &[Vec<u8>]) -> std::io::Result<()> {
+            self.writes.fetch_add(1, Ordering::Relaxed);
+            self.inner.write_batch(data)
+        }
+
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn shutdown(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn coalesce_window(&self) -> Duration {
+            self.window
+        }
+    }
+
+    #[test]
+    fn test_coalesce_window_defers_batch_end_flush() {
+        let sink = Arc::new(CoalescingSink::new(Duration::from_millis(500)));
+        let logger = AsyncLogger::new(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_secs(10),
+        );
+
+        // 间隔远小于窗口的零散记录合并为一次批尾刷新
+        for i in 0..5 {
+            let _ = logger.log(Record::new(
+                Level::Info,
+                "c",
+                file!(),
+                line!(),
+                format!("{}", i),
+            ));
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while logger.flush_stats().reason(FlushReason::BatchEnd).count == 0
+            && Instant::now() < deadline
+        {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let stats = logger.flush_stats();
+        assert_eq!(stats.reason(FlushReason::BatchEnd).count, 1);
+        assert_eq!(
+            stats.reason(FlushReason::BatchEnd).bytes,
+            stats.written_bytes
+        );
+        // 写入同样推迟到窗口结束，零散记录只产生一次写入调用
+        assert_eq!(sink.writes.load(Ordering::Relaxed), 1);
+        assert_eq!(sink.inner.get_content().len(), 5 * "[INFO] 0\n".len());
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_coalesce_window_parks_with_idle_parking() {
+        let sink = Arc::new(CoalescingSink::new(Duration::from_millis(300)));
+        let logger = crate::builder::AsyncLoggerBuilder::new()
+            .formatter(Arc::new(crate::format::SimpleFormatter::new()))
+            .sink(sink.clone())
+            .flush_interval(Duration::from_secs(10))
+            .idle_parking(Duration::from_secs(60))
+            .build()
+            .unwrap();
+
+        // 空闲阈值远大于测试时长，停靠只可能来自合并窗口内的等待；新记录唤醒消费者并合并写入
+        let _ = logger.log(Record::new(Level::Info, "p", file!(), line!(), "a"));
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(logger.wake_stats().parks >= 1);
+        assert!(sink.inner.get_content().is_empty());
+        let _ = logger.log(Record::new(Level::Info, "p", file!(), line!(), "b"));
+        assert!(logger.flush().is_ok());
+        assert!(logger.wake_stats().wakeups >= 1);
+        assert_eq!(sink.writes.load(Ordering::Relaxed), 1);
+        assert_eq!(sink.inner.get_content(), b"[INFO] a\n[INFO] b\n");
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_flush_on_level_bypasses_coalesce_window() {
+        let sink = Arc::new(CoalescingSink::new(Duration::from_secs(5)));
+        let logger = crate::builder::AsyncLoggerBuilder::new()
+            .formatter(Arc::new(crate::format::SimpleFormatter::new()))
+            .sink(sink.clone())
+            .flush_interval(Duration::from_secs(10))
+            .flush_on(Level::Error)
+            .build()
+            .unwrap();
+
+        let _ = logger.log(Record::new(Level::Info, "f", file!(), line!(), "a"));
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(
+            logger
+                .flush_stats()
+                .reason(FlushReason::LevelThreshold)
+                .count,
+            0
+        );
+
+        // 错误记录打断合并窗口，写入后立即刷新
+        let _ = logger.log(Record::new(Level::Error, "f", file!(), line!(), "b"));
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while logger
+            .flush_stats()
+            .reason(FlushReason::LevelThreshold)
+            .count
+            == 0
+            && Instant::now() < deadline
+        {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let stats = logger.flush_stats();
+        assert_eq!(stats.reason(FlushReason::LevelThreshold).count, 1);
+        assert_eq!(stats.reason(FlushReason::BatchEnd).count, 0);
+        assert_eq!(sink.inner.get_content(), b"[INFO] a\n[ERROR] b\n");
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_immediate_record_written_before_log_returns() {
+        let sink = Arc::new(CoalescingSink::new(Duration::from_secs(5)));
+        let logger = AsyncLogger::new(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            1024,
+            Duration::from_secs(10),
+        );
+
+        let _ = logger.log(Record::new(Level::Info, "s", file!(), line!(), "a"));
+        let audit = Record::new(Level::Info, "s", file!(), line!(), "audit").immediate(true);
+        assert!(audit.is_immediate());
+        assert!(logger.log(audit).is_ok());
+        assert_eq!(sink.inner.get_content(), b"[INFO] a\n[INFO] audit\n");
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_max_age_drops_stale_low_level_records() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = crate::builder::AsyncLoggerBuilder::new()
+            .level(Level::Debug)
+            .formatter(Arc::new(crate::format::SimpleFormatter::new()))
+            .sink(sink.clone())
+            .max_age(Duration::from_millis(20), Level::Debug)
+            .build()
+            .unwrap();
+
+        // 模拟积压：记录创建后迟迟未被消费
+        let stale_debug = Record::new(Level::Debug, "s", file!(), line!(), "old");
+        let stale_warn = Record::new(Level::Warn, "s", file!(), line!(), "old");
+        std::thread::sleep(Duration::from_millis(40));
+        let _ = logger.log(stale_debug);
+        let _ = logger.log(stale_warn);
+        let _ = logger.log(Record::new(Level::Debug, "s", file!(), line!(), "new"));
+        logger.flush_barrier().wait();
+
+        assert_eq!(sink.get_content(), b"[WARN] old\n[DEBUG] new\n");
+        assert_eq!(logger.stale_dropped(), 1);
+        assert_eq!(logger.get_loss_stats(), (3, 2, 1));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    /// 首次写入时停住直到被放行的输出目标，使后续记录在队列中积压
+    struct StallingSink {
+        inner: crate::sink::MemorySink,
+        /// （已进入首次写入，已放行）
+        state: Mutex<(bool, bool)>,
+        changed: std::sync::Condvar,
+    }
+
+    impl StallingSink {
+        fn new() -> Self {
+            Self {
+                inner: crate::sink::MemorySink::new(),
+                state: Mutex::new((false, false)),
+                changed: std::sync::Condvar::new(),
+            }
+        }
+
+        /// 等待消费者停在首次写入上
+        fn wait_entered(&self) {
+            let state = self.state.lock().unwrap();
+            let _state = self.changed.wait_while(state, |(entered, _)| !*entered);
+        }
+
+        /// 放行停住的写入
+        fn release(&self) {
+            self.state.lock().unwrap().1 = true;
+            self.changed.notify_all();
+        }
+    }
+
+    impl Sink for StallingSink {
+        fn write(&self, data: &[u8]) -> std::io::Result<()> {
+            self.write_batch(&[data.to_vec()])
+        }
+
+        fn write_batch(&self, data: &[Vec<u8>]) -> std::io::Result<()> {
+            let mut state = self.state.lock().unwrap();
+            if !state.0 {
+                state.0 = true;
+                self.changed.notify_all();
+                let _state = self.changed.wait_while(state, |(_, released)| !*released);
+            } else {
+                drop(state);
+            }
+            self.inner.write_batch(data)
+        }
+
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn shutdown(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_shed_on_overload_drops_low_levels_first() {
+        let sink = Arc::new(StallingSink::new());
+        let logger = crate::builder::AsyncLoggerBuilder::new()
+            .level(Level::Debug)
+            .formatter(Arc::new(crate::format::SimpleFormatter::new()))
+            .sink(sink.clone())
+            .queue_capacity(64)
+            .overflow_policy(OverflowPolicy::DropNewest)
+            .shed_on_overload(Level::Debug)
+            .build()
+            .unwrap();
+
+        // 消费者停在首条记录的写入上，其余记录积压
+        let _ = logger.log(Record::new(Level::Info, "s", file!(), line!(), "first"));
+        sink.wait_entered();
+        for i in 0..60 {
+            let _ = logger.log(Record::new(
+                Level::Debug,
+                "s",
+                file!(),
+                line!(),
+                i.to_string(),
+            ));
+        }
+        for i in 0..10 {
+            let _ = logger.log(Record::new(
+                Level::Info,
+                "s",
+                file!(),
+                line!(),
+                i.to_string(),
+            ));
+        }
+        sink.release();
+        assert!(logger.flush().is_ok());
+
+        // 积压达到 48 条后只拒绝调试记录，信息记录全部写出
+        let content = String::from_utf8(sink.inner.get_content()).unwrap();
+        assert_eq!(logger.shed_dropped(), 13);
+        assert_eq!(content.matches("[DEBUG]").count(), 47);
+        assert_eq!(content.matches("[INFO]").count(), 11);
+        assert_eq!(logger.get_loss_stats(), (71, 58, 13));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_try_log_reports_shed_records() {
+        let sink = Arc::new(StallingSink::new());
+        let logger = crate::builder::AsyncLoggerBuilder::new()
+            .level(Level::Debug)
+            .formatter(Arc::new(crate::format::SimpleFormatter::new()))
+            .sink(sink.clone())
+            .queue_capacity(64)
+            .shed_on_overload(Level::Debug)
+            .build()
+            .unwrap();
+
+        // 消费者停在首条记录的写入上，积压的记录数是确定的
+        let _ = logger.try_log(Record::new(Level::Info, "s", file!(), line!(), "first"));
+        sink.wait_entered();
+        let mut shed = 0;
+        for i in 0..60 {
+            match logger.try_log(Record::new(Level::Debug, "s", file!(), 1, i.to_string())) {
+                Ok(()) => {}
+                Err(err) => {
+                    assert!(matches!(err, Error::Overloaded));
+                    shed += 1;
+                }
+            }
+        }
+        assert_eq!(shed, 13);
+        sink.release();
+        assert!(logger.flush().is_ok());
+
+        // 被拒绝的记录由调用方处理，不计入拒绝数与丢失统计
+        assert_eq!(logger.shed_dropped(), 0);
+        assert_eq!(logger.get_loss_stats(), (48, 48, 0));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_try_log_reports_rate_limited_records() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = crate::builder::AsyncLoggerBuilder::new()
+            .formatter(Arc::new(crate::format::SimpleFormatter::new()))
+            .sink(sink.clone())
+            .rate_limit(1, 5)
+            .build()
+            .unwrap();
+
+        let results: Vec<_> = (0..20)
+            .map(|i| logger.try_log(Record::new(Level::Info, "r", file!(), 1, i.to_string())))
+            .collect();
+        assert!(results[..5].iter().all(Result::is_ok));
+        assert!(
+            results[5..]
+                .iter()
+                .all(|r| matches!(r, Err(Error::RateLimited)))
+        );
+        assert!(logger.flush().is_ok());
+
+        assert_eq!(logger.rate_limited(), 0);
+        assert_eq!(logger.get_loss_stats(), (5, 5, 0));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_rate_limit_rejects_beyond_burst() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = crate::builder::AsyncLoggerBuilder::new()
+            .formatter(Arc::new(crate::format::SimpleFormatter::new()))
+            .sink(sink.clone())
+            .rate_limit(1, 5)
+            .build()
+            .unwrap();
+
+        for i in 0..20 {
+            let _ = logger.log(Record::new(
+                Level::Info,
+                "r",
+                file!(),
+                line!(),
+                i.to_string(),
+            ));
+        }
+        // 同步记录不受限流影响
+        let _ =
+            logger.log(Record::new(Level::Error, "r", file!(), line!(), "sync").immediate(true));
+        assert!(logger.flush().is_ok());
+
+        assert_eq!(logger.rate_limited(), 15);
+        assert_eq!(logger.get_loss_stats(), (21, 6, 15));
+        let content = String::from_utf8(sink.get_content()).unwrap();
+        assert!(content.starts_with("[INFO] 0\n[INFO] 1\n"));
+        assert!(content.ends_with("[INFO] 4\n[ERROR] sync\n"));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_dedup_collapses_repeated_messages() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = crate::builder::AsyncLoggerBuilder::new()
+            .formatter(Arc::new(crate::format::SimpleFormatter::new()))
+            .sink(sink.clone())
+            .dedup(Duration::from_secs(60))
+            .build()
+            .unwrap();
+
+        for _ in 0..5 {
+            let _ = logger.log(Record::new(
+                Level::Warn,
+                "retry",
+                file!(),
+                line!(),
+                "connect failed",
+            ));
+        }
+        let _ = logger.log(Record::new(Level::Info, "retry", file!(), line!(), "ok"));
+        assert!(logger.flush().is_ok());
+
+        assert_eq!(
+            sink.get_content(),
+            b"[WARN] connect failed\n[WARN] last message repeated 4 times\n[INFO] ok\n"
+        );
+        assert_eq!(logger.duplicates_suppressed(), 4);
+        assert_eq!(logger.get_loss_stats(), (6, 6, 0));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_aggregate_errors_by_call_site() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = crate::builder::AsyncLoggerBuilder::new()
+            .formatter(Arc::new(crate::format::SimpleFormatter::new()))
+            .sink(sink.clone())
+            .aggregate_errors(Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        for i in 0..4 {
+            let _ = logger.log(Record::new(
+                Level::Error,
+                "payment",
+                "pay.rs",
+                10,
+                "payment timeout",
+            ));
+            let _ = logger.log(Record::new(
+                Level::Error,
+                "payment",
+                "pay.rs",
+                20,
+                format!("card {} declined", i),
+            ));
+        }
+        assert!(logger.flush().is_ok());
+        assert_eq!(
+            sink.get_content(),
+            b"[ERROR] payment timeout\n[ERROR] card 0 declined\n"
+        );
+
+        // 窗口结束后的批尾写出汇总
+        std::thread::sleep(Duration::from_millis(60));
+        let _ = logger.log(Record::new(
+            Level::Info,
+            "payment",
+            "pay.rs",
+            30,
+            "recovered",
+        ));
+        assert!(logger.flush().is_ok());
+        assert_eq!(
+            sink.get_content(),
+            "[ERROR] payment timeout\n[ERROR] card 0 declined\n[INFO] recovered\n\
+             [ERROR] payment timeout ×4 in last 50ms\n[ERROR] card 0 declined ×4 in last 50ms\n"
+                .as_bytes()
+        );
+        assert_eq!(logger.errors_aggregated(), 6);
+        assert_eq!(logger.get_loss_stats(), (9, 9, 0));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_flush_barrier_and_async_flush() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::new(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_secs(10),
+        );
+
+        for i in 0..10 {
+            let _ = logger.log(Record::new(
+                Level::Info,
+                "b",
+                file!(),
+                line!(),
+                format!("{}", i),
+            ));
+        }
+        let barrier = logger.flush_barrier();
+        assert!(barrier.wait_timeout(Duration::from_secs(2)));
+        assert!(barrier.is_complete());
+        assert_eq!(sink.get_content().len(), 10 * "[INFO] 0\n".len());
+
+        // 所有记录均已处理时，异步刷新不会留下待执行请求
+        logger.flush_async();
+        assert_eq!(logger.progress.flush_request.load(Ordering::Acquire), 0);
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_lazy_record_formatted_on_consumer_thread() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::new(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+        );
+        let formatted_on = Arc::new(Mutex::new(None));
+        let formatted_on_c = formatted_on.clone();
+
+        let _ = logger.log(Record::lazy(Level::Info, "l", file!(), line!(), move |f| {
+            *formatted_on_c.lock().unwrap() = Some(std::thread::current().id());
+            write!(f, "lazy {}", 1)
+        }));
+        assert!(logger.flush().is_ok());
+
+        assert_eq!(sink.get_content(), b"[INFO] lazy 1\n");
+        let thread = *formatted_on.lock().unwrap();
+        assert!(thread.is_some_and(|id| id != std::thread::current().id()));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_pipeline_formats_on_separate_thread() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let json = Arc::new(crate::sink::MemorySink::new());
+        let transformer = |r: &Record| {
+            let thread = std::thread::current();
+            Some(format!(
+                "{} on {}",
+                r.message(),
+                thread.name().unwrap_or("?")
+            ))
+        };
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                pipeline: true,
+                transformer: Some(Arc::new(transformer)),
+                outputs: vec![(Arc::new(crate::format::JsonFormatter::new()), json.clone())],
+                dedup_window: Some(Duration::from_secs(60)),
+                ..LoggerOptions::default()
+            },
+        );
+
+        // 一圈以上的记录使写入阶段换出的缓冲区在槽位中复用
+        for i in 0..100 {
+            let _ = logger.log(Record::lazy(Level::Info, "p", file!(), 1, move |f| {
+                write!(f, "lazy {}", i)
+            }));
+        }
+        for _ in 0..3 {
+            let _ = logger.log(Record::new(Level::Warn, "p", file!(), 2, "repeat"));
+        }
+        assert!(logger.flush().is_ok());
+
+        let content = String::from_utf8(sink.get_content()).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 101);
+        assert_eq!(lines[0], "[INFO] lazy 0 on nanolog-format");
+        assert_eq!(lines[99], "[INFO] lazy 99 on nanolog-format");
+        assert_eq!(lines[100], "[WARN] repeat on nanolog-format");
+        let json = String::from_utf8(json.get_content()).unwrap();
+        assert_eq!(json.lines().count(), 101);
+        assert!(json.contains(r#""message":"lazy 42 on nanolog-format""#));
+        assert_eq!(logger.duplicates_suppressed(), 2);
+        assert!(logger.debug_dump().contains("pipeline: true"));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_target_filter() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::with_options(
+            Level::Trace,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                filter: Some(crate::filter::Filter::parse("warn,app::db=debug").unwrap()),
+                ..LoggerOptions::default()
+            },
+        );
+
+        assert!(logger.should_log(Level::Debug));
+        assert!(!logger.should_log(Level::Trace));
+        for (target, level, msg) in [
+            ("app::db", Level::Debug, "kept"),
+            ("app::web", Level::Info, "dropped"),
+            ("app::web", Level::Warn, "warned"),
+        ] {
+            let _ = logger.log(Record::new(
+                level,
+                target,
+                file!(),
+                line!(),
+                msg.to_string(),
+            ));
+        }
+        assert!(logger.flush().is_ok());
+        assert_eq!(sink.get_content(), b"[DEBUG] kept\n[WARN] warned\n");
+        assert!(logger.shutdown().is_ok());
+    }
+
+    /// 每次写入前暂停的输出目标，用于填满队列
+    struct SlowSink {
+        inner: crate::sink::MemorySink,
+        delay: Duration,
+    }
+
+    impl Sink for SlowSink {
+        fn write(&self, data: &[u8]) -> std::io::Result<()> {
+            std::thread::sleep(self.delay);
+            self.inner.write(data)
+        }
+
+        fn write_batch(&self, data: &[Vec<u8>]) -> std::io::Result<()> {
+            std::thread::sleep(self.delay * data.len() as u32);
+            self.inner.write_batch(data)
+        }
+
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn shutdown(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn overflow_logger(policy: OverflowPolicy) -> (AsyncLogger, Arc<SlowSink>) {
+        let sink = Arc::new(SlowSink {
+            inner: crate::sink::MemorySink::new(),
+            delay: Duration::from_micros(200),
+        });
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                overflow_policy: policy,
+                ..LoggerOptions::default()
+            },
+        );
+        (logger, sink)
+    }
+
+    #[test]
+    fn test_overflow_drop_newest() {
+        let (logger, sink) = overflow_logger(OverflowPolicy::DropNewest);
+        for i in 0..1000 {
+            assert!(
+                logger
+                    .log(Record::new(Level::Info, "t", file!(), 1, i.to_string()))
+                    .is_ok()
+            );
+        }
+        assert!(logger.flush().is_ok());
+
+        let (sent, written, lost) = logger.get_loss_stats();
+        assert_eq!(sent, 1000);
+        assert!(written < 1000);
+        assert_eq!(lost, sent - written);
+        let content = String::from_utf8(sink.inner.get_content()).unwrap();
+        assert_eq!(content.lines().count(), written);
+        assert!(content.starts_with("[INFO] 0\n"));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_overflow_drop_oldest() {
+        let (logger, sink) = overflow_logger(OverflowPolicy::DropOldest);
+        for i in 0..1000 {
+            assert!(
+                logger
+                    .log(Record::new(Level::Info, "t", file!(), 1, i.to_string()))
+                    .is_ok()
+            );
+        }
+        assert!(logger.flush().is_ok());
+
+        let (sent, written, lost) = logger.get_loss_stats();
+        assert_eq!(sent, 1000);
+        assert!(written < 1000);
+        assert_eq!(lost, sent - written);
+        let content = String::from_utf8(sink.inner.get_content()).unwrap();
+        assert!(content.ends_with("[INFO] 999\n"));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_drop_oldest_keeps_immediate_records() {
+        let (logger, sink) = overflow_logger(OverflowPolicy::DropOldest);
+        for i in 0..1000 {
+            let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, i.to_string()));
+        }
+        // 同步记录到达消费者时仍有待执行的淘汰：淘汰顺延到其后的普通记录
+        logger.progress.evict.fetch_add(128, Ordering::AcqRel);
+        let audit = Record::new(Level::Warn, "t", file!(), 2, "audit").immediate(true);
+        assert!(logger.log(audit).is_ok());
+        let content = String::from_utf8(sink.inner.get_content()).unwrap();
+        assert!(content.ends_with("[WARN] audit\n"));
+
+        for i in 0..200 {
+            let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, i.to_string()));
+        }
+        assert!(logger.flush().is_ok());
+        assert_eq!(logger.progress.evict.load(Ordering::Acquire), 0);
+        let (sent, written, lost) = logger.get_loss_stats();
+        assert_eq!(lost, sent - written);
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_flush_barrier_tolerates_rolled_back_publish() {
+        let progress = Arc::new(Progress::default());
+        progress.published.store(3, Ordering::Release);
+        progress.processed.store(2, Ordering::Release);
+        let barrier = FlushBarrier {
+            target: 3,
+            progress: progress.clone(),
+        };
+        assert!(!barrier.is_complete());
+        // 第三条记录尝试发布失败并回退计数
+        progress.published.fetch_sub(1, Ordering::Release);
+        assert!(barrier.is_complete());
+    }
+
+    #[test]
+    fn test_immediate_records_written_despite_concurrent_try_log() {
+        let (logger, sink) = overflow_logger(OverflowPolicy::Block);
+        let logger = Arc::new(logger);
+        let stop = Arc::new(AtomicBool::new(false));
+        let spammers: Vec<_> = (0..2)
+            .map(|_| {
+                let logger = logger.clone();
+                let stop = stop.clone();
+                std::thread::spawn(move || {
+                    while !stop.load(Ordering::Acquire) {
+                        let _ = logger.try_log(Record::new(Level::Info, "t", file!(), 1, "spam"));
+                    }
+                })
+            })
+            .collect();
+        for i in 0..50 {
+            let message = format!("audit {}", i);
+            let audit = Record::new(Level::Warn, "t", file!(), 2, message.clone()).immediate(true);
+            assert!(logger.log(audit).is_ok());
+            let content = String::from_utf8(sink.inner.get_content()).unwrap();
+            assert!(
+                content.contains(&format!("[WARN] {}\n", message)),
+                "{}",
+                message
+            );
+        }
+        stop.store(true, Ordering::Release);
+        for spammer in spammers {
+            spammer.join().unwrap();
+        }
+        assert!(logger.flush().is_ok());
+        assert_eq!(
+            logger.progress.published.load(Ordering::Acquire),
+            logger.progress.processed.load(Ordering::Acquire)
+        );
+        assert!(logger.shutdown().is_ok());
+    }
+
+    /// 标记行中的丢失记录数之和
+    fn marked_drops(content: &str) -> usize {
+        content
+            .lines()
+            .filter_map(|line| line.strip_prefix("[WARN] ... "))
+            .filter_map(|rest| rest.split(' ').next()?.parse::<usize>().ok())
+            .sum()
+    }
+
+    #[test]
+    fn test_drop_markers_fill_gaps() {
+        let sink = Arc::new(SlowSink {
+            inner: crate::sink::MemorySink::new(),
+            delay: Duration::from_micros(200),
+        });
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                overflow_policy: OverflowPolicy::DropOldest,
+                drop_markers: true,
+                ..LoggerOptions::default()
+            },
+        );
+        for i in 0..1000 {
+            assert!(
+                logger
+                    .log(Record::new(Level::Info, "t", file!(), 1, i.to_string()))
+                    .is_ok()
+            );
+        }
+        assert!(logger.flush().is_ok());
+
+        let (sent, written, lost) = logger.get_loss_stats();
+        assert!(lost > 0);
+        let content = String::from_utf8(sink.inner.get_content()).unwrap();
+        let markers = content
+            .lines()
+            .filter(|l| l.ends_with(" dropped ..."))
+            .count();
+        assert!(markers > 0);
+        assert_eq!(content.lines().count(), written + markers);
+        // 跳过的最旧记录全部在输出中标记
+        assert_eq!(marked_drops(&content), sent - written);
+        assert!(content.ends_with("[INFO] 999\n"));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_try_log_reports_queue_full() {
+        let (logger, _sink) = overflow_logger(OverflowPolicy::Block);
+        let mut rejected = 0;
+        for i in 0..1000 {
+            match logger.try_log(Record::new(Level::Info, "t", file!(), 1, i.to_string())) {
+                Ok(()) => {}
+                Err(err) => {
+                    assert!(matches!(err, Error::QueueFull));
+                    rejected += 1;
+                }
+            }
+        }
+        assert!(rejected > 0);
+        assert!(logger.flush().is_ok());
+
+        let (sent, written, lost) = logger.get_loss_stats();
+        assert_eq!(sent, 1000 - rejected);
+        assert_eq!(written, sent);
+        assert_eq!(lost, 0);
+        assert!(logger.shutdown().is_ok());
+    }
+
+    /// 记录每次写入调用的批大小
+    #[derive(Default)]
+    struct BatchRecordingSink {
+        batches: Mutex<Vec<usize>>,
+    }
+
+    impl Sink for BatchRecordingSink {
+        fn write(&self, _data: &[u8]) -> std::io::Result<()> {
+            self.batches.lock().unwrap().push(1);
+            Ok(())
+        }
+
+        fn write_batch(&self, data: &[Vec<u8>]) -> std::io::Result<()> {
+            self.batches.lock().unwrap().push(data.len());
+            Ok(())
+        }
+
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn shutdown(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_records_written_in_batches() {
+        let sink = Arc::new(BatchRecordingSink::default());
+        let logger = AsyncLogger::new(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            1024,
+            16,
+            Duration::from_secs(60),
+        );
+
+        for i in 0..500 {
+            let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, i.to_string()));
+        }
+        assert!(logger.flush().is_ok());
+
+        let batches = sink.batches.lock().unwrap().clone();
+        assert_eq!(batches.iter().sum::<usize>(), 500);
+        assert!(batches.iter().all(|&n| (1..=16).contains(&n)));
+        assert_eq!(logger.get_loss_stats(), (500, 500, 0));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_fan_out_to_multiple_formatters() {
+        let primary = Arc::new(crate::sink::MemorySink::new());
+        let json = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            primary.clone(),
+            1024,
+            16,
+            Duration::from_secs(60),
+            LoggerOptions {
+                outputs: vec![(Arc::new(crate::format::JsonFormatter::new()), json.clone())],
+                ..LoggerOptions::default()
+            },
+        );
+
+        for i in 0..20 {
+            let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, i.to_string()));
+        }
+        assert!(logger.flush().is_ok());
+
+        let primary = String::from_utf8(primary.get_content()).unwrap();
+        let json = String::from_utf8(json.get_content()).unwrap();
+        assert_eq!(primary.lines().count(), 20);
+        assert_eq!(primary.lines().next(), Some("[INFO] 0"));
+        assert_eq!(json.lines().count(), 20);
+        assert!(json.lines().all(|l| l.starts_with('{')));
+        assert_eq!(logger.get_loss_stats(), (20, 20, 0));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_subscribe_receives_matching_records() {
+        let logger = AsyncLogger::new(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            Arc::new(crate::sink::NullSink::new()),
+            64,
+            8,
+            Duration::from_secs(60),
+        );
+        let rx = logger.subscribe(Filter::new(Level::Warn));
+
+        let _ = logger.log(Record::new(
+            Level::Info,
+            "t",
+            file!(),
+            1,
+            "quiet".to_string(),
+        ));
+        let _ = logger.log(Record::new(
+            Level::Warn,
+            "t",
+            file!(),
+            2,
+            "loud".to_string(),
+        ));
+        assert!(logger.flush().is_ok());
+
+        assert_eq!(rx.try_recv().as_deref(), Some(&b"[WARN] loud\n"[..]));
+        assert!(rx.try_recv().is_none());
+        assert!(logger.shutdown().is_ok());
+        assert!(rx.recv().is_none());
+    }
+
+    #[test]
+    fn test_batch_formatter_writes_one_payload_per_batch() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            1024,
+            16,
+            Duration::from_secs(60),
+            LoggerOptions {
+                batch_formatter: Some(Arc::new(crate::format::ElasticsearchBulkFormatter::new(
+                    "logs",
+                ))),
+                ..LoggerOptions::default()
+            },
+        );
+
+        for i in 0..40 {
+            let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, i.to_string()));
+        }
+        assert!(logger.flush().is_ok());
+
+        let content = String::from_utf8(sink.get_content()).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 80);
+        assert!(
+            lines
+                .iter()
+                .step_by(2)
+                .all(|l| *l == r#"{"index":{"_index":"logs"}}"#)
+        );
+        assert!(lines[1].contains(r#""message":"0""#));
+        assert_eq!(logger.get_loss_stats(), (40, 40, 0));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_set_level_at_runtime() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::new(
+            Level::Warn,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+        );
+
+        let _ = logger.log(Record::new(
+            Level::Debug,
+            "t",
+            file!(),
+            1,
+            "hidden".to_string(),
+        ));
+        logger.set_level(Level::Debug);
+        assert_eq!(logger.level(), Level::Debug);
+        let _ = logger.log(Record::new(
+            Level::Debug,
+            "t",
+            file!(),
+            2,
+            "shown".to_string(),
+        ));
+        assert!(logger.flush().is_ok());
+        assert_eq!(sink.get_content(), b"[DEBUG] shown\n");
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_level_change_reported_as_diagnostic() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_c = seen.clone();
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            Arc::new(crate::sink::NullSink::new()),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                diagnostics: Some(Arc::new(move |d: &Diagnostic| {
+                    seen_c.lock().unwrap().push(d.clone());
+                })),
+                ..LoggerOptions::default()
+            },
+        );
+        logger.set_level(Level::Debug);
+        logger.set_level(Level::Debug);
+        logger.set_level(Level::Error);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                Diagnostic::LevelChanged {
+                    from: Level::Info,
+                    to: Level::Debug
+                },
+                Diagnostic::LevelChanged {
+                    from: Level::Debug,
+                    to: Level::Error
+                },
+            ]
+        );
+        assert!(
+            logger
+                .debug_dump()
+                .contains("log level changed: DEBUG -> ERROR")
+        );
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_context_fields_attached() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = AsyncLogger::new(
+            Level::Info,
+            Arc::new(crate::format::DefaultFormatter::new()),
+            sink.clone(),
+            64,
+            8,
+            Duration::from_millis(10),
+        );
+
+        {
+            let _guard = crate::context::push("request_id", "r-9");
+            let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, "in".to_string()));
+        }
+        let _ = logger.log(Record::new(Level::Info, "t", file!(), 2, "out".to_string()));
+        assert!(logger.flush().is_ok());
+
+        let content = String::from_utf8(sink.get_content()).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines[0].ends_with("in request_id=r-9"));
+        assert!(lines[1].ends_with("out"));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_trace_context_provider() {
+        use crate::trace::TraceContext;
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let current = TraceContext::new([0xab; 16], [0xcd; 8]);
+        let logger = crate::builder::AsyncLoggerBuilder::new()
+            .formatter(Arc::new(crate::format::DefaultFormatter::plain()))
+            .sink(sink.clone())
+            .trace_context(Arc::new(move || Some(current)))
+            .build()
+            .unwrap();
+
+        let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, "a".to_string()));
+        // 已设置上下文的记录保持不变
+        let explicit = TraceContext::new([1; 16], [2; 8]);
+        let _ = logger.log(
+            Record::new(Level::Info, "t", file!(), 2, "b".to_string()).with_trace_context(explicit),
+        );
+        assert!(logger.flush().is_ok());
+
+        let content = String::from_utf8(sink.get_content()).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines[0].ends_with(&format!(
+            "a trace_id={} span_id={}",
+            "ab".repeat(16),
+            "cd".repeat(8)
+        )));
+        assert!(lines[1].ends_with(&format!(
+            "b trace_id={} span_id={}",
+            "01".repeat(16),
+            "02".repeat(8)
+        )));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_process_metadata_attached() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = crate::builder::AsyncLoggerBuilder::new()
+            .formatter(Arc::new(crate::format::DefaultFormatter::plain()))
+            .sink(sink.clone())
+            .process_metadata(ProcessMetadata::new().with_pid().with_service("checkout"))
+            .build()
+            .unwrap();
+
+        {
+            let _guard = crate::context::push("request_id", "r-9");
+            let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, "in".to_string()));
+        }
+        assert!(logger.flush().is_ok());
+        let content = String::from_utf8(sink.get_content()).unwrap();
+        assert!(content.ends_with(&format!(
+            "in request_id=r-9 pid={} service=checkout\n",
+            std::process::id()
+        )));
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_capture_thread_on_publish() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let logger = Arc::new(
+            crate::builder::AsyncLoggerBuilder::new()
+                .formatter(Arc::new(
+                    crate::pattern::PatternFormatter::new("%i %v%n").unwrap(),
+                ))
+                .sink(sink.clone())
+                .capture_thread(true)
+                .build()
+                .unwrap(),
+        );
+
+        let logger_t = logger.clone();
+        std::thread::Builder::new()
+            .name("ingest".into())
+            .spawn(move || {
+                let _ = logger_t.log(Record::new(Level::Info, "t", file!(), 1, "a".to_string()));
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert!(logger.flush().is_ok());
+        assert_eq!(sink.get_content(), b"ingest a\n");
+        assert!(logger.shutdown().is_ok());
+    }
+
+    /// 写入总是失败的输出目标
+    struct FailingSink;
+
+    impl Sink for FailingSink {
+        fn write(&self, _data: &[u8]) -> std::io::Result<()> {
+            Err(std::io::Error::other("disk full"))
+        }
+
+        fn write_batch(&self, _data: &[Vec<u8>]) -> std::io::Result<()> {
+            Err(std::io::Error::other("disk full"))
+        }
+
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn shutdown(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "nanolog-rs detected record loss: sink write failed: disk full")]
+    fn test_panic_on_loss_reports_sink_errors() {
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            Arc::new(FailingSink),
+            64,
+            8,
+            Duration::from_secs(60),
+            LoggerOptions {
+                diagnostics: Some(Arc::new(|_: &Diagnostic| {})),
+                panic_on_loss: true,
+                ..LoggerOptions::default()
+            },
+        );
+        let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, "lost"));
+        let _ = logger.flush();
+    }
+
+    /// 丢失即 panic 模式下写入总是失败的日志器
+    fn lossy_logger() -> AsyncLogger {
+        AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            Arc::new(FailingSink),
+            64,
+            8,
+            Duration::from_secs(60),
+            LoggerOptions {
+                diagnostics: Some(Arc::new(|_: &Diagnostic| {})),
+                panic_on_loss: true,
+                ..LoggerOptions::default()
+            },
+        )
+    }
+
+    #[test]
+    #[allow(clippy::panic)]
+    fn test_panic_on_loss_skips_check_while_unwinding() {
+        /// 展开时刷新并关闭日志器，模拟 panic 钩子与析构中的清理
+        struct ShutdownOnDrop(Arc<AsyncLogger>);
+
+        impl Drop for ShutdownOnDrop {
+            fn drop(&mut self) {
+                let _ = self.0.flush();
+                let _ = self.0.shutdown();
+            }
+        }
+
+        let logger = Arc::new(lossy_logger());
+        let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, "lost"));
+        let guard = ShutdownOnDrop(logger.clone());
+        // 清理中再次 panic 会中止进程，而不是让线程以原 panic 结束
+        let result = std::thread::spawn(move || {
+            let _guard = guard;
+            panic!("boom");
+        })
+        .join();
+        let payload = result.expect_err("thread panicked");
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"boom"));
+    }
+
+    #[test]
+    fn test_global_logger_lock_survives_loss_panic() {
+        let global = GlobalLogger::new();
+        global.init(Arc::new(lossy_logger())).unwrap();
+        let _ = global.log(Record::new(Level::Info, "t", file!(), 1, "lost"));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| global.shutdown()));
+        assert!(result.is_err());
+
+        // panic 发生在锁外，全局日志器仍可使用
+        assert!(global.set_level(Level::Warn).is_ok());
+        assert!(global.init(Arc::new(lossy_logger())).is_ok());
+    }
+
+    #[test]
+    fn test_panic_on_loss_catches_publish_drops() {
+        let sink = Arc::new(SlowSink {
+            inner: crate::sink::MemorySink::new(),
+            delay: Duration::from_millis(1),
+        });
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            sink,
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                overflow_policy: OverflowPolicy::DropNewest,
+                panic_on_loss: true,
+                ..LoggerOptions::default()
+            },
+        );
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            for i in 0..1000 {
+                let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, i.to_string()));
+            }
+        }));
+        assert!(result.is_err());
+
+        // 未启用时同样的丢弃只计入统计
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            Arc::new(SlowSink {
+                inner: crate::sink::MemorySink::new(),
+                delay: Duration::from_millis(1),
+            }),
+            64,
+            8,
+            Duration::from_millis(10),
+            LoggerOptions {
+                overflow_policy: OverflowPolicy::DropNewest,
+                ..LoggerOptions::default()
+            },
+        );
+        for i in 0..1000 {
+            assert!(
+                logger
+                    .log(Record::new(Level::Info, "t", file!(), 1, i.to_string()))
+                    .is_ok()
+            );
+        }
+        assert!(logger.flush().is_ok());
+        assert!(logger.get_loss_stats().2 > 0);
+    }
+
+    #[test]
+    fn test_write_profiler_times_each_output() {
+        let timings = Arc::new(crate::stats::WriteTimings::new());
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            Arc::new(SlowSink {
+                inner: crate::sink::MemorySink::new(),
+                delay: Duration::from_millis(2),
+            }),
+            64,
+            8,
+            Duration::from_secs(60),
+            LoggerOptions {
+                outputs: vec![(
+                    Arc::new(crate::format::SimpleFormatter::new()),
+                    Arc::new(FailingSink),
+                )],
+                diagnostics: Some(Arc::new(|_: &Diagnostic| {})),
+                write_profiler: Some(timings.clone()),
+                ..LoggerOptions::default()
+            },
+        );
+        assert!(
+            logger
+                .log(Record::new(Level::Info, "t", file!(), 1, "hello"))
+                .is_ok()
+        );
+        assert!(logger.flush().is_ok());
+
+        let snapshot = timings.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].writes, 1);
+        assert_eq!(snapshot[0].failures, 0);
+        assert_eq!(snapshot[0].bytes, b"[INFO] hello\n".len() as u64);
+        assert!(snapshot[0].max_time >= Duration::from_millis(2));
+        assert_eq!(snapshot[1].failures, 1);
+        assert!(logger.shutdown().is_ok());
+    }
+
+    #[test]
+    fn test_debug_dump_reports_state() {
+        let logger = AsyncLogger::with_options(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            Arc::new(crate::sink::NullSink::new()),
+            100,
+            16,
+            Duration::from_secs(60),
+            LoggerOptions {
+                filter: Some(Filter::parse("info,app::db=debug").unwrap()),
+                outputs: vec![(
+                    Arc::new(crate::format::SimpleFormatter::new()),
+                    Arc::new(FailingSink),
+                )],
+                diagnostics: Some(Arc::new(|_: &Diagnostic| {})),
+                ..LoggerOptions::default()
+            },
+        );
+        for i in 0..3 {
+            let _ = logger.log(Record::new(Level::Info, "t", file!(), 1, i.to_string()));
+        }
+        assert!(logger.flush().is_ok());
+
+        let dump = logger.debug_dump();
+        assert!(dump.starts_with("nanolog debug dump\ngenerated_at: "));
+        assert!(dump.contains("state: running\n"));
+        assert!(dump.contains("filter: info,app::db=debug\n"));
+        assert!(dump.contains("queue_capacity: 128\n"));
+        assert!(dump.contains("\n[queue]\npublished: 3\nprocessed: 3\nin_flight: 0\n"));
+        assert!(dump.contains("\n[records]\nsent: 3\nwritten: 3\nlost: 0\n"));
+        assert!(dump.contains("sink[0] (primary): ok, "));
+        assert!(dump.contains("sink[1] (output): write failing, "));
+        assert!(dump.contains("Z sink write failed: disk full\n"));
+
+        assert!(logger.shutdown().is_ok());
+        assert!(logger.debug_dump().contains("state: shut down\n"));
+    }
+}
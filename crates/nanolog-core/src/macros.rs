@@ -0,0 +1,465 @@
+//! 日志宏模块
+//!
+//! 提供类似标准log crate的宏，但与nanolog-rs集成
+
+/// 记录日志的宏实现
+///
+/// 该宏具有惰性求值特性：只有当日志级别启用时，才会执行格式化操作，
+/// 避免了不必要的字符串格式化开销。
+///
+/// 级别宏支持 `lazy:` 前缀（如 `info!(lazy: "id={}", id)`），格式化推迟到消费者线程，
+/// 调用线程只保存格式串与按值捕获的参数；参数需满足 `Send + Sync + 'static`。
+///
+/// `sync:` 前缀（如 `info!(sync: "audit: {}", event)`）将记录标记为同步写出，
+/// 调用在记录写入并刷新输出目标后才返回，用于审计等关键事件。
+///
+/// 级别宏还支持在格式串之前附加结构化字段，如 `info!(user = name, latency_us = 12, "login")`；
+/// 启用 `serde` 特性后，`info!(payload = serde(&value), "...")` 可捕获任意 `Serialize` 值；
+/// `error!(err = &e, "operation failed")` 将错误及其 `source()` 链记为 `error.message` 与
+/// `error.cause[n]` 字段（见 [`Record::with_error`](crate::Record::with_error)）。
+#[macro_export]
+macro_rules! log {
+    (target: $target:expr, $lvl:expr, $($arg:tt)+) => ({
+        let lvl = $lvl;
+        if let Some(logger) = $crate::global_logger() {
+            if logger.get().map_or(false, |l| l.should_log(lvl)) {
+                let record = $crate::Record::new(
+                    lvl,
+                    $target,
+                    file!(),
+                    line!(),
+                    $crate::record::message_from_args(format_args!($($arg)+)),
+                );
+                let _ = logger.log(record);
+            }
+        }
+    });
+    ($lvl:expr, $($arg:tt)+) => (
+        $crate::log!(target: module_path!(), $lvl, $($arg)+)
+    );
+}
+
+/// 以闭包直接向消息缓冲区写入的日志宏
+///
+/// 闭包签名为 `|buf: &mut dyn fmt::Write| -> fmt::Result`，消息缓冲区取自本线程的缓冲区池，
+/// 不经过 `format!` 生成中间 `String`；级别未启用时闭包不执行。级别须为常量表达式：
+///
+/// ```
+/// use nanolog_rs::{Level, write_with};
+///
+/// let ids = [3u64, 5, 8];
+/// write_with!(Level::Info, |buf| {
+///     buf.write_str("batch ids=")?;
+///     for id in ids {
+///         write!(buf, "{},", id)?;
+///     }
+///     Ok(())
+/// });
+/// write_with!(target: "net", Level::Debug, |buf| write!(buf, "{} peers", ids.len()));
+/// ```
+#[macro_export]
+macro_rules! write_with {
+    (target: $target:expr, $lvl:expr, $write:expr $(,)?) => ({
+        static CALLSITE: $crate::callsite::Callsite =
+            $crate::callsite::Callsite::new($lvl, module_path!(), file!(), line!());
+        if CALLSITE.is_enabled() {
+            if let Some(logger) = $crate::global_logger() {
+                let record = $crate::Record::write_with(
+                    CALLSITE.level(),
+                    $target,
+                    CALLSITE.file(),
+                    CALLSITE.line(),
+                    $write,
+                )
+                .with_callsite(&CALLSITE);
+                let _ = logger.log(record);
+            }
+        }
+    });
+    ($lvl:expr, $write:expr $(,)?) => (
+        $crate::write_with!(target: module_path!(), $lvl, $write)
+    );
+}
+
+/// 每 N 次执行记录一次的日志宏（首次执行即记录），计数按调用点独立
+///
+/// ```
+/// use nanolog_rs::{Level, log_every_n};
+///
+/// for i in 0..100 {
+///     log_every_n!(10, Level::Debug, "tick {}", i);
+/// }
+/// log_every_n!(target: "net", 10, Level::Info, "retry");
+/// ```
+#[macro_export]
+macro_rules! log_every_n {
+    (target: $target:expr, $n:expr, $lvl:expr, $($arg:tt)+) => ({
+        static EVERY: $crate::ratelimit::EveryN = $crate::ratelimit::EveryN::new();
+        if EVERY.tick($n) {
+            $crate::log!(target: $target, $lvl, $($arg)+);
+        }
+    });
+    ($n:expr, $lvl:expr, $($arg:tt)+) => (
+        $crate::log_every_n!(target: module_path!(), $n, $lvl, $($arg)+)
+    );
+}
+
+/// 间隔内至多记录一次的日志宏（首次执行即记录），时间按调用点独立计算
+///
+/// ```
+/// use nanolog_rs::{Level, log_at_most_every};
+/// use std::time::Duration;
+///
+/// log_at_most_every!(Duration::from_secs(5), Level::Warn, "disk almost full");
+/// ```
+#[macro_export]
+macro_rules! log_at_most_every {
+    (target: $target:expr, $interval:expr, $lvl:expr, $($arg:tt)+) => ({
+        static LIMIT: $crate::ratelimit::AtMostEvery = $crate::ratelimit::AtMostEvery::new();
+        if LIMIT.allow($interval) {
+            $crate::log!(target: $target, $lvl, $($arg)+);
+        }
+    });
+    ($interval:expr, $lvl:expr, $($arg:tt)+) => (
+        $crate::log_at_most_every!(target: module_path!(), $interval, $lvl, $($arg)+)
+    );
+}
+
+/// 固定级别日志宏的实现：通过静态调用点缓存级别检查结果
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_callsite {
+    (target: $target:expr, $lvl:expr, [$($fields:tt)*], $($arg:tt)+) => ({
+        static CALLSITE: $crate::callsite::Callsite =
+            $crate::callsite::Callsite::new($lvl, module_path!(), file!(), line!());
+        if CALLSITE.is_enabled() {
+            if let Some(logger) = $crate::global_logger() {
+                let record = $crate::Record::new(
+                    CALLSITE.level(),
+                    $target,
+                    CALLSITE.file(),
+                    CALLSITE.line(),
+                    $crate::record::message_from_args(format_args!($($arg)+)),
+                )
+                .with_callsite(&CALLSITE)
+                $($fields)*;
+                let _ = logger.log(record);
+            }
+        }
+    });
+    (lazy: target: $target:expr, $lvl:expr, $($arg:tt)+) => ({
+        static CALLSITE: $crate::callsite::Callsite =
+            $crate::callsite::Callsite::new($lvl, module_path!(), file!(), line!());
+        if CALLSITE.is_enabled() {
+            if let Some(logger) = $crate::global_logger() {
+                let record = $crate::Record::lazy(
+                    CALLSITE.level(),
+                    $target,
+                    CALLSITE.file(),
+                    CALLSITE.line(),
+                    move |f| ::std::write!(f, $($arg)+),
+                )
+                .with_callsite(&CALLSITE);
+                let _ = logger.log(record);
+            }
+        }
+    });
+    (sync: target: $target:expr, $lvl:expr, $($arg:tt)+) => ({
+        static CALLSITE: $crate::callsite::Callsite =
+            $crate::callsite::Callsite::new($lvl, module_path!(), file!(), line!());
+        if CALLSITE.is_enabled() {
+            if let Some(logger) = $crate::global_logger() {
+                let record = $crate::Record::new(
+                    CALLSITE.level(),
+                    $target,
+                    CALLSITE.file(),
+                    CALLSITE.line(),
+                    $crate::record::message_from_args(format_args!($($arg)+)),
+                )
+                .with_callsite(&CALLSITE)
+                .immediate(true);
+                let _ = logger.log(record);
+            }
+        }
+    });
+    (target: $target:expr, $lvl:expr, $($arg:tt)+) => (
+        $crate::__log_callsite!(target: $target, $lvl, [], $($arg)+)
+    );
+    ($lvl:expr, $($arg:tt)+) => (
+        $crate::__log_callsite!(target: module_path!(), $lvl, [], $($arg)+)
+    );
+}
+
+/// 解析级别宏中格式串之前的 `key = value` 字段；`key = serde(&v)` 捕获任意 `Serialize` 值，
+/// `err = &e` 捕获错误及其原因链
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_fields {
+    (target: $target:expr, $lvl:expr, [$($fields:tt)*], $key:ident = serde($value:expr), $($rest:tt)+) => (
+        $crate::__log_fields!(
+            target: $target, $lvl,
+            [$($fields)* .with_field(stringify!($key), $crate::field::Value::serialize($value))],
+            $($rest)+
+        )
+    );
+    (target: $target:expr, $lvl:expr, [$($fields:tt)*], err = $value:expr, $($rest:tt)+) => (
+        $crate::__log_fields!(target: $target, $lvl, [$($fields)* .with_error($value)], $($rest)+)
+    );
+    (target: $target:expr, $lvl:expr, [$($fields:tt)*], $key:ident = $value:expr, $($rest:tt)+) => (
+        $crate::__log_fields!(
+            target: $target, $lvl,
+            [$($fields)* .with_field(stringify!($key), $value)],
+            $($rest)+
+        )
+    );
+    (target: $target:expr, $lvl:expr, [$($fields:tt)*], $key:ident = $value:expr $(,)?) => (
+        ::core::compile_error!(concat!(
+            "structured field `", stringify!($key),
+            "` must be followed by a format string, e.g. `info!(",
+            stringify!($key), " = value, \"message\")`"
+        ))
+    );
+    (target: $target:expr, $lvl:expr, [$($fields:tt)*], $($arg:tt)+) => (
+        $crate::__log_callsite!(target: $target, $lvl, [$($fields)*], $($arg)+)
+    );
+}
+
+/// 记录错误级别日志
+#[macro_export]
+macro_rules! error {
+    () => (
+        ::core::compile_error!("`error!` requires a format string, e.g. `error!(\"message\")`")
+    );
+    (target: $target:expr, lazy: $($arg:tt)+) => (
+        $crate::__log_callsite!(lazy: target: $target, $crate::Level::Error, $($arg)+)
+    );
+    (target: $target:expr, sync: $($arg:tt)+) => (
+        $crate::__log_callsite!(sync: target: $target, $crate::Level::Error, $($arg)+)
+    );
+    (target: $target:expr, $key:ident = $($rest:tt)+) => (
+        $crate::__log_fields!(target: $target, $crate::Level::Error, [], $key = $($rest)+)
+    );
+    (target: $target:expr, $($arg:tt)+) => (
+        $crate::__log_callsite!(target: $target, $crate::Level::Error, $($arg)+)
+    );
+    (lazy: $($arg:tt)+) => (
+        $crate::__log_callsite!(lazy: target: module_path!(), $crate::Level::Error, $($arg)+)
+    );
+    (sync: $($arg:tt)+) => (
+        $crate::__log_callsite!(sync: target: module_path!(), $crate::Level::Error, $($arg)+)
+    );
+    ($key:ident = $($rest:tt)+) => (
+        $crate::__log_fields!(target: module_path!(), $crate::Level::Error, [], $key = $($rest)+)
+    );
+    ($($arg:tt)+) => (
+        $crate::__log_callsite!($crate::Level::Error, $($arg)+)
+    );
+}
+
+/// 记录警告级别日志
+#[macro_export]
+macro_rules! warn {
+    () => (
+        ::core::compile_error!("`warn!` requires a format string, e.g. `warn!(\"message\")`")
+    );
+    (target: $target:expr, lazy: $($arg:tt)+) => (
+        $crate::__log_callsite!(lazy: target: $target, $crate::Level::Warn, $($arg)+)
+    );
+    (target: $target:expr, sync: $($arg:tt)+) => (
+        $crate::__log_callsite!(sync: target: $target, $crate::Level::Warn, $($arg)+)
+    );
+    (target: $target:expr, $key:ident = $($rest:tt)+) => (
+        $crate::__log_fields!(target: $target, $crate::Level::Warn, [], $key = $($rest)+)
+    );
+    (target: $target:expr, $($arg:tt)+) => (
+        $crate::__log_callsite!(target: $target, $crate::Level::Warn, $($arg)+)
+    );
+    (lazy: $($arg:tt)+) => (
+        $crate::__log_callsite!(lazy: target: module_path!(), $crate::Level::Warn, $($arg)+)
+    );
+    (sync: $($arg:tt)+) => (
+        $crate::__log_callsite!(sync: target: module_path!(), $crate::Level::Warn, $($arg)+)
+    );
+    ($key:ident = $($rest:tt)+) => (
+        $crate::__log_fields!(target: module_path!(), $crate::Level::Warn, [], $key = $($rest)+)
+    );
+    ($($arg:tt)+) => (
+        $crate::__log_callsite!($crate::Level::Warn, $($arg)+)
+    );
+}
+
+/// 记录信息级别日志
+#[macro_export]
+macro_rules! info {
+    () => (
+        ::core::compile_error!("`info!` requires a format string, e.g. `info!(\"message\")`")
+    );
+    (target: $target:expr, lazy: $($arg:tt)+) => (
+        $crate::__log_callsite!(lazy: target: $target, $crate::Level::Info, $($arg)+)
+    );
+    (target: $target:expr, sync: $($arg:tt)+) => (
+        $crate::__log_callsite!(sync: target: $target, $crate::Level::Info, $($arg)+)
+    );
+    (target: $target:expr, $key:ident = $($rest:tt)+) => (
+        $crate::__log_fields!(target: $target, $crate::Level::Info, [], $key = $($rest)+)
+    );
+    (target: $target:expr, $($arg:tt)+) => (
+        $crate::__log_callsite!(target: $target, $crate::Level::Info, $($arg)+)
+    );
+    (lazy: $($arg:tt)+) => (
+        $crate::__log_callsite!(lazy: target: module_path!(), $crate::Level::Info, $($arg)+)
+    );
+    (sync: $($arg:tt)+) => (
+        $crate::__log_callsite!(sync: target: module_path!(), $crate::Level::Info, $($arg)+)
+    );
+    ($key:ident = $($rest:tt)+) => (
+        $crate::__log_fields!(target: module_path!(), $crate::Level::Info, [], $key = $($rest)+)
+    );
+    ($($arg:tt)+) => (
+        $crate::__log_callsite!($crate::Level::Info, $($arg)+)
+    );
+}
+
+/// 记录调试级别日志
+#[macro_export]
+macro_rules! debug {
+    () => (
+        ::core::compile_error!("`debug!` requires a format string, e.g. `debug!(\"message\")`")
+    );
+    (target: $target:expr, lazy: $($arg:tt)+) => (
+        $crate::__log_callsite!(lazy: target: $target, $crate::Level::Debug, $($arg)+)
+    );
+    (target: $target:expr, sync: $($arg:tt)+) => (
+        $crate::__log_callsite!(sync: target: $target, $crate::Level::Debug, $($arg)+)
+    );
+    (target: $target:expr, $key:ident = $($rest:tt)+) => (
+        $crate::__log_fields!(target: $target, $crate::Level::Debug, [], $key = $($rest)+)
+    );
+    (target: $target:expr, $($arg:tt)+) => (
+        $crate::__log_callsite!(target: $target, $crate::Level::Debug, $($arg)+)
+    );
+    (lazy: $($arg:tt)+) => (
+        $crate::__log_callsite!(lazy: target: module_path!(), $crate::Level::Debug, $($arg)+)
+    );
+    (sync: $($arg:tt)+) => (
+        $crate::__log_callsite!(sync: target: module_path!(), $crate::Level::Debug, $($arg)+)
+    );
+    ($key:ident = $($rest:tt)+) => (
+        $crate::__log_fields!(target: module_path!(), $crate::Level::Debug, [], $key = $($rest)+)
+    );
+    ($($arg:tt)+) => (
+        $crate::__log_callsite!($crate::Level::Debug, $($arg)+)
+    );
+}
+
+/// 记录跟踪级别日志
+#[macro_export]
+macro_rules! trace {
+    () => (
+        ::core::compile_error!("`trace!` requires a format string, e.g. `trace!(\"message\")`")
+    );
+    (target: $target:expr, lazy: $($arg:tt)+) => (
+        $crate::__log_callsite!(lazy: target: $target, $crate::Level::Trace, $($arg)+)
+    );
+    (target: $target:expr, sync: $($arg:tt)+) => (
+        $crate::__log_callsite!(sync: target: $target, $crate::Level::Trace, $($arg)+)
+    );
+    (target: $target:expr, $key:ident = $($rest:tt)+) => (
+        $crate::__log_fields!(target: $target, $crate::Level::Trace, [], $key = $($rest)+)
+    );
+    (target: $target:expr, $($arg:tt)+) => (
+        $crate::__log_callsite!(target: $target, $crate::Level::Trace, $($arg)+)
+    );
+    (lazy: $($arg:tt)+) => (
+        $crate::__log_callsite!(lazy: target: module_path!(), $crate::Level::Trace, $($arg)+)
+    );
+    (sync: $($arg:tt)+) => (
+        $crate::__log_callsite!(sync: target: module_path!(), $crate::Level::Trace, $($arg)+)
+    );
+    ($key:ident = $($rest:tt)+) => (
+        $crate::__log_fields!(target: module_path!(), $crate::Level::Trace, [], $key = $($rest)+)
+    );
+    ($($arg:tt)+) => (
+        $crate::__log_callsite!($crate::Level::Trace, $($arg)+)
+    );
+}
+
+/// 在调用点预取 [`RecordSlot`](crate::slot::RecordSlot)，目标、文件与行号取自调用处
+///
+/// ```
+/// use nanolog_rs::{AsyncLogger, Level, NullSink, record_slot};
+/// use std::sync::Arc;
+///
+/// let logger = AsyncLogger::builder().sink(Arc::new(NullSink::new())).build()?;
+/// let mut slot = record_slot!(&logger, Level::Info);
+/// for i in 0..3u64 {
+///     slot.log(|r| write!(r, "tick {}", i))?;
+/// }
+/// let mut net = record_slot!(target: "net", &logger, Level::Warn);
+/// net.log(|r| r.write_str("peer lost"))?;
+/// # logger.shutdown()?;
+/// # Ok::<(), nanolog_rs::error::Error>(())
+/// ```
+#[macro_export]
+macro_rules! record_slot {
+    (target: $target:expr, $logger:expr, $lvl:expr $(,)?) => (
+        $crate::slot::RecordSlot::new($logger, $lvl, $target, file!(), line!())
+    );
+    ($logger:expr, $lvl:expr $(,)?) => (
+        $crate::record_slot!(target: module_path!(), $logger, $lvl)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AsyncLogger, ConsoleSink, DefaultFormatter, Level, init_global_logger};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_macro_compilation() {
+        // 创建测试日志器
+        let formatter = Arc::new(DefaultFormatter::new());
+        let sink = Arc::new(ConsoleSink::new());
+        let logger = Arc::new(AsyncLogger::new(
+            Level::Trace,
+            formatter,
+            sink,
+            1024,
+            64,
+            Duration::from_millis(50),
+        ));
+
+        let _ = init_global_logger(logger);
+
+        // 测试宏是否能正常编译
+        error!("This is an error message");
+        warn!("This is a warning message");
+        info!("This is an info message");
+        debug!("This is a debug message");
+        trace!("This is a trace message");
+
+        // 带参数的宏测试
+        let x = 42;
+        info!("The answer is {}", x);
+        error!("Error occurred with value: {}", x);
+
+        // 延迟格式化
+        let name = String::from("lazy");
+        info!(lazy: "deferred {} {}", name, x);
+        warn!(target: "custom", lazy: "deferred {x}");
+
+        // 结构化字段
+        info!(user = "alice", answer = x, "login {}", x);
+        debug!(target: "custom", latency_us = 12u64, "done");
+
+        // 直接写入消息缓冲区
+        crate::write_with!(Level::Info, |buf| write!(buf, "streamed {}", x));
+
+        // 按调用点限流
+        for i in 0..3 {
+            log_every_n!(2, Level::Info, "every other {}", i);
+            log_at_most_every!(Duration::from_secs(60), Level::Warn, "once {}", i);
+        }
+    }
+}
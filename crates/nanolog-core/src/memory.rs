@@ -0,0 +1,216 @@
+/*!
+内存预算。
+
+为队列、缓冲池与溢出缓冲设置字节上限，并在各子系统间统一计量。
+超出预算时行为是明确的：队列拒绝新记录（`log()` 返回 [`Error::Memory`](crate::error::Error::Memory)），
+缓冲池丢弃归还的缓冲区，溢出缓冲丢弃新数据；所有丢弃都会计数，并可在运行时查询当前用量。
+*/
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// 受预算约束的内存区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryArea {
+    /// 环形队列（固定槽位开销 + 排队中记录的消息字节）
+    Queue,
+    /// 缓冲池中空闲缓冲区的容量
+    Pool,
+    /// 输出目标断连期间的溢出缓冲
+    Spill,
+}
+
+impl MemoryArea {
+    /// 所有内存区域
+    pub const ALL: [MemoryArea; 3] = [MemoryArea::Queue, MemoryArea::Pool, MemoryArea::Spill];
+
+    #[inline]
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// 内存预算配置（`None` 表示不限制）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryBudget {
+    queue: Option<usize>,
+    pool: Option<usize>,
+    spill: Option<usize>,
+}
+
+impl MemoryBudget {
+    /// 创建不限制任何区域的预算
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置队列最大字节数
+    ///
+    /// 环形队列的固定槽位开销计入该预算：若固定开销已超出预算，队列容量会被缩小（最少 64 个槽位）。
+    /// 预算不超过 64 个槽位的开销时无法容纳任何消息，构建日志器返回 [`Error::Config`](crate::error::Error::Config)。
+    pub fn max_queue_bytes(mut self, bytes: usize) -> Self {
+        self.queue = Some(bytes);
+        self
+    }
+
+    /// 设置缓冲池最大字节数
+    pub fn max_pool_bytes(mut self, bytes: usize) -> Self {
+        self.pool = Some(bytes);
+        self
+    }
+
+    /// 设置溢出缓冲最大字节数
+    pub fn max_spill_bytes(mut self, bytes: usize) -> Self {
+        self.spill = Some(bytes);
+        self
+    }
+
+    /// 获取指定区域的上限
+    pub fn limit(&self, area: MemoryArea) -> Option<usize> {
+        match area {
+            MemoryArea::Queue => self.queue,
+            MemoryArea::Pool => self.pool,
+            MemoryArea::Spill => self.spill,
+        }
+    }
+
+    /// 所有区域上限之和（任一区域不限制或总和溢出时返回 `None`）
+    pub fn total(&self) -> Option<usize> {
+        self.queue?
+            .checked_add(self.pool?)?
+            .checked_add(self.spill?)
+    }
+}
+
+/// 内存用量快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// 队列当前占用字节数
+    pub queue_bytes: usize,
+    /// 缓冲池当前占用字节数
+    pub pool_bytes: usize,
+    /// 溢出缓冲当前占用字节数
+    pub spill_bytes: usize,
+    /// 因队列预算被拒绝的记录数
+    pub queue_dropped: u64,
+    /// 因缓冲池预算被丢弃的缓冲区数
+    pub pool_dropped: u64,
+    /// 因溢出预算被丢弃的数据块数
+    pub spill_dropped: u64,
+}
+
+impl MemoryUsage {
+    /// 所有区域的占用之和
+    pub fn total_bytes(&self) -> usize {
+        self.queue_bytes + self.pool_bytes + self.spill_bytes
+    }
+}
+
+/// 内存计量器（各子系统共享）
+#[derive(Debug, Default)]
+pub struct MemoryTracker {
+    budget: MemoryBudget,
+    used: [AtomicUsize; 3],
+    dropped: [AtomicU64; 3],
+}
+
+impl MemoryTracker {
+    /// 使用指定预算创建计量器
+    pub fn new(budget: MemoryBudget) -> Self {
+        Self {
+            budget,
+            ..Self::default()
+        }
+    }
+
+    /// 获取预算配置
+    pub fn budget(&self) -> &MemoryBudget {
+        &self.budget
+    }
+
+    /// 尝试在指定区域预留字节；超出预算时记录一次丢弃并返回 `false`
+    #[inline]
+    pub fn try_reserve(&self, area: MemoryArea, bytes: usize) -> bool {
+        let used = &self.used[area.index()];
+        let Some(limit) = self.budget.limit(area) else {
+            used.fetch_add(bytes, Ordering::Relaxed);
+            return true;
+        };
+
+        let reserved = used.fetch_update(Ordering::AcqRel, Ordering::Relaxed, |current| {
+            current.checked_add(bytes).filter(|next| *next <= limit)
+        });
+        if reserved.is_err() {
+            self.dropped[area.index()].fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    /// 无条件预留字节（用于无法拒绝的固定开销）
+    #[inline]
+    pub fn reserve(&self, area: MemoryArea, bytes: usize) {
+        self.used[area.index()].fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// 释放之前预留的字节
+    #[inline]
+    pub fn release(&self, area: MemoryArea, bytes: usize) {
+        let _ =
+            self.used[area.index()].fetch_update(Ordering::AcqRel, Ordering::Relaxed, |current| {
+                Some(current.saturating_sub(bytes))
+            });
+    }
+
+    /// 获取指定区域当前占用
+    pub fn used(&self, area: MemoryArea) -> usize {
+        self.used[area.index()].load(Ordering::Relaxed)
+    }
+
+    /// 获取用量快照
+    pub fn usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            queue_bytes: self.used(MemoryArea::Queue),
+            pool_bytes: self.used(MemoryArea::Pool),
+            spill_bytes: self.used(MemoryArea::Spill),
+            queue_dropped: self.dropped[MemoryArea::Queue.index()].load(Ordering::Relaxed),
+            pool_dropped: self.dropped[MemoryArea::Pool.index()].load(Ordering::Relaxed),
+            spill_dropped: self.dropped[MemoryArea::Spill.index()].load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_enforces_limit_and_counts_drops() {
+        let tracker = MemoryTracker::new(MemoryBudget::new().max_spill_bytes(100));
+        assert!(tracker.try_reserve(MemoryArea::Spill, 60));
+        assert!(!tracker.try_reserve(MemoryArea::Spill, 60));
+        tracker.release(MemoryArea::Spill, 60);
+        assert!(tracker.try_reserve(MemoryArea::Spill, 100));
+
+        let usage = tracker.usage();
+        assert_eq!(usage.spill_bytes, 100);
+        assert_eq!(usage.spill_dropped, 1);
+    }
+
+    #[test]
+    fn test_unlimited_area_always_reserves() {
+        let tracker = MemoryTracker::new(MemoryBudget::new());
+        assert!(tracker.try_reserve(MemoryArea::Queue, usize::MAX / 2));
+        assert_eq!(tracker.usage().queue_dropped, 0);
+        assert_eq!(MemoryBudget::new().total(), None);
+    }
+
+    #[test]
+    fn test_total_returns_none_on_overflow() {
+        let budget = MemoryBudget::new()
+            .max_queue_bytes(1024)
+            .max_pool_bytes(2048)
+            .max_spill_bytes(4096);
+        assert_eq!(budget.total(), Some(7168));
+        assert_eq!(budget.max_spill_bytes(usize::MAX).total(), None);
+    }
+}
@@ -0,0 +1,124 @@
+/*!
+进程元数据。
+
+[`ProcessMetadata`] 在构建器上配置一次（见 [`AsyncLoggerBuilder::process_metadata`](crate::AsyncLoggerBuilder::process_metadata)），
+之后每条发布的记录都附带进程号、主机名、服务名等字段，无需在每条消息中手动拼接：
+
+```
+use nanolog_rs::{AsyncLoggerBuilder, ProcessMetadata};
+
+let logger = AsyncLoggerBuilder::new()
+    .process_metadata(ProcessMetadata::detect().with_service("checkout"))
+    .build()?;
+# logger.shutdown()?;
+# Ok::<(), nanolog_rs::error::Error>(())
+```
+
+字段附加在调用线程的上下文字段之后，随其他结构化字段一起由各格式化器输出。
+元数据中的字符串在创建时转为进程内常驻的 `&'static str`，附加到记录时不再分配。
+*/
+
+use crate::field::{Field, Value};
+use std::borrow::Cow;
+
+/// 附加到每条记录的进程元数据
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessMetadata {
+    fields: Vec<Field>,
+}
+
+impl ProcessMetadata {
+    /// 创建空的元数据
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 包含进程号与主机名（可获取时）的元数据
+    pub fn detect() -> Self {
+        Self::new().with_pid().with_hostname()
+    }
+
+    /// 附加进程号（`pid`）
+    pub fn with_pid(self) -> Self {
+        self.with_field("pid", u64::from(std::process::id()))
+    }
+
+    /// 附加主机名（`hostname`），无法获取时不附加
+    pub fn with_hostname(self) -> Self {
+        match hostname() {
+            Some(name) => self.with_field("hostname", name),
+            None => self,
+        }
+    }
+
+    /// 附加服务名（`service`）
+    pub fn with_service(self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.with_field("service", name.into())
+    }
+
+    /// 附加自定义字段（如 `env`、`version`），同名字段替换先前的值
+    pub fn with_field(mut self, key: &'static str, value: impl Into<Value>) -> Self {
+        let value = match value.into() {
+            Value::Str(Cow::Owned(s)) => Value::Str(Cow::Borrowed(Box::leak(s.into_boxed_str()))),
+            value => value,
+        };
+        self.fields.retain(|field| field.key() != key);
+        self.fields.push(Field::new(key, value));
+        self
+    }
+
+    /// 元数据字段
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// 将元数据字段附加到记录上
+    pub(crate) fn attach(&self, fields: &mut Vec<Field>) {
+        fields.extend(self.fields.iter().cloned());
+    }
+}
+
+/// 本机主机名：依次读取 `/proc/sys/kernel/hostname`、`/etc/hostname` 与环境变量
+/// `HOSTNAME`、`COMPUTERNAME`
+pub fn hostname() -> Option<String> {
+    ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .chain(
+            ["HOSTNAME", "COMPUTERNAME"]
+                .iter()
+                .filter_map(|var| std::env::var(var).ok()),
+        )
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_fields() {
+        let metadata = ProcessMetadata::new()
+            .with_pid()
+            .with_service(String::from("checkout"))
+            .with_field("env", "prod")
+            .with_field("env", "staging");
+        let keys: Vec<&str> = metadata.fields().iter().map(Field::key).collect();
+        assert_eq!(keys, ["pid", "service", "env"]);
+        assert_eq!(
+            metadata.fields()[0].value(),
+            &Value::U64(u64::from(std::process::id()))
+        );
+        // 字符串值常驻，附加到记录时只复制引用
+        assert!(matches!(
+            metadata.fields()[1].value(),
+            Value::Str(Cow::Borrowed("checkout"))
+        ));
+
+        let mut fields = vec![Field::new("user", "alice")];
+        metadata.attach(&mut fields);
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[3].value(), &Value::from("staging"));
+    }
+}
@@ -0,0 +1,128 @@
+/*!
+ID 生成。
+
+[`IdGenerator`] 为未设置模板的轮转文件后缀与确认式投递的批次名称生成 ID，
+部署方可借此在各处统一命名约定：
+
+| 生成器 | 示例 |
+|--------|------|
+| [`Counter`] | `7`，零填充时 `000007` |
+| [`UnixTime`] | `1709622489` |
+| [`Ulid`] | `01HR2ZQ8V3K4X9T6M0B5C7D1EF` |
+
+序号由调用方维护（从 1 开始递增），生成器可以使用也可以忽略；闭包
+`Fn(u64, &mut String)` 也可直接作为生成器。
+
+```
+use nanolog_rs::naming::Counter;
+use nanolog_rs::rotation::RotationPolicy;
+use std::sync::Arc;
+
+// 轮转文件命名为 app.log.000001、app.log.000002……
+let policy = RotationPolicy::new()
+    .max_size(64 << 20)
+    .suffix(Arc::new(Counter::zero_padded(6)));
+# let _ = policy;
+```
+*/
+
+use std::fmt::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// ID 生成器
+pub trait IdGenerator: Send + Sync {
+    /// 将第 `sequence` 个 ID 追加到 `out`
+    fn write_id(&self, sequence: u64, out: &mut String);
+
+    /// 生成第 `sequence` 个 ID
+    fn id(&self, sequence: u64) -> String {
+        let mut out = String::new();
+        self.write_id(sequence, &mut out);
+        out
+    }
+}
+
+impl fmt::Debug for dyn IdGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("IdGenerator")
+    }
+}
+
+impl<F> IdGenerator for F
+where
+    F: Fn(u64, &mut String) + Send + Sync,
+{
+    fn write_id(&self, sequence: u64, out: &mut String) {
+        self(sequence, out)
+    }
+}
+
+/// 十进制序号，可零填充到固定宽度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counter {
+    width: usize,
+}
+
+impl Counter {
+    /// 不填充的序号
+    pub const fn new() -> Self {
+        Self { width: 0 }
+    }
+
+    /// 零填充到 `width` 位的序号（超出宽度时原样输出）
+    pub const fn zero_padded(width: usize) -> Self {
+        Self { width }
+    }
+}
+
+impl IdGenerator for Counter {
+    fn write_id(&self, sequence: u64, out: &mut String) {
+        let _ = write!(out, "{:0width$}", sequence, width = self.width);
+    }
+}
+
+/// 生成时刻的 Unix 秒（忽略序号）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UnixTime;
+
+impl IdGenerator for UnixTime {
+    fn write_id(&self, _sequence: u64, out: &mut String) {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let _ = write!(out, "{}", secs);
+    }
+}
+
+/// ULID（忽略序号），按字典序即按生成时间排序，跨进程重启不重复
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Ulid;
+
+impl IdGenerator for Ulid {
+    fn write_id(&self, _sequence: u64, out: &mut String) {
+        out.push_str(&crate::context::new_request_id());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_generators() {
+        assert_eq!(Counter::new().id(7), "7");
+        assert_eq!(Counter::zero_padded(6).id(7), "000007");
+        assert_eq!(Counter::zero_padded(2).id(1234), "1234");
+        assert!(UnixTime.id(1).bytes().all(|b| b.is_ascii_digit()));
+
+        let (first, second) = (Ulid.id(1), Ulid.id(1));
+        assert_eq!(first.len(), 26);
+        assert!(first < second);
+
+        let custom = |sequence: u64, out: &mut String| {
+            let _ = write!(out, "seg-{:04x}", sequence);
+        };
+        assert_eq!(custom.id(255), "seg-00ff");
+    }
+}
@@ -0,0 +1,191 @@
+/*!
+NUMA 感知的内存放置。
+
+在多路服务器上，将环形队列与缓冲池的内存分配在消费者线程所在的 NUMA 节点，
+避免格式化/写入路径上的跨节点内存访问。实际的线程绑定与内存策略设置仅在
+Linux 且启用 `numa` 特性时生效，其他情况下相关操作返回 `Unsupported` 并优雅退化。
+*/
+
+use std::io;
+
+/// 检查当前构建是否支持 NUMA 绑定
+pub fn is_supported() -> bool {
+    cfg!(all(target_os = "linux", feature = "numa"))
+}
+
+/// 获取在线的 NUMA 节点列表
+pub fn online_nodes() -> io::Result<Vec<usize>> {
+    let list = std::fs::read_to_string("/sys/devices/system/node/online")?;
+    Ok(parse_cpu_list(&list))
+}
+
+/// 获取指定 NUMA 节点上的 CPU 列表
+pub fn node_cpus(node: usize) -> io::Result<Vec<usize>> {
+    let path = format!("/sys/devices/system/node/node{}/cpulist", node);
+    let list = std::fs::read_to_string(path)?;
+    let cpus = parse_cpu_list(&list);
+    if cpus.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "NUMA node has no online CPUs",
+        ));
+    }
+    Ok(cpus)
+}
+
+/// 解析 sysfs 风格的 CPU/节点列表，如 `0-3,8,10-11`
+pub(crate) fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) {
+                    cpus.extend(start..=end);
+                }
+            }
+            None => {
+                if let Ok(cpu) = part.parse::<usize>() {
+                    cpus.push(cpu);
+                }
+            }
+        }
+    }
+    cpus
+}
+
+/// 在绑定到指定节点的辅助线程中执行 `f`
+///
+/// 辅助线程的 CPU 亲和性与内存策略都指向该节点，因此 `f` 中首次触碰的内存
+/// 以及由其创建的线程（继承内存策略）的分配都落在该节点上。绑定失败时 `f` 照常执行。
+pub(crate) fn run_on_node<T, F>(node: usize, f: F) -> T
+where
+    T: Send,
+    F: FnOnce() -> T + Send,
+{
+    std::thread::scope(|scope| {
+        let handle = scope.spawn(move || {
+            let _ = bind_current_thread(node);
+            f()
+        });
+        handle
+            .join()
+            .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+    })
+}
+
+/// 将当前线程的 CPU 亲和性与内存策略绑定到指定节点
+#[cfg(all(target_os = "linux", feature = "numa"))]
+pub fn bind_current_thread(node: usize) -> io::Result<()> {
+    let cpus = node_cpus(node)?;
+    // SAFETY: cpu_set_t 是普通位图，全零初始化合法；传给内核的指针在调用期间有效。
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for cpu in cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    let mask = node_mask(node)?;
+    // SAFETY: mask 在调用期间有效，maxnode 与其位数一致。
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_set_mempolicy,
+            MPOL_PREFERRED,
+            mask.as_ptr(),
+            (mask.len() * 64) as libc::c_ulong,
+        )
+    };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// 将当前线程的 CPU 亲和性与内存策略绑定到指定节点
+#[cfg(not(all(target_os = "linux", feature = "numa")))]
+pub fn bind_current_thread(_node: usize) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "NUMA binding requires Linux and the `numa` feature",
+    ))
+}
+
+/// 将已分配的内存区域迁移/绑定到指定节点（按页对齐，区域内不足一页的部分忽略）
+#[cfg(all(target_os = "linux", feature = "numa"))]
+pub fn bind_memory(ptr: *const u8, len: usize, node: usize) -> io::Result<()> {
+    // SAFETY: sysconf 无副作用。
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = (ptr as usize).div_ceil(page) * page;
+    let end = (ptr as usize + len) / page * page;
+    if end <= start {
+        return Ok(());
+    }
+
+    let mask = node_mask(node)?;
+    // SAFETY: [start, end) 位于调用方拥有的分配内，mbind 只改变页的放置策略。
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            start as *mut libc::c_void,
+            (end - start) as libc::c_ulong,
+            MPOL_PREFERRED,
+            mask.as_ptr(),
+            (mask.len() * 64) as libc::c_ulong,
+            MPOL_MF_MOVE,
+        )
+    };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// 将已分配的内存区域迁移/绑定到指定节点（按页对齐，区域内不足一页的部分忽略）
+#[cfg(not(all(target_os = "linux", feature = "numa")))]
+pub fn bind_memory(_ptr: *const u8, _len: usize, _node: usize) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "NUMA binding requires Linux and the `numa` feature",
+    ))
+}
+
+#[cfg(all(target_os = "linux", feature = "numa"))]
+const MPOL_PREFERRED: libc::c_int = 1;
+#[cfg(all(target_os = "linux", feature = "numa"))]
+const MPOL_MF_MOVE: libc::c_uint = 1 << 1;
+
+/// 构造节点位图
+#[cfg(all(target_os = "linux", feature = "numa"))]
+fn node_mask(node: usize) -> io::Result<Vec<libc::c_ulong>> {
+    const MAX_NODES: usize = 1024;
+    if node >= MAX_NODES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "NUMA node out of range",
+        ));
+    }
+    let mut mask = vec![0 as libc::c_ulong; MAX_NODES / 64];
+    mask[node / 64] |= 1 << (node % 64);
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("0-3,8,10-11\n"), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(parse_cpu_list("0"), vec![0]);
+        assert!(parse_cpu_list("").is_empty());
+    }
+
+    #[test]
+    fn test_run_on_node_always_runs_closure() {
+        let value = run_on_node(0, || 42);
+        assert_eq!(value, 42);
+    }
+}
@@ -0,0 +1,683 @@
+/*!
+消费者线程上的输出管道。
+
+每路输出由格式化器与输出目标组成。同一条记录依次借给各路格式化器（不复制记录），
+格式化结果写入各路复用的缓冲区，攒批后通过 `write_batch` 一次写入。
+
+启用丢失标记时，丢弃或格式化失败的记录在各路输出中留下一行 `... N messages dropped ...`，
+标记经该路格式化器渲染，写在丢失位置之后的下一条记录之前（或批尾），不计入已写入的记录数。
+
+两级流水线中，格式化阶段先将记录格式化到事件槽位中的 [`Preformatted`] 缓冲区，
+写入阶段将其换入批次而不再调用格式化器；换出的空缓冲区留在槽位中供下一轮复用。
+*/
+
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::diagnostics::{Diagnostic, DiagnosticHandler};
+use crate::format::{BatchFormatter, Formatter};
+use crate::sink::{RecordMeta, Sink, SinkErrorHandler, SinkErrorPolicy, SinkFailure};
+use crate::stats::{FlushStats, WriteProfiler};
+use crate::subscribe::Subscribers;
+use crate::{Level, Record};
+
+/// 清空批次时保留容量的单个缓冲区上限，超出的缓冲区释放，避免偶发的超长记录长期占用内存
+const MAX_POOLED_BUFFER: usize = 64 * 1024;
+
+/// [`SinkErrorPolicy::Retry`] 的基础退避间隔，第 n 次重试前等待 n 倍
+const RETRY_BACKOFF: Duration = Duration::from_millis(5);
+
+/// 复用缓冲区的格式化批次：清空时保留各缓冲区及其容量
+#[derive(Default)]
+struct FormattedBatch {
+    bufs: Vec<Vec<u8>>,
+    len: usize,
+    /// 逐条格式化的记录的元数据（与缓冲区一一对应）
+    meta: Vec<RecordMeta>,
+}
+
+impl FormattedBatch {
+    /// 取出下一个已清空的缓冲区，用于格式化 `meta` 对应的记录
+    fn next(&mut self, meta: RecordMeta) -> &mut Vec<u8> {
+        if self.len == self.bufs.len() {
+            self.bufs.push(Vec::new());
+        }
+        self.meta.push(meta);
+        let buf = &mut self.bufs[self.len];
+        buf.clear();
+        self.len += 1;
+        buf
+    }
+
+    /// 撤销最近一次 [`next`](Self::next)（格式化失败时）
+    fn discard_last(&mut self) {
+        self.meta.pop();
+        self.len -= 1;
+    }
+
+    /// 交换最近放入的两个缓冲区及其元数据
+    fn swap_last(&mut self) {
+        if self.len >= 2 {
+            self.bufs.swap(self.len - 2, self.len - 1);
+            let meta = self.meta.len();
+            if meta >= 2 {
+                self.meta.swap(meta - 2, meta - 1);
+            }
+        }
+    }
+
+    /// 放入整批序列化的数据（不对应单条记录）
+    fn push(&mut self, data: Vec<u8>) {
+        if self.len == self.bufs.len() {
+            self.bufs.push(data);
+        } else {
+            self.bufs[self.len] = data;
+        }
+        self.len += 1;
+    }
+
+    fn as_slice(&self) -> &[Vec<u8>] {
+        &self.bufs[..self.len]
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 写入输出目标：逐条格式化的批次附带记录元数据
+    fn write_to(&self, sink: &dyn Sink) -> std::io::Result<()> {
+        if self.meta.len() == self.len {
+            sink.write_records(&self.meta, self.as_slice())
+        } else {
+            sink.write_batch(self.as_slice())
+        }
+    }
+
+    fn clear(&mut self) {
+        for buf in &mut self.bufs[..self.len] {
+            if buf.capacity() > MAX_POOLED_BUFFER {
+                *buf = Vec::new();
+            }
+        }
+        self.len = 0;
+        self.meta.clear();
+    }
+}
+
+/// 格式化阶段为单条记录预先格式化的各路输出结果
+#[derive(Default)]
+pub(crate) struct Preformatted {
+    bufs: Vec<Vec<u8>>,
+    /// 各路格式化是否成功
+    ok: Vec<bool>,
+}
+
+impl Preformatted {
+    /// 以各路格式化器格式化记录；`skip_primary` 时主输出留给写入阶段整批序列化
+    pub(crate) fn format(
+        &mut self,
+        formatters: &[Arc<dyn Formatter>],
+        skip_primary: bool,
+        record: &Record,
+    ) {
+        self.bufs.resize_with(formatters.len(), Vec::new);
+        self.ok.resize(formatters.len(), false);
+        for (index, formatter) in formatters.iter().enumerate() {
+            let buf = &mut self.bufs[index];
+            buf.clear();
+            self.ok[index] =
+                !(index == 0 && skip_primary) && formatter.format_into(record, buf).is_ok();
+        }
+    }
+
+    /// 将第 `index` 路的结果换入 `buf`，`buf` 原有的缓冲区留在槽位中
+    fn take(&mut self, index: usize, buf: &mut Vec<u8>) -> Result<(), std::fmt::Error> {
+        match self.bufs.get_mut(index) {
+            Some(staged) if self.ok[index] => {
+                std::mem::swap(staged, buf);
+                Ok(())
+            }
+            _ => Err(std::fmt::Error),
+        }
+    }
+}
+
+/// 一路输出的健康状态（由消费者线程更新，供调试快照读取）
+#[derive(Debug, Default)]
+pub(crate) struct SinkHealth {
+    /// 最近一次写入是否失败
+    pub(crate) write_failing: AtomicBool,
+    /// 最近一次刷新是否失败
+    pub(crate) flush_failing: AtomicBool,
+    /// 写入批次数
+    pub(crate) writes: AtomicU64,
+    /// 写入失败次数
+    pub(crate) write_errors: AtomicU64,
+    /// 刷新失败次数
+    pub(crate) flush_errors: AtomicU64,
+}
+
+/// 一路输出
+struct Output {
+    formatter: Arc<dyn Formatter>,
+    sink: Arc<dyn Sink>,
+    batch: FormattedBatch,
+    health: Arc<SinkHealth>,
+    /// 压缩前的整批载荷（输出目标声明了压缩编码时使用）
+    payload: Vec<u8>,
+    /// 压缩后的帧
+    frame: Vec<u8>,
+    /// 尚未在本路输出中标记的丢失记录数
+    dropped: u64,
+}
+
+impl Output {
+    /// 输出目标声明了压缩编码时，将本批次组装并压缩为一帧，返回是否已压缩
+    fn compress(&mut self, stats: &FlushStats) -> std::io::Result<bool> {
+        let Some(compression) = self.sink.batch_compression() else {
+            return Ok(false);
+        };
+        self.payload.clear();
+        self.frame.clear();
+        self.sink
+            .encode_batch(self.batch.as_slice(), &mut self.payload);
+        let started = Instant::now();
+        compression.compress(&self.payload, &mut self.frame)?;
+        stats.record_compression(self.payload.len(), self.frame.len(), started.elapsed());
+        Ok(true)
+    }
+
+    /// 在批次中放入合成记录（丢失标记或去重汇总）
+    fn push_marker(&mut self, marker: &Record, stats: &FlushStats) {
+        let buf = self.batch.next(RecordMeta::of(marker));
+        if self.formatter.format_into(marker, buf).is_err() {
+            buf.clear();
+            buf.extend_from_slice(marker.message().as_bytes());
+            buf.push(b'\n');
+        }
+        stats.record_write(buf.len());
+        self.sink.stage_record(marker);
+    }
+
+    /// 写入本批次（已压缩时写入压缩帧）
+    /// 本次写入的字节数
+    fn batch_bytes(&self, compressed: bool) -> usize {
+        if compressed {
+            self.frame.len()
+        } else {
+            self.batch.as_slice().iter().map(Vec::len).sum()
+        }
+    }
+
+    fn write_batch(&self, compressed: bool) -> std::io::Result<()> {
+        if compressed {
+            self.sink.write_compressed(&self.frame, self.batch.len)
+        } else {
+            self.batch.write_to(self.sink.as_ref())
+        }
+    }
+}
+
+/// 消费者线程上的全部输出（首个为主输出）
+pub(crate) struct Outputs {
+    outputs: Vec<Output>,
+    /// 主输出的批量格式化器（设置后主输出攒批记录而非逐条格式化）
+    batch_formatter: Option<Arc<dyn BatchFormatter>>,
+    records: Vec<Record>,
+    /// 自上次写入以来处理的记录数
+    pending: usize,
+    /// 主输出待写入批次中包含的记录数
+    primary_records: usize,
+    /// 实时订阅者（接收主输出格式的记录）
+    subscribers: Arc<Subscribers>,
+    /// 写入失败时的处理策略
+    error_policy: SinkErrorPolicy,
+    /// 操作失败回调
+    on_error: Option<SinkErrorHandler>,
+    /// 是否为丢失的记录写入标记行
+    drop_markers: bool,
+    /// 主输出攒批记录中的合成记录数（丢失标记与去重汇总）
+    batched_markers: usize,
+    /// 格式化失败的记录数（各路输出分别计数）
+    format_failures: u64,
+    /// 写入计时钩子
+    profiler: Option<Arc<dyn WriteProfiler>>,
+}
+
+impl Outputs {
+    /// 创建输出集合，`outputs` 的首项为主输出
+    pub(crate) fn new(
+        outputs: impl IntoIterator<Item = (Arc<dyn Formatter>, Arc<dyn Sink>)>,
+        batch_formatter: Option<Arc<dyn BatchFormatter>>,
+        subscribers: Arc<Subscribers>,
+        error_policy: SinkErrorPolicy,
+        on_error: Option<SinkErrorHandler>,
+        drop_markers: bool,
+        profiler: Option<Arc<dyn WriteProfiler>>,
+    ) -> Self {
+        Self {
+            outputs: outputs
+                .into_iter()
+                .map(|(formatter, sink)| Output {
+                    formatter,
+                    sink,
+                    batch: FormattedBatch::default(),
+                    health: Arc::default(),
+                    payload: Vec::new(),
+                    frame: Vec::new(),
+                    dropped: 0,
+                })
+                .collect(),
+            batch_formatter,
+            records: Vec::new(),
+            pending: 0,
+            primary_records: 0,
+            subscribers,
+            error_policy,
+            on_error,
+            drop_markers,
+            batched_markers: 0,
+            format_failures: 0,
+            profiler,
+        }
+    }
+
+    /// 记录在到达输出前丢失（溢出丢弃、过期丢弃等），各路输出在下一条记录前写入标记
+    pub(crate) fn note_dropped(&mut self, count: u64) {
+        if self.drop_markers && count > 0 {
+            for output in &mut self.outputs {
+                output.dropped += count;
+            }
+        }
+    }
+
+    /// 将合成记录（如去重汇总）写入各路输出，不计入已写入的记录数
+    pub(crate) fn push_synthetic(&mut self, record: &Record, stats: &FlushStats) {
+        for (index, output) in self.outputs.iter_mut().enumerate() {
+            if index == 0 && self.batch_formatter.is_some() {
+                self.records.push(record.clone());
+                self.batched_markers += 1;
+            } else {
+                output.push_marker(record, stats);
+            }
+        }
+    }
+
+    /// 批尾为各路输出中尚未标记的丢失记录写入标记
+    fn mark_dropped(&mut self, stats: &FlushStats) {
+        for (index, output) in self.outputs.iter_mut().enumerate() {
+            if output.dropped == 0 {
+                continue;
+            }
+            let marker = dropped_marker(std::mem::take(&mut output.dropped));
+            if index == 0 && self.batch_formatter.is_some() {
+                self.records.push(marker);
+                self.batched_markers += 1;
+            } else {
+                output.push_marker(&marker, stats);
+            }
+        }
+    }
+
+    /// 各路输出的格式化器（与输出顺序一致）
+    pub(crate) fn formatters(&self) -> Vec<Arc<dyn Formatter>> {
+        self.outputs
+            .iter()
+            .map(|output| output.formatter.clone())
+            .collect()
+    }
+
+    /// 主输出是否由批量格式化器整批序列化
+    pub(crate) fn batches_primary(&self) -> bool {
+        self.batch_formatter.is_some()
+    }
+
+    /// 各路输出目标中最长的合并写入窗口
+    pub(crate) fn coalesce_window(&self) -> Duration {
+        self.outputs
+            .iter()
+            .map(|output| output.sink.coalesce_window())
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// 各路输出的健康状态（与输出顺序一致）
+    pub(crate) fn health(&self) -> Vec<Arc<SinkHealth>> {
+        self.outputs
+            .iter()
+            .map(|output| output.health.clone())
+            .collect()
+    }
+
+    /// 取出并清零格式化失败计数
+    pub(crate) fn take_format_failures(&mut self) -> u64 {
+        std::mem::take(&mut self.format_failures)
+    }
+
+    /// 自上次写入以来处理的记录数
+    pub(crate) fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// 将记录借给各路格式化器，结果放入各自的批次；`preformatted` 为格式化阶段已得到的结果
+    pub(crate) fn push(
+        &mut self,
+        record: &Record,
+        stats: &FlushStats,
+        mut preformatted: Option<&mut Preformatted>,
+    ) {
+        self.pending += 1;
+        for (index, output) in self.outputs.iter_mut().enumerate() {
+            if index == 0 && self.batch_formatter.is_some() {
+                if output.dropped > 0 {
+                    self.records
+                        .push(dropped_marker(std::mem::take(&mut output.dropped)));
+                    self.batched_markers += 1;
+                }
+                self.records.push(record.clone());
+                // 订阅者接收逐条格式化的记录
+                if self.subscribers.matches(record)
+                    && let Ok(formatted) = output.formatter.format(record)
+                {
+                    self.subscribers.publish(record, &formatted);
+                }
+                continue;
+            }
+            let buf = output.batch.next(RecordMeta::of(record));
+            let result = match preformatted.as_deref_mut() {
+                Some(preformatted) => preformatted.take(index, buf),
+                None => output.formatter.format_into(record, buf),
+            };
+            match result {
+                Ok(()) => {
+                    stats.record_write(buf.len());
+                    if index == 0 {
+                        self.primary_records += 1;
+                        if self.subscribers.is_active() {
+                            self.subscribers.publish(record, buf);
+                        }
+                    }
+                    // 连续的丢失合并为一条标记，放在本条记录之前
+                    if output.dropped > 0 {
+                        let marker = dropped_marker(std::mem::take(&mut output.dropped));
+                        output.push_marker(&marker, stats);
+                        output.batch.swap_last();
+                    }
+                    output.sink.stage_record(record);
+                }
+                Err(_) => {
+                    output.batch.discard_last();
+                    self.format_failures += 1;
+                    if self.drop_markers {
+                        output.dropped += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 通知各路格式化器批次结束
+    pub(crate) fn end_batch(&self) {
+        for output in &self.outputs {
+            output.formatter.end_batch();
+        }
+    }
+
+    /// 将各路攒批的结果写入输出目标，主输出写入的记录计入 `written`
+    pub(crate) fn write(
+        &mut self,
+        written: &AtomicUsize,
+        stats: &FlushStats,
+        diagnostics: &dyn DiagnosticHandler,
+    ) {
+        if self.drop_markers {
+            self.mark_dropped(stats);
+        }
+        self.format_records(stats);
+        for (index, output) in self.outputs.iter_mut().enumerate() {
+            if output.batch.is_empty() {
+                continue;
+            }
+            // 压缩失败不重试，也不退回未压缩写入
+            let compressed = output.compress(stats);
+            let profiled = self.profiler.as_ref().map(|profiler| {
+                let bytes = output.batch_bytes(matches!(compressed, Ok(true)));
+                profiler.on_write_start(index, bytes);
+                (profiler, bytes, Instant::now())
+            });
+            let (compressed, mut result) = match compressed {
+                Ok(compressed) => (Some(compressed), output.write_batch(compressed)),
+                Err(err) => (None, Err(err)),
+            };
+            let mut attempts = 1;
+            if let (SinkErrorPolicy::Retry(retries), Some(compressed)) =
+                (self.error_policy, compressed)
+            {
+                while result.is_err() && attempts <= retries {
+                    std::thread::sleep(RETRY_BACKOFF * attempts);
+                    result = output.write_batch(compressed);
+                    attempts += 1;
+                }
+            }
+            if let Some((profiler, bytes, started)) = profiled {
+                profiler.on_write_end(index, bytes, started.elapsed(), result.is_ok());
+            }
+            if let Err(error) = &result {
+                if let Some(on_error) = &self.on_error {
+                    on_error(&SinkFailure {
+                        output: index,
+                        operation: "write",
+                        error,
+                        attempts,
+                        records: output.batch.len,
+                    });
+                }
+                if self.error_policy == SinkErrorPolicy::FallbackToStderr {
+                    let mut stderr = std::io::stderr().lock();
+                    for buf in output.batch.as_slice() {
+                        let _ = stderr.write_all(buf);
+                    }
+                    let _ = stderr.flush();
+                }
+            }
+            output.batch.clear();
+            let health = &output.health;
+            health.writes.fetch_add(1, Ordering::Relaxed);
+            check_sink(
+                result,
+                "write",
+                &health.write_failing,
+                &health.write_errors,
+                diagnostics,
+            );
+        }
+        written.fetch_add(std::mem::take(&mut self.primary_records), Ordering::Relaxed);
+        self.pending = 0;
+    }
+
+    /// 刷新各路输出目标
+    pub(crate) fn flush(&mut self, diagnostics: &dyn DiagnosticHandler) {
+        for (index, output) in self.outputs.iter_mut().enumerate() {
+            let result = output.sink.flush();
+            if let (Err(error), Some(on_error)) = (&result, &self.on_error) {
+                on_error(&SinkFailure {
+                    output: index,
+                    operation: "flush",
+                    error,
+                    attempts: 1,
+                    records: 0,
+                });
+            }
+            let health = &output.health;
+            check_sink(
+                result,
+                "flush",
+                &health.flush_failing,
+                &health.flush_errors,
+                diagnostics,
+            );
+        }
+    }
+
+    /// 将攒批的记录整批序列化为一个载荷放入主输出的批次
+    fn format_records(&mut self, stats: &FlushStats) {
+        let (Some(batch_formatter), Some(primary)) =
+            (&self.batch_formatter, self.outputs.first_mut())
+        else {
+            return;
+        };
+        if self.records.is_empty() {
+            return;
+        }
+        let records = self.records.len() - std::mem::take(&mut self.batched_markers);
+        let mut payload = Vec::new();
+        if batch_formatter
+            .format_batch(&self.records, &mut payload)
+            .is_ok()
+        {
+            stats.record_write(payload.len());
+            primary.batch.push(payload);
+            self.primary_records += records;
+        } else {
+            self.format_failures += records as u64;
+            if self.drop_markers {
+                primary.dropped += records as u64;
+            }
+        }
+        self.records.clear();
+    }
+}
+
+/// 丢失标记记录
+fn dropped_marker(count: u64) -> Record {
+    let noun = if count == 1 { "message" } else { "messages" };
+    Record::new(
+        Level::Warn,
+        "nanolog_rs",
+        "",
+        0,
+        format!("... {} {} dropped ...", count, noun),
+    )
+}
+
+/// 输出目标操作由正常转为失败时通过诊断通道上报，避免持续失败时刷屏
+fn check_sink(
+    result: std::io::Result<()>,
+    operation: &'static str,
+    failing: &AtomicBool,
+    errors: &AtomicU64,
+    diagnostics: &dyn DiagnosticHandler,
+) {
+    match result {
+        Ok(()) => failing.store(false, Ordering::Relaxed),
+        Err(err) => {
+            errors.fetch_add(1, Ordering::Relaxed);
+            if !failing.swap(true, Ordering::Relaxed) {
+                diagnostics.handle(&Diagnostic::SinkError {
+                    operation,
+                    error: err.to_string(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formatted_batch_reuses_buffers() {
+        let record = Record::new(crate::Level::Info, "t", file!(), 1, String::new());
+        let meta = RecordMeta::of(&record);
+        let mut batch = FormattedBatch::default();
+        batch.next(meta).extend_from_slice(b"first record");
+        let ptr = batch.as_slice()[0].as_ptr();
+        batch.next(meta).extend_from_slice(b"x");
+        batch.discard_last();
+        assert_eq!(batch.as_slice(), &[b"first record".to_vec()]);
+        assert_eq!(batch.meta, vec![meta]);
+
+        batch.clear();
+        assert!(batch.is_empty());
+        let buf = batch.next(meta);
+        assert!(buf.is_empty());
+        assert_eq!(buf.as_ptr(), ptr);
+    }
+
+    /// 拒绝格式化消息为 `bad` 的记录
+    struct PickyFormatter;
+
+    impl Formatter for PickyFormatter {
+        fn format(&self, record: &Record) -> Result<Vec<u8>, std::fmt::Error> {
+            if record.message() == "bad" {
+                return Err(std::fmt::Error);
+            }
+            Ok(format!("{}\n", record.message()).into_bytes())
+        }
+    }
+
+    #[test]
+    fn test_preformatted_buffers_swap_into_batch() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let formatter = Arc::new(PickyFormatter) as Arc<dyn Formatter>;
+        let mut outputs = Outputs::new(
+            [(formatter.clone(), sink.clone() as Arc<dyn Sink>)],
+            None,
+            Arc::default(),
+            SinkErrorPolicy::Ignore,
+            None,
+            true,
+            None,
+        );
+        let stats = FlushStats::new();
+        let written = AtomicUsize::new(0);
+        let mut preformatted = Preformatted::default();
+        for message in ["a", "bad", "b"] {
+            let record = Record::new(Level::Info, "t", file!(), 1, message);
+            preformatted.format(&outputs.formatters(), false, &record);
+            outputs.push(&record, &stats, Some(&mut preformatted));
+        }
+        outputs.write(&written, &stats, &crate::diagnostics::StderrDiagnostics);
+        assert_eq!(sink.get_content(), b"a\n... 1 message dropped ...\nb\n");
+        assert_eq!(written.load(Ordering::Relaxed), 2);
+
+        // 主输出整批序列化时不预先格式化
+        let record = Record::new(Level::Info, "t", file!(), 1, "c");
+        preformatted.format(&[formatter], true, &record);
+        assert!(preformatted.take(0, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_format_failures_leave_marker() {
+        let sink = Arc::new(crate::sink::MemorySink::new());
+        let mut outputs = Outputs::new(
+            [(
+                Arc::new(PickyFormatter) as Arc<dyn Formatter>,
+                sink.clone() as Arc<dyn Sink>,
+            )],
+            None,
+            Arc::default(),
+            SinkErrorPolicy::Ignore,
+            None,
+            true,
+            None,
+        );
+        let stats = FlushStats::new();
+        let written = AtomicUsize::new(0);
+        for message in ["a", "bad", "bad", "b", "bad"] {
+            let record = Record::new(Level::Info, "t", file!(), 1, message.to_string());
+            outputs.push(&record, &stats, None);
+        }
+        outputs.note_dropped(3);
+        outputs.write(&written, &stats, &crate::diagnostics::StderrDiagnostics);
+
+        assert_eq!(
+            sink.get_content(),
+            b"a\n... 2 messages dropped ...\nb\n... 4 messages dropped ...\n"
+        );
+        assert_eq!(written.load(Ordering::Relaxed), 2);
+    }
+}
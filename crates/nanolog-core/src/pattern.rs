@@ -0,0 +1,487 @@
+/*!
+模式字符串格式化器。
+
+[`PatternFormatter`] 由布局字符串配置，构造时编译为片段列表，格式化时顺序执行，
+无需自定义 [`Formatter`] 即可控制输出布局：
+
+```
+use nanolog_rs::PatternFormatter;
+
+let formatter =
+    PatternFormatter::new("%Y-%m-%d %H:%M:%S%.3f [%l] %t (%f:%L) - %m%n").unwrap();
+# let _ = formatter;
+```
+
+| 说明符 | 含义 |
+|--------|------|
+| `%Y` `%m` `%d` | 年（四位）、月、日 |
+| `%H` `%M` `%S` | 时、分、秒 |
+| `%.3f` `%.6f` `%.9f` | 带点的毫秒、微秒、纳秒小数 |
+| `%r` | 相对 [`timestamp_base`](crate::record::timestamp_base) 的秒数（纳秒精度，如 `12.000345678`） |
+| `%l` | 级别名称 |
+| `%t` | 目标 |
+| `%i` | 线程名称，未命名时为 `thread-<编号>`（仅捕获了线程的记录输出） |
+| `%f` `%L` | 源文件与行号 |
+| `%m` `%v` | 消息 |
+| `%k` | 结构化字段（logfmt，空格分隔） |
+| `%n` `%%` | 换行与百分号 |
+
+`%m` 紧跟在时间说明符及至多一个分隔字符之后时表示月份（如 `%Y-%m-%d`），否则表示消息；
+需要避免歧义时可用 `%v` 表示消息。
+加载配置时可用 [`PatternLayout::validate`] 提前校验，错误带有位置与修正建议。
+时间默认为 UTC，可通过 [`with_offset`](PatternFormatter::with_offset) 指定时区偏移。
+*/
+
+use crate::Record;
+use crate::error::Error;
+use crate::format::{Formatter, utc_datetime, write_padded, write_relative, write_thread};
+use chrono::{Datelike, FixedOffset, NaiveDateTime, Timelike};
+use std::fmt;
+
+/// 编译后的布局片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// 原样输出的文本（相邻文本、`%n`、`%%` 已合并）
+    Literal(String),
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    /// 带点的秒小数，值为位数
+    Fraction(u8),
+    Relative,
+    Level,
+    Target,
+    Thread,
+    File,
+    Line,
+    Message,
+    Fields,
+}
+
+impl Segment {
+    fn needs_time(&self) -> bool {
+        matches!(
+            self,
+            Segment::Year
+                | Segment::Month
+                | Segment::Day
+                | Segment::Hour
+                | Segment::Minute
+                | Segment::Second
+                | Segment::Fraction(_)
+        )
+    }
+}
+
+/// 编译后的布局
+///
+/// 由 [`validate`](Self::validate) 在加载配置时校验并编译，错误带有精确位置与修正建议，
+/// 避免错误的布局在运行时输出乱码。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternLayout {
+    segments: Vec<Segment>,
+}
+
+impl PatternLayout {
+    /// 校验并编译布局字符串
+    pub fn validate(layout: &str) -> Result<Self, LayoutError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = layout.char_indices().peekable();
+        while let Some((position, c)) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+            let error =
+                |chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>, reason, suggestion| {
+                    let end = chars.peek().map_or(layout.len(), |(i, _)| *i);
+                    LayoutError {
+                        position,
+                        specifier: layout[position..end].to_string(),
+                        reason,
+                        suggestion,
+                    }
+                };
+            let segment = match chars.next().map(|(_, c)| c) {
+                Some('%') => {
+                    literal.push('%');
+                    continue;
+                }
+                Some('n') => {
+                    literal.push('\n');
+                    continue;
+                }
+                Some('Y') => Segment::Year,
+                // 紧跟时间片段（至多隔一个分隔字符）时为月份，否则为消息
+                Some('m')
+                    if literal.chars().count() <= 1
+                        && segments.last().is_some_and(Segment::needs_time) =>
+                {
+                    Segment::Month
+                }
+                Some('d') => Segment::Day,
+                Some('H') => Segment::Hour,
+                Some('M') => Segment::Minute,
+                Some('S') => Segment::Second,
+                Some('.') => {
+                    let digits = chars.next_if(|(_, c)| c.is_ascii_digit()).map(|(_, c)| c);
+                    match (digits, chars.next_if(|(_, c)| *c == 'f').is_some()) {
+                        (Some('3'), true) => Segment::Fraction(3),
+                        (Some('6'), true) => Segment::Fraction(6),
+                        (Some('9'), true) => Segment::Fraction(9),
+                        (Some('6'), false) => {
+                            return Err(error(&mut chars, "invalid fraction", Some("%.6f")));
+                        }
+                        (Some('9'), false) => {
+                            return Err(error(&mut chars, "invalid fraction", Some("%.9f")));
+                        }
+                        _ => return Err(error(&mut chars, "invalid fraction", Some("%.3f"))),
+                    }
+                }
+                Some('r') => Segment::Relative,
+                Some('l') => Segment::Level,
+                Some('t') => Segment::Target,
+                Some('i') => Segment::Thread,
+                Some('f') => Segment::File,
+                Some('L') => Segment::Line,
+                Some('m') | Some('v') => Segment::Message,
+                Some('k') => Segment::Fields,
+                Some(c) => return Err(error(&mut chars, "unknown specifier", suggest(c))),
+                None => return Err(error(&mut chars, "incomplete specifier", Some("%%"))),
+            };
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            }
+            segments.push(segment);
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Self { segments })
+    }
+
+    fn needs_time(&self) -> bool {
+        self.segments.iter().any(Segment::needs_time)
+    }
+}
+
+impl std::str::FromStr for PatternLayout {
+    type Err = LayoutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::validate(s)
+    }
+}
+
+/// 其他日志库或 strftime 中的常见说明符对应的写法
+fn suggest(c: char) -> Option<&'static str> {
+    match c {
+        'p' | 'P' => Some("%l"),
+        'c' => Some("%t"),
+        'F' => Some("%f"),
+        'y' => Some("%Y"),
+        'D' | 'e' => Some("%d"),
+        'h' | 'I' => Some("%H"),
+        's' => Some("%S"),
+        'T' => Some("%H:%M:%S"),
+        'K' => Some("%k"),
+        'N' => Some("%n"),
+        'V' => Some("%v"),
+        '3' => Some("%.3f"),
+        '6' => Some("%.6f"),
+        '9' => Some("%.9f"),
+        _ => None,
+    }
+}
+
+/// 布局校验错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutError {
+    position: usize,
+    specifier: String,
+    reason: &'static str,
+    suggestion: Option<&'static str>,
+}
+
+impl LayoutError {
+    /// 出错说明符在布局中的字节偏移
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// 出错的说明符原文
+    pub fn specifier(&self) -> &str {
+        &self.specifier
+    }
+
+    /// 错误原因
+    pub fn reason(&self) -> &'static str {
+        self.reason
+    }
+
+    /// 建议的写法
+    pub fn suggestion(&self) -> Option<&'static str> {
+        self.suggestion
+    }
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} `{}` at byte {}",
+            self.reason, self.specifier, self.position
+        )?;
+        match self.suggestion {
+            Some("%%") => write!(f, " (use `%%` for a literal percent sign)"),
+            Some(suggestion) => write!(f, " (did you mean `{}`?)", suggestion),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+impl From<LayoutError> for Error {
+    fn from(_: LayoutError) -> Self {
+        Error::Config("invalid pattern layout")
+    }
+}
+
+/// 模式字符串格式化器
+pub struct PatternFormatter {
+    layout: PatternLayout,
+    /// 是否含时间片段（不含时跳过时间转换）
+    needs_time: bool,
+    offset: Option<FixedOffset>,
+}
+
+impl PatternFormatter {
+    /// 编译布局字符串；含未知或不完整的说明符时返回配置错误
+    ///
+    /// 需要错误位置时先用 [`PatternLayout::validate`] 校验，再以
+    /// [`from_layout`](Self::from_layout) 构造。
+    pub fn new(layout: &str) -> Result<Self, Error> {
+        Ok(Self::from_layout(PatternLayout::validate(layout)?))
+    }
+
+    /// 使用已校验的布局创建格式化器
+    pub fn from_layout(layout: PatternLayout) -> Self {
+        Self {
+            needs_time: layout.needs_time(),
+            layout,
+            offset: None,
+        }
+    }
+
+    /// 按指定时区偏移输出时间（默认 UTC）
+    pub fn with_offset(mut self, offset: FixedOffset) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    fn local_time(&self, timestamp_ns: u128) -> NaiveDateTime {
+        let utc = utc_datetime(timestamp_ns);
+        match self.offset {
+            Some(offset) => utc.with_timezone(&offset).naive_local(),
+            None => utc.naive_utc(),
+        }
+    }
+}
+
+impl Formatter for PatternFormatter {
+    fn format(&self, record: &Record) -> Result<Vec<u8>, fmt::Error> {
+        let mut result = Vec::with_capacity(record.message().len() + 64);
+        self.format_into(record, &mut result)?;
+        Ok(result)
+    }
+
+    fn format_into(&self, record: &Record, out: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        let time = self.needs_time.then(|| self.local_time(record.timestamp()));
+        let mut buf = itoa::Buffer::new();
+        for segment in &self.layout.segments {
+            match (segment, &time) {
+                (Segment::Literal(text), _) => out.extend_from_slice(text.as_bytes()),
+                (Segment::Year, Some(t)) => {
+                    let year = t.year();
+                    if year < 0 {
+                        out.push(b'-');
+                    }
+                    write_padded(out, year.unsigned_abs(), 4);
+                }
+                (Segment::Month, Some(t)) => write_padded(out, t.month(), 2),
+                (Segment::Day, Some(t)) => write_padded(out, t.day(), 2),
+                (Segment::Hour, Some(t)) => write_padded(out, t.hour(), 2),
+                (Segment::Minute, Some(t)) => write_padded(out, t.minute(), 2),
+                (Segment::Second, Some(t)) => write_padded(out, t.second(), 2),
+                (Segment::Fraction(digits), Some(t)) => {
+                    let digits = u32::from(*digits);
+                    out.push(b'.');
+                    write_padded(
+                        out,
+                        t.nanosecond() % 1_000_000_000 / 10u32.pow(9 - digits),
+                        digits as usize,
+                    );
+                }
+                (Segment::Relative, _) => write_relative(out, record.relative_timestamp()),
+                (Segment::Level, _) => out.extend_from_slice(record.level().as_str().as_bytes()),
+                (Segment::Target, _) => out.extend_from_slice(record.target().as_bytes()),
+                (Segment::Thread, _) => {
+                    if let Some(thread) = record.thread() {
+                        write_thread(out, thread);
+                    }
+                }
+                (Segment::File, _) => out.extend_from_slice(record.file().as_bytes()),
+                (Segment::Line, _) => out.extend_from_slice(buf.format(record.line()).as_bytes()),
+                (Segment::Message, _) => out.extend_from_slice(record.message().as_bytes()),
+                (Segment::Fields, _) => {
+                    for (i, field) in record.fields().iter().enumerate() {
+                        if i > 0 {
+                            out.push(b' ');
+                        }
+                        out.extend_from_slice(field.key().as_bytes());
+                        out.push(b'=');
+                        field.value().write_logfmt(out);
+                    }
+                }
+                // 时间片段存在时 `time` 必然已计算
+                (_, None) => return Err(fmt::Error),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    #[test]
+    fn test_pattern_layout() {
+        let record = Record::new(Level::Warn, "db", "src/db.rs", 42, "slow query".to_string())
+            .with_field("ms", 250u64)
+            .with_field("table", "users");
+        let formatter =
+            PatternFormatter::new("%Y-%m-%d %H:%M:%S%.3f [%l] %t (%f:%L) - %m {%k} 100%%%n")
+                .unwrap();
+        let out = String::from_utf8(formatter.format(&record).unwrap()).unwrap();
+
+        let time = utc_datetime(record.timestamp())
+            .format("%Y-%m-%d %H:%M:%S%.3f")
+            .to_string();
+        assert_eq!(
+            out,
+            format!(
+                "{} [WARN] db (src/db.rs:42) - slow query {{ms=250 table=users}} 100%\n",
+                time
+            )
+        );
+    }
+
+    #[test]
+    fn test_pattern_offset_and_literals_merge() {
+        let formatter = PatternFormatter::new("%H | %m%n")
+            .unwrap()
+            .with_offset(FixedOffset::east_opt(8 * 3600).unwrap());
+        assert_eq!(formatter.layout.segments.len(), 4);
+        let record = Record::new(Level::Info, "app", "a.rs", 1, "hi".to_string());
+        let out = String::from_utf8(formatter.format(&record).unwrap()).unwrap();
+        let hour = utc_datetime(record.timestamp())
+            .with_timezone(&FixedOffset::east_opt(8 * 3600).unwrap())
+            .format("%H")
+            .to_string();
+        assert_eq!(out, format!("{} | hi\n", hour));
+    }
+
+    #[test]
+    fn test_pattern_thread() {
+        let formatter = PatternFormatter::new("[%i] %v%n").unwrap();
+        let record = Record::new(Level::Info, "app", "a.rs", 1, "hi".to_string());
+        assert_eq!(formatter.format(&record).unwrap(), b"[] hi\n");
+        let record = std::thread::Builder::new()
+            .name("io".into())
+            .spawn(move || record.with_thread())
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(formatter.format(&record).unwrap(), b"[io] hi\n");
+    }
+
+    #[test]
+    fn test_pattern_relative_timestamp() {
+        let formatter = PatternFormatter::new("%r %v").unwrap();
+        let record = Record::new(Level::Info, "app", "a.rs", 1, "hi".to_string());
+        let mut expected = Vec::new();
+        write_relative(&mut expected, record.relative_timestamp());
+        expected.extend_from_slice(b" hi");
+        assert_eq!(formatter.format(&record).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_pattern_rejects_bad_specifiers() {
+        assert!(PatternFormatter::new("%q").is_err());
+        assert!(PatternFormatter::new("%.4f").is_err());
+        assert!(PatternFormatter::new("done %").is_err());
+        assert!(PatternFormatter::new("plain text").is_ok());
+    }
+
+    #[test]
+    fn test_pattern_month_or_message() {
+        let segments = PatternLayout::validate("%Y%m %d/%m %m %S - %m %v")
+            .unwrap()
+            .segments;
+        let specifiers: Vec<&Segment> = segments
+            .iter()
+            .filter(|s| !matches!(s, Segment::Literal(_)))
+            .collect();
+        assert_eq!(
+            specifiers,
+            [
+                &Segment::Year,
+                &Segment::Month,
+                &Segment::Day,
+                &Segment::Month,
+                &Segment::Month,
+                &Segment::Second,
+                &Segment::Message,
+                &Segment::Message,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_position_and_suggestion() {
+        let err = PatternLayout::validate("%H:%M [%p] %m").unwrap_err();
+        assert_eq!(err.position(), 7);
+        assert_eq!(err.specifier(), "%p");
+        assert_eq!(err.suggestion(), Some("%l"));
+        assert_eq!(
+            err.to_string(),
+            "unknown specifier `%p` at byte 7 (did you mean `%l`?)"
+        );
+
+        let err = PatternLayout::validate("时间 %S%.6 %m").unwrap_err();
+        assert_eq!(err.position(), 9);
+        assert_eq!(err.specifier(), "%.6");
+        assert_eq!(err.suggestion(), Some("%.6f"));
+
+        let err = "100%".parse::<PatternLayout>().unwrap_err();
+        assert_eq!(err.position(), 3);
+        assert_eq!(
+            err.to_string(),
+            "incomplete specifier `%` at byte 3 (use `%%` for a literal percent sign)"
+        );
+
+        let layout = PatternLayout::validate("[%l] %m%n").unwrap();
+        let record = Record::new(Level::Info, "app", "a.rs", 1, "ok".to_string());
+        let out = PatternFormatter::from_layout(layout)
+            .format(&record)
+            .unwrap();
+        assert_eq!(out, b"[INFO] ok\n");
+    }
+}
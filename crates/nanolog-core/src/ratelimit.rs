@@ -0,0 +1,149 @@
+/*!
+日志限流。
+
+[`log_every_n!`](crate::log_every_n) 与 [`log_at_most_every!`](crate::log_at_most_every)
+在宏展开处生成静态的 [`EveryN`] / [`AtMostEvery`]，按调用点限流，热循环中的日志不会刷屏：
+
+```
+use nanolog_rs::{Level, log_at_most_every, log_every_n};
+use std::time::Duration;
+
+for i in 0..10_000 {
+    // 第 1、1001、2001……次执行时记录
+    log_every_n!(1000, Level::Info, "processed {} items", i);
+    // 每秒至多一条
+    log_at_most_every!(Duration::from_secs(1), Level::Warn, "queue backlog at item {}", i);
+}
+```
+
+日志器级别的令牌桶（[`AsyncLoggerBuilder::rate_limit`](crate::AsyncLoggerBuilder::rate_limit)）
+作为兜底，限制全部调用点的总速率。计数与时间检查均为无锁原子操作。
+*/
+
+use crate::record::monotonic_now;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// 每 N 次执行放行一次（首次放行）
+pub struct EveryN {
+    count: AtomicU64,
+}
+
+impl EveryN {
+    /// 创建计数器（用于宏展开中的静态变量）
+    pub const fn new() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+        }
+    }
+
+    /// 计数一次，第 1、n+1、2n+1……次返回 `true`（`n` 为 0 时视为 1）
+    #[inline]
+    pub fn tick(&self, n: u64) -> bool {
+        self.count.fetch_add(1, Ordering::Relaxed) % n.max(1) == 0
+    }
+}
+
+impl Default for EveryN {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 间隔内至多放行一次（首次放行）
+pub struct AtMostEvery {
+    /// 上次放行的单调时间加一（0 表示从未放行）
+    last: AtomicU64,
+}
+
+impl AtMostEvery {
+    /// 创建限流器（用于宏展开中的静态变量）
+    pub const fn new() -> Self {
+        Self {
+            last: AtomicU64::new(0),
+        }
+    }
+
+    /// 距上次放行已超过 `interval` 时放行；并发调用中只有一个放行
+    #[inline]
+    pub fn allow(&self, interval: Duration) -> bool {
+        let now = monotonic_now().saturating_add(1);
+        let last = self.last.load(Ordering::Relaxed);
+        if last != 0 && now.saturating_sub(last) < interval.as_nanos() as u64 {
+            return false;
+        }
+        self.last
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    }
+}
+
+impl Default for AtMostEvery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 令牌桶（GCRA 实现）：平均每秒 `rate` 个，突发至多 `burst` 个
+pub(crate) struct TokenBucket {
+    /// 每个令牌的间隔（纳秒）
+    interval: u64,
+    /// 允许提前的时长（纳秒），即突发容量减一个间隔
+    tolerance: u64,
+    /// 理论到达时间（单调纳秒）
+    tat: AtomicU64,
+}
+
+impl TokenBucket {
+    /// 创建令牌桶，`rate` 与 `burst` 至少为 1
+    pub(crate) fn new(rate: u32, burst: u32) -> Self {
+        let interval = 1_000_000_000 / u64::from(rate.max(1));
+        Self {
+            interval,
+            tolerance: interval * u64::from(burst.max(1) - 1),
+            tat: AtomicU64::new(0),
+        }
+    }
+
+    /// 取一个令牌，桶空时返回 `false`
+    pub(crate) fn try_acquire(&self) -> bool {
+        let now = monotonic_now();
+        self.tat
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |tat| {
+                (tat <= now + self.tolerance).then(|| tat.max(now) + self.interval)
+            })
+            .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_n() {
+        let every = EveryN::new();
+        let passed: Vec<bool> = (0..7).map(|_| every.tick(3)).collect();
+        assert_eq!(passed, [true, false, false, true, false, false, true]);
+        assert!(EveryN::new().tick(0));
+    }
+
+    #[test]
+    fn test_at_most_every() {
+        let limiter = AtMostEvery::new();
+        assert!(limiter.allow(Duration::from_millis(20)));
+        assert!(!limiter.allow(Duration::from_millis(20)));
+        std::thread::sleep(Duration::from_millis(25));
+        assert!(limiter.allow(Duration::from_millis(20)));
+        assert!(!limiter.allow(Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn test_token_bucket_burst_then_rate() {
+        let bucket = TokenBucket::new(50, 5);
+        let burst = (0..20).filter(|_| bucket.try_acquire()).count();
+        assert_eq!(burst, 5);
+        std::thread::sleep(Duration::from_millis(45));
+        assert!(bucket.try_acquire());
+    }
+}
@@ -0,0 +1,736 @@
+/*!
+高性能日志记录结构。
+
+简化设计，专注于核心功能，确保零拷贝和低内存开销。
+*/
+
+use crate::Level;
+use crate::callsite::Callsite;
+use crate::field::{ERROR_CAUSE_KEYS, ERROR_MESSAGE_KEY, Field, Value};
+use crate::trace::TraceContext;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fmt;
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 单调时钟基准（进程内首次读取单调时间时确定）
+static MONOTONIC_ANCHOR: OnceLock<Instant> = OnceLock::new();
+
+/// 当前单调时间（相对进程内基准的纳秒数），不受系统时钟调整影响
+#[inline]
+pub fn monotonic_now() -> u64 {
+    MONOTONIC_ANCHOR
+        .get_or_init(Instant::now)
+        .elapsed()
+        .as_nanos() as u64
+}
+
+/// 相对时间戳的基准（UNIX 纳秒，0 表示尚未确定）
+static TIMESTAMP_BASE: AtomicU64 = AtomicU64::new(0);
+
+/// 设置相对时间戳（[`Record::relative_timestamp`]）的基准，进程内全局生效
+///
+/// 各进程使用相同的基准时，相对时间戳可直接对齐，便于与链路追踪工具关联。
+pub fn set_timestamp_base(base: SystemTime) {
+    let nanos = base
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    TIMESTAMP_BASE.store(clamp_nanos(nanos), Ordering::Relaxed);
+}
+
+/// 相对时间戳的基准；未设置时取首次读取的时刻（通常为首个日志器构建时，即服务启动时）
+pub fn timestamp_base() -> SystemTime {
+    UNIX_EPOCH + Duration::from_nanos(timestamp_base_nanos())
+}
+
+fn timestamp_base_nanos() -> u64 {
+    let base = TIMESTAMP_BASE.load(Ordering::Relaxed);
+    if base != 0 {
+        return base;
+    }
+    let now = clamp_nanos(Record::current_timestamp());
+    match TIMESTAMP_BASE.compare_exchange(0, now, Ordering::Relaxed, Ordering::Relaxed) {
+        Ok(_) => now,
+        Err(base) => base,
+    }
+}
+
+/// 纳秒数收窄到 `u64`，0 保留为“未设置”
+fn clamp_nanos(nanos: u128) -> u64 {
+    u64::try_from(nanos).unwrap_or(u64::MAX).max(1)
+}
+
+/// 每个线程缓存的消息缓冲区数
+const MESSAGE_POOL_SLOTS: usize = 8;
+
+/// 缓存的单个消息缓冲区容量上限，超出的缓冲区直接释放
+const MAX_POOLED_MESSAGE: usize = 4096;
+
+/// 线程本地的消息缓冲区池（定长数组，存取不分配；容量为零的槽位视为空）
+struct MessagePool {
+    slots: [String; MESSAGE_POOL_SLOTS],
+}
+
+thread_local! {
+    static MESSAGE_POOL: RefCell<MessagePool> = const {
+        RefCell::new(MessagePool {
+            slots: [EMPTY_MESSAGE; MESSAGE_POOL_SLOTS],
+        })
+    };
+}
+
+const EMPTY_MESSAGE: String = String::new();
+
+/// 下一个分配的线程编号
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// 本线程的线程信息（首次读取时分配编号并复制名称）
+    static CURRENT_THREAD: ThreadInfo = ThreadInfo {
+        id: NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed),
+        name: std::thread::current().name().map(Arc::from),
+    };
+}
+
+/// 记录所属线程：进程内唯一的编号与线程名称
+///
+/// 编号按线程首次读取的顺序从 1 开始分配，不同于操作系统的线程 ID；
+/// 名称与编号在每个线程上只读取一次，之后复制记录只增加名称的引用计数。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreadInfo {
+    id: u64,
+    name: Option<Arc<str>>,
+}
+
+impl ThreadInfo {
+    /// 当前线程的信息
+    #[inline]
+    pub fn current() -> Self {
+        CURRENT_THREAD
+            .try_with(Clone::clone)
+            .unwrap_or_else(|_| ThreadInfo {
+                id: 0,
+                name: std::thread::current().name().map(Arc::from),
+            })
+    }
+
+    /// 线程编号（线程本地存储已销毁时为 0）
+    #[inline]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 线程名称（未命名的线程为 `None`）
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl fmt::Display for ThreadInfo {
+    /// 输出线程名称，未命名时输出 `thread-<编号>`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => f.write_str(name),
+            None => write!(f, "thread-{}", self.id),
+        }
+    }
+}
+
+/// 归还消息缓冲区（发布时取回槽位中已写出记录的消息），供 [`Record::write_with`] 复用
+#[inline]
+pub(crate) fn recycle_message(message: String) {
+    let _ = MESSAGE_POOL.try_with(|pool| {
+        if message.capacity() == 0 || message.capacity() > MAX_POOLED_MESSAGE {
+            return;
+        }
+        if let Ok(mut pool) = pool.try_borrow_mut()
+            && let Some(slot) = pool.slots.iter_mut().find(|s| s.capacity() == 0)
+        {
+            *slot = message;
+        }
+    });
+}
+
+/// 取出一个已清空的消息缓冲区，池为空时新建
+#[inline]
+pub(crate) fn pooled_message() -> String {
+    let mut message = MESSAGE_POOL
+        .try_with(|pool| {
+            pool.try_borrow_mut().ok().and_then(|mut pool| {
+                pool.slots
+                    .iter_mut()
+                    .find(|s| s.capacity() > 0)
+                    .map(std::mem::take)
+            })
+        })
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    message.clear();
+    message
+}
+
+/// 日志记录结构体
+///
+/// 包含日志的所有元数据和内容信息，使用零拷贝技术优化性能。
+///
+/// 每条记录同时携带墙上时间戳（[`timestamp`](Self::timestamp)，供格式化输出）与单调时间
+/// （[`monotonic`](Self::monotonic)，供计算排队延迟、区间耗时等，不受时钟回拨或 NTP 调整影响）。
+#[derive(Clone, Debug)]
+pub struct Record {
+    /// 日志级别
+    level: Level,
+    /// 时间戳（纳秒精度）
+    timestamp: u128,
+    /// 单调时间（相对进程内基准的纳秒数）
+    monotonic: u64,
+    /// 目标/模块名称（使用 &'static str 避免分配）
+    target: &'static str,
+    /// 文件路径（使用 &'static str 避免分配）
+    file: &'static str,
+    /// 行号
+    line: u32,
+    /// 消息内容（字面量消息直接借用，不分配）
+    message: Cow<'static, str>,
+    /// 调用点 ID（由日志宏注册）
+    callsite_id: Option<NonZeroU32>,
+    /// 延迟格式化的消息（在消费者线程上渲染）
+    lazy: Option<LazyMessage>,
+    /// 结构化字段
+    fields: Vec<Field>,
+    /// 是否同步写出（绕过批处理，写入并刷新后调用才返回）
+    immediate: bool,
+    /// 创建记录的线程（可选捕获）
+    thread: Option<ThreadInfo>,
+    /// 分布式追踪上下文
+    trace: Option<TraceContext>,
+}
+
+/// 延迟格式化的消息：保存格式串与捕获的参数，在消费者线程上渲染
+#[derive(Clone)]
+struct LazyMessage(Arc<dyn Fn(&mut fmt::Formatter<'_>) -> fmt::Result + Send + Sync>);
+
+impl fmt::Display for LazyMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.0)(f)
+    }
+}
+
+impl fmt::Debug for LazyMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LazyMessage")
+    }
+}
+
+impl Record {
+    /// 创建新的日志记录（高性能版本）
+    ///
+    /// 消息可为 `String` 或 `&'static str`，后者不分配。
+    #[inline]
+    pub fn new(
+        level: Level,
+        target: &'static str,
+        file: &'static str,
+        line: u32,
+        message: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            level,
+            timestamp: Self::current_timestamp(),
+            monotonic: monotonic_now(),
+            target,
+            file,
+            line,
+            message: message.into(),
+            callsite_id: None,
+            lazy: None,
+            fields: Vec::new(),
+            immediate: false,
+            thread: None,
+            trace: None,
+        }
+    }
+
+    /// 创建由 `write` 直接写入消息的日志记录
+    ///
+    /// 消息缓冲区取自本线程的缓冲区池（由此前发布的记录归还），稳定状态下不产生中间 `String`
+    /// 也不分配，适合热循环与 `Display` 实现代价较高的类型。`write` 返回错误时保留已写入的部分。
+    #[inline]
+    pub fn write_with<F>(
+        level: Level,
+        target: &'static str,
+        file: &'static str,
+        line: u32,
+        write: F,
+    ) -> Self
+    where
+        F: FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+    {
+        let mut message = pooled_message();
+        let _ = write(&mut message);
+        Self::new(level, target, file, line, message)
+    }
+
+    /// 创建延迟格式化的日志记录
+    ///
+    /// 调用线程只保存格式化闭包，消息在消费者线程上通过 [`resolve`](Self::resolve) 渲染。
+    /// 渲染前 [`message`](Self::message) 返回空字符串。
+    #[inline]
+    pub fn lazy<F>(
+        level: Level,
+        target: &'static str,
+        file: &'static str,
+        line: u32,
+        format: F,
+    ) -> Self
+    where
+        F: Fn(&mut fmt::Formatter<'_>) -> fmt::Result + Send + Sync + 'static,
+    {
+        let mut record = Self::new(level, target, file, line, "");
+        record.lazy = Some(LazyMessage(Arc::new(format)));
+        record
+    }
+
+    /// 附加结构化字段
+    #[inline]
+    pub fn with_field(mut self, key: &'static str, value: impl Into<Value>) -> Self {
+        self.fields.push(Field::new(key, value));
+        self
+    }
+
+    /// 附加错误及其 `source()` 原因链：错误消息记为 `error.message`，
+    /// 第 n 层原因记为 `error.cause[n]`（至多 8 层）
+    pub fn with_error<E>(mut self, error: &E) -> Self
+    where
+        E: std::error::Error + ?Sized,
+    {
+        self.fields
+            .push(Field::new(ERROR_MESSAGE_KEY, error.to_string()));
+        let causes = std::iter::successors(error.source(), |cause| cause.source());
+        for (key, cause) in ERROR_CAUSE_KEYS.into_iter().zip(causes) {
+            self.fields.push(Field::new(key, cause.to_string()));
+        }
+        self
+    }
+
+    /// 获取结构化字段
+    #[inline]
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// 获取可修改的结构化字段
+    #[inline]
+    pub(crate) fn fields_mut(&mut self) -> &mut Vec<Field> {
+        &mut self.fields
+    }
+
+    /// 获取可修改的消息缓冲区
+    #[inline]
+    pub(crate) fn message_mut(&mut self) -> &mut String {
+        self.message.to_mut()
+    }
+
+    /// 标记为同步写出：[`AsyncLogger::log`](crate::AsyncLogger::log) 在记录写入并刷新后才返回，
+    /// 用于异步管道中的审计等关键事件
+    #[inline]
+    pub fn immediate(mut self, immediate: bool) -> Self {
+        self.immediate = immediate;
+        self
+    }
+
+    /// 是否同步写出
+    #[inline]
+    pub fn is_immediate(&self) -> bool {
+        self.immediate
+    }
+
+    /// 是否为尚未渲染的延迟格式化记录
+    #[inline]
+    pub fn is_lazy(&self) -> bool {
+        self.lazy.is_some()
+    }
+
+    /// 渲染延迟格式化的消息，返回携带完整消息的记录
+    pub fn resolve(&self) -> Self {
+        match &self.lazy {
+            Some(lazy) => self.with_message(lazy.to_string()),
+            None => self.clone(),
+        }
+    }
+
+    /// 关联调用点，记录其 ID 供格式化器输出
+    #[inline]
+    pub fn with_callsite(mut self, callsite: &'static Callsite) -> Self {
+        self.callsite_id = NonZeroU32::new(callsite.id());
+        self
+    }
+
+    /// 记下当前线程的编号与名称，供格式化器输出
+    ///
+    /// 读取线程本地存储有少量开销，因此默认不捕获；
+    /// 也可通过 [`AsyncLoggerBuilder::capture_thread`](crate::AsyncLoggerBuilder::capture_thread)
+    /// 为发布的每条记录捕获。
+    #[inline]
+    pub fn with_thread(mut self) -> Self {
+        self.thread = Some(ThreadInfo::current());
+        self
+    }
+
+    /// 为尚未捕获线程的记录记下当前线程
+    #[inline]
+    pub(crate) fn capture_thread(&mut self) {
+        if self.thread.is_none() {
+            self.thread = Some(ThreadInfo::current());
+        }
+    }
+
+    /// 关联分布式追踪上下文（追踪 ID 与 span ID）
+    #[inline]
+    pub fn with_trace_context(mut self, context: TraceContext) -> Self {
+        self.trace = Some(context);
+        self
+    }
+
+    /// 为尚未关联追踪上下文的记录设置上下文
+    #[inline]
+    pub(crate) fn set_trace_context(&mut self, context: Option<TraceContext>) {
+        if self.trace.is_none() {
+            self.trace = context;
+        }
+    }
+
+    /// 获取当前时间戳（纳秒精度）
+    #[inline]
+    fn current_timestamp() -> u128 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default() // 如果系统时间在UNIX EPOCH之前，则使用默认值
+            .as_nanos()
+    }
+
+    /// 获取日志级别
+    #[inline]
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// 获取时间戳（纳秒）
+    #[inline]
+    pub fn timestamp(&self) -> u128 {
+        self.timestamp
+    }
+
+    /// 相对 [`timestamp_base`] 的纳秒数（早于基准的记录为 0）
+    #[inline]
+    pub fn relative_timestamp(&self) -> u64 {
+        let relative = self
+            .timestamp
+            .saturating_sub(u128::from(timestamp_base_nanos()));
+        u64::try_from(relative).unwrap_or(u64::MAX)
+    }
+
+    /// 获取单调时间（相对进程内基准），用于延迟计算
+    #[inline]
+    pub fn monotonic(&self) -> Duration {
+        Duration::from_nanos(self.monotonic)
+    }
+
+    /// 记录创建至今经过的时间（按单调时钟计算，如消费者侧的排队延迟）
+    #[inline]
+    pub fn age(&self) -> Duration {
+        Duration::from_nanos(monotonic_now().saturating_sub(self.monotonic))
+    }
+
+    /// 两条记录创建时刻的间隔（按单调时钟计算，`earlier` 晚于本记录时为零）
+    #[inline]
+    pub fn elapsed_since(&self, earlier: &Record) -> Duration {
+        Duration::from_nanos(self.monotonic.saturating_sub(earlier.monotonic))
+    }
+
+    /// 获取目标/模块名称
+    #[inline]
+    pub fn target(&self) -> &'static str {
+        self.target
+    }
+
+    /// 获取文件路径
+    #[inline]
+    pub fn file(&self) -> &'static str {
+        self.file
+    }
+
+    /// 获取行号
+    #[inline]
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// 获取消息内容
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// 获取调用点 ID（仅由日志宏创建的记录携带）
+    #[inline]
+    pub fn callsite_id(&self) -> Option<u32> {
+        self.callsite_id.map(NonZeroU32::get)
+    }
+
+    /// 获取创建记录的线程（仅捕获了线程的记录携带）
+    #[inline]
+    pub fn thread(&self) -> Option<&ThreadInfo> {
+        self.thread.as_ref()
+    }
+
+    /// 获取分布式追踪上下文
+    #[inline]
+    pub fn trace_context(&self) -> Option<&TraceContext> {
+        self.trace.as_ref()
+    }
+
+    /// 获取追踪 ID
+    #[inline]
+    pub fn trace_id(&self) -> Option<[u8; 16]> {
+        self.trace.map(|t| t.trace_id())
+    }
+
+    /// 获取 span ID
+    #[inline]
+    pub fn span_id(&self) -> Option<[u8; 8]> {
+        self.trace.map(|t| t.span_id())
+    }
+
+    /// 复制元数据并替换消息内容
+    #[inline]
+    pub(crate) fn with_message(&self, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            level: self.level,
+            timestamp: self.timestamp,
+            monotonic: self.monotonic,
+            target: self.target,
+            file: self.file,
+            line: self.line,
+            message: message.into(),
+            callsite_id: self.callsite_id,
+            lazy: None,
+            fields: self.fields.clone(),
+            immediate: self.immediate,
+            thread: self.thread.clone(),
+            trace: self.trace,
+        }
+    }
+
+    /// 消费记录并返回消息内容（零拷贝优化）
+    #[inline]
+    pub fn into_message(self) -> Cow<'static, str> {
+        self.message
+    }
+}
+
+/// 日志宏的消息：不含参数的格式串直接借用字面量，否则格式化到本线程缓冲区池取出的缓冲区
+///
+/// 发布时队列槽位中已写出记录的消息缓冲区归还缓冲区池，稳定状态下格式化消息不再分配。
+#[doc(hidden)]
+#[inline]
+pub fn message_from_args(args: fmt::Arguments<'_>) -> Cow<'static, str> {
+    match args.as_str() {
+        Some(literal) => Cow::Borrowed(literal),
+        None => {
+            let mut message = pooled_message();
+            let _ = fmt::Write::write_fmt(&mut message, args);
+            Cow::Owned(message)
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 高性能格式化：避免不必要的字符串分配
+        write!(
+            f,
+            "[{}] [{:?}] [{}:{}] ",
+            self.timestamp, self.level, self.target, self.line
+        )?;
+        match &self.lazy {
+            Some(lazy) => lazy.fmt(f),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_creation() {
+        let record = Record::new(
+            Level::Info,
+            "test_module",
+            "test_file.rs",
+            42,
+            "Test message".to_string(),
+        );
+
+        assert_eq!(record.level(), Level::Info);
+        assert_eq!(record.target(), "test_module");
+        assert_eq!(record.file(), "test_file.rs");
+        assert_eq!(record.line(), 42);
+        assert_eq!(record.message(), "Test message");
+        assert!(record.timestamp() > 0);
+    }
+
+    #[test]
+    fn test_write_with_reuses_recycled_buffer() {
+        let record = Record::write_with(Level::Info, "app", file!(), 1, |buf| {
+            for id in [1, 2, 3] {
+                write!(buf, "{},", id)?;
+            }
+            Ok(())
+        });
+        assert_eq!(record.message(), "1,2,3,");
+
+        let message = record.into_message().into_owned();
+        let ptr = message.as_ptr();
+        recycle_message(message);
+        let record = Record::write_with(Level::Info, "app", file!(), 1, |buf| buf.write_str("x"));
+        assert_eq!(record.message(), "x");
+        assert_eq!(record.message().as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_literal_message_is_borrowed() {
+        let literal = message_from_args(format_args!("static text"));
+        assert!(matches!(literal, Cow::Borrowed("static text")));
+        let id = 7;
+        let formatted = message_from_args(format_args!("order {id}"));
+        assert!(matches!(&formatted, Cow::Owned(s) if s == "order 7"));
+
+        // 格式化的消息写入归还的缓冲区
+        let ptr = formatted.as_ptr();
+        recycle_message(formatted.into_owned());
+        let reused = message_from_args(format_args!("order {}", id + 1));
+        assert_eq!(reused, "order 8");
+        assert_eq!(reused.as_ptr(), ptr);
+
+        let record = Record::new(Level::Info, "t", "t.rs", 1, literal);
+        assert_eq!(record.message(), "static text");
+        assert!(matches!(record.into_message(), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_record_into_message() {
+        let record = Record::new(
+            Level::Error,
+            "test",
+            "test.rs",
+            10,
+            "Error message".to_string(),
+        );
+
+        let message = record.into_message();
+        assert_eq!(message, "Error message");
+    }
+
+    #[test]
+    fn test_lazy_record_resolves_message() {
+        let id = 7;
+        let record = Record::lazy(Level::Info, "t", "t.rs", 1, move |f| {
+            write!(f, "order {} filled", id)
+        });
+
+        assert!(record.is_lazy());
+        assert_eq!(record.message(), "");
+        assert!(record.to_string().ends_with("order 7 filled"));
+
+        let resolved = record.resolve();
+        assert!(!resolved.is_lazy());
+        assert_eq!(resolved.message(), "order 7 filled");
+        assert_eq!(resolved.timestamp(), record.timestamp());
+    }
+
+    #[test]
+    fn test_record_fields() {
+        let record = Record::new(Level::Info, "t", "t.rs", 1, "m".to_string())
+            .with_field("user_id", 42u64)
+            .with_field("peer", std::net::Ipv4Addr::LOCALHOST);
+
+        assert_eq!(record.fields().len(), 2);
+        assert_eq!(record.fields()[0].key(), "user_id");
+        assert_eq!(record.fields()[0].value(), &Value::U64(42));
+        assert_eq!(
+            record.with_message("n".to_string()).fields(),
+            record.fields()
+        );
+    }
+
+    #[test]
+    fn test_record_error_chain() {
+        #[derive(Debug)]
+        struct Chain(u32);
+
+        impl fmt::Display for Chain {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "level {}", self.0)
+            }
+        }
+
+        impl std::error::Error for Chain {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                static CAUSES: [Chain; 12] = [
+                    Chain(1),
+                    Chain(2),
+                    Chain(3),
+                    Chain(4),
+                    Chain(5),
+                    Chain(6),
+                    Chain(7),
+                    Chain(8),
+                    Chain(9),
+                    Chain(10),
+                    Chain(11),
+                    Chain(12),
+                ];
+                CAUSES.get(self.0 as usize).map(|c| c as _)
+            }
+        }
+
+        let record =
+            Record::new(Level::Error, "t", "t.rs", 1, "failed".to_string()).with_error(&Chain(0));
+        let fields = record.fields();
+        assert_eq!(fields[0].key(), "error.message");
+        assert_eq!(fields[0].value(), &Value::from("level 0"));
+        assert_eq!(fields[1].key(), "error.cause[0]");
+        assert_eq!(fields[1].value(), &Value::from("level 1"));
+        // 原因链至多记录 8 层
+        assert_eq!(fields.len(), 1 + ERROR_CAUSE_KEYS.len());
+        assert_eq!(fields[8].value(), &Value::from("level 8"));
+
+        let dynamic: &dyn std::error::Error = &Chain(11);
+        let record = Record::new(Level::Error, "t", "t.rs", 1, String::new()).with_error(dynamic);
+        assert_eq!(record.fields().len(), 2);
+    }
+
+    #[test]
+    fn test_monotonic_timestamps() {
+        let first = Record::new(Level::Info, "t", "t.rs", 1, String::new());
+        std::thread::sleep(Duration::from_millis(2));
+        let second = Record::new(Level::Info, "t", "t.rs", 2, String::new());
+
+        assert!(second.monotonic() > first.monotonic());
+        assert!(second.elapsed_since(&first) >= Duration::from_millis(2));
+        assert_eq!(first.elapsed_since(&second), Duration::ZERO);
+        assert!(first.age() >= second.elapsed_since(&first));
+        // 消息替换与延迟渲染保留两种时间
+        let copy = first.with_message("x".to_string());
+        assert_eq!(copy.monotonic(), first.monotonic());
+        assert_eq!(copy.timestamp(), first.timestamp());
+    }
+}
@@ -0,0 +1,380 @@
+/*!
+工作负载捕获与回放。
+
+[`WorkloadCapture`] 是一个格式化器，作为额外输出挂到生产环境的日志器上，将每条记录的
+（时间戳、级别、目标、消息长度）编码为紧凑的二进制轨迹，不保留消息内容：
+
+```no_run
+use nanolog_rs::AsyncLoggerBuilder;
+use nanolog_rs::replay::WorkloadCapture;
+use nanolog_rs::sink::FileSink;
+use std::sync::Arc;
+
+let logger = AsyncLoggerBuilder::new()
+    .output(Arc::new(WorkloadCapture::new()), Arc::new(FileSink::new("workload.trace")?))
+    .build()?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+[`Workload`] 解析轨迹后按原有顺序与节奏（或不限速）重新发布同样形态的记录，
+基准测试即可在不同配置下回放真实流量，而非合成的循环：
+
+```no_run
+use nanolog_rs::AsyncLoggerBuilder;
+use nanolog_rs::replay::{Pacing, Workload};
+
+let workload = Workload::load("workload.trace")?;
+let logger = AsyncLoggerBuilder::new().build()?;
+let elapsed = workload.replay(&logger, Pacing::Unpaced)?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+轨迹格式：以 [`MAGIC`] 开头，之后每条记录依次为时间戳差值（相对上一条，zigzag varint 纳秒）、
+级别（1 字节）、目标（varint：此前出现过的目标为序号加一，新目标为 0 并紧跟 varint 长度与
+UTF-8 字节）与消息长度（varint）。目标按出现顺序编号，因此一个捕获实例只应写入一个输出目标。
+*/
+
+use crate::error::Error;
+use crate::format::Formatter;
+use crate::logger::AsyncLogger;
+use crate::{Level, Record};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 轨迹文件头
+pub const MAGIC: &[u8; 6] = b"NLWT1\n";
+
+/// 回放消息的填充内容
+const FILLER: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ-_";
+
+/// 捕获状态：上一条记录的时间戳（`None` 表示尚未写出文件头）与目标编号
+#[derive(Default)]
+struct CaptureState {
+    last: Option<u64>,
+    targets: HashMap<&'static str, u64>,
+}
+
+/// 将记录编码为工作负载轨迹的格式化器
+#[derive(Default)]
+pub struct WorkloadCapture {
+    state: Mutex<CaptureState>,
+}
+
+impl WorkloadCapture {
+    /// 创建捕获格式化器
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Formatter for WorkloadCapture {
+    fn format(&self, record: &Record) -> Result<Vec<u8>, fmt::Error> {
+        let mut out = Vec::with_capacity(16);
+        self.format_into(record, &mut out)?;
+        Ok(out)
+    }
+
+    fn format_into(&self, record: &Record, out: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        let timestamp = u64::try_from(record.timestamp()).unwrap_or(u64::MAX);
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let last = match state.last.replace(timestamp) {
+            Some(last) => last,
+            None => {
+                out.extend_from_slice(MAGIC);
+                timestamp
+            }
+        };
+        // 多生产者下记录的时间戳可能早于上一条，差值带符号
+        let delta = timestamp as i128 - last as i128;
+        varint(
+            out,
+            zigzag(delta.clamp(i64::MIN as i128, i64::MAX as i128) as i64),
+        );
+        out.push(record.level() as u8);
+
+        let target = record.target();
+        let next = state.targets.len() as u64 + 1;
+        match state.targets.get(target) {
+            Some(&index) => varint(out, index),
+            None => {
+                state.targets.insert(target, next);
+                varint(out, 0);
+                varint(out, target.len() as u64);
+                out.extend_from_slice(target.as_bytes());
+            }
+        }
+        varint(out, record.message().len() as u64);
+        Ok(())
+    }
+}
+
+/// 轨迹中的一条记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkloadEvent {
+    /// 相对首条记录的时刻（单调不减）
+    pub offset: Duration,
+    /// 日志级别
+    pub level: Level,
+    /// 目标
+    pub target: &'static str,
+    /// 消息长度（字节）
+    pub message_len: usize,
+}
+
+/// 回放节奏
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pacing {
+    /// 不等待，尽快发布全部记录
+    Unpaced,
+    /// 按记录的原有间隔发布
+    Recorded,
+    /// 按原有间隔的倍速发布（如 `2.0` 为两倍速）
+    Scaled(f64),
+}
+
+/// 解析后的工作负载轨迹
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Workload {
+    events: Vec<WorkloadEvent>,
+}
+
+impl Workload {
+    /// 解析轨迹
+    ///
+    /// 轨迹中的目标在进程内常驻（回放的记录要求 `&'static str` 目标），每个不同的目标只保留一份。
+    pub fn parse(data: &[u8]) -> io::Result<Self> {
+        let mut data = data
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or_else(|| invalid("missing workload trace header"))?;
+        let mut events = Vec::new();
+        let mut targets: Vec<&'static str> = Vec::new();
+        let mut offset = 0i128;
+        let mut max_offset = 0i128;
+        while !data.is_empty() {
+            offset += i128::from(unzigzag(read_varint(&mut data)?));
+            max_offset = max_offset.max(offset);
+            let (&level, rest) = data
+                .split_first()
+                .ok_or_else(|| invalid("truncated record"))?;
+            data = rest;
+            if level > Level::Error as u8 {
+                return Err(invalid("invalid level"));
+            }
+            let target = match read_varint(&mut data)? {
+                0 => {
+                    let len = read_varint(&mut data)? as usize;
+                    if data.len() < len {
+                        return Err(invalid("truncated target"));
+                    }
+                    let (bytes, rest) = data.split_at(len);
+                    data = rest;
+                    let target =
+                        std::str::from_utf8(bytes).map_err(|_| invalid("target is not UTF-8"))?;
+                    let target: &'static str = Box::leak(target.into());
+                    targets.push(target);
+                    target
+                }
+                index => *targets
+                    .get(index as usize - 1)
+                    .ok_or_else(|| invalid("unknown target index"))?,
+            };
+            let message_len = read_varint(&mut data)? as usize;
+            events.push(WorkloadEvent {
+                offset: Duration::from_nanos(max_offset.min(u64::MAX as i128) as u64),
+                level: Level::from_u8(level),
+                target,
+                message_len,
+            });
+        }
+        Ok(Self { events })
+    }
+
+    /// 读取并解析轨迹文件
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&std::fs::read(path)?)
+    }
+
+    /// 轨迹中的记录
+    pub fn events(&self) -> &[WorkloadEvent] {
+        &self.events
+    }
+
+    /// 首条到末条记录的时长
+    pub fn duration(&self) -> Duration {
+        self.events.last().map_or(Duration::ZERO, |e| e.offset)
+    }
+
+    /// 按 `pacing` 将轨迹中的记录依次发布到 `logger`，返回发布耗时
+    ///
+    /// 消息以固定内容填充到原有长度，同一轨迹每次回放发布完全相同的记录序列。
+    pub fn replay(&self, logger: &AsyncLogger, pacing: Pacing) -> Result<Duration, Error> {
+        let speed = match pacing {
+            Pacing::Unpaced => None,
+            Pacing::Recorded => Some(1.0),
+            Pacing::Scaled(speed) if speed > 0.0 && speed.is_finite() => Some(speed),
+            Pacing::Scaled(_) => return Err(Error::Config("replay speed must be positive")),
+        };
+        let start = Instant::now();
+        for event in &self.events {
+            if let Some(speed) = speed {
+                wait_until(start + event.offset.div_f64(speed));
+            }
+            logger.log(Record::write_with(
+                event.level,
+                event.target,
+                file!(),
+                line!(),
+                |message| fill(message, event.message_len),
+            ))?;
+        }
+        Ok(start.elapsed())
+    }
+}
+
+/// 等待到 `deadline`：距离较远时睡眠，最后一毫秒自旋以保持间隔精度
+fn wait_until(deadline: Instant) {
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return;
+        }
+        let remaining = deadline - now;
+        if remaining > Duration::from_millis(1) {
+            std::thread::sleep(remaining - Duration::from_millis(1));
+        } else {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+/// 以填充内容写满 `len` 字节
+fn fill(message: &mut dyn fmt::Write, mut len: usize) -> fmt::Result {
+    while len > 0 {
+        let chunk = len.min(FILLER.len());
+        message.write_str(&FILLER[..chunk])?;
+        len -= chunk;
+    }
+    Ok(())
+}
+
+fn invalid(reason: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(data: &mut &[u8]) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = data
+            .split_first()
+            .ok_or_else(|| invalid("truncated varint"))?;
+        *data = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid("varint too long"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::SimpleFormatter;
+    use crate::sink::MemorySink;
+    use std::sync::Arc;
+
+    fn capture(records: &[Record]) -> Vec<u8> {
+        let capture = WorkloadCapture::new();
+        let mut out = Vec::new();
+        for record in records {
+            capture.format_into(record, &mut out).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn test_capture_round_trip() {
+        let records = [
+            Record::new(Level::Info, "orders", "a.rs", 1, "accepted"),
+            Record::new(Level::Warn, "risk", "b.rs", 2, "x".repeat(200)),
+            Record::new(Level::Error, "orders", "a.rs", 3, String::new()),
+        ];
+        let trace = capture(&records);
+        assert!(trace.starts_with(MAGIC));
+        // 重复的目标只写序号
+        assert_eq!(trace.windows(6).filter(|w| w == b"orders").count(), 1);
+
+        let workload = Workload::parse(&trace).unwrap();
+        let events = workload.events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].offset, Duration::ZERO);
+        assert_eq!(
+            (events[1].level, events[1].target, events[1].message_len),
+            (Level::Warn, "risk", 200)
+        );
+        assert_eq!(events[2].target, "orders");
+        let span = records[2].timestamp() - records[0].timestamp();
+        assert_eq!(workload.duration().as_nanos(), span);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_traces() {
+        assert!(Workload::parse(b"garbage").is_err());
+        assert_eq!(Workload::parse(MAGIC).unwrap().events().len(), 0);
+        let trace = capture(&[Record::new(Level::Info, "t", "a.rs", 1, "m")]);
+        assert!(Workload::parse(&trace[..trace.len() - 1]).is_err());
+        // 引用未定义的目标序号
+        let mut bad = MAGIC.to_vec();
+        bad.extend_from_slice(&[0, 2, 5, 1]);
+        assert!(Workload::parse(&bad).is_err());
+    }
+
+    #[test]
+    fn test_replay_is_deterministic() {
+        let trace = capture(&[
+            Record::new(Level::Info, "orders", "a.rs", 1, "accepted"),
+            Record::new(Level::Debug, "orders", "a.rs", 2, "hidden"),
+            Record::new(Level::Warn, "risk", "b.rs", 2, "limit"),
+        ]);
+        let workload = Workload::parse(&trace).unwrap();
+
+        let run = |pacing| {
+            let sink = Arc::new(MemorySink::new());
+            let logger = crate::AsyncLoggerBuilder::new()
+                .formatter(Arc::new(SimpleFormatter::new()))
+                .sink(sink.clone())
+                .build()
+                .unwrap();
+            workload.replay(&logger, pacing).unwrap();
+            logger.shutdown().unwrap();
+            sink.get_content()
+        };
+        let unpaced = run(Pacing::Unpaced);
+        assert_eq!(unpaced, b"[INFO] 01234567\n[WARN] 01234\n");
+        assert_eq!(run(Pacing::Scaled(4.0)), unpaced);
+
+        let logger = crate::AsyncLoggerBuilder::new().build().unwrap();
+        assert!(workload.replay(&logger, Pacing::Scaled(0.0)).is_err());
+        logger.shutdown().unwrap();
+    }
+}
@@ -0,0 +1,686 @@
+/*!
+文件轮转策略。
+
+[`RotationPolicy`] 组合大小、时间间隔与日历周期触发条件（任一满足即轮转），并可通过
+strftime 风格的文件名模板命名轮转文件：
+
+| 占位符 | 含义 |
+|--------|------|
+| `%Y` | 四位年份 |
+| `%m` `%d` | 月、日（两位） |
+| `%H` `%M` `%S` | 时、分、秒（两位） |
+| `%N` | 同一时间段内的序号（从 1 开始，取首个未占用的值） |
+| `%%` | 字面量 `%` |
+
+时间取被轮转文件开始写入的时刻，因此 `app-%Y-%m-%d-%H.%N.log` 中的小时即文件覆盖的时段。
+日历周期（[`Period`]）在整点或午夜轮转，与进程启动时刻无关，每个自然日（或小时）一个文件。
+周期边界与模板时间按 [`RotationClock`] 计算：默认本地时间，也可指定 UTC 或固定时差
+（如按总部所在时区的业务日留存）。
+模板不含 `%N` 且文件名已被占用时，在末尾追加 `.1`、`.2` 等后缀。
+未设置模板时轮转文件命名为 `<文件名>.<后缀>`，后缀由 [`RotationPolicy::suffix`] 指定的
+[`IdGenerator`] 生成（默认 Unix 秒）。
+`max_files` 与 `max_age` 只作用于匹配模板的文件，目录中的其他文件不受影响。
+`max_age` 在每次轮转时检查，设置 [`RotationPolicy::sweep_interval`] 后维护线程还会定期清理，
+长时间不轮转（如低流量服务）时过期文件也能按时删除。
+
+启用 `gzip` 或 `zstd` 特性后，可通过 [`RotationPolicy::compress`] 在后台线程压缩轮转文件
+（追加 `.gz` / `.zst` 扩展名），写入路径的延迟不受影响；压缩后的文件同样计入 `max_files`。
+
+```
+use nanolog_rs::FileSink;
+use nanolog_rs::rotation::{FileNameTemplate, Period, RotationPolicy};
+
+let policy = RotationPolicy::new()
+    .max_size(64 << 20)
+    .period(Period::Hourly)
+    .template(FileNameTemplate::parse("app-%Y-%m-%d-%H.%N.log")?)
+    .max_files(48);
+# let dir = std::env::temp_dir().join("nanolog-rotation-doc");
+let sink = FileSink::new(dir.join("app.log"))?.with_rotation(policy);
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+
+use crate::error::Error;
+use crate::naming::{IdGenerator, UnixTime};
+use chrono::{Datelike, FixedOffset, Local, NaiveDateTime, Offset, TimeZone, Timelike, Utc};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 模板片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Sequence,
+}
+
+impl Part {
+    /// 时间字段的固定宽度（序号与字面量返回 `None`）
+    fn width(&self) -> Option<usize> {
+        match self {
+            Part::Year => Some(4),
+            Part::Month | Part::Day | Part::Hour | Part::Minute | Part::Second => Some(2),
+            Part::Literal(_) | Part::Sequence => None,
+        }
+    }
+}
+
+/// 轮转文件名模板
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileNameTemplate {
+    parts: Vec<Part>,
+}
+
+impl FileNameTemplate {
+    /// 解析模板；模板只能是文件名（轮转文件与当前文件位于同一目录）
+    pub fn parse(template: &str) -> Result<Self, Error> {
+        if template.is_empty() {
+            return Err(Error::Rotation("empty file name template"));
+        }
+        if template.contains(['/', '\\']) {
+            return Err(Error::Rotation(
+                "file name template must not contain path separators",
+            ));
+        }
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+            let part = match chars.next() {
+                Some('%') => {
+                    literal.push('%');
+                    continue;
+                }
+                Some('Y') => Part::Year,
+                Some('m') => Part::Month,
+                Some('d') => Part::Day,
+                Some('H') => Part::Hour,
+                Some('M') => Part::Minute,
+                Some('S') => Part::Second,
+                Some('N') => Part::Sequence,
+                _ => return Err(Error::Rotation("unknown specifier in file name template")),
+            };
+            if !literal.is_empty() {
+                parts.push(Part::Literal(std::mem::take(&mut literal)));
+            }
+            parts.push(part);
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    /// 是否包含序号占位符
+    fn has_sequence(&self) -> bool {
+        self.parts.contains(&Part::Sequence)
+    }
+
+    /// 按时间与序号生成文件名
+    pub fn render(&self, dt: &NaiveDateTime, sequence: u32) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            let value = match part {
+                Part::Literal(text) => {
+                    out.push_str(text);
+                    continue;
+                }
+                Part::Sequence => {
+                    out.push_str(&sequence.to_string());
+                    continue;
+                }
+                Part::Year => dt.year().max(0) as u32,
+                Part::Month => dt.month(),
+                Part::Day => dt.day(),
+                Part::Hour => dt.hour(),
+                Part::Minute => dt.minute(),
+                Part::Second => dt.second(),
+            };
+            let width = part.width().unwrap_or(0);
+            out.push_str(&format!("{:0width$}", value, width = width));
+        }
+        out
+    }
+
+    /// 文件名是否由该模板生成（含冲突时追加的 `.N` 后缀与压缩扩展名）
+    pub fn matches(&self, name: &str) -> bool {
+        let name = strip_compressed_extension(name);
+        if match_parts(&self.parts, name) {
+            return true;
+        }
+        // 冲突后缀仅在模板不含序号时出现
+        !self.has_sequence()
+            && name
+                .rsplit_once('.')
+                .is_some_and(|(base, suffix)| is_digits(suffix) && match_parts(&self.parts, base))
+    }
+
+    /// 在 `dir` 中为开始于 `unix_secs` 的文件选择首个未占用的路径
+    pub(crate) fn next_path(
+        &self,
+        dir: &Path,
+        unix_secs: u64,
+        clock: RotationClock,
+    ) -> io::Result<PathBuf> {
+        let time = clock.naive(unix_secs);
+        if self.has_sequence() {
+            for sequence in 1..=u32::MAX {
+                let path = dir.join(self.render(&time, sequence));
+                if !is_taken(&path) {
+                    return Ok(path);
+                }
+            }
+            Err(no_free_name())
+        } else {
+            first_free(dir.join(self.render(&time, 0)))
+        }
+    }
+}
+
+/// 在 `path` 后追加生成器给出的后缀，选择首个未占用的路径
+///
+/// 后缀已被占用时以后续序号重试；生成器忽略序号（如 [`UnixTime`]）时改为追加 `.1`、`.2` 等后缀。
+pub(crate) fn suffixed_path(
+    path: &Path,
+    generator: &dyn IdGenerator,
+    sequence: u64,
+) -> io::Result<PathBuf> {
+    let mut sequence = sequence.max(1);
+    let mut id = generator.id(sequence);
+    loop {
+        let candidate = with_extension_suffix(path, &id);
+        if !is_taken(&candidate) {
+            return Ok(candidate);
+        }
+        sequence = sequence.checked_add(1).ok_or_else(no_free_name)?;
+        let next = generator.id(sequence);
+        if next == id {
+            return first_free(candidate);
+        }
+        id = next;
+    }
+}
+
+/// `base` 未被占用时直接使用，否则追加 `.1`、`.2` 等后缀
+fn first_free(base: PathBuf) -> io::Result<PathBuf> {
+    if !is_taken(&base) {
+        return Ok(base);
+    }
+    for suffix in 1..=u32::MAX {
+        let path = with_extension_suffix(&base, &suffix.to_string());
+        if !is_taken(&path) {
+            return Ok(path);
+        }
+    }
+    Err(no_free_name())
+}
+
+fn no_free_name() -> io::Error {
+    io::Error::new(io::ErrorKind::AlreadyExists, "no free rotated file name")
+}
+
+/// 逐片段匹配文件名
+fn match_parts(parts: &[Part], name: &str) -> bool {
+    let Some((part, rest)) = parts.split_first() else {
+        return name.is_empty();
+    };
+    match part {
+        Part::Literal(text) => name
+            .strip_prefix(text.as_str())
+            .is_some_and(|name| match_parts(rest, name)),
+        Part::Sequence => {
+            // 序号位数不定：尝试每个数字前缀
+            let digits = name.bytes().take_while(u8::is_ascii_digit).count();
+            (1..=digits).any(|n| match_parts(rest, &name[n..]))
+        }
+        _ => {
+            let width = part.width().unwrap_or(0);
+            name.get(..width).is_some_and(is_digits) && match_parts(rest, &name[width..])
+        }
+    }
+}
+
+/// 压缩后的轮转文件扩展名
+const COMPRESSED_EXTENSIONS: [&str; 2] = ["gz", "zst"];
+
+/// 去掉压缩扩展名
+fn strip_compressed_extension(name: &str) -> &str {
+    COMPRESSED_EXTENSIONS
+        .iter()
+        .find_map(|ext| {
+            name.strip_suffix(ext)
+                .and_then(|base| base.strip_suffix('.'))
+        })
+        .unwrap_or(name)
+}
+
+/// 文件名是否已被占用（含其压缩后的文件）
+fn is_taken(path: &Path) -> bool {
+    path.exists()
+        || COMPRESSED_EXTENSIONS
+            .iter()
+            .any(|ext| with_extension_suffix(path, ext).exists())
+}
+
+/// 在路径末尾追加扩展名（`app.log` → `app.log.gz`）
+fn with_extension_suffix(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(ext);
+    PathBuf::from(name)
+}
+
+/// 轮转文件的压缩方式（需启用对应特性）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// gzip（`.gz`，`gzip` 特性）
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// zstd（`.zst`，`zstd` 特性）
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    /// 压缩文件的扩展名
+    pub fn extension(self) -> &'static str {
+        match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => "gz",
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => "zst",
+        }
+    }
+
+    /// 将 `path` 压缩为 `path.<扩展名>` 并删除原文件，返回压缩文件路径
+    ///
+    /// 先写入 `.part` 临时文件，完成后重命名，进程中途退出时原文件保持完整。
+    pub(crate) fn compress_file(self, path: &Path) -> io::Result<PathBuf> {
+        let target = with_extension_suffix(path, self.extension());
+        let part = with_extension_suffix(&target, "part");
+        let result = self
+            .encode(path, &part)
+            .and_then(|()| std::fs::rename(&part, &target));
+        if let Err(err) = result {
+            let _ = std::fs::remove_file(&part);
+            return Err(err);
+        }
+        std::fs::remove_file(path)?;
+        Ok(target)
+    }
+
+    #[allow(unused_variables)]
+    fn encode(self, source: &Path, dest: &Path) -> io::Result<()> {
+        #[allow(unused_mut)]
+        let mut input = std::fs::File::open(source)?;
+        let output = std::fs::File::create(dest)?;
+        match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(output, flate2::Compression::default());
+                io::copy(&mut input, &mut encoder)?;
+                encoder.finish()?.sync_all()
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                let mut encoder = zstd::stream::Encoder::new(output, 0)?;
+                io::copy(&mut input, &mut encoder)?;
+                encoder.finish()?.sync_all()
+            }
+        }
+    }
+}
+
+/// 日历边界与文件名模板使用的时钟
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationClock {
+    /// 本地时间（默认，随夏令时调整）
+    #[default]
+    Local,
+    /// UTC
+    Utc,
+    /// 相对 UTC 的固定时差（秒，东为正；超出 ±24 小时按 UTC 处理）
+    Offset(i32),
+}
+
+impl RotationClock {
+    /// 固定时差对应的时区
+    fn fixed(offset: i32) -> FixedOffset {
+        FixedOffset::east_opt(offset).unwrap_or(Utc.fix())
+    }
+
+    /// Unix 秒对应的该时钟下的时间
+    pub(crate) fn naive(self, unix_secs: u64) -> NaiveDateTime {
+        let utc = crate::format::utc_datetime(unix_secs as u128 * 1_000_000_000);
+        match self {
+            RotationClock::Local => utc.with_timezone(&Local).naive_local(),
+            RotationClock::Utc => utc.naive_utc(),
+            RotationClock::Offset(offset) => utc.with_timezone(&Self::fixed(offset)).naive_local(),
+        }
+    }
+
+    /// `unix_secs` 所在周期结束时（下一个边界）的 Unix 秒
+    pub(crate) fn next_boundary(self, period: Period, unix_secs: u64) -> u64 {
+        match self {
+            RotationClock::Local => next_boundary_in(&Local, period, unix_secs),
+            RotationClock::Utc => next_boundary_in(&Utc, period, unix_secs),
+            RotationClock::Offset(offset) => {
+                next_boundary_in(&Self::fixed(offset), period, unix_secs)
+            }
+        }
+    }
+}
+
+/// 日历轮转周期（按 [`RotationClock`] 对齐）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// 每小时整点
+    Hourly,
+    /// 每天午夜
+    Daily,
+}
+
+/// 在时区 `tz` 中计算下一个周期边界
+fn next_boundary_in<Tz: TimeZone>(tz: &Tz, period: Period, unix_secs: u64) -> u64 {
+    let utc = crate::format::utc_datetime(unix_secs as u128 * 1_000_000_000);
+    let local = utc.with_timezone(tz).naive_local();
+    let start = match period {
+        Period::Hourly => local.date().and_hms_opt(local.hour(), 0, 0),
+        Period::Daily => local.date().and_hms_opt(0, 0, 0),
+    };
+    let step = match period {
+        Period::Hourly => chrono::Duration::hours(1),
+        Period::Daily => chrono::Duration::days(1),
+    };
+    let Some(mut next) = start.map(|start| start + step) else {
+        return u64::MAX;
+    };
+    // 夏令时跳过的本地时刻不存在，顺延到之后首个存在的整点
+    for _ in 0..24 {
+        if let Some(boundary) = tz.from_local_datetime(&next).earliest() {
+            return boundary.timestamp().max(0) as u64;
+        }
+        next += chrono::Duration::hours(1);
+    }
+    u64::MAX
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// 轮转策略：大小、时间间隔与日历周期任一满足即轮转
+#[derive(Debug, Clone, Default)]
+pub struct RotationPolicy {
+    pub(crate) max_size: Option<usize>,
+    pub(crate) interval: Option<Duration>,
+    pub(crate) period: Option<Period>,
+    pub(crate) template: Option<FileNameTemplate>,
+    pub(crate) max_files: Option<usize>,
+    pub(crate) compression: Option<Compression>,
+    pub(crate) clock: RotationClock,
+    pub(crate) max_age: Option<Duration>,
+    pub(crate) sweep_interval: Option<Duration>,
+    pub(crate) suffix: Option<Arc<dyn IdGenerator>>,
+}
+
+impl RotationPolicy {
+    /// 创建不轮转的策略
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 文件达到 `bytes` 字节时轮转
+    pub fn max_size(mut self, bytes: usize) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// 距上次轮转超过 `interval` 时轮转（按秒计）
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// 在日历边界（整点或午夜）轮转
+    pub fn period(mut self, period: Period) -> Self {
+        self.period = Some(period);
+        self
+    }
+
+    /// 日历边界与模板时间使用的时钟（默认本地时间）
+    pub fn clock(mut self, clock: RotationClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// `unix_secs` 之后的下一个日历边界（未设置周期时为 `u64::MAX`）
+    pub(crate) fn next_boundary(&self, unix_secs: u64) -> u64 {
+        self.period.map_or(u64::MAX, |period| {
+            self.clock.next_boundary(period, unix_secs)
+        })
+    }
+
+    /// 轮转文件的命名模板（默认 `<文件名>.<Unix 秒>`）
+    pub fn template(mut self, template: FileNameTemplate) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    /// 未设置模板时轮转文件名后缀的生成器（默认 [`UnixTime`]），序号为本输出目标的轮转次数加一
+    pub fn suffix(mut self, generator: Arc<dyn IdGenerator>) -> Self {
+        self.suffix = Some(generator);
+        self
+    }
+
+    /// 未设置模板时的后缀生成器
+    pub(crate) fn suffix_generator(&self) -> &dyn IdGenerator {
+        self.suffix.as_deref().unwrap_or(&UnixTime)
+    }
+
+    /// 保留的轮转文件数量（设置模板时只统计匹配模板的文件）
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    /// 删除最后修改时间早于 `max_age` 之前的轮转文件（与 `max_files` 同时生效）
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// 维护线程按 `interval` 定期执行保留清理（不依赖轮转发生）
+    pub fn sweep_interval(mut self, interval: Duration) -> Self {
+        self.sweep_interval = Some(interval);
+        self
+    }
+
+    /// 轮转后在后台线程压缩轮转文件
+    pub fn compress(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// 当前文件大小与开始时间下是否应当轮转
+    pub(crate) fn should_rotate(&self, size: usize, opened_secs: u64, now_secs: u64) -> bool {
+        self.max_size.is_some_and(|max| size >= max)
+            || self
+                .interval
+                .is_some_and(|interval| now_secs.saturating_sub(opened_secs) >= interval.as_secs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-03-05T07:08:09Z
+    const T: u64 = 1_709_622_489;
+
+    #[test]
+    fn test_render_template() {
+        let time = crate::format::utc_datetime(T as u128 * 1_000_000_000).naive_utc();
+        let template = FileNameTemplate::parse("app-%Y-%m-%d-%H.%N.log").unwrap();
+        assert_eq!(template.render(&time, 3), "app-2024-03-05-07.3.log");
+        let template = FileNameTemplate::parse("%H%M%S-100%%.log").unwrap();
+        assert_eq!(template.render(&time, 0), "070809-100%.log");
+    }
+
+    #[test]
+    fn test_calendar_boundaries() {
+        // UTC：下一个整点 08:00，下一个午夜 03-06 00:00
+        assert_eq!(next_boundary_in(&Utc, Period::Hourly, T), 1_709_625_600);
+        assert_eq!(next_boundary_in(&Utc, Period::Daily, T), 1_709_683_200);
+        // 边界本身属于新周期
+        assert_eq!(
+            next_boundary_in(&Utc, Period::Daily, 1_709_683_200),
+            1_709_683_200 + 86_400
+        );
+        // UTC+8：本地 15:08，午夜为 UTC 03-05 16:00
+        let east8 = FixedOffset::east_opt(8 * 3600).unwrap();
+        assert_eq!(next_boundary_in(&east8, Period::Daily, T), 1_709_654_400);
+        // UTC+5:30：本地整点不在 UTC 整点
+        let india = FixedOffset::east_opt(5 * 3600 + 1800).unwrap();
+        assert_eq!(next_boundary_in(&india, Period::Hourly, T), 1_709_623_800);
+    }
+
+    #[test]
+    fn test_rotation_clock_day_boundaries() {
+        // T 为 UTC 07:08，UTC+8 的 15:08，UTC-10 的前一天 21:08
+        let utc = RotationPolicy::new()
+            .period(Period::Daily)
+            .clock(RotationClock::Utc);
+        assert_eq!(utc.next_boundary(T), 1_709_683_200);
+        let east8 = utc.clone().clock(RotationClock::Offset(8 * 3600));
+        assert_eq!(east8.next_boundary(T), 1_709_654_400);
+        let west10 = utc.clone().clock(RotationClock::Offset(-10 * 3600));
+        assert_eq!(west10.next_boundary(T), 1_709_632_800);
+        // 超出范围的时差按 UTC 处理
+        let invalid = utc.clone().clock(RotationClock::Offset(86_400));
+        assert_eq!(invalid.next_boundary(T), 1_709_683_200);
+        assert_eq!(RotationPolicy::new().next_boundary(T), u64::MAX);
+
+        // 模板时间与边界使用同一时钟
+        let template = FileNameTemplate::parse("app-%Y-%m-%d.log").unwrap();
+        let name = |clock: RotationClock| template.render(&clock.naive(T), 0);
+        assert_eq!(name(RotationClock::Utc), "app-2024-03-05.log");
+        assert_eq!(
+            name(RotationClock::Offset(-10 * 3600)),
+            "app-2024-03-04.log"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_templates() {
+        assert!(FileNameTemplate::parse("").is_err());
+        assert!(FileNameTemplate::parse("app-%Q.log").is_err());
+        assert!(FileNameTemplate::parse("app-%").is_err());
+        assert!(FileNameTemplate::parse("logs/app-%Y.log").is_err());
+    }
+
+    #[test]
+    fn test_matches_generated_names_only() {
+        let template = FileNameTemplate::parse("app-%Y-%m-%d.%N.log").unwrap();
+        assert!(template.matches("app-2024-03-05.1.log"));
+        assert!(template.matches("app-2024-03-05.12.log"));
+        assert!(!template.matches("app-2024-03-05.log"));
+        assert!(!template.matches("app-2024-3-05.1.log"));
+        assert!(!template.matches("app.log"));
+
+        assert!(template.matches("app-2024-03-05.1.log.gz"));
+        assert!(template.matches("app-2024-03-05.1.log.zst"));
+        assert!(!template.matches("app-2024-03-05.1.log.gz.part"));
+
+        let template = FileNameTemplate::parse("app-%Y-%m-%d.log").unwrap();
+        assert!(template.matches("app-2024-03-05.log"));
+        assert!(template.matches("app-2024-03-05.log.2"));
+        assert!(!template.matches("app-2024-03-05.log.x"));
+    }
+
+    #[test]
+    fn test_next_path_skips_taken_names() {
+        let dir = std::env::temp_dir().join(format!("nanolog-rotation-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let template = FileNameTemplate::parse("a-%Y.%N.log").unwrap();
+        std::fs::write(dir.join("a-2024.1.log"), b"").unwrap();
+        assert_eq!(
+            template.next_path(&dir, T, RotationClock::Local).unwrap(),
+            dir.join("a-2024.2.log")
+        );
+
+        let template = FileNameTemplate::parse("b-%Y.log").unwrap();
+        assert_eq!(
+            template.next_path(&dir, T, RotationClock::Local).unwrap(),
+            dir.join("b-2024.log")
+        );
+        std::fs::write(dir.join("b-2024.log.gz"), b"").unwrap();
+        assert_eq!(
+            template.next_path(&dir, T, RotationClock::Local).unwrap(),
+            dir.join("b-2024.log.1")
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_combined_triggers() {
+        let policy = RotationPolicy::new()
+            .max_size(100)
+            .interval(Duration::from_secs(60));
+        assert!(!policy.should_rotate(10, T, T + 59));
+        assert!(policy.should_rotate(100, T, T + 1));
+        assert!(policy.should_rotate(10, T, T + 60));
+        assert!(!RotationPolicy::new().should_rotate(usize::MAX, 0, u64::MAX));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_compress_file() {
+        use std::io::Read;
+
+        let dir = std::env::temp_dir().join(format!("nanolog-gzip-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log.1");
+        std::fs::write(&path, b"line 1\nline 2\n").unwrap();
+
+        let target = Compression::Gzip.compress_file(&path).unwrap();
+        assert_eq!(target, dir.join("app.log.1.gz"));
+        assert!(!path.exists());
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(std::fs::File::open(&target).unwrap())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, "line 1\nline 2\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_compress_file() {
+        let dir = std::env::temp_dir().join(format!("nanolog-zstd-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log.1");
+        std::fs::write(&path, b"line 1\n").unwrap();
+
+        let target = Compression::Zstd.compress_file(&path).unwrap();
+        assert_eq!(target, dir.join("app.log.1.zst"));
+        let decoded = zstd::stream::decode_all(std::fs::File::open(&target).unwrap()).unwrap();
+        assert_eq!(decoded, b"line 1\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,2280 @@
+/*!
+高性能日志输出目标。
+
+简化设计，专注于零拷贝和低延迟输出。
+*/
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::Level;
+use crate::Record;
+use crate::diagnostics::{Diagnostic, DiagnosticHandler, StderrDiagnostics};
+use crate::format::Formatter;
+use crate::rotation::RotationPolicy;
+
+/// 已格式化记录的元数据，供按级别或目标分区、路由的输出目标使用
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordMeta {
+    /// 日志级别
+    pub level: Level,
+    /// 目标/模块名称
+    pub target: &'static str,
+    /// 时间戳（纳秒精度）
+    pub timestamp: u128,
+}
+
+impl RecordMeta {
+    /// 提取记录的元数据
+    pub fn of(record: &Record) -> Self {
+        Self {
+            level: record.level(),
+            target: record.target(),
+            timestamp: record.timestamp(),
+        }
+    }
+}
+
+/// 高性能输出目标接口
+pub trait Sink: Send + Sync {
+    /// 写入日志数据（高性能版本）
+    fn write(&self, data: &[u8]) -> io::Result<()>;
+
+    /// 批量写入日志数据
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()>;
+
+    /// 批量写入已格式化的记录及其元数据（`meta[i]` 对应 `data[i]`）
+    ///
+    /// 需要按记录分区或路由的输出目标重写此方法；默认忽略元数据，调用 [`write_batch`](Self::write_batch)。
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        let _ = meta;
+        self.write_batch(data)
+    }
+
+    /// 记录格式化进当前批次时由消费者线程调用，随后的 [`write_records`](Self::write_records) 写入同一批次
+    ///
+    /// 需要原始记录自行格式化的输出目标（如 [`CompositeSink`] 中带独立格式化器的子目标）在此暂存；
+    /// 默认不做任何事。整批序列化（批量格式化器）的输出不会调用此方法。
+    fn stage_record(&self, _record: &Record) {}
+
+    /// 输出目标接受的批次压缩编码（默认 `None`，不压缩）
+    ///
+    /// 返回 `Some` 时消费者以 [`encode_batch`](Self::encode_batch) 组装整批载荷、压缩为一帧后
+    /// 调用 [`write_compressed`](Self::write_compressed)，不再调用逐批写入方法。装饰器转发其
+    /// 写入目标的声明；降级到未声明压缩的目标时压缩帧写入失败。
+    fn batch_compression(&self) -> Option<BatchCompression> {
+        None
+    }
+
+    /// 将一批已格式化的记录组装为压缩前的载荷（默认直接拼接）
+    fn encode_batch(&self, data: &[Vec<u8>], out: &mut Vec<u8>) {
+        for record in data {
+            out.extend_from_slice(record);
+        }
+    }
+
+    /// 写入压缩后的一帧，`records` 为其中的记录条数
+    fn write_compressed(&self, _frame: &[u8], _records: usize) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "sink does not accept compressed batches",
+        ))
+    }
+
+    /// 刷新输出缓冲区
+    fn flush(&self) -> io::Result<()>;
+
+    /// 关闭输出目标
+    fn shutdown(&self) -> io::Result<()>;
+
+    /// 合并写入窗口：批尾等待更多事件的最长时间，期间推迟写入与刷新以合并写系统调用
+    ///
+    /// 消费者按配置的等待策略（或空闲停靠）等待。默认为零，即批尾立即写入并刷新。
+    fn coalesce_window(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// 健康检查，供 [`FallbackSink`] 判断降级后能否升级回该目标
+    ///
+    /// 默认视为健康。
+    fn health_check(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 控制台输出目标
+pub struct ConsoleSink {
+    /// 是否使用标准错误输出
+    stderr: bool,
+}
+
+impl ConsoleSink {
+    /// 创建新的控制台输出目标
+    pub fn new() -> Self {
+        Self { stderr: false }
+    }
+
+    /// 创建使用标准错误输出的控制台输出目标
+    pub fn stderr() -> Self {
+        Self { stderr: true }
+    }
+}
+
+impl Default for ConsoleSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sink for ConsoleSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        if self.stderr {
+            io::stderr().write_all(data)?;
+        } else {
+            io::stdout().write_all(data)?;
+        }
+        Ok(())
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        // 整批只加锁一次
+        if self.stderr {
+            let mut out = io::stderr().lock();
+            for item in data {
+                out.write_all(item)?;
+            }
+        } else {
+            let mut out = io::stdout().lock();
+            for item in data {
+                out.write_all(item)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        if self.stderr {
+            io::stderr().flush()?;
+        } else {
+            io::stdout().flush()?;
+        }
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 消费者线程上输出目标写入失败时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SinkErrorPolicy {
+    /// 不做额外处理，批次丢弃（默认）
+    #[default]
+    Ignore,
+    /// 以递增的退避间隔重试给定次数，仍失败时丢弃批次
+    Retry(u32),
+    /// 将写入失败的批次原样写到标准错误
+    FallbackToStderr,
+}
+
+/// 一次输出目标操作失败的信息，传给构建器 [`on_error`](crate::AsyncLoggerBuilder::on_error) 回调
+#[derive(Debug)]
+pub struct SinkFailure<'a> {
+    /// 输出的位置（0 为主输出，其余按 `output` 添加顺序）
+    pub output: usize,
+    /// 失败的操作（`write` / `flush`）
+    pub operation: &'static str,
+    /// 最后一次失败的错误
+    pub error: &'a io::Error,
+    /// 已尝试的次数（含重试）
+    pub attempts: u32,
+    /// 批次中的记录条数（刷新失败时为 0）
+    pub records: usize,
+}
+
+/// 输出目标失败回调
+pub type SinkErrorHandler = Arc<dyn Fn(&SinkFailure<'_>) + Send + Sync>;
+
+/// 网络输出目标的批次压缩编码
+///
+/// 由输出目标通过 [`Sink::batch_compression`] 声明；消费者线程在格式化后将整批压缩为一帧，
+/// 压缩比与耗时计入刷新统计。多帧直接拼接仍是合法的压缩流。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchCompression {
+    /// LZ4 帧格式（`lz4` 特性），压缩快、CPU 开销低
+    #[cfg(feature = "lz4")]
+    Lz4,
+    /// zstd 帧格式及压缩级别（`zstd` 特性），压缩比更高；级别 0 为库默认值
+    #[cfg(feature = "zstd")]
+    Zstd(i32),
+}
+
+impl BatchCompression {
+    /// HTTP `Content-Encoding` 取值
+    pub fn content_encoding(self) -> &'static str {
+        match self {
+            #[cfg(feature = "lz4")]
+            BatchCompression::Lz4 => "lz4",
+            #[cfg(feature = "zstd")]
+            BatchCompression::Zstd(_) => "zstd",
+        }
+    }
+
+    /// 将 `input` 压缩为一帧追加到 `out`
+    // 未启用任何压缩特性时枚举没有取值，参数不会被使用
+    #[allow(unused_variables, clippy::ptr_arg)]
+    pub fn compress(self, input: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            #[cfg(feature = "lz4")]
+            BatchCompression::Lz4 => {
+                let mut encoder = lz4_flex::frame::FrameEncoder::new(out);
+                encoder.write_all(input)?;
+                encoder.finish().map(drop).map_err(io::Error::other)
+            }
+            #[cfg(feature = "zstd")]
+            BatchCompression::Zstd(level) => {
+                let mut encoder = zstd::stream::Encoder::new(out, level)?;
+                encoder.write_all(input)?;
+                encoder.finish().map(drop)
+            }
+        }
+    }
+}
+
+/// 文件落盘（fsync）策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Durability {
+    /// 不主动同步，由操作系统决定落盘时机（默认）
+    #[default]
+    Never,
+    /// 每次刷新后同步
+    EveryFlush,
+    /// 自上次同步起写入达到给定字节数后立即同步
+    EveryBytes(usize),
+}
+
+/// 文件输出目标（高性能版本）
+///
+/// 轮转（重命名、重新打开、压缩与清理）在后台维护线程中完成，写入路径只在交换写入器时短暂持锁；
+/// 轮转完成前写入的记录仍进入原文件。
+pub struct FileSink {
+    /// 文件路径
+    path: std::path::PathBuf,
+    /// 文件写入器（使用缓冲写入器提高性能）
+    writer: Arc<Mutex<BufWriter<File>>>,
+    /// 当前文件大小
+    current_size: Arc<std::sync::atomic::AtomicUsize>,
+    /// 轮转策略
+    rotation: RotationPolicy,
+    /// 最后轮转时间（当前文件开始写入的时刻）
+    last_rotate: Arc<std::sync::atomic::AtomicU64>,
+    /// 下一个日历轮转边界（Unix 秒，未设置周期时为 `u64::MAX`）
+    next_boundary: Arc<std::sync::atomic::AtomicU64>,
+    /// 进行中的后台压缩
+    compressions: Arc<Mutex<Vec<std::thread::JoinHandle<()>>>>,
+    /// 诊断处理器（报告轮转与压缩失败）
+    diagnostics: Arc<dyn DiagnosticHandler>,
+    /// 已请求轮转、尚未完成
+    rotation_pending: Arc<AtomicBool>,
+    /// 已完成的轮转次数
+    rotations: Arc<std::sync::atomic::AtomicU64>,
+    /// 维护线程（首次轮转时启动，设置定期清理时在首次写入时启动）
+    maintenance: Mutex<Option<Maintenance>>,
+    /// 维护线程已启动
+    maintenance_started: AtomicBool,
+    /// 合并写入窗口
+    coalesce_window: Duration,
+    /// 落盘策略
+    durability: Durability,
+    /// 写入错误级别记录的批次后立即落盘
+    sync_on_error: bool,
+    /// 自上次同步起写入的字节数
+    unsynced: std::sync::atomic::AtomicUsize,
+    /// 已执行的同步次数
+    syncs: Arc<std::sync::atomic::AtomicU64>,
+}
+
+/// 维护线程句柄
+struct Maintenance {
+    requests: std::sync::mpsc::Sender<()>,
+    handle: std::thread::JoinHandle<()>,
+}
+
+impl FileSink {
+    /// 创建新的文件输出目标
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+
+        // 确保父目录存在
+        if let Some(parent) = path.parent()
+            && !parent.exists()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let file_size = file.metadata()?.len() as usize;
+
+        Ok(Self {
+            path,
+            writer: Arc::new(Mutex::new(BufWriter::new(file))),
+            current_size: Arc::new(std::sync::atomic::AtomicUsize::new(file_size)),
+            rotation: RotationPolicy::default(),
+            next_boundary: Arc::new(std::sync::atomic::AtomicU64::new(u64::MAX)),
+            compressions: Arc::new(Mutex::new(Vec::new())),
+            diagnostics: Arc::new(StderrDiagnostics),
+            rotation_pending: Arc::new(AtomicBool::new(false)),
+            rotations: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            maintenance: Mutex::new(None),
+            maintenance_started: AtomicBool::new(false),
+            last_rotate: Arc::new(std::sync::atomic::AtomicU64::new(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            )),
+            coalesce_window: Duration::ZERO,
+            durability: Durability::Never,
+            sync_on_error: false,
+            unsynced: std::sync::atomic::AtomicUsize::new(0),
+            syncs: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+        })
+    }
+
+    /// 创建带缓冲区大小的文件输出目标
+    pub fn with_buffer_size<P: AsRef<Path>>(path: P, buffer_size: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+
+        // 确保父目录存在
+        if let Some(parent) = path.parent()
+            && !parent.exists()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let file_size = file.metadata()?.len() as usize;
+
+        Ok(Self {
+            path,
+            writer: Arc::new(Mutex::new(BufWriter::with_capacity(buffer_size, file))),
+            current_size: Arc::new(std::sync::atomic::AtomicUsize::new(file_size)),
+            rotation: RotationPolicy::default(),
+            next_boundary: Arc::new(std::sync::atomic::AtomicU64::new(u64::MAX)),
+            compressions: Arc::new(Mutex::new(Vec::new())),
+            diagnostics: Arc::new(StderrDiagnostics),
+            rotation_pending: Arc::new(AtomicBool::new(false)),
+            rotations: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            maintenance: Mutex::new(None),
+            maintenance_started: AtomicBool::new(false),
+            last_rotate: Arc::new(std::sync::atomic::AtomicU64::new(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            )),
+            coalesce_window: Duration::ZERO,
+            durability: Durability::Never,
+            sync_on_error: false,
+            unsynced: std::sync::atomic::AtomicUsize::new(0),
+            syncs: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+        })
+    }
+
+    /// 设置最大文件大小（字节）
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.rotation.max_size = Some(max_size);
+        self
+    }
+
+    /// 设置轮转时间间隔（秒）
+    pub fn with_rotate_interval(mut self, interval: u64) -> Self {
+        self.rotation.interval = Some(Duration::from_secs(interval));
+        self
+    }
+
+    /// 设置保留的日志文件数量
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.rotation.max_files = Some(max_files);
+        self
+    }
+
+    /// 删除最后修改时间早于 `max_age` 之前的轮转文件（如保留 30 天）
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.rotation.max_age = Some(max_age);
+        self
+    }
+
+    /// 设置轮转策略（替换此前设置的大小、间隔与保留数量）
+    pub fn with_rotation(mut self, policy: RotationPolicy) -> Self {
+        if policy.period.is_some() {
+            // 已有内容的文件从最后修改时刻起算，属于上一周期的文件在首次写入时即轮转
+            let mut opened = self.last_rotate.load(std::sync::atomic::Ordering::Relaxed);
+            if self.current_size.load(std::sync::atomic::Ordering::Relaxed) > 0
+                && let Ok(modified) = std::fs::metadata(&self.path).and_then(|m| m.modified())
+                && let Ok(modified) = modified.duration_since(std::time::UNIX_EPOCH)
+            {
+                opened = opened.min(modified.as_secs());
+            }
+            self.last_rotate
+                .store(opened, std::sync::atomic::Ordering::Relaxed);
+            self.next_boundary.store(
+                policy.next_boundary(opened),
+                std::sync::atomic::Ordering::Relaxed,
+            );
+        }
+        self.rotation = policy;
+        self
+    }
+
+    /// 设置合并写入窗口（建议不超过数百微秒），中等负载下合并写系统调用，
+    /// 尾延迟最多增加一个窗口
+    pub fn with_coalesce_window(mut self, window: Duration) -> Self {
+        self.coalesce_window = window;
+        self
+    }
+
+    /// 设置落盘策略（默认 [`Durability::Never`]），审计类日志可要求每次刷新或每写入 N 字节同步
+    pub fn with_durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// 写入包含错误级别记录的批次后立即落盘，不受落盘策略影响
+    pub fn with_sync_on_error(mut self, enabled: bool) -> Self {
+        self.sync_on_error = enabled;
+        self
+    }
+
+    /// 已执行的同步（fsync）次数
+    pub fn syncs(&self) -> u64 {
+        self.syncs.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// 写出缓冲区并将文件数据同步到存储设备
+    fn sync(&self, writer: &mut BufWriter<File>) -> io::Result<()> {
+        writer.flush()?;
+        writer.get_ref().sync_data()?;
+        self.unsynced.store(0, std::sync::atomic::Ordering::Relaxed);
+        self.syncs
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// 写入后更新文件大小，按字节阈值落盘
+    fn wrote(&self, writer: &mut BufWriter<File>, bytes: usize) -> io::Result<()> {
+        self.current_size
+            .fetch_add(bytes, std::sync::atomic::Ordering::Relaxed);
+        let unsynced = self
+            .unsynced
+            .fetch_add(bytes, std::sync::atomic::Ordering::Relaxed)
+            + bytes;
+        match self.durability {
+            Durability::EveryBytes(threshold) if unsynced >= threshold => self.sync(writer),
+            _ => Ok(()),
+        }
+    }
+
+    /// 检查是否需要轮转
+    fn should_rotate(&self) -> bool {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        now >= self
+            .next_boundary
+            .load(std::sync::atomic::Ordering::Relaxed)
+            || self.rotation.should_rotate(
+                self.current_size.load(std::sync::atomic::Ordering::Relaxed),
+                self.last_rotate.load(std::sync::atomic::Ordering::Relaxed),
+                now,
+            )
+    }
+
+    /// 设置诊断处理器（默认输出到标准错误），用于报告轮转与压缩失败
+    pub fn with_diagnostics(mut self, handler: Arc<dyn DiagnosticHandler>) -> Self {
+        self.diagnostics = handler;
+        self
+    }
+
+    /// 等待进行中的后台压缩完成
+    fn wait_compressions(&self) {
+        let handles =
+            std::mem::take(&mut *self.compressions.lock().unwrap_or_else(|e| e.into_inner()));
+        for handle in handles {
+            let _ = handle.join();
+        }
+    }
+
+    /// 已完成的轮转次数
+    pub fn rotations(&self) -> u64 {
+        self.rotations.load(std::sync::atomic::Ordering::Acquire)
+    }
+
+    /// 需要轮转时请求维护线程执行（同一时刻至多一个未完成的请求）
+    fn maybe_rotate(&self) {
+        // 定期清理需要维护线程常驻
+        if self.rotation.sweep_interval.is_some()
+            && !self.maintenance_started.load(Ordering::Acquire)
+        {
+            let mut maintenance = self.maintenance.lock().unwrap_or_else(|e| e.into_inner());
+            self.start_maintenance(&mut maintenance);
+        }
+        if self.rotation_pending.load(Ordering::Acquire)
+            || !self.should_rotate()
+            || self.rotation_pending.swap(true, Ordering::AcqRel)
+        {
+            return;
+        }
+        let mut maintenance = self.maintenance.lock().unwrap_or_else(|e| e.into_inner());
+        if !self.start_maintenance(&mut maintenance) {
+            // 无法启动维护线程时在写入路径上轮转
+            drop(maintenance);
+            self.rotator().run();
+            return;
+        }
+        if let Some(m) = maintenance.as_ref() {
+            let _ = m.requests.send(());
+        }
+    }
+
+    /// 启动维护线程（已启动时直接返回），返回维护线程是否可用
+    fn start_maintenance(&self, maintenance: &mut Option<Maintenance>) -> bool {
+        if maintenance.is_some() {
+            return true;
+        }
+        let rotator = self.rotator();
+        let sweep = self.rotation.sweep_interval;
+        let (requests, rx) = std::sync::mpsc::channel::<()>();
+        let spawned = std::thread::Builder::new()
+            .name("nanolog-rotate".into())
+            .spawn(move || {
+                loop {
+                    let request = match sweep {
+                        Some(interval) => rx.recv_timeout(interval),
+                        None => rx
+                            .recv()
+                            .map_err(|_| std::sync::mpsc::RecvTimeoutError::Disconnected),
+                    };
+                    match request {
+                        Ok(()) => rotator.run(),
+                        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => rotator.sweep(),
+                        Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+            });
+        self.maintenance_started.store(true, Ordering::Release);
+        match spawned {
+            Ok(handle) => {
+                *maintenance = Some(Maintenance { requests, handle });
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// 创建共享本目标状态的轮转执行者
+    fn rotator(&self) -> Rotator {
+        Rotator {
+            path: self.path.clone(),
+            writer: self.writer.clone(),
+            current_size: self.current_size.clone(),
+            rotation: self.rotation.clone(),
+            last_rotate: self.last_rotate.clone(),
+            next_boundary: self.next_boundary.clone(),
+            compressions: self.compressions.clone(),
+            diagnostics: self.diagnostics.clone(),
+            rotation_pending: self.rotation_pending.clone(),
+            rotations: self.rotations.clone(),
+            durable: self.durability != Durability::Never,
+            syncs: self.syncs.clone(),
+        }
+    }
+
+    /// 停止维护线程（先完成已请求的轮转）
+    fn stop_maintenance(&self) {
+        let maintenance = self
+            .maintenance
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(Maintenance { requests, handle }) = maintenance {
+            drop(requests);
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 轮转执行者：共享文件输出目标的写入器与状态，在维护线程上完成重命名、重新打开与清理，
+/// 写入路径只在交换写入器的瞬间等待
+struct Rotator {
+    path: std::path::PathBuf,
+    writer: Arc<Mutex<BufWriter<File>>>,
+    current_size: Arc<std::sync::atomic::AtomicUsize>,
+    rotation: RotationPolicy,
+    last_rotate: Arc<std::sync::atomic::AtomicU64>,
+    next_boundary: Arc<std::sync::atomic::AtomicU64>,
+    compressions: Arc<Mutex<Vec<std::thread::JoinHandle<()>>>>,
+    diagnostics: Arc<dyn DiagnosticHandler>,
+    rotation_pending: Arc<AtomicBool>,
+    rotations: Arc<std::sync::atomic::AtomicU64>,
+    /// 轮转出的文件关闭前落盘
+    durable: bool,
+    syncs: Arc<std::sync::atomic::AtomicU64>,
+}
+
+impl Rotator {
+    /// 执行一次已请求的轮转，失败经诊断通道上报
+    fn run(&self) {
+        let result = self.rotate();
+        self.rotation_pending.store(false, Ordering::Release);
+        match result {
+            Ok(()) => {
+                self.rotations
+                    .fetch_add(1, std::sync::atomic::Ordering::Release);
+            }
+            Err(err) => self.diagnostics.handle(&Diagnostic::SinkError {
+                operation: "rotate",
+                error: err.to_string(),
+            }),
+        }
+    }
+
+    /// 定期保留清理，失败经诊断通道上报
+    fn sweep(&self) {
+        if let Err(err) = self.cleanup_old_files() {
+            self.diagnostics.handle(&Diagnostic::SinkError {
+                operation: "sweep",
+                error: err.to_string(),
+            });
+        }
+    }
+
+    /// 在后台线程压缩轮转文件，不阻塞写入路径
+    fn compress_in_background(&self, rotated: std::path::PathBuf) {
+        let Some(compression) = self.rotation.compression else {
+            return;
+        };
+        let diagnostics = self.diagnostics.clone();
+        let path = rotated.display().to_string();
+        let compress = move || {
+            if let Err(err) = compression.compress_file(&rotated)
+                // 已被保留数量清理的文件无需压缩
+                && err.kind() != io::ErrorKind::NotFound
+            {
+                diagnostics.handle(&Diagnostic::CompressionFailed {
+                    path: rotated.display().to_string(),
+                    error: err.to_string(),
+                });
+            }
+        };
+        let mut compressions = self.compressions.lock().unwrap_or_else(|e| e.into_inner());
+        compressions.retain(|handle| !handle.is_finished());
+        match std::thread::Builder::new()
+            .name("nanolog-compress".into())
+            .spawn(compress)
+        {
+            Ok(handle) => compressions.push(handle),
+            Err(err) => self.diagnostics.handle(&Diagnostic::CompressionFailed {
+                path,
+                error: err.to_string(),
+            }),
+        }
+    }
+
+    /// 轮转文件所在目录
+    fn dir(&self) -> &Path {
+        self.path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+    }
+
+    /// 执行日志轮转
+    fn rotate(&self) -> io::Result<()> {
+        // 生成轮转文件名
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let rotated_path = match &self.rotation.template {
+            Some(template) => template.next_path(
+                self.dir(),
+                self.last_rotate.load(std::sync::atomic::Ordering::Relaxed),
+                self.rotation.clock,
+            )?,
+            None => crate::rotation::suffixed_path(
+                &self.path,
+                self.rotation.suffix_generator(),
+                self.rotations.load(std::sync::atomic::Ordering::Acquire) + 1,
+            )?,
+        };
+
+        // 重命名正在写入的文件：已打开的句柄继续写入轮转文件，写入路径不受影响
+        std::fs::rename(&self.path, &rotated_path)?;
+
+        // 创建新的日志文件
+        let new_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        // 仅在交换写入器时持锁
+        let mut old_writer = {
+            let mut writer_guard = self
+                .writer
+                .lock()
+                .map_err(|_| io::Error::other("lock poisoned"))?;
+            let capacity = writer_guard.capacity();
+            self.current_size
+                .store(0, std::sync::atomic::Ordering::Relaxed);
+            std::mem::replace(
+                &mut *writer_guard,
+                BufWriter::with_capacity(capacity, new_file),
+            )
+        };
+
+        // 旧缓冲区中的剩余数据写入轮转文件
+        old_writer.flush()?;
+        if self.durable {
+            old_writer.get_ref().sync_data()?;
+            self.syncs
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+        drop(old_writer);
+
+        // 更新最后轮转时间
+        self.last_rotate
+            .store(timestamp, std::sync::atomic::Ordering::Relaxed);
+        self.next_boundary.store(
+            self.rotation.next_boundary(timestamp),
+            std::sync::atomic::Ordering::Relaxed,
+        );
+
+        self.compress_in_background(rotated_path);
+
+        // 清理旧日志文件
+        self.cleanup_old_files()?;
+
+        Ok(())
+    }
+
+    /// 清理旧日志文件
+    fn cleanup_old_files(&self) -> io::Result<()> {
+        let (max_files, max_age) = (self.rotation.max_files, self.rotation.max_age);
+        if max_files.is_none() && max_age.is_none() {
+            return Ok(());
+        }
+
+        // 获取所有日志文件
+        let mut files = Vec::new();
+        let file_name = self.path.file_name().unwrap_or_default().to_string_lossy();
+
+        if let Some(template) = &self.rotation.template {
+            // 只统计匹配模板的轮转文件
+            for entry in std::fs::read_dir(self.dir())? {
+                let entry = entry?;
+                let path = entry.path();
+                if path.is_file() && template.matches(&entry.file_name().to_string_lossy()) {
+                    files.push((path.metadata()?.modified()?, path));
+                }
+            }
+        } else if let Some(parent) = self.path.parent() {
+            for entry in std::fs::read_dir(parent)? {
+                let entry = entry?;
+                let path = entry.path();
+
+                if path.is_file() {
+                    let file_stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                    // 跳过压缩中的临时文件
+                    if file_stem.starts_with(&*file_name)
+                        && path.extension() != Some(std::ffi::OsStr::new("part"))
+                    {
+                        let metadata = path.metadata()?;
+                        let mtime = metadata.modified()?;
+                        files.push((mtime, path));
+                    }
+                }
+            }
+        }
+
+        // 按修改时间排序
+        files.sort_by_key(|b| std::cmp::Reverse(b.0));
+
+        // 删除超出数量或过期的文件
+        let cutoff = max_age.and_then(|age| std::time::SystemTime::now().checked_sub(age));
+        for (index, (mtime, path)) in files.into_iter().enumerate() {
+            let excess = max_files.is_some_and(|max| index >= max);
+            let expired = cutoff.is_some_and(|cutoff| mtime < cutoff);
+            if excess || expired {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        // 需要轮转时交给维护线程
+        self.maybe_rotate();
+
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| io::Error::other("lock poisoned"))?;
+        writer.write_all(data)?;
+        self.wrote(&mut writer, data.len())
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        // 需要轮转时交给维护线程
+        self.maybe_rotate();
+
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| io::Error::other("lock poisoned"))?;
+
+        let mut total_size = 0;
+        for item in data {
+            writer.write_all(item)?;
+            total_size += item.len();
+        }
+        self.wrote(&mut writer, total_size)
+    }
+
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        self.write_batch(data)?;
+        if self.sync_on_error && meta.iter().any(|m| m.level >= Level::Error) {
+            let mut writer = self
+                .writer
+                .lock()
+                .map_err(|_| io::Error::other("lock poisoned"))?;
+            self.sync(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| io::Error::other("lock poisoned"))?;
+        if self.durability == Durability::EveryFlush
+            && self.unsynced.load(std::sync::atomic::Ordering::Relaxed) > 0
+        {
+            self.sync(&mut writer)
+        } else {
+            writer.flush()
+        }
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        // 先完成已请求的轮转，再刷新当前文件
+        self.stop_maintenance();
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| io::Error::other("lock poisoned"))?;
+        if self.durability == Durability::Never {
+            writer.flush()?;
+        } else {
+            self.sync(&mut writer)?;
+        }
+        drop(writer);
+        self.wait_compressions();
+        Ok(())
+    }
+
+    fn coalesce_window(&self) -> Duration {
+        self.coalesce_window
+    }
+}
+
+/// 内存输出目标（用于测试和调试）
+pub struct MemorySink {
+    /// 内存缓冲区
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl MemorySink {
+    /// 创建新的内存输出目标
+    pub fn new() -> Self {
+        Self {
+            buffer: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// 获取缓冲区内容
+    pub fn get_content(&self) -> Vec<u8> {
+        self.buffer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// 清空缓冲区
+    pub fn clear(&self) {
+        if let Ok(mut buffer) = self.buffer.lock() {
+            buffer.clear();
+        }
+    }
+}
+
+impl Default for MemorySink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sink for MemorySink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        let mut buffer = self
+            .buffer
+            .lock()
+            .map_err(|_| io::Error::other("lock poisoned"))?;
+        buffer.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        let mut buffer = self
+            .buffer
+            .lock()
+            .map_err(|_| io::Error::other("lock poisoned"))?;
+        for item in data {
+            buffer.extend_from_slice(item);
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        // 内存输出目标不需要刷新
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 空输出目标（用于性能测试）
+#[derive(Default)]
+pub struct NullSink;
+
+impl NullSink {
+    /// 创建新的空输出目标
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Sink for NullSink {
+    fn write(&self, _data: &[u8]) -> io::Result<()> {
+        // 不执行任何操作
+        Ok(())
+    }
+
+    fn write_batch(&self, _data: &[Vec<u8>]) -> io::Result<()> {
+        // 不执行任何操作
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        // 不执行任何操作
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        // 不执行任何操作
+        Ok(())
+    }
+}
+
+/// 复合输出目标（支持多个输出目标）
+///
+/// 每个子目标可单独设置最低级别与格式化器（如彩色文本写控制台、紧凑 JSON 写文件）：
+/// 带格式化器的子目标通过 [`Sink::stage_record`] 取得原始记录自行格式化，只在复合目标直接（或经其他复合目标、
+/// 路由、超时、后备等装饰器转发）挂接到日志器的逐条格式化管道时生效；不附带元数据的写入（[`write`](Sink::write)、[`write_batch`](Sink::write_batch)）
+/// 不写入这些子目标，其余子目标则不按级别过滤。
+///
+/// ```
+/// use nanolog_rs::{CompositeSink, JsonFormatter, Level, MemorySink};
+/// use std::sync::Arc;
+///
+/// let mut sink = CompositeSink::new();
+/// sink.add_sink(Arc::new(MemorySink::new()));
+/// sink.add_sink_with(
+///     Arc::new(MemorySink::new()),
+///     Level::Warn,
+///     Some(Arc::new(JsonFormatter::new())),
+/// );
+/// ```
+#[derive(Default)]
+pub struct CompositeSink {
+    /// 输出目标列表
+    sinks: Vec<CompositeMember>,
+}
+
+/// 复合输出目标的一个子目标
+struct CompositeMember {
+    sink: Arc<dyn Sink>,
+    /// 最低级别
+    min_level: Level,
+    /// 独立格式化器（`None` 表示写入共享的格式化结果）
+    formatter: Option<Arc<dyn Formatter>>,
+    /// 独立格式化器暂存的本批记录
+    staged: Mutex<StagedRecords>,
+}
+
+#[derive(Default)]
+struct StagedRecords {
+    meta: Vec<RecordMeta>,
+    data: Vec<Vec<u8>>,
+}
+
+impl CompositeMember {
+    fn accepts(&self, level: Level) -> bool {
+        level >= self.min_level
+    }
+
+    /// 写入共享的格式化结果，按最低级别过滤（连续通过的记录合并为一次写入）
+    fn write_shared(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        if meta.iter().all(|m| self.accepts(m.level)) {
+            return self.sink.write_records(meta, data);
+        }
+        let len = meta.len().min(data.len());
+        let mut start = 0;
+        while start < len {
+            if !self.accepts(meta[start].level) {
+                start += 1;
+                continue;
+            }
+            let end = meta[start..len]
+                .iter()
+                .position(|m| !self.accepts(m.level))
+                .map_or(len, |n| start + n);
+            self.sink
+                .write_records(&meta[start..end], &data[start..end])?;
+            start = end;
+        }
+        Ok(())
+    }
+
+    /// 写入并清空暂存的记录
+    fn write_staged(&self) -> io::Result<()> {
+        let mut staged = self.staged.lock().unwrap_or_else(|e| e.into_inner());
+        if staged.meta.is_empty() {
+            return Ok(());
+        }
+        let result = self.sink.write_records(&staged.meta, &staged.data);
+        staged.meta.clear();
+        staged.data.clear();
+        result
+    }
+}
+
+impl CompositeSink {
+    /// 创建新的复合输出目标
+    pub fn new() -> Self {
+        Self { sinks: Vec::new() }
+    }
+
+    /// 添加输出目标
+    pub fn add_sink(&mut self, sink: Arc<dyn Sink>) {
+        self.add_sink_with(sink, Level::Trace, None);
+    }
+
+    /// 添加输出目标，只写入不低于 `min_level` 的记录；`formatter` 为 `Some` 时以其格式化记录
+    pub fn add_sink_with(
+        &mut self,
+        sink: Arc<dyn Sink>,
+        min_level: Level,
+        formatter: Option<Arc<dyn Formatter>>,
+    ) {
+        self.sinks.push(CompositeMember {
+            sink,
+            min_level,
+            formatter,
+            staged: Mutex::default(),
+        });
+    }
+}
+
+impl Sink for CompositeSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        for member in self.sinks.iter().filter(|m| m.formatter.is_none()) {
+            member.sink.write(data)?;
+        }
+        Ok(())
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        for member in self.sinks.iter().filter(|m| m.formatter.is_none()) {
+            member.sink.write_batch(data)?;
+        }
+        Ok(())
+    }
+
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        for member in &self.sinks {
+            match member.formatter {
+                Some(_) => member.write_staged()?,
+                None => member.write_shared(meta, data)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn stage_record(&self, record: &Record) {
+        for member in &self.sinks {
+            if !member.accepts(record.level()) {
+                continue;
+            }
+            if let Some(formatter) = &member.formatter {
+                let mut staged = member.staged.lock().unwrap_or_else(|e| e.into_inner());
+                let mut buf = Vec::new();
+                if formatter.format_into(record, &mut buf).is_ok() {
+                    staged.meta.push(RecordMeta::of(record));
+                    staged.data.push(buf);
+                }
+            }
+            // 嵌套的复合目标同样需要原始记录
+            member.sink.stage_record(record);
+        }
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        for member in &self.sinks {
+            member.sink.flush()?;
+        }
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        for member in &self.sinks {
+            member.sink.shutdown()?;
+        }
+        Ok(())
+    }
+
+    fn coalesce_window(&self) -> Duration {
+        // 取各输出目标中最长的窗口
+        self.sinks
+            .iter()
+            .map(|member| member.sink.coalesce_window())
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// 按级别分流的输出目标
+///
+/// 每条记录按级别写入对应的输出目标（如警告与错误写入单独的 `error.log`），未配置路由的级别写入默认目标。
+/// 路由依赖记录元数据：不附带元数据的写入（[`write`](Sink::write)、[`write_batch`](Sink::write_batch)）
+/// 全部进入默认目标。批内连续同一目标的记录合并为一次写入，各目标内记录保持原有顺序。
+///
+/// ```
+/// use nanolog_rs::{Level, LevelRoutingSink, MemorySink};
+/// use std::sync::Arc;
+///
+/// let errors = Arc::new(MemorySink::new());
+/// let sink = LevelRoutingSink::new(Arc::new(MemorySink::new()))
+///     .route(Level::Warn..=Level::Error, errors);
+/// ```
+pub struct LevelRoutingSink {
+    /// 去重后的输出目标（首个为默认目标）
+    sinks: Vec<Arc<dyn Sink>>,
+    /// 各级别对应的目标下标
+    routes: [usize; 5],
+}
+
+impl LevelRoutingSink {
+    /// 创建所有级别都写入 `default` 的路由目标
+    pub fn new(default: Arc<dyn Sink>) -> Self {
+        Self {
+            sinks: vec![default],
+            routes: [0; 5],
+        }
+    }
+
+    /// 不低于 `threshold` 的记录写入 `high`，其余写入 `low`
+    pub fn split(threshold: Level, high: Arc<dyn Sink>, low: Arc<dyn Sink>) -> Self {
+        Self::new(low).route(threshold..=Level::Error, high)
+    }
+
+    /// 将 `levels` 范围内的记录写入 `sink`（覆盖此前对这些级别的设置）
+    pub fn route(mut self, levels: std::ops::RangeInclusive<Level>, sink: Arc<dyn Sink>) -> Self {
+        let index = match self.sinks.iter().position(|s| Arc::ptr_eq(s, &sink)) {
+            Some(index) => index,
+            None => {
+                self.sinks.push(sink);
+                self.sinks.len() - 1
+            }
+        };
+        for level in *levels.start() as usize..=*levels.end() as usize {
+            self.routes[level] = index;
+        }
+        self
+    }
+}
+
+impl Sink for LevelRoutingSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        self.sinks[0].write(data)
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        self.sinks[0].write_batch(data)
+    }
+
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        dispatch(&self.sinks, meta, data, |m| self.routes[m.level as usize])
+    }
+
+    fn stage_record(&self, record: &Record) {
+        self.sinks[self.routes[record.level() as usize]].stage_record(record);
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        for sink in &self.sinks {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        for sink in &self.sinks {
+            sink.shutdown()?;
+        }
+        Ok(())
+    }
+
+    fn coalesce_window(&self) -> Duration {
+        self.sinks
+            .iter()
+            .map(|sink| sink.coalesce_window())
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn health_check(&self) -> io::Result<()> {
+        for sink in &self.sinks {
+            sink.health_check()?;
+        }
+        Ok(())
+    }
+}
+
+/// 按目标前缀路由的输出目标
+///
+/// 每条记录按目标（模块路径）匹配路由规则，写入对应的输出目标（如 `audit::*` 写入审计文件），
+/// 未匹配的记录写入默认目标。前缀按模块路径边界匹配，最长前缀优先；规则末尾的 `::*` 可省略。
+/// 与 [`LevelRoutingSink`] 相同，路由依赖 [`Sink::write_records`] 传入的记录元数据，
+/// 不附带元数据的写入全部进入默认目标。
+///
+/// ```
+/// use nanolog_rs::{ConsoleSink, MemorySink, RouterSink};
+/// use std::sync::Arc;
+///
+/// let audit = Arc::new(MemorySink::new());
+/// let sink = RouterSink::new(Arc::new(ConsoleSink::new())).route("audit::*", audit);
+/// ```
+pub struct RouterSink {
+    /// 去重后的输出目标（首个为默认目标）
+    sinks: Vec<Arc<dyn Sink>>,
+    /// 按前缀长度降序排列的路由规则（前缀，目标下标）
+    rules: Vec<(String, usize)>,
+}
+
+impl RouterSink {
+    /// 创建未匹配任何规则的记录写入 `default` 的路由目标
+    pub fn new(default: Arc<dyn Sink>) -> Self {
+        Self {
+            sinks: vec![default],
+            rules: Vec::new(),
+        }
+    }
+
+    /// 目标匹配 `prefix`（如 `audit` 或 `audit::*`）的记录写入 `sink`
+    pub fn route(mut self, prefix: &str, sink: Arc<dyn Sink>) -> Self {
+        let prefix = prefix.strip_suffix("::*").unwrap_or(prefix);
+        let index = match self.sinks.iter().position(|s| Arc::ptr_eq(s, &sink)) {
+            Some(index) => index,
+            None => {
+                self.sinks.push(sink);
+                self.sinks.len() - 1
+            }
+        };
+        self.rules.retain(|(p, _)| p != prefix);
+        self.rules.push((prefix.to_string(), index));
+        self.rules.sort_by_key(|(p, _)| std::cmp::Reverse(p.len()));
+        self
+    }
+
+    /// 目标对应的输出目标下标
+    fn route_of(&self, target: &str) -> usize {
+        self.rules
+            .iter()
+            .find(|(prefix, _)| crate::filter::matches_prefix(target, prefix))
+            .map_or(0, |(_, index)| *index)
+    }
+}
+
+impl Sink for RouterSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        self.sinks[0].write(data)
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        self.sinks[0].write_batch(data)
+    }
+
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        dispatch(&self.sinks, meta, data, |m| self.route_of(m.target))
+    }
+
+    fn stage_record(&self, record: &Record) {
+        self.sinks[self.route_of(record.target())].stage_record(record);
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        for sink in &self.sinks {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        for sink in &self.sinks {
+            sink.shutdown()?;
+        }
+        Ok(())
+    }
+
+    fn coalesce_window(&self) -> Duration {
+        self.sinks
+            .iter()
+            .map(|sink| sink.coalesce_window())
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn health_check(&self) -> io::Result<()> {
+        for sink in &self.sinks {
+            sink.health_check()?;
+        }
+        Ok(())
+    }
+}
+
+/// 按 `route` 将记录分发到 `sinks`：连续进入同一目标的记录合并为一次写入，不复制数据
+fn dispatch(
+    sinks: &[Arc<dyn Sink>],
+    meta: &[RecordMeta],
+    data: &[Vec<u8>],
+    route: impl Fn(&RecordMeta) -> usize,
+) -> io::Result<()> {
+    let len = meta.len().min(data.len());
+    let mut start = 0;
+    while start < len {
+        let target = route(&meta[start]);
+        let end = meta[start + 1..len]
+            .iter()
+            .position(|m| route(m) != target)
+            .map_or(len, |n| start + 1 + n);
+        sinks[target].write_records(&meta[start..end], &data[start..end])?;
+        start = end;
+    }
+    Ok(())
+}
+
+/// TCP 输出目标
+///
+/// 将已格式化的日志流式发送到 `host:port`（如日志采集器）。写入先攒在自有缓冲区中，满
+/// 8KB 或刷新时再写入连接；只有连接已接收的字节才算送出，写入失败时未送出的记录
+/// 整条留在溢出缓冲区，并按指数退避自动重连，重连成功后先补发缓冲区内容。被打断的记录在新连接上
+/// 完整重发，旧连接上至多残留它的一段截断数据。缓冲区超出上限时丢弃最旧的数据并返回错误；
+/// 断线期间刷新返回错误，数据仍留在缓冲区中。
+pub struct TcpSink {
+    addr: String,
+    connect_timeout: Duration,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    max_spill_bytes: usize,
+    compression: Option<BatchCompression>,
+    state: Mutex<TcpState>,
+}
+
+/// 连接正常时攒批的字节数，达到后写入连接
+const TCP_BUFFER_BYTES: usize = 8 * 1024;
+
+/// TCP 连接状态
+struct TcpState {
+    stream: Option<std::net::TcpStream>,
+    /// 尚未被连接接收的记录（断线期间即溢出缓冲区）
+    unsent: std::collections::VecDeque<Vec<u8>>,
+    unsent_bytes: usize,
+    /// 队首记录已写入连接的字节数
+    front_sent: usize,
+    dropped_bytes: u64,
+    backoff: Duration,
+    next_attempt: std::time::Instant,
+}
+
+impl TcpSink {
+    /// 创建 TCP 输出目标（首次写入时连接）
+    pub fn new(addr: impl Into<String>) -> Self {
+        let min_backoff = Duration::from_millis(100);
+        Self {
+            addr: addr.into(),
+            connect_timeout: Duration::from_secs(1),
+            min_backoff,
+            max_backoff: Duration::from_secs(30),
+            max_spill_bytes: 16 * 1024 * 1024,
+            compression: None,
+            state: Mutex::new(TcpState {
+                stream: None,
+                unsent: std::collections::VecDeque::new(),
+                unsent_bytes: 0,
+                front_sent: 0,
+                dropped_bytes: 0,
+                backoff: min_backoff,
+                next_attempt: std::time::Instant::now(),
+            }),
+        }
+    }
+
+    /// 设置连接超时（默认 1 秒）
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// 设置重连退避区间（默认 100ms 起，翻倍至 30s）
+    pub fn with_backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max.max(min);
+        if let Ok(mut state) = self.state.lock() {
+            state.backoff = min;
+        }
+        self
+    }
+
+    /// 设置断线期间溢出缓冲区的上限字节数（默认 16MB）
+    pub fn with_spill_limit(mut self, bytes: usize) -> Self {
+        self.max_spill_bytes = bytes;
+        self
+    }
+
+    /// 设置批次压缩：每个消费者批次压缩为一帧写入连接，接收端按压缩流解码
+    pub fn with_compression(mut self, compression: BatchCompression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// 当前是否已连接
+    pub fn is_connected(&self) -> bool {
+        self.lock_state().is_ok_and(|state| state.stream.is_some())
+    }
+
+    /// 断线期间溢出缓冲区中待补发的字节数（连接正常时为 0）
+    pub fn spilled_bytes(&self) -> usize {
+        self.lock_state().map_or(0, |state| {
+            if state.stream.is_some() {
+                0
+            } else {
+                state.unsent_bytes
+            }
+        })
+    }
+
+    /// 因溢出缓冲区已满而丢弃的字节数
+    pub fn dropped_bytes(&self) -> u64 {
+        self.lock_state().map_or(0, |state| state.dropped_bytes)
+    }
+
+    fn lock_state(&self) -> io::Result<std::sync::MutexGuard<'_, TcpState>> {
+        self.state
+            .lock()
+            .map_err(|_| io::Error::other("lock poisoned"))
+    }
+
+    /// 未连接且已到重连时间时尝试连接，成功后补发溢出缓冲区；仅在溢出缓冲区丢弃数据时返回错误
+    fn ensure_connected(&self, state: &mut TcpState) -> io::Result<()> {
+        if state.stream.is_some() || std::time::Instant::now() < state.next_attempt {
+            return Ok(());
+        }
+        match self.connect() {
+            Ok(stream) => {
+                state.stream = Some(stream);
+                state.backoff = self.min_backoff;
+                if Self::send(state).is_err() {
+                    self.disconnect(state);
+                    return self.trim_spill(state);
+                }
+                Ok(())
+            }
+            Err(_) => {
+                self.disconnect(state);
+                Ok(())
+            }
+        }
+    }
+
+    fn connect(&self) -> io::Result<std::net::TcpStream> {
+        use std::net::ToSocketAddrs;
+        let mut last_err = io::Error::new(io::ErrorKind::NotFound, "address resolved to nothing");
+        for addr in self.addr.to_socket_addrs()? {
+            match std::net::TcpStream::connect_timeout(&addr, self.connect_timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_err = err,
+            }
+        }
+        Err(last_err)
+    }
+
+    /// 将未送出的记录写入当前连接，只移除连接已接收的部分
+    fn send(state: &mut TcpState) -> io::Result<()> {
+        let Some(stream) = state.stream.as_mut() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "tcp sink not connected",
+            ));
+        };
+        while !state.unsent.is_empty() {
+            let slices: Vec<io::IoSlice<'_>> = state
+                .unsent
+                .iter()
+                .take(64)
+                .enumerate()
+                .map(|(i, data)| {
+                    io::IoSlice::new(if i == 0 {
+                        &data[state.front_sent..]
+                    } else {
+                        data
+                    })
+                })
+                .collect();
+            let mut sent = match stream.write_vectored(&slices) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => n + state.front_sent,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            state.front_sent = 0;
+            while let Some(front) = state.unsent.front() {
+                if sent < front.len() {
+                    state.front_sent = sent;
+                    break;
+                }
+                sent -= front.len();
+                state.unsent_bytes -= front.len();
+                state.unsent.pop_front();
+            }
+        }
+        Ok(())
+    }
+
+    /// 断开连接并安排下一次重连（指数退避）；被打断的记录重连后整条重发
+    fn disconnect(&self, state: &mut TcpState) {
+        state.stream = None;
+        state.front_sent = 0;
+        state.next_attempt = std::time::Instant::now() + state.backoff;
+        state.backoff = (state.backoff * 2).min(self.max_backoff);
+    }
+
+    /// 溢出缓冲区超出上限时丢弃最旧的数据
+    fn trim_spill(&self, state: &mut TcpState) -> io::Result<()> {
+        let mut dropped = 0;
+        while state.unsent_bytes > self.max_spill_bytes {
+            match state.unsent.pop_front() {
+                Some(old) => {
+                    state.unsent_bytes -= old.len();
+                    dropped += old.len();
+                }
+                None => break,
+            }
+        }
+        if dropped == 0 {
+            return Ok(());
+        }
+        state.dropped_bytes += dropped as u64;
+        Err(io::Error::other(format!(
+            "tcp sink disconnected and spill buffer full, dropped {} bytes",
+            dropped
+        )))
+    }
+}
+
+impl Sink for TcpSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        let mut state = self.lock_state()?;
+        let reconnected = self.ensure_connected(&mut state);
+        state.unsent_bytes += data.len();
+        state.unsent.push_back(data.to_vec());
+        let result = if state.stream.is_none() {
+            self.trim_spill(&mut state)
+        } else if state.unsent_bytes >= TCP_BUFFER_BYTES && Self::send(&mut state).is_err() {
+            // 未送出的记录留在溢出缓冲区，连接错误由刷新上报，避免重试策略重复写入
+            self.disconnect(&mut state);
+            self.trim_spill(&mut state)
+        } else {
+            Ok(())
+        };
+        reconnected.and(result)
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        let mut result = Ok(());
+        for item in data {
+            if let Err(err) = self.write(item) {
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    fn batch_compression(&self) -> Option<BatchCompression> {
+        self.compression
+    }
+
+    fn write_compressed(&self, frame: &[u8], _records: usize) -> io::Result<()> {
+        self.write(frame)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        let mut state = self.lock_state()?;
+        self.ensure_connected(&mut state)?;
+        if state.stream.is_none() {
+            if state.unsent.is_empty() {
+                return Ok(());
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                format!(
+                    "tcp sink disconnected, {} bytes spilled",
+                    state.unsent_bytes
+                ),
+            ));
+        }
+        if let Err(err) = Self::send(&mut state) {
+            self.disconnect(&mut state);
+            self.trim_spill(&mut state)?;
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    fn health_check(&self) -> io::Result<()> {
+        let mut state = self.lock_state()?;
+        self.ensure_connected(&mut state)?;
+        if state.stream.is_some() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "tcp sink not connected",
+            ))
+        }
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.flush()?;
+        let mut state = self.lock_state()?;
+        if let Some(stream) = state.stream.take() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+        Ok(())
+    }
+}
+
+/// 在工作线程上执行的输出目标操作
+type TimeoutJob = Box<dyn FnOnce() + Send>;
+
+/// 超时输出目标装饰器
+///
+/// 内部输出目标的操作在专用工作线程上执行，调用方最多等待设定的时限，
+/// 超时返回 [`io::ErrorKind::TimedOut`] 错误（经诊断通道上报），而不是让消费者线程无限期挂起，
+/// 例如写入卡死的 NFS 挂载点。超时的操作仍在工作线程上继续，完成前后续调用立即返回超时错误。
+/// 写入的数据需复制一份交给工作线程。
+pub struct TimeoutSink<S> {
+    inner: Arc<S>,
+    write_timeout: Duration,
+    flush_timeout: Duration,
+    jobs: std::sync::mpsc::Sender<TimeoutJob>,
+    busy: Arc<AtomicBool>,
+}
+
+impl<S: Sink + 'static> TimeoutSink<S> {
+    /// 包装输出目标，写入与刷新均使用同一时限
+    pub fn new(inner: S, timeout: Duration) -> io::Result<Self> {
+        let (jobs, rx) = std::sync::mpsc::channel::<TimeoutJob>();
+        std::thread::Builder::new()
+            .name("nanolog-timeout-sink".to_string())
+            .spawn(move || {
+                for job in rx {
+                    job();
+                }
+            })?;
+        Ok(Self {
+            inner: Arc::new(inner),
+            write_timeout: timeout,
+            flush_timeout: timeout,
+            jobs,
+            busy: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// 单独设置刷新（含关闭）的时限
+    pub fn with_flush_timeout(mut self, timeout: Duration) -> Self {
+        self.flush_timeout = timeout;
+        self
+    }
+
+    /// 获取内部输出目标
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// 在工作线程上执行操作并在时限内等待结果
+    fn run<F>(&self, timeout: Duration, operation: &'static str, op: F) -> io::Result<()>
+    where
+        F: FnOnce(&S) -> io::Result<()> + Send + 'static,
+    {
+        if self.busy.swap(true, Ordering::AcqRel) {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "sink still blocked by a previous operation",
+            ));
+        }
+
+        let (tx, rx) = std::sync::mpsc::sync_channel(1);
+        let inner = self.inner.clone();
+        let busy = self.busy.clone();
+        let job: TimeoutJob = Box::new(move || {
+            let result = op(&inner);
+            busy.store(false, Ordering::Release);
+            let _ = tx.send(result);
+        });
+        if self.jobs.send(job).is_err() {
+            self.busy.store(false, Ordering::Release);
+            return Err(io::Error::other("timeout sink worker exited"));
+        }
+
+        match rx.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("sink {} exceeded {:?}", operation, timeout),
+            )),
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                Err(io::Error::other("timeout sink worker exited"))
+            }
+        }
+    }
+}
+
+impl<S: Sink + 'static> Sink for TimeoutSink<S> {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        let data = data.to_vec();
+        self.run(self.write_timeout, "write", move |sink| sink.write(&data))
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        let data = data.to_vec();
+        self.run(self.write_timeout, "write", move |sink| {
+            sink.write_batch(&data)
+        })
+    }
+
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        let meta = meta.to_vec();
+        let data = data.to_vec();
+        self.run(self.write_timeout, "write", move |sink| {
+            sink.write_records(&meta, &data)
+        })
+    }
+
+    fn stage_record(&self, record: &Record) {
+        // 内部目标仍被超时的操作占用时不暂存（随后的写入同样以超时失败），避免消费者线程阻塞在其锁上
+        if !self.busy.load(Ordering::Acquire) {
+            self.inner.stage_record(record);
+        }
+    }
+
+    fn batch_compression(&self) -> Option<BatchCompression> {
+        self.inner.batch_compression()
+    }
+
+    fn encode_batch(&self, data: &[Vec<u8>], out: &mut Vec<u8>) {
+        self.inner.encode_batch(data, out)
+    }
+
+    fn write_compressed(&self, frame: &[u8], records: usize) -> io::Result<()> {
+        let frame = frame.to_vec();
+        self.run(self.write_timeout, "write", move |sink| {
+            sink.write_compressed(&frame, records)
+        })
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.run(self.flush_timeout, "flush", |sink| sink.flush())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.run(self.flush_timeout, "shutdown", |sink| sink.shutdown())
+    }
+
+    fn coalesce_window(&self) -> Duration {
+        self.inner.coalesce_window()
+    }
+
+    fn health_check(&self) -> io::Result<()> {
+        self.run(self.write_timeout, "health check", |sink| {
+            sink.health_check()
+        })
+    }
+}
+
+/// 按优先级排列的后备输出目标链（如网络 → 本地文件 → 标准错误）
+///
+/// 写入当前活动的目标，失败时依次尝试链中后续目标。活动目标连续失败达到阈值后降级到下一个目标；
+/// 降级期间按探测间隔对更高优先级的目标执行 [`Sink::health_check`]，成功即升级回该目标。
+/// 降级与升级经诊断通道上报。
+pub struct FallbackSink {
+    sinks: Vec<Arc<dyn Sink>>,
+    failure_threshold: u32,
+    probe_interval: Duration,
+    diagnostics: Arc<dyn DiagnosticHandler>,
+    state: Mutex<FallbackState>,
+}
+
+/// 后备链状态
+struct FallbackState {
+    active: usize,
+    failures: u32,
+    next_probe: Instant,
+}
+
+impl FallbackSink {
+    /// 按优先级从高到低创建后备链
+    pub fn new(sinks: Vec<Arc<dyn Sink>>) -> Self {
+        Self {
+            sinks,
+            failure_threshold: 3,
+            probe_interval: Duration::from_secs(5),
+            diagnostics: Arc::new(StderrDiagnostics),
+            state: Mutex::new(FallbackState {
+                active: 0,
+                failures: 0,
+                next_probe: Instant::now(),
+            }),
+        }
+    }
+
+    /// 设置降级前允许的连续失败次数（默认 3）
+    pub fn with_failure_threshold(mut self, failures: u32) -> Self {
+        self.failure_threshold = failures.max(1);
+        self
+    }
+
+    /// 设置降级期间健康检查的间隔（默认 5 秒）
+    pub fn with_probe_interval(mut self, interval: Duration) -> Self {
+        self.probe_interval = interval;
+        self
+    }
+
+    /// 设置降级与升级的诊断处理器（默认输出到标准错误）
+    pub fn with_diagnostics(mut self, handler: Arc<dyn DiagnosticHandler>) -> Self {
+        self.diagnostics = handler;
+        self
+    }
+
+    /// 当前活动目标在链中的位置
+    pub fn active(&self) -> usize {
+        self.lock_state().map_or(0, |state| state.active)
+    }
+
+    fn lock_state(&self) -> io::Result<std::sync::MutexGuard<'_, FallbackState>> {
+        self.state
+            .lock()
+            .map_err(|_| io::Error::other("fallback sink state poisoned"))
+    }
+
+    /// 从活动目标起依次执行操作，直到某个目标成功
+    fn run(&self, op: impl Fn(&dyn Sink) -> io::Result<()>) -> io::Result<()> {
+        let mut state = self.lock_state()?;
+        self.probe(&mut state);
+        let mut last_err = io::Error::new(io::ErrorKind::NotFound, "fallback chain is empty");
+        for index in state.active..self.sinks.len() {
+            match op(self.sinks[index].as_ref()) {
+                Ok(()) => {
+                    if index == state.active {
+                        state.failures = 0;
+                    }
+                    return Ok(());
+                }
+                Err(err) => {
+                    if index == state.active {
+                        state.failures += 1;
+                        if state.failures >= self.failure_threshold && index + 1 < self.sinks.len()
+                        {
+                            state.active = index + 1;
+                            state.failures = 0;
+                            state.next_probe = Instant::now() + self.probe_interval;
+                            self.diagnostics.handle(&Diagnostic::SinkDemoted {
+                                from: index,
+                                to: index + 1,
+                                error: err.to_string(),
+                            });
+                        }
+                    }
+                    last_err = err;
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    /// 降级期间到达探测时间时，检查更高优先级的目标并升级到第一个健康的目标
+    fn probe(&self, state: &mut FallbackState) {
+        if state.active == 0 || Instant::now() < state.next_probe {
+            return;
+        }
+        state.next_probe = Instant::now() + self.probe_interval;
+        if let Some(index) = (0..state.active).find(|&i| self.sinks[i].health_check().is_ok()) {
+            self.diagnostics.handle(&Diagnostic::SinkPromoted {
+                from: state.active,
+                to: index,
+            });
+            state.active = index;
+            state.failures = 0;
+        }
+    }
+}
+
+impl Sink for FallbackSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        self.run(|sink| sink.write(data))
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        self.run(|sink| sink.write_batch(data))
+    }
+
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        self.run(|sink| sink.write_records(meta, data))
+    }
+
+    fn stage_record(&self, record: &Record) {
+        // 只暂存到活动目标：同一批次内降级时，后续目标中带独立格式化器的子目标收不到这批记录
+        if let Some(sink) = self.sinks.get(self.active()) {
+            sink.stage_record(record);
+        }
+    }
+
+    fn batch_compression(&self) -> Option<BatchCompression> {
+        self.sinks
+            .get(self.active())
+            .and_then(|sink| sink.batch_compression())
+    }
+
+    fn encode_batch(&self, data: &[Vec<u8>], out: &mut Vec<u8>) {
+        match self.sinks.get(self.active()) {
+            Some(sink) => sink.encode_batch(data, out),
+            None => data.iter().for_each(|record| out.extend_from_slice(record)),
+        }
+    }
+
+    fn write_compressed(&self, frame: &[u8], records: usize) -> io::Result<()> {
+        // 压缩帧按活动目标的编码组装，不接受压缩批次的后续目标写入失败
+        self.run(|sink| sink.write_compressed(frame, records))
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        // 切换前写入的目标可能仍有缓冲数据，全部刷新，仅返回活动目标的结果
+        let active = self.active();
+        let mut result = Ok(());
+        for (index, sink) in self.sinks.iter().enumerate() {
+            let flushed = sink.flush();
+            if index == active {
+                result = flushed;
+            }
+        }
+        result
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        let mut result = Ok(());
+        for sink in &self.sinks {
+            if let Err(err) = sink.shutdown() {
+                result = result.and(Err(err));
+            }
+        }
+        result
+    }
+
+    fn coalesce_window(&self) -> Duration {
+        self.sinks
+            .get(self.active())
+            .map(|sink| sink.coalesce_window())
+            .unwrap_or_default()
+    }
+
+    fn health_check(&self) -> io::Result<()> {
+        let mut last_err = io::Error::new(io::ErrorKind::NotFound, "fallback chain is empty");
+        for sink in &self.sinks {
+            match sink.health_check() {
+                Ok(()) => return Ok(()),
+                Err(err) => last_err = err,
+            }
+        }
+        Err(last_err)
+    }
+}
+
+/// 主备切换输出目标（如 TCP 采集器 → 本地文件）
+///
+/// 主目标写入失败时立即以同一数据改写后备目标并切换过去，调用方看不到这次失败；切换期间每隔
+/// 探测间隔以实际写入重试一次主目标，成功即切回。切换与恢复计入计数器并经诊断通道上报。
+///
+/// 与 [`FallbackSink`] 不同，这里不需要连续失败达到阈值，也不依赖 [`Sink::health_check`]。
+///
+/// ```
+/// use nanolog_rs::{FailoverSink, MemorySink};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let sink = FailoverSink::new(Arc::new(MemorySink::new()), Arc::new(MemorySink::new()))
+///     .with_probe_interval(Duration::from_secs(10));
+/// assert_eq!(sink.failovers(), 0);
+/// ```
+pub struct FailoverSink {
+    primary: Arc<dyn Sink>,
+    fallback: Arc<dyn Sink>,
+    probe_interval: Duration,
+    diagnostics: Arc<dyn DiagnosticHandler>,
+    /// 切换期间下一次重试主目标的时间（`None` 表示正在使用主目标）
+    state: Mutex<Option<Instant>>,
+    failovers: std::sync::atomic::AtomicU64,
+    recoveries: std::sync::atomic::AtomicU64,
+}
+
+impl FailoverSink {
+    /// 创建主备切换目标
+    pub fn new(primary: Arc<dyn Sink>, fallback: Arc<dyn Sink>) -> Self {
+        Self {
+            primary,
+            fallback,
+            probe_interval: Duration::from_secs(5),
+            diagnostics: Arc::new(StderrDiagnostics),
+            state: Mutex::new(None),
+            failovers: std::sync::atomic::AtomicU64::new(0),
+            recoveries: std::sync::atomic::AtomicU64::new(0),
+        }
+    }
+
+    /// 设置切换期间重试主目标的间隔（默认 5 秒）
+    pub fn with_probe_interval(mut self, interval: Duration) -> Self {
+        self.probe_interval = interval;
+        self
+    }
+
+    /// 设置切换与恢复的诊断处理器（默认输出到标准错误）
+    pub fn with_diagnostics(mut self, handler: Arc<dyn DiagnosticHandler>) -> Self {
+        self.diagnostics = handler;
+        self
+    }
+
+    /// 当前是否正在使用后备目标
+    pub fn is_failed_over(&self) -> bool {
+        self.lock_state().is_some()
+    }
+
+    /// 从主目标切换到后备目标的次数
+    pub fn failovers(&self) -> u64 {
+        self.failovers.load(Ordering::Relaxed)
+    }
+
+    /// 从后备目标切回主目标的次数
+    pub fn recoveries(&self) -> u64 {
+        self.recoveries.load(Ordering::Relaxed)
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, Option<Instant>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 下一次写入将使用的目标（切换期间到达探测时间时为主目标）
+    fn target(&self) -> &Arc<dyn Sink> {
+        let probing = self
+            .lock_state()
+            .map_or(true, |next_probe| Instant::now() >= next_probe);
+        if probing {
+            &self.primary
+        } else {
+            &self.fallback
+        }
+    }
+
+    /// 写入主目标（切换期间到达探测时间时重试），失败时改写后备目标
+    fn run(&self, op: impl Fn(&dyn Sink) -> io::Result<()>) -> io::Result<()> {
+        let mut state = self.lock_state();
+        let now = Instant::now();
+        if state.is_some_and(|next_probe| now < next_probe) {
+            return op(self.fallback.as_ref());
+        }
+        match op(self.primary.as_ref()) {
+            Ok(()) => {
+                if state.take().is_some() {
+                    self.recoveries.fetch_add(1, Ordering::Relaxed);
+                    self.diagnostics
+                        .handle(&Diagnostic::SinkPromoted { from: 1, to: 0 });
+                }
+                Ok(())
+            }
+            Err(err) => {
+                if state.replace(now + self.probe_interval).is_none() {
+                    self.failovers.fetch_add(1, Ordering::Relaxed);
+                    self.diagnostics.handle(&Diagnostic::SinkDemoted {
+                        from: 0,
+                        to: 1,
+                        error: err.to_string(),
+                    });
+                }
+                op(self.fallback.as_ref())
+            }
+        }
+    }
+}
+
+impl Sink for FailoverSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        self.run(|sink| sink.write(data))
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        self.run(|sink| sink.write_batch(data))
+    }
+
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        self.run(|sink| sink.write_records(meta, data))
+    }
+
+    fn stage_record(&self, record: &Record) {
+        // 只暂存到下一次写入将使用的目标：同一批次内切换时，后备目标中带独立格式化器的子目标收不到这批记录
+        self.target().stage_record(record);
+    }
+
+    fn batch_compression(&self) -> Option<BatchCompression> {
+        self.target().batch_compression()
+    }
+
+    fn encode_batch(&self, data: &[Vec<u8>], out: &mut Vec<u8>) {
+        self.target().encode_batch(data, out)
+    }
+
+    fn write_compressed(&self, frame: &[u8], records: usize) -> io::Result<()> {
+        // 压缩帧按下一次写入目标的编码组装，切换到不接受压缩批次的目标时写入失败
+        self.run(|sink| sink.write_compressed(frame, records))
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        // 两个目标都可能有缓冲数据，全部刷新，仅返回活动目标的结果
+        let primary = self.primary.flush();
+        let fallback = self.fallback.flush();
+        if self.is_failed_over() {
+            fallback
+        } else {
+            primary
+        }
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        let primary = self.primary.shutdown();
+        let fallback = self.fallback.shutdown();
+        primary.and(fallback)
+    }
+
+    fn coalesce_window(&self) -> Duration {
+        if self.is_failed_over() {
+            self.fallback.coalesce_window()
+        } else {
+            self.primary.coalesce_window()
+        }
+    }
+
+    fn health_check(&self) -> io::Result<()> {
+        self.primary
+            .health_check()
+            .or_else(|_| self.fallback.health_check())
+    }
+}
+
+/// journald 原生协议套接字路径
+#[cfg(unix)]
+pub const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// journald 输出目标
+///
+/// 将每条已格式化的记录作为一个数据报发送到 journald 原生协议套接字，
+/// 需搭配 [`JournaldFormatter`](crate::format::JournaldFormatter) 使用。
+#[cfg(unix)]
+pub struct JournaldSink {
+    socket: std::os::unix::net::UnixDatagram,
+    path: std::path::PathBuf,
+}
+
+#[cfg(unix)]
+impl JournaldSink {
+    /// 连接默认的 journald 套接字
+    pub fn new() -> io::Result<Self> {
+        Self::with_path(JOURNALD_SOCKET)
+    }
+
+    /// 使用指定的套接字路径
+    pub fn with_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self {
+            socket: std::os::unix::net::UnixDatagram::unbound()?,
+            path: path.as_ref().to_path_buf(),
+        })
+    }
+
+    /// 当前进程是否运行在 journald 之下（标准错误已连接到日志流且套接字存在）
+    pub fn is_available() -> bool {
+        std::env::var_os("JOURNAL_STREAM").is_some() && Path::new(JOURNALD_SOCKET).exists()
+    }
+}
+
+#[cfg(unix)]
+impl Sink for JournaldSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        self.socket.send_to(data, &self.path)?;
+        Ok(())
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        // 每条记录必须是独立的数据报
+        for entry in data {
+            self.write(entry)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+/*!
+预取的记录槽位。
+
+热循环中的调用点可预先取得 [`RecordSlot`]（通常经 [`record_slot!`](crate::record_slot)），
+之后反复以闭包填充并发布：级别、目标、文件与行号在取得时确定，消息缓冲区在槽位与环形队列之间
+轮转复用（发布时取回槽位中已写出记录的缓冲区），稳定状态下每次记录不分配；级别未启用时闭包不执行。
+
+```
+use nanolog_rs::{AsyncLogger, Level, NullSink, RecordSlot};
+use std::sync::Arc;
+
+let logger = AsyncLogger::builder().sink(Arc::new(NullSink::new())).build()?;
+let mut slot = RecordSlot::new(&logger, Level::Info, "worker", file!(), line!());
+for i in 0..1000u64 {
+    slot.log(|r| {
+        r.field("i", i);
+        write!(r, "tick {}", i)
+    })?;
+}
+# logger.shutdown()?;
+# Ok::<(), nanolog_rs::error::Error>(())
+```
+
+附加字段时字段列表仍按记录分配。
+*/
+
+use crate::error::Error;
+use crate::field::{Field, Value};
+use crate::logger::AsyncLogger;
+use crate::record::pooled_message;
+use crate::{Level, Record};
+use std::fmt;
+
+/// 预取的记录槽位
+pub struct RecordSlot<'a> {
+    logger: &'a AsyncLogger,
+    level: Level,
+    target: &'static str,
+    file: &'static str,
+    line: u32,
+    /// 下一条记录使用的消息缓冲区
+    message: String,
+}
+
+impl<'a> RecordSlot<'a> {
+    /// 为 `logger` 上的一个调用点取得槽位
+    pub fn new(
+        logger: &'a AsyncLogger,
+        level: Level,
+        target: &'static str,
+        file: &'static str,
+        line: u32,
+    ) -> Self {
+        Self {
+            logger,
+            level,
+            target,
+            file,
+            line,
+            message: pooled_message(),
+        }
+    }
+
+    /// 槽位的级别与目标当前是否启用
+    pub fn is_enabled(&self) -> bool {
+        self.logger.enabled(self.level, self.target)
+    }
+
+    /// 以 `fill` 填充一条记录并发布；`fill` 返回错误时保留已写入的部分
+    pub fn log<F>(&mut self, fill: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut SlotRecord) -> fmt::Result,
+    {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let mut message = std::mem::take(&mut self.message);
+        message.clear();
+        let mut record = SlotRecord(Record::new(
+            self.level,
+            self.target,
+            self.file,
+            self.line,
+            message,
+        ));
+        let _ = fill(&mut record);
+        let result = self.logger.log(record.0);
+        // 发布时队列槽位中已写出记录的消息缓冲区已归还本线程的缓冲区池
+        self.message = pooled_message();
+        result
+    }
+}
+
+/// 正在填充的记录：消息可经 `write!` 写入，字段经 [`field`](Self::field) 附加
+pub struct SlotRecord(Record);
+
+impl SlotRecord {
+    /// 追加消息文本
+    pub fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.message_mut().push_str(s);
+        Ok(())
+    }
+
+    /// 追加格式化的消息文本（供 `write!` 使用，无需引入 `fmt::Write`）
+    pub fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        fmt::Write::write_fmt(self.0.message_mut(), args)
+    }
+
+    /// 附加结构化字段
+    pub fn field(&mut self, key: &'static str, value: impl Into<Value>) -> &mut Self {
+        self.0.fields_mut().push(Field::new(key, value));
+        self
+    }
+}
+
+impl fmt::Write for SlotRecord {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        SlotRecord::write_str(self, s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::MemorySink;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_slot_logs_repeatedly() {
+        let sink = Arc::new(MemorySink::new());
+        let logger = AsyncLogger::builder()
+            .formatter(Arc::new(crate::format::SimpleFormatter::new()))
+            .sink(sink.clone())
+            .build()
+            .unwrap();
+        let mut slot = crate::record_slot!(&logger, Level::Info);
+        for i in 0..3u64 {
+            assert!(slot.log(|r| write!(r, "tick {}", i)).is_ok());
+        }
+        let mut skipped = crate::record_slot!(&logger, Level::Debug);
+        assert!(!skipped.is_enabled());
+        assert!(skipped.log(|_| unreachable!()).is_ok());
+        assert!(logger.flush().is_ok());
+
+        assert_eq!(
+            sink.get_content(),
+            b"[INFO] tick 0\n[INFO] tick 1\n[INFO] tick 2\n"
+        );
+        assert!(logger.shutdown().is_ok());
+    }
+}
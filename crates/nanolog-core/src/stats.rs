@@ -0,0 +1,391 @@
+/*!
+运行时统计信息。
+
+以原子计数器记录消费者线程的刷新行为，调用方可随时获取快照，
+用真实数据而非猜测来调整批处理参数。
+*/
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// 刷新原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlushReason {
+    /// 消费者处理完一批事件
+    BatchEnd,
+    /// 距上次刷新超过 `flush_interval`
+    Timer,
+    /// 待刷新字节数达到阈值
+    SizeThreshold,
+    /// 写入了不低于 `flush_on` 阈值级别的记录
+    LevelThreshold,
+    /// 调用方显式调用 `flush()`
+    Explicit,
+    /// 日志器关闭
+    Shutdown,
+}
+
+impl FlushReason {
+    /// 所有刷新原因
+    pub const ALL: [FlushReason; 6] = [
+        FlushReason::BatchEnd,
+        FlushReason::Timer,
+        FlushReason::SizeThreshold,
+        FlushReason::LevelThreshold,
+        FlushReason::Explicit,
+        FlushReason::Shutdown,
+    ];
+
+    /// 获取原因的字符串表示
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlushReason::BatchEnd => "batch_end",
+            FlushReason::Timer => "timer",
+            FlushReason::SizeThreshold => "size_threshold",
+            FlushReason::LevelThreshold => "level_threshold",
+            FlushReason::Explicit => "explicit",
+            FlushReason::Shutdown => "shutdown",
+        }
+    }
+
+    #[inline]
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// 单个刷新原因的统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlushReasonStats {
+    /// 刷新次数
+    pub count: u64,
+    /// 刷新的字节总数
+    pub bytes: u64,
+}
+
+/// 刷新统计快照
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlushStatsSnapshot {
+    /// 写入输出目标的字节总数
+    pub written_bytes: u64,
+    /// 已写入但尚未刷新的字节数
+    pub pending_bytes: u64,
+    /// 批次压缩统计
+    pub compression: CompressionStats,
+    by_reason: [FlushReasonStats; 6],
+}
+
+/// 批次压缩统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompressionStats {
+    /// 压缩的批次数
+    pub batches: u64,
+    /// 压缩前的字节数
+    pub input_bytes: u64,
+    /// 压缩后的字节数
+    pub output_bytes: u64,
+    /// 消费者线程上的压缩耗时
+    pub cpu_time: Duration,
+}
+
+impl CompressionStats {
+    /// 压缩比（压缩前 / 压缩后），未压缩过时为 1
+    pub fn ratio(&self) -> f64 {
+        if self.output_bytes == 0 {
+            return 1.0;
+        }
+        self.input_bytes as f64 / self.output_bytes as f64
+    }
+
+    /// 每压缩 1MB 输入的平均耗时
+    pub fn time_per_mib(&self) -> Duration {
+        if self.input_bytes == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(
+            self.cpu_time.as_secs_f64() * (1 << 20) as f64 / self.input_bytes as f64,
+        )
+    }
+}
+
+impl FlushStatsSnapshot {
+    /// 获取指定原因的统计
+    pub fn reason(&self, reason: FlushReason) -> FlushReasonStats {
+        self.by_reason[reason.index()]
+    }
+
+    /// 刷新总次数
+    pub fn total_flushes(&self) -> u64 {
+        self.by_reason.iter().map(|s| s.count).sum()
+    }
+
+    /// 平均每次刷新的字节数
+    pub fn avg_bytes_per_flush(&self) -> f64 {
+        let flushes = self.total_flushes();
+        if flushes == 0 {
+            return 0.0;
+        }
+        let bytes: u64 = self.by_reason.iter().map(|s| s.bytes).sum();
+        bytes as f64 / flushes as f64
+    }
+}
+
+/// 刷新统计（线程安全）
+#[derive(Debug, Default)]
+pub struct FlushStats {
+    written_bytes: AtomicU64,
+    pending_bytes: AtomicU64,
+    counts: [AtomicU64; 6],
+    bytes: [AtomicU64; 6],
+    compressed_batches: AtomicU64,
+    compress_input_bytes: AtomicU64,
+    compress_output_bytes: AtomicU64,
+    compress_nanos: AtomicU64,
+}
+
+impl FlushStats {
+    /// 创建新的刷新统计
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次写入
+    #[inline]
+    pub(crate) fn record_write(&self, bytes: usize) {
+        self.written_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        self.pending_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// 记录一次批次压缩
+    #[inline]
+    pub(crate) fn record_compression(&self, input: usize, output: usize, elapsed: Duration) {
+        self.compressed_batches.fetch_add(1, Ordering::Relaxed);
+        self.compress_input_bytes
+            .fetch_add(input as u64, Ordering::Relaxed);
+        self.compress_output_bytes
+            .fetch_add(output as u64, Ordering::Relaxed);
+        self.compress_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// 记录一次刷新，返回本次刷新的字节数
+    #[inline]
+    pub(crate) fn record_flush(&self, reason: FlushReason) -> u64 {
+        let flushed = self.pending_bytes.swap(0, Ordering::Relaxed);
+        self.counts[reason.index()].fetch_add(1, Ordering::Relaxed);
+        self.bytes[reason.index()].fetch_add(flushed, Ordering::Relaxed);
+        flushed
+    }
+
+    /// 获取当前待刷新的字节数
+    #[inline]
+    pub fn pending_bytes(&self) -> u64 {
+        self.pending_bytes.load(Ordering::Relaxed)
+    }
+
+    /// 获取统计快照
+    pub fn snapshot(&self) -> FlushStatsSnapshot {
+        let mut by_reason = [FlushReasonStats::default(); 6];
+        for reason in FlushReason::ALL {
+            let i = reason.index();
+            by_reason[i] = FlushReasonStats {
+                count: self.counts[i].load(Ordering::Relaxed),
+                bytes: self.bytes[i].load(Ordering::Relaxed),
+            };
+        }
+        FlushStatsSnapshot {
+            written_bytes: self.written_bytes.load(Ordering::Relaxed),
+            pending_bytes: self.pending_bytes.load(Ordering::Relaxed),
+            compression: CompressionStats {
+                batches: self.compressed_batches.load(Ordering::Relaxed),
+                input_bytes: self.compress_input_bytes.load(Ordering::Relaxed),
+                output_bytes: self.compress_output_bytes.load(Ordering::Relaxed),
+                cpu_time: Duration::from_nanos(self.compress_nanos.load(Ordering::Relaxed)),
+            },
+            by_reason,
+        }
+    }
+
+    /// 重置统计
+    pub fn reset(&self) {
+        self.written_bytes.store(0, Ordering::Relaxed);
+        self.pending_bytes.store(0, Ordering::Relaxed);
+        for i in 0..FlushReason::ALL.len() {
+            self.counts[i].store(0, Ordering::Relaxed);
+            self.bytes[i].store(0, Ordering::Relaxed);
+        }
+        for counter in [
+            &self.compressed_batches,
+            &self.compress_input_bytes,
+            &self.compress_output_bytes,
+            &self.compress_nanos,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 消费者停靠/唤醒统计快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WakeStatsSnapshot {
+    /// 停靠次数
+    pub parks: u64,
+    /// 被发布者唤醒的次数
+    pub wakeups: u64,
+    /// 平均唤醒延迟（从发布者通知到消费者恢复运行）
+    pub avg_wake_latency: Duration,
+    /// 最大唤醒延迟
+    pub max_wake_latency: Duration,
+}
+
+/// 消费者停靠/唤醒统计（线程安全）
+#[derive(Debug, Default)]
+pub struct WakeStats {
+    parks: AtomicU64,
+    wakeups: AtomicU64,
+    total_latency_ns: AtomicU64,
+    max_latency_ns: AtomicU64,
+}
+
+impl WakeStats {
+    /// 创建新的唤醒统计
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次停靠
+    #[inline]
+    pub(crate) fn record_park(&self) {
+        self.parks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次唤醒及其延迟
+    #[inline]
+    pub(crate) fn record_wake(&self, latency: Duration) {
+        let ns = latency.as_nanos() as u64;
+        self.wakeups.fetch_add(1, Ordering::Relaxed);
+        self.total_latency_ns.fetch_add(ns, Ordering::Relaxed);
+        self.max_latency_ns.fetch_max(ns, Ordering::Relaxed);
+    }
+
+    /// 获取统计快照
+    pub fn snapshot(&self) -> WakeStatsSnapshot {
+        let wakeups = self.wakeups.load(Ordering::Relaxed);
+        let total = self.total_latency_ns.load(Ordering::Relaxed);
+        WakeStatsSnapshot {
+            parks: self.parks.load(Ordering::Relaxed),
+            wakeups,
+            avg_wake_latency: Duration::from_nanos(total.checked_div(wakeups).unwrap_or(0)),
+            max_wake_latency: Duration::from_nanos(self.max_latency_ns.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// 输出目标写入的计时钩子
+///
+/// 消费者线程围绕每次批量写入（含重试）调用，实现应尽快返回。可接入自有的性能分析器，
+/// 或使用内置的 [`WriteTimings`] 汇总，在生产环境中定位慢的输出目标。
+pub trait WriteProfiler: Send + Sync {
+    /// 写入开始：输出序号（0 为主输出）与本次写入的字节数（压缩时为压缩后字节数）
+    fn on_write_start(&self, _output: usize, _bytes: usize) {}
+
+    /// 写入结束：耗时与是否成功
+    fn on_write_end(&self, output: usize, bytes: usize, elapsed: Duration, ok: bool);
+}
+
+/// 单路输出的写入耗时统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteTimingStats {
+    /// 写入次数
+    pub writes: u64,
+    /// 失败次数
+    pub failures: u64,
+    /// 写入字节数
+    pub bytes: u64,
+    /// 累计耗时
+    pub total_time: Duration,
+    /// 单次最长耗时
+    pub max_time: Duration,
+}
+
+impl WriteTimingStats {
+    /// 平均每次写入耗时
+    pub fn avg_time(&self) -> Duration {
+        self.total_time
+            .checked_div(self.writes.min(u64::from(u32::MAX)) as u32)
+            .unwrap_or_default()
+    }
+}
+
+/// 按输出汇总写入耗时的 [`WriteProfiler`]
+#[derive(Debug, Default)]
+pub struct WriteTimings {
+    outputs: Mutex<Vec<WriteTimingStats>>,
+}
+
+impl WriteTimings {
+    /// 创建空的写入耗时统计
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取各路输出的统计快照（按输出序号）
+    pub fn snapshot(&self) -> Vec<WriteTimingStats> {
+        self.outputs
+            .lock()
+            .map(|outputs| outputs.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl WriteProfiler for WriteTimings {
+    fn on_write_end(&self, output: usize, bytes: usize, elapsed: Duration, ok: bool) {
+        let Ok(mut outputs) = self.outputs.lock() else {
+            return;
+        };
+        if outputs.len() <= output {
+            outputs.resize(output + 1, WriteTimingStats::default());
+        }
+        let stats = &mut outputs[output];
+        stats.writes += 1;
+        stats.failures += u64::from(!ok);
+        stats.bytes += bytes as u64;
+        stats.total_time += elapsed;
+        stats.max_time = stats.max_time.max(elapsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flush_stats_accounting() {
+        let stats = FlushStats::new();
+        stats.record_write(100);
+        stats.record_write(50);
+        assert_eq!(stats.record_flush(FlushReason::BatchEnd), 150);
+        stats.record_write(10);
+        assert_eq!(stats.record_flush(FlushReason::Explicit), 10);
+        assert_eq!(stats.record_flush(FlushReason::Explicit), 0);
+
+        let snap = stats.snapshot();
+        assert_eq!(snap.written_bytes, 160);
+        assert_eq!(snap.pending_bytes, 0);
+        assert_eq!(
+            snap.reason(FlushReason::BatchEnd),
+            FlushReasonStats {
+                count: 1,
+                bytes: 150
+            }
+        );
+        assert_eq!(snap.reason(FlushReason::Explicit).count, 2);
+        assert_eq!(snap.total_flushes(), 3);
+
+        stats.reset();
+        assert_eq!(stats.snapshot(), FlushStatsSnapshot::default());
+    }
+}
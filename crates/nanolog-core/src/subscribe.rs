@@ -0,0 +1,261 @@
+/*!
+实时日志订阅。
+
+管理界面的实时追踪端点（WebSocket、SSE 等）通过 [`AsyncLogger::subscribe`](crate::AsyncLogger::subscribe)
+接收匹配过滤器的已格式化记录。每个订阅者拥有独立的有界队列，满时丢弃最旧的记录，
+慢订阅者因此不会阻塞消费者线程。
+*/
+
+use crate::Record;
+use crate::filter::Filter;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// 订阅队列的默认容量
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// 有界通道（满时丢弃最旧的元素）
+struct Channel<T> {
+    state: Mutex<ChannelState<T>>,
+    ready: Condvar,
+    capacity: usize,
+}
+
+struct ChannelState<T> {
+    items: VecDeque<T>,
+    dropped: u64,
+    closed: bool,
+}
+
+impl<T> Channel<T> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(ChannelState {
+                items: VecDeque::new(),
+                dropped: 0,
+                closed: false,
+            }),
+            ready: Condvar::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ChannelState<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn send(&self, item: T) {
+        let mut state = self.lock();
+        if state.items.len() >= self.capacity {
+            state.items.pop_front();
+            state.dropped += 1;
+        }
+        state.items.push_back(item);
+        drop(state);
+        self.ready.notify_one();
+    }
+
+    fn close(&self) {
+        self.lock().closed = true;
+        self.ready.notify_all();
+    }
+}
+
+/// 订阅接收端
+///
+/// 日志器关闭且队列取空后，接收方法返回 `None`。丢弃接收端即取消订阅。
+pub struct Receiver<T> {
+    channel: Arc<Channel<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// 阻塞等待下一条记录
+    pub fn recv(&self) -> Option<T> {
+        let mut state = self.channel.lock();
+        loop {
+            if let Some(item) = state.items.pop_front() {
+                return Some(item);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self
+                .channel
+                .ready
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// 最多等待 `timeout`，超时返回 `None`
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<T> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.channel.lock();
+        loop {
+            if let Some(item) = state.items.pop_front() {
+                return Some(item);
+            }
+            let now = Instant::now();
+            if state.closed || now >= deadline {
+                return None;
+            }
+            state = self
+                .channel
+                .ready
+                .wait_timeout(state, deadline - now)
+                .map(|(state, _)| state)
+                .unwrap_or_else(|e| e.into_inner().0);
+        }
+    }
+
+    /// 立即返回队列中的下一条记录
+    pub fn try_recv(&self) -> Option<T> {
+        self.channel.lock().items.pop_front()
+    }
+
+    /// 因队列已满而丢弃的记录数
+    pub fn dropped(&self) -> u64 {
+        self.channel.lock().dropped
+    }
+
+    /// 日志器是否已关闭
+    pub fn is_closed(&self) -> bool {
+        self.channel.lock().closed
+    }
+}
+
+impl<T> Iterator for Receiver<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.recv()
+    }
+}
+
+/// 订阅者
+struct Subscriber {
+    filter: Filter,
+    channel: Arc<Channel<Arc<[u8]>>>,
+}
+
+/// 订阅者集合（由日志器与消费者线程共享）
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    /// 是否存在订阅者（供消费者快速跳过）
+    active: AtomicBool,
+    list: Mutex<Vec<Subscriber>>,
+}
+
+impl Subscribers {
+    fn lock(&self) -> MutexGuard<'_, Vec<Subscriber>> {
+        self.list.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 添加订阅者
+    pub(crate) fn subscribe(&self, filter: Filter, capacity: usize) -> Receiver<Arc<[u8]>> {
+        let channel = Arc::new(Channel::new(capacity));
+        self.lock().push(Subscriber {
+            filter,
+            channel: channel.clone(),
+        });
+        self.active.store(true, Ordering::Release);
+        Receiver { channel }
+    }
+
+    /// 是否存在订阅者
+    #[inline]
+    pub(crate) fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// 是否有订阅者匹配该记录
+    pub(crate) fn matches(&self, record: &Record) -> bool {
+        self.is_active()
+            && self
+                .lock()
+                .iter()
+                .any(|s| s.filter.enabled(record.level(), record.target()))
+    }
+
+    /// 将已格式化的记录投递给匹配的订阅者，并移除已取消的订阅
+    pub(crate) fn publish(&self, record: &Record, formatted: &[u8]) {
+        let mut list = self.lock();
+        list.retain(|s| Arc::strong_count(&s.channel) > 1);
+        if list.is_empty() {
+            self.active.store(false, Ordering::Release);
+            return;
+        }
+        let mut shared: Option<Arc<[u8]>> = None;
+        for subscriber in list.iter() {
+            if subscriber.filter.enabled(record.level(), record.target()) {
+                let item = shared.get_or_insert_with(|| Arc::from(formatted)).clone();
+                subscriber.channel.send(item);
+            }
+        }
+    }
+
+    /// 关闭全部订阅
+    pub(crate) fn close(&self) {
+        let mut list = self.lock();
+        for subscriber in list.drain(..) {
+            subscriber.channel.close();
+        }
+        self.active.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    fn record(level: Level, target: &'static str) -> Record {
+        Record::new(level, target, "t.rs", 1, String::new())
+    }
+
+    #[test]
+    fn test_drops_oldest_when_full() {
+        let subscribers = Subscribers::default();
+        let rx = subscribers.subscribe(Filter::new(Level::Info), 2);
+        for line in [&b"a"[..], b"b", b"c"] {
+            subscribers.publish(&record(Level::Info, "app"), line);
+        }
+        assert_eq!(rx.dropped(), 1);
+        assert_eq!(rx.try_recv().as_deref(), Some(&b"b"[..]));
+        assert_eq!(rx.try_recv().as_deref(), Some(&b"c"[..]));
+        assert!(rx.try_recv().is_none());
+    }
+
+    #[test]
+    fn test_filters_and_unsubscribes() {
+        let subscribers = Subscribers::default();
+        let db = subscribers.subscribe(
+            Filter::new(Level::Error).with_directive("app::db", Some(Level::Debug)),
+            8,
+        );
+        let all = subscribers.subscribe(Filter::new(Level::Trace), 8);
+
+        subscribers.publish(&record(Level::Debug, "app::db"), b"query");
+        subscribers.publish(&record(Level::Debug, "app::http"), b"request");
+        assert_eq!(db.try_recv().as_deref(), Some(&b"query"[..]));
+        assert!(db.try_recv().is_none());
+        assert_eq!(all.into_iter().take(2).count(), 2);
+
+        // 取消全部订阅后不再处于活动状态
+        drop(db);
+        subscribers.publish(&record(Level::Info, "app"), b"x");
+        assert!(!subscribers.is_active());
+    }
+
+    #[test]
+    fn test_close_wakes_receivers() {
+        let subscribers = Subscribers::default();
+        let rx = subscribers.subscribe(Filter::new(Level::Info), 8);
+        subscribers.close();
+        assert!(rx.is_closed());
+        assert!(rx.recv().is_none());
+        assert!(rx.recv_timeout(Duration::from_millis(1)).is_none());
+    }
+}
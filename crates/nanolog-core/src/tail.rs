@@ -0,0 +1,603 @@
+/*!
+实时日志追踪服务。
+
+基于 [`AsyncLogger::subscribe`] 通过 Server-Sent Events 或 WebSocket 推送日志流，
+浏览器开发工具无需登录主机即可追踪运行中的服务：
+
+```text
+GET /?level=debug&target=my_app::db      → text/event-stream，每条记录一个 `data:` 事件
+GET /?filter=info,hyper=warn             → 同上，使用完整的过滤指令
+GET / + Upgrade: websocket               → 每条记录一个文本帧
+```
+
+未指定过滤条件时推送 `info` 及以上级别。每个连接在独立线程上服务，
+订阅队列满时丢弃最旧的记录，慢客户端不会影响日志器。请求头有读取时限与长度上限，
+并发连接数超过上限时直接应答 503；跨域访问须通过 [`TailOptions::allow_origin`] 显式开启。
+仅用于受信任的网络环境。
+*/
+
+use crate::filter::Filter;
+use crate::logger::AsyncLogger;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// 无新记录时发送保活消息的间隔，同时用于发现已断开的客户端
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// RFC 6455 握手使用的固定 GUID
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// 实时追踪服务配置
+#[derive(Debug, Clone)]
+pub struct TailOptions {
+    allow_origin: Option<String>,
+    max_connections: usize,
+    read_timeout: Duration,
+    max_header_bytes: usize,
+}
+
+impl TailOptions {
+    /// 默认配置：不发送跨域头，最多 16 个并发连接，请求头 5 秒内读完且不超过 8 KiB
+    pub fn new() -> Self {
+        Self {
+            allow_origin: None,
+            max_connections: 16,
+            read_timeout: Duration::from_secs(5),
+            max_header_bytes: 8 * 1024,
+        }
+    }
+
+    /// 允许指定来源的浏览器页面跨域读取 SSE 流（`Access-Control-Allow-Origin`）
+    pub fn allow_origin(mut self, origin: impl Into<String>) -> Self {
+        self.allow_origin = Some(origin.into());
+        self
+    }
+
+    /// 设置并发连接上限，超出时应答 `503 Service Unavailable`
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = max;
+        self
+    }
+
+    /// 设置读取请求头的时限，超时未读完即断开
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// 设置请求头（请求行与各头部之和）的字节上限，超出时应答 `431`
+    pub fn max_header_bytes(mut self, bytes: usize) -> Self {
+        self.max_header_bytes = bytes;
+        self
+    }
+}
+
+impl Default for TailOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 实时追踪服务
+pub struct TailServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    accept: Option<JoinHandle<()>>,
+}
+
+impl TailServer {
+    /// 以默认配置绑定地址并开始服务
+    pub fn bind(logger: Arc<AsyncLogger>, addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::bind_with(logger, addr, TailOptions::new())
+    }
+
+    /// 以指定配置绑定地址并开始服务
+    pub fn bind_with(
+        logger: Arc<AsyncLogger>,
+        addr: impl ToSocketAddrs,
+        options: TailOptions,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_c = stop.clone();
+        let options = Arc::new(options);
+        let active = Arc::new(AtomicUsize::new(0));
+        let accept = std::thread::Builder::new()
+            .name("nanolog-tail".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if stop_c.load(Ordering::Acquire) {
+                        break;
+                    }
+                    let Ok(mut stream) = stream else { continue };
+                    if active.fetch_add(1, Ordering::AcqRel) >= options.max_connections {
+                        active.fetch_sub(1, Ordering::AcqRel);
+                        let _ = stream.set_write_timeout(Some(options.read_timeout));
+                        let _ = respond(
+                            &mut stream,
+                            "503 Service Unavailable",
+                            "too many tail connections",
+                        );
+                        continue;
+                    }
+                    let logger = logger.clone();
+                    let stop = stop_c.clone();
+                    let options = options.clone();
+                    let slot = active.clone();
+                    let spawned = std::thread::Builder::new()
+                        .name("nanolog-tail-conn".to_string())
+                        .spawn(move || {
+                            let _ = serve(&logger, stream, &stop, &options);
+                            slot.fetch_sub(1, Ordering::AcqRel);
+                        });
+                    if spawned.is_err() {
+                        active.fetch_sub(1, Ordering::AcqRel);
+                    }
+                }
+            })?;
+        Ok(Self {
+            addr,
+            stop,
+            accept: Some(accept),
+        })
+    }
+
+    /// 实际监听的地址
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 停止接受新连接；已建立的连接在下一次推送或保活时结束
+    pub fn shutdown(mut self) {
+        self.stop_accepting();
+    }
+
+    fn stop_accepting(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(accept) = self.accept.take() {
+            // 连接自身以唤醒阻塞在 accept 上的线程
+            let _ = TcpStream::connect_timeout(&self.addr, Duration::from_secs(1));
+            let _ = accept.join();
+        }
+    }
+}
+
+impl Drop for TailServer {
+    fn drop(&mut self) {
+        self.stop_accepting();
+    }
+}
+
+/// 推送协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protocol {
+    ServerSentEvents,
+    WebSocket,
+}
+
+/// 读取到的请求头
+struct RequestHead {
+    request_line: String,
+    websocket_key: Option<String>,
+}
+
+/// 读取请求行与头部；超出字节上限时返回 `None`
+fn read_head(reader: impl BufRead, max_bytes: usize) -> io::Result<Option<RequestHead>> {
+    let mut reader = reader.take(max_bytes as u64);
+    let mut request_line = String::new();
+    if !read_header_line(&mut reader, &mut request_line)? {
+        return Ok(None);
+    }
+    let mut websocket_key = None;
+    loop {
+        let mut line = String::new();
+        if !read_header_line(&mut reader, &mut line)? {
+            return Ok(None);
+        }
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.trim().eq_ignore_ascii_case("sec-websocket-key")
+        {
+            websocket_key = Some(value.trim().to_string());
+        }
+    }
+    Ok(Some(RequestHead {
+        request_line,
+        websocket_key,
+    }))
+}
+
+/// 读取一行请求头（连接关闭时为空行）；读到字节上限仍未遇到换行时返回 `false`
+fn read_header_line(reader: &mut io::Take<impl BufRead>, line: &mut String) -> io::Result<bool> {
+    reader.read_line(line)?;
+    Ok(reader.limit() > 0 || line.ends_with('\n'))
+}
+
+/// 应答一个纯文本响应并关闭连接
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// 服务单个连接
+fn serve(
+    logger: &AsyncLogger,
+    mut stream: TcpStream,
+    stop: &AtomicBool,
+    options: &TailOptions,
+) -> io::Result<()> {
+    // 请求头须在时限内读完；之后只写不读，写超时用于发现不再读取的客户端
+    stream.set_read_timeout(Some(options.read_timeout))?;
+    stream.set_write_timeout(Some(KEEPALIVE_INTERVAL))?;
+    let reader = BufReader::new(stream.try_clone()?);
+    let Some(head) = read_head(reader, options.max_header_bytes)? else {
+        return respond(
+            &mut stream,
+            "431 Request Header Fields Too Large",
+            "request head too large",
+        );
+    };
+
+    let target = head.request_line.split_whitespace().nth(1).unwrap_or("/");
+    let filter = match parse_filter(target) {
+        Ok(filter) => filter,
+        Err(reason) => return respond(&mut stream, "400 Bad Request", &reason),
+    };
+
+    // 先订阅再应答，客户端收到响应头后即可确保不遗漏后续记录
+    let rx = logger.subscribe(filter);
+    let protocol = match head.websocket_key {
+        Some(key) => {
+            write!(
+                stream,
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                websocket_accept(&key)
+            )?;
+            Protocol::WebSocket
+        }
+        None => {
+            stream.write_all(
+                b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n",
+            )?;
+            if let Some(origin) = &options.allow_origin {
+                write!(stream, "Access-Control-Allow-Origin: {}\r\n", origin)?;
+            }
+            stream.write_all(b"Connection: keep-alive\r\n\r\n")?;
+            Protocol::ServerSentEvents
+        }
+    };
+    stream.flush()?;
+
+    let mut frame = Vec::new();
+    while !stop.load(Ordering::Acquire) {
+        frame.clear();
+        match rx.recv_timeout(KEEPALIVE_INTERVAL) {
+            Some(line) => encode(protocol, trim_newline(&line), &mut frame),
+            None if rx.is_closed() => break,
+            None => keepalive(protocol, &mut frame),
+        }
+        stream.write_all(&frame)?;
+        stream.flush()?;
+    }
+    Ok(())
+}
+
+/// 由请求目标的查询参数构造过滤器
+fn parse_filter(target: &str) -> Result<Filter, String> {
+    let query = target.split_once('?').map_or("", |(_, q)| q);
+    let (mut level, mut module, mut spec) = (None, None, None);
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value);
+        match key {
+            "level" => level = Some(value),
+            "target" => module = Some(value),
+            "filter" => spec = Some(value),
+            _ => {}
+        }
+    }
+
+    let level = level.unwrap_or_else(|| "info".to_string());
+    if level.parse::<crate::Level>().is_err() {
+        return Err(format!("invalid level: {}", level));
+    }
+    let spec = match (spec, module) {
+        (Some(spec), _) => spec,
+        (None, Some(module)) => {
+            if module.contains([',', '=']) {
+                return Err(format!("invalid target: {}", module));
+            }
+            format!("off,{}={}", module, level)
+        }
+        (None, None) => level,
+    };
+    Filter::parse(&spec).map_err(|e| format!("invalid filter {:?}: {}", spec, e))
+}
+
+/// 解码 URL 查询参数（`%XX` 与 `+`）
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        out.push(high << 4 | low);
+                        i += 2;
+                    }
+                    _ => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn trim_newline(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// 编码一条记录：SSE 的多行记录每行一个 `data:` 字段；WebSocket 为一个文本帧
+fn encode(protocol: Protocol, line: &[u8], out: &mut Vec<u8>) {
+    match protocol {
+        Protocol::ServerSentEvents => {
+            for part in line.split(|&b| b == b'\n') {
+                out.extend_from_slice(b"data: ");
+                out.extend_from_slice(trim_newline(part));
+                out.push(b'\n');
+            }
+            out.push(b'\n');
+        }
+        Protocol::WebSocket => websocket_frame(0x1, line, out),
+    }
+}
+
+/// 保活：SSE 注释行；WebSocket ping 帧
+fn keepalive(protocol: Protocol, out: &mut Vec<u8>) {
+    match protocol {
+        Protocol::ServerSentEvents => out.extend_from_slice(b": keepalive\n\n"),
+        Protocol::WebSocket => websocket_frame(0x9, b"", out),
+    }
+}
+
+/// 服务端发出的未掩码 WebSocket 帧
+fn websocket_frame(opcode: u8, payload: &[u8], out: &mut Vec<u8>) {
+    out.push(0x80 | opcode);
+    match payload.len() {
+        len @ 0..=125 => out.push(len as u8),
+        len @ 126..=0xFFFF => {
+            out.push(126);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            out.push(127);
+            out.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(payload);
+}
+
+/// `Sec-WebSocket-Accept` 响应头：`base64(sha1(key + GUID))`
+fn websocket_accept(key: &str) -> String {
+    let digest = sha1(format!("{}{}", key, WEBSOCKET_GUID).as_bytes());
+    base64(&digest)
+}
+
+/// SHA-1 摘要（仅用于 WebSocket 握手）
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (out, word) in digest.chunks_exact_mut(4).zip(h) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// 标准 base64 编码（带填充）
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = (u32::from(chunk[0]) << 16)
+            | (u32::from(*chunk.get(1).unwrap_or(&0)) << 8)
+            | u32::from(*chunk.get(2).unwrap_or(&0));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3F] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Level, Record};
+    use std::io::Read;
+
+    #[test]
+    fn test_websocket_accept_matches_rfc_example() {
+        assert_eq!(
+            websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        assert_eq!(base64(b"ab"), "YWI=");
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("app%3A%3Adb+x%zz%4"), "app::db x%zz%4");
+    }
+
+    #[test]
+    fn test_parse_filter_from_query() {
+        let filter = parse_filter("/?level=debug&target=app%3A%3Adb").unwrap();
+        assert!(filter.enabled(Level::Debug, "app::db::pool"));
+        assert!(!filter.enabled(Level::Error, "app::http"));
+
+        let filter = parse_filter("/").unwrap();
+        assert!(filter.enabled(Level::Info, "any"));
+        assert!(!filter.enabled(Level::Debug, "any"));
+
+        assert!(parse_filter("/?target=a,b").is_err());
+        assert!(parse_filter("/?level=loud").is_err());
+    }
+
+    fn tail_logger() -> Arc<AsyncLogger> {
+        Arc::new(AsyncLogger::new(
+            Level::Info,
+            Arc::new(crate::format::SimpleFormatter::new()),
+            Arc::new(crate::sink::NullSink::new()),
+            64,
+            8,
+            Duration::from_secs(60),
+        ))
+    }
+
+    /// 发送请求并读取响应头
+    fn request(server: &TailServer, request: &[u8]) -> (BufReader<TcpStream>, String) {
+        let mut client = TcpStream::connect(server.local_addr()).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        client.write_all(request).unwrap();
+        let mut reader = BufReader::new(client);
+        let mut head = String::new();
+        while reader.read_line(&mut head).unwrap() > 2 {}
+        (reader, head)
+    }
+
+    #[test]
+    fn test_sse_stream_delivers_records() {
+        let logger = tail_logger();
+        let server = TailServer::bind(logger.clone(), "127.0.0.1:0").unwrap();
+
+        let (mut reader, head) = request(&server, b"GET /?level=warn HTTP/1.1\r\nHost: x\r\n\r\n");
+        assert!(head.starts_with("HTTP/1.1 200"));
+        assert!(head.contains("text/event-stream"));
+        assert!(!head.contains("Access-Control-Allow-Origin"));
+
+        let _ = logger.log(Record::new(
+            Level::Info,
+            "t",
+            file!(),
+            1,
+            "hidden".to_string(),
+        ));
+        let _ = logger.log(Record::new(
+            Level::Warn,
+            "t",
+            file!(),
+            2,
+            "shown".to_string(),
+        ));
+        let mut event = [0u8; 20];
+        reader.read_exact(&mut event).unwrap();
+        assert_eq!(&event, b"data: [WARN] shown\n\n");
+        server.shutdown();
+    }
+
+    #[test]
+    fn test_cross_origin_access_is_opt_in() {
+        let options = TailOptions::new().allow_origin("http://localhost:3000");
+        let server = TailServer::bind_with(tail_logger(), "127.0.0.1:0", options).unwrap();
+        let (_reader, head) = request(&server, b"GET / HTTP/1.1\r\n\r\n");
+        assert!(head.contains("Access-Control-Allow-Origin: http://localhost:3000\r\n"));
+        server.shutdown();
+    }
+
+    #[test]
+    fn test_request_head_is_bounded() {
+        let options = TailOptions::new()
+            .max_header_bytes(64)
+            .read_timeout(Duration::from_millis(50));
+        let server = TailServer::bind_with(tail_logger(), "127.0.0.1:0", options).unwrap();
+
+        let oversized = format!("GET / HTTP/1.1\r\nX-Pad: {}\r\n\r\n", "a".repeat(200));
+        let (_reader, head) = request(&server, oversized.as_bytes());
+        assert!(head.starts_with("HTTP/1.1 431"), "{}", head);
+
+        // 迟迟不发送请求头的客户端在读取时限后被断开
+        let mut idle = TcpStream::connect(server.local_addr()).unwrap();
+        idle.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut rest = Vec::new();
+        assert!(idle.read_to_end(&mut rest).is_ok());
+        assert!(rest.is_empty());
+        server.shutdown();
+    }
+
+    #[test]
+    fn test_connections_over_limit_are_refused() {
+        let options = TailOptions::new().max_connections(1);
+        let server = TailServer::bind_with(tail_logger(), "127.0.0.1:0", options).unwrap();
+        let (_first, head) = request(&server, b"GET / HTTP/1.1\r\n\r\n");
+        assert!(head.starts_with("HTTP/1.1 200"));
+
+        let (_second, head) = request(&server, b"GET / HTTP/1.1\r\n\r\n");
+        assert!(head.starts_with("HTTP/1.1 503"), "{}", head);
+        server.shutdown();
+    }
+}
@@ -0,0 +1,147 @@
+/*!
+分布式追踪关联。
+
+记录可携带 W3C Trace Context 格式的追踪 ID（16 字节）与 span ID（8 字节），
+格式化器以小写十六进制输出（JSON 的 `trace_id` / `span_id`，OTLP 的 `trace_id` / `span_id` 字段），
+日志行因此可与分布式追踪关联。
+
+追踪上下文可逐条设置（[`Record::with_trace_context`](crate::Record::with_trace_context)），
+也可在构建器上注册 [`TraceContextProvider`]，发布时从调用线程当前的追踪上下文
+（如 OpenTelemetry 的当前 span 或 tracing span 中保存的上下文）读取：
+
+```
+use nanolog_rs::AsyncLoggerBuilder;
+use nanolog_rs::trace::TraceContext;
+use std::sync::Arc;
+
+thread_local! {
+    static CURRENT: std::cell::Cell<Option<TraceContext>> = const { std::cell::Cell::new(None) };
+}
+
+let logger = AsyncLoggerBuilder::new()
+    .trace_context(Arc::new(|| CURRENT.with(|c| c.get())))
+    .build()?;
+# logger.shutdown()?;
+# Ok::<(), nanolog_rs::error::Error>(())
+```
+*/
+
+use std::fmt;
+
+/// 小写十六进制字母表
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// 追踪上下文：追踪 ID 与 span ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceContext {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+}
+
+impl TraceContext {
+    /// 以追踪 ID 与 span ID 创建上下文
+    pub const fn new(trace_id: [u8; 16], span_id: [u8; 8]) -> Self {
+        Self { trace_id, span_id }
+    }
+
+    /// 解析十六进制的追踪 ID（32 位）与 span ID（16 位），格式错误时返回 `None`
+    pub fn from_hex(trace_id: &str, span_id: &str) -> Option<Self> {
+        Some(Self {
+            trace_id: parse_hex(trace_id)?,
+            span_id: parse_hex(span_id)?,
+        })
+    }
+
+    /// 解析 W3C `traceparent` 头（如 `00-<trace_id>-<span_id>-01`）
+    pub fn from_traceparent(header: &str) -> Option<Self> {
+        let mut parts = header.trim().split('-');
+        let (_version, trace_id, span_id, _flags) =
+            (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+        Self::from_hex(trace_id, span_id)
+    }
+
+    /// 追踪 ID
+    pub fn trace_id(&self) -> [u8; 16] {
+        self.trace_id
+    }
+
+    /// span ID
+    pub fn span_id(&self) -> [u8; 8] {
+        self.span_id
+    }
+}
+
+impl fmt::Display for TraceContext {
+    /// 输出 `<trace_id>-<span_id>`（小写十六进制）
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = Vec::with_capacity(49);
+        write_hex(&mut out, &self.trace_id);
+        out.push(b'-');
+        write_hex(&mut out, &self.span_id);
+        f.write_str(std::str::from_utf8(&out).map_err(|_| fmt::Error)?)
+    }
+}
+
+/// 追踪上下文提供者：发布记录时在调用线程上读取当前的追踪上下文
+pub trait TraceContextProvider: Send + Sync {
+    /// 当前的追踪上下文（不在追踪中时返回 `None`）
+    fn current(&self) -> Option<TraceContext>;
+}
+
+impl<F> TraceContextProvider for F
+where
+    F: Fn() -> Option<TraceContext> + Send + Sync,
+{
+    fn current(&self) -> Option<TraceContext> {
+        self()
+    }
+}
+
+/// 以小写十六进制写入字节
+pub(crate) fn write_hex(out: &mut Vec<u8>, bytes: &[u8]) {
+    for &b in bytes {
+        out.push(HEX[usize::from(b >> 4)]);
+        out.push(HEX[usize::from(b & 0x0f)]);
+    }
+}
+
+/// 解析定长的十六进制字符串
+fn parse_hex<const N: usize>(s: &str) -> Option<[u8; N]> {
+    let s = s.as_bytes();
+    if s.len() != N * 2 {
+        return None;
+    }
+    let digit = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    let mut out = [0u8; N];
+    for (byte, pair) in out.iter_mut().zip(s.chunks_exact(2)) {
+        *byte = digit(pair[0])? << 4 | digit(pair[1])?;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_context_hex_round_trip() {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let context = TraceContext::from_traceparent(header).unwrap();
+        assert_eq!(
+            context.span_id(),
+            [0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7]
+        );
+        assert_eq!(
+            context.to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7"
+        );
+        assert_eq!(
+            TraceContext::from_hex("4BF92F3577B34DA6A3CE929D0E0E4736", "00f067aa0ba902b7"),
+            Some(context)
+        );
+
+        assert!(TraceContext::from_hex("4bf9", "00f067aa0ba902b7").is_none());
+        assert!(TraceContext::from_hex("zz".repeat(16).as_str(), "00f067aa0ba902b7").is_none());
+        assert!(TraceContext::from_traceparent("00-4bf92f3577b34da6").is_none());
+    }
+}
@@ -0,0 +1,95 @@
+/*!
+消息转换钩子。
+
+在消费者线程格式化之前改写日志消息，例如将消息模板 ID 映射为本地化文本，
+用于面向用户的审计日志。转换发生在后台线程，不增加调用方延迟。
+*/
+
+use std::collections::HashMap;
+
+use crate::Record;
+
+/// 消息转换器接口
+///
+/// 返回 `Some(message)` 表示以新消息替换原消息，返回 `None` 表示保持原样（不产生分配）。
+pub trait MessageTransformer: Send + Sync {
+    /// 转换日志消息
+    fn transform(&self, record: &Record) -> Option<String>;
+}
+
+impl<F> MessageTransformer for F
+where
+    F: Fn(&Record) -> Option<String> + Send + Sync,
+{
+    fn transform(&self, record: &Record) -> Option<String> {
+        self(record)
+    }
+}
+
+/// 基于模板 ID 的消息目录
+///
+/// 当消息内容恰好等于某个已登记的模板 ID 时，替换为对应的本地化文本；
+/// 未登记的消息原样输出。
+#[derive(Debug, Clone, Default)]
+pub struct MessageCatalog {
+    /// 模板 ID 到本地化文本的映射
+    entries: HashMap<String, String>,
+}
+
+impl MessageCatalog {
+    /// 创建空的消息目录
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记模板 ID 及其本地化文本
+    pub fn with_entry(mut self, id: impl Into<String>, text: impl Into<String>) -> Self {
+        self.entries.insert(id.into(), text.into());
+        self
+    }
+
+    /// 登记模板 ID 及其本地化文本
+    pub fn insert(&mut self, id: impl Into<String>, text: impl Into<String>) {
+        self.entries.insert(id.into(), text.into());
+    }
+
+    /// 获取已登记的条目数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 检查目录是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl MessageTransformer for MessageCatalog {
+    fn transform(&self, record: &Record) -> Option<String> {
+        self.entries.get(record.message()).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    #[test]
+    fn test_message_catalog_lookup() {
+        let catalog = MessageCatalog::new().with_entry("AUDIT_LOGIN", "用户登录成功");
+
+        let hit = Record::new(Level::Info, "audit", "a.rs", 1, "AUDIT_LOGIN".to_string());
+        assert_eq!(catalog.transform(&hit).as_deref(), Some("用户登录成功"));
+
+        let miss = Record::new(Level::Info, "audit", "a.rs", 1, "plain".to_string());
+        assert!(catalog.transform(&miss).is_none());
+    }
+
+    #[test]
+    fn test_closure_transformer() {
+        let upper = |r: &Record| Some(r.message().to_uppercase());
+        let record = Record::new(Level::Info, "t", "t.rs", 1, "hello".to_string());
+        assert_eq!(upper.transform(&record).as_deref(), Some("HELLO"));
+    }
+}
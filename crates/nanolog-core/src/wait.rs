@@ -0,0 +1,315 @@
+/*!
+消费者等待策略。
+
+默认的忙等策略延迟最低，但空闲时会占满一个 CPU 核心。[`WaitStrategy`] 可在构建器上
+换成带自旋提示的忙等、让出或休眠，以延迟换取 CPU。混合等待策略先忙等一段时间，
+若持续无日志到达则在条件变量上停靠，并由下一次发布立即唤醒。两级流水线中
+写入线程使用独立的停靠器，由格式化线程在每批之后唤醒。
+*/
+
+use std::cell::OnceCell;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use disruptor::Sequence;
+use disruptor::wait_strategies::WaitStrategy as DisruptorWait;
+
+use crate::stats::{WakeStats, WakeStatsSnapshot};
+
+/// 消费者无事件可处理时的等待方式（见 [`AsyncLoggerBuilder::wait_strategy`](crate::AsyncLoggerBuilder::wait_strategy)）
+///
+/// 启用 [空闲停靠](crate::AsyncLoggerBuilder::idle_parking) 时以停靠为准，忽略此设置。
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum WaitStrategy {
+    /// 忙等：延迟最低，空闲时占满一个 CPU 核心
+    #[default]
+    BusySpin,
+    /// 忙等并发出自旋提示（`spin_loop`），降低功耗并让出超线程的执行资源
+    BusySpinWithSpinLoopHint,
+    /// 每次检查之间让出时间片，空闲时仍频繁调度但不独占核心
+    Yielding,
+    /// 每次检查之间休眠指定时长，唤醒延迟至多为该时长
+    Sleeping(Duration),
+}
+
+impl WaitStrategy {
+    /// 在处理函数内等待新事件的一步（合并写入窗口），不超过截止时刻
+    pub(crate) fn pause_until(self, deadline: Instant) {
+        match self {
+            WaitStrategy::BusySpin | WaitStrategy::BusySpinWithSpinLoopHint => {
+                std::hint::spin_loop()
+            }
+            WaitStrategy::Yielding => std::thread::yield_now(),
+            WaitStrategy::Sleeping(interval) => {
+                std::thread::sleep(interval.min(deadline.saturating_duration_since(Instant::now())))
+            }
+        }
+    }
+}
+
+/// 让出时间片的等待策略
+#[derive(Clone, Copy)]
+pub(crate) struct YieldingWait;
+
+impl DisruptorWait for YieldingWait {
+    #[inline]
+    fn wait_for(&self, _sequence: Sequence) {
+        std::thread::yield_now();
+    }
+}
+
+/// 定时休眠的等待策略
+#[derive(Clone, Copy)]
+pub(crate) struct SleepingWait(pub(crate) Duration);
+
+impl DisruptorWait for SleepingWait {
+    #[inline]
+    fn wait_for(&self, _sequence: Sequence) {
+        std::thread::sleep(self.0);
+    }
+}
+
+/// 停靠的最长时间，超时后消费者自行醒来重新检查（防御性兜底）
+const MAX_PARK: Duration = Duration::from_millis(100);
+
+thread_local! {
+    /// 当前消费者线程使用的停靠器（由 [`IdleParker::install`] 设置，线程退出时释放）
+    static CURRENT: OnceCell<Arc<IdleParker>> = const { OnceCell::new() };
+}
+
+/// 空闲停靠器（日志器、发布者与消费者线程以 `Arc` 共享）
+pub(crate) struct IdleParker {
+    /// 持续空闲多久后停靠
+    idle_after: Duration,
+    /// 时间基准（用于以纳秒整数保存时刻）
+    base: Instant,
+    /// 已交给消费者的事件数（由发布者或上一级消费者递增）
+    published: AtomicU64,
+    /// 消费者最后一次等待时已处理的事件数
+    seen: AtomicU64,
+    /// 本轮空闲开始的时刻（相对 `base` 的纳秒数）
+    idle_since: AtomicU64,
+    /// 消费者是否已停靠
+    parked: AtomicBool,
+    /// 日志器正在关闭，不再停靠
+    closing: AtomicBool,
+    /// 发布者发出唤醒的时刻（相对 `base` 的纳秒数）
+    notified_at: AtomicU64,
+    lock: Mutex<()>,
+    condvar: Condvar,
+    stats: Arc<WakeStats>,
+}
+
+impl IdleParker {
+    /// 创建停靠器
+    pub(crate) fn new(idle_after: Duration) -> Arc<IdleParker> {
+        Self::with_stats(idle_after, Arc::new(WakeStats::new()))
+    }
+
+    /// 为下一级消费者创建停靠器（空闲阈值与唤醒统计与本停靠器共用）
+    pub(crate) fn next_stage(&self) -> Arc<IdleParker> {
+        Self::with_stats(self.idle_after, self.stats.clone())
+    }
+
+    fn with_stats(idle_after: Duration, stats: Arc<WakeStats>) -> Arc<IdleParker> {
+        Arc::new(IdleParker {
+            idle_after,
+            base: Instant::now(),
+            published: AtomicU64::new(0),
+            seen: AtomicU64::new(0),
+            idle_since: AtomicU64::new(0),
+            parked: AtomicBool::new(false),
+            closing: AtomicBool::new(false),
+            notified_at: AtomicU64::new(0),
+            lock: Mutex::new(()),
+            condvar: Condvar::new(),
+            stats,
+        })
+    }
+
+    /// 在消费者线程上调用：此后该线程上的 [`HybridWait`] 使用此停靠器
+    ///
+    /// 等待策略要求 `Copy`，无法持有 `Arc`，因此停靠器保存在消费者线程的线程局部变量中，
+    /// 随线程退出释放，日志器销毁后不留下停靠器。
+    pub(crate) fn install(self: &Arc<Self>) {
+        CURRENT.with(|current| {
+            let _ = current.set(self.clone());
+        });
+    }
+
+    #[inline]
+    fn now_ns(&self) -> u64 {
+        self.base.elapsed().as_nanos() as u64
+    }
+
+    /// 向消费者交出 `events` 个事件后调用：计数并在消费者停靠时唤醒它
+    #[inline]
+    pub(crate) fn notify(&self, events: u64) {
+        self.published.fetch_add(events, Ordering::SeqCst);
+        if self.parked.load(Ordering::SeqCst) {
+            let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+            self.notified_at.store(self.now_ns(), Ordering::Relaxed);
+            self.condvar.notify_one();
+        }
+    }
+
+    /// 关闭时调用：唤醒消费者并禁止再次停靠
+    pub(crate) fn close(&self) {
+        self.closing.store(true, Ordering::SeqCst);
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.condvar.notify_all();
+    }
+
+    /// 获取唤醒统计快照
+    pub(crate) fn stats(&self) -> WakeStatsSnapshot {
+        self.stats.snapshot()
+    }
+
+    /// 消费者在无事件可处理时调用，`consumed` 为其已处理的事件数（即等待的序号）
+    fn idle(&self, consumed: u64) {
+        if self.closing.load(Ordering::Relaxed) {
+            return;
+        }
+
+        // 处理进度有变化说明刚刚结束一轮处理，重新开始计算空闲时间
+        if consumed != self.seen.load(Ordering::Relaxed) {
+            self.seen.store(consumed, Ordering::Relaxed);
+            self.idle_since.store(self.now_ns(), Ordering::Relaxed);
+            return;
+        }
+
+        // 已交出的事件尚未对消费者可见（发布者或上一级正在提交进度），继续忙等
+        if self.published.load(Ordering::SeqCst) > consumed {
+            std::hint::spin_loop();
+            return;
+        }
+
+        let idle_ns = self
+            .now_ns()
+            .saturating_sub(self.idle_since.load(Ordering::Relaxed));
+        if idle_ns < self.idle_after.as_nanos() as u64 {
+            std::hint::spin_loop();
+            return;
+        }
+
+        self.park(consumed, MAX_PARK);
+    }
+
+    /// 在处理函数内等待新事件（合并写入窗口）：停靠到有新事件交出或到达截止时刻
+    pub(crate) fn park_until(&self, consumed: u64, deadline: Instant) {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        if !timeout.is_zero() {
+            self.park(consumed, timeout.min(MAX_PARK));
+        }
+    }
+
+    /// 在条件变量上停靠，直到发布者唤醒或超时
+    fn park(&self, consumed: u64, timeout: Duration) {
+        let guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.parked.store(true, Ordering::SeqCst);
+
+        // 设置停靠标志后再次检查，避免与发布者竞争导致丢失唤醒
+        if self.published.load(Ordering::SeqCst) > consumed || self.closing.load(Ordering::SeqCst) {
+            self.parked.store(false, Ordering::SeqCst);
+            return;
+        }
+
+        self.stats.record_park();
+        self.notified_at.store(0, Ordering::Relaxed);
+        let (guard, _timeout) = self
+            .condvar
+            .wait_timeout(guard, timeout)
+            .unwrap_or_else(|e| e.into_inner());
+        self.parked.store(false, Ordering::SeqCst);
+
+        let notified_at = self.notified_at.load(Ordering::Relaxed);
+        drop(guard);
+        if notified_at != 0 {
+            let latency = self.now_ns().saturating_sub(notified_at);
+            self.stats.record_wake(Duration::from_nanos(latency));
+        }
+    }
+}
+
+/// 混合等待策略：先忙等，持续空闲后在当前线程安装的停靠器上停靠
+///
+/// 未安装停靠器的线程上退化为忙等。
+#[derive(Clone, Copy)]
+pub(crate) struct HybridWait;
+
+impl DisruptorWait for HybridWait {
+    #[inline]
+    fn wait_for(&self, sequence: Sequence) {
+        CURRENT.with(|parker| match parker.get() {
+            Some(parker) => parker.idle(sequence as u64),
+            None => std::hint::spin_loop(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parker_parks_when_idle_and_wakes_on_notify() {
+        let parker = IdleParker::new(Duration::ZERO);
+        let installed = parker.clone();
+
+        let handle = std::thread::spawn(move || {
+            installed.install();
+            // 空闲阈值为零，第一次等待即停靠；唤醒后事件已交出，第二次等待不再停靠
+            HybridWait.wait_for(0);
+            HybridWait.wait_for(0);
+        });
+
+        while !parker.parked.load(Ordering::SeqCst) {
+            std::thread::yield_now();
+        }
+        parker.notify(1);
+        handle.join().unwrap();
+
+        let stats = parker.stats();
+        assert_eq!(stats.parks, 1);
+        assert_eq!(stats.wakeups, 1);
+        assert!(stats.max_wake_latency <= MAX_PARK);
+        // 消费者线程退出后释放其持有的停靠器
+        assert_eq!(Arc::strong_count(&parker), 1);
+    }
+
+    #[test]
+    fn test_parker_does_not_park_after_close() {
+        let parker = IdleParker::new(Duration::ZERO);
+        parker.close();
+        parker.idle(0);
+        parker.idle(0);
+        assert_eq!(parker.stats().parks, 0);
+    }
+
+    #[test]
+    fn test_parker_waits_for_events_not_yet_visible() {
+        let parker = IdleParker::new(Duration::ZERO);
+        let next = parker.next_stage();
+        // 上一级已交出事件但尚未提交进度时，下一级不停靠
+        next.notify(3);
+        next.idle(0);
+        next.idle(0);
+        assert_eq!(next.stats().parks, 0);
+
+        // 处理完已交出的事件后才停靠，统计与上一级共用
+        let installed = next.clone();
+        let handle = std::thread::spawn(move || {
+            installed.install();
+            HybridWait.wait_for(3);
+            HybridWait.wait_for(3);
+        });
+        while !next.parked.load(Ordering::SeqCst) {
+            std::thread::yield_now();
+        }
+        next.notify(1);
+        handle.join().unwrap();
+        assert_eq!(parker.stats().parks, 1);
+        assert_eq!(parker.stats().wakeups, 1);
+    }
+}
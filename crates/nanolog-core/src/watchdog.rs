@@ -0,0 +1,177 @@
+/*!
+消费者存活看门狗。
+
+后台线程定期比较已发布与已处理的记录数：有记录待处理而处理进度持续停滞超过设定时长时
+（输出目标卡死或消费者线程退出），通过诊断通道上报，并可自动切换到后备输出目标。
+*/
+
+use crate::Record;
+use crate::diagnostics::{Diagnostic, DiagnosticHandler};
+use crate::sink::{BatchCompression, RecordMeta, Sink};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// 看门狗配置
+#[derive(Clone)]
+pub struct Watchdog {
+    stall_after: Duration,
+    fallback: Option<Arc<dyn Sink>>,
+}
+
+impl Watchdog {
+    /// 处理进度停滞超过 `stall_after` 时告警
+    pub fn new(stall_after: Duration) -> Self {
+        Self {
+            stall_after,
+            fallback: None,
+        }
+    }
+
+    /// 告警时将后续写入切换到后备输出目标
+    ///
+    /// 已经阻塞在原输出目标中的调用无法被打断，可配合
+    /// [`TimeoutSink`](crate::sink::TimeoutSink) 让卡住的写入超时返回。
+    pub fn with_fallback(mut self, sink: Arc<dyn Sink>) -> Self {
+        self.fallback = Some(sink);
+        self
+    }
+
+    /// 停滞判定时长
+    pub fn stall_after(&self) -> Duration {
+        self.stall_after
+    }
+
+    /// 按配置包装输出目标：配置了后备目标时返回可切换的输出目标
+    pub(crate) fn wrap_sink(&self, sink: Arc<dyn Sink>) -> Option<Arc<SwitchableSink>> {
+        self.fallback.as_ref().map(|fallback| {
+            Arc::new(SwitchableSink {
+                current: RwLock::new(sink),
+                fallback: fallback.clone(),
+                switched: AtomicBool::new(false),
+            })
+        })
+    }
+
+    /// 启动看门狗线程，`shutdown` 置位后退出
+    ///
+    /// `counters` 返回（已发布数，已处理数）。
+    pub(crate) fn spawn(
+        &self,
+        counters: impl Fn() -> (u64, u64) + Send + 'static,
+        shutdown: Arc<AtomicBool>,
+        diagnostics: Arc<dyn DiagnosticHandler>,
+        switch: Option<Arc<SwitchableSink>>,
+    ) -> io::Result<()> {
+        let stall_after = self.stall_after;
+        let interval = (stall_after / 4).max(Duration::from_millis(1));
+        std::thread::Builder::new()
+            .name("nanolog-watchdog".to_string())
+            .spawn(move || {
+                let mut last_processed = counters().1;
+                let mut stalled_since: Option<Instant> = None;
+                let mut reported = false;
+                while !shutdown.load(Ordering::Acquire) {
+                    std::thread::sleep(interval);
+                    let (published, done) = counters();
+                    let pending = published.saturating_sub(done);
+                    if done != last_processed || pending == 0 {
+                        last_processed = done;
+                        stalled_since = None;
+                        reported = false;
+                        continue;
+                    }
+                    let since = *stalled_since.get_or_insert_with(Instant::now);
+                    if reported || since.elapsed() < stall_after {
+                        continue;
+                    }
+                    reported = true;
+                    let switched_to_fallback = switch.as_ref().is_some_and(|s| s.switch());
+                    diagnostics.handle(&Diagnostic::ConsumerStalled {
+                        stalled_for: since.elapsed(),
+                        pending,
+                        switched_to_fallback,
+                    });
+                }
+            })?;
+        Ok(())
+    }
+}
+
+/// 可切换到后备目标的输出目标
+pub(crate) struct SwitchableSink {
+    current: RwLock<Arc<dyn Sink>>,
+    fallback: Arc<dyn Sink>,
+    switched: AtomicBool,
+}
+
+impl SwitchableSink {
+    /// 切换到后备目标，仅首次切换返回真
+    fn switch(&self) -> bool {
+        if self.switched.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+        match self.current.write() {
+            Ok(mut current) => *current = self.fallback.clone(),
+            Err(poisoned) => *poisoned.into_inner() = self.fallback.clone(),
+        }
+        true
+    }
+
+    fn current(&self) -> Arc<dyn Sink> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl Sink for SwitchableSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        self.current().write(data)
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        self.current().write_batch(data)
+    }
+
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        self.current().write_records(meta, data)
+    }
+
+    fn stage_record(&self, record: &Record) {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .stage_record(record);
+    }
+
+    fn batch_compression(&self) -> Option<BatchCompression> {
+        self.current().batch_compression()
+    }
+
+    fn encode_batch(&self, data: &[Vec<u8>], out: &mut Vec<u8>) {
+        self.current().encode_batch(data, out)
+    }
+
+    fn write_compressed(&self, frame: &[u8], records: usize) -> io::Result<()> {
+        self.current().write_compressed(frame, records)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.current().flush()
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.current().shutdown()
+    }
+
+    fn coalesce_window(&self) -> Duration {
+        self.current().coalesce_window()
+    }
+
+    fn health_check(&self) -> io::Result<()> {
+        self.current().health_check()
+    }
+}
@@ -0,0 +1,309 @@
+/*!
+Kafka 输出目标。
+
+每条已格式化的记录作为一条消息发布到指定主题（去掉行尾换行符），消息键可按记录目标、
+级别或固定值设置，便于按服务模块分区。发送在 librdkafka 内部队列中异步完成，
+批尾刷新只处理已到达的投递回报；关闭时等待全部未完成的投递。
+
+```no_run
+use nanolog_rs::kafka::{DeliveryGuarantee, KafkaKey, KafkaSink};
+
+let sink = KafkaSink::builder("localhost:9092", "app-logs")
+    .key(KafkaKey::Target)
+    .delivery(DeliveryGuarantee::Idempotent)
+    .build()?;
+# Ok::<(), std::io::Error>(())
+```
+*/
+
+#![warn(missing_docs)]
+
+use nanolog_core::sink::{RecordMeta, Sink};
+use rdkafka::ClientContext;
+use rdkafka::config::ClientConfig;
+use rdkafka::error::KafkaError;
+use rdkafka::producer::{BaseProducer, BaseRecord, DeliveryResult, Producer, ProducerContext};
+use rdkafka::types::RDKafkaErrorCode;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// 本地发送队列满时每次等待投递回报的时长
+const QUEUE_FULL_BACKOFF: Duration = Duration::from_millis(10);
+
+/// 消息键
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum KafkaKey {
+    /// 不设置键（由分区器轮询分区）
+    #[default]
+    None,
+    /// 记录目标（同一模块的记录进入同一分区，保持相对顺序）
+    Target,
+    /// 记录级别
+    Level,
+    /// 固定键
+    Fixed(String),
+}
+
+/// 投递保证
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeliveryGuarantee {
+    /// 不等待确认（`acks=0`），吞吐最高，可能丢失
+    AtMostOnce,
+    /// 等待全部同步副本确认（`acks=all`），失败重试，可能重复
+    #[default]
+    AtLeastOnce,
+    /// 幂等生产者（`enable.idempotence=true`），重试不产生重复
+    Idempotent,
+}
+
+/// 投递回报计数
+#[derive(Default)]
+struct DeliveryCounters {
+    delivered: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl ClientContext for DeliveryCounters {}
+
+impl ProducerContext for DeliveryCounters {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, result: &DeliveryResult<'_>, _: ()) {
+        let counter = match result {
+            Ok(_) => &self.delivered,
+            Err(_) => &self.failed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// [`KafkaSink`] 构建器
+pub struct KafkaSinkBuilder {
+    config: ClientConfig,
+    topic: String,
+    key: KafkaKey,
+    delivery: DeliveryGuarantee,
+    shutdown_timeout: Duration,
+}
+
+impl KafkaSinkBuilder {
+    /// 设置消息键（默认不设置）
+    pub fn key(mut self, key: KafkaKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// 设置投递保证（默认 [`DeliveryGuarantee::AtLeastOnce`]）
+    pub fn delivery(mut self, delivery: DeliveryGuarantee) -> Self {
+        self.delivery = delivery;
+        self
+    }
+
+    /// 设置任意 librdkafka 生产者参数（如 `linger.ms`、`compression.type`）
+    pub fn set(mut self, key: &str, value: &str) -> Self {
+        self.config.set(key, value);
+        self
+    }
+
+    /// 设置关闭时等待未完成投递的最长时间（默认 10 秒）
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// 创建生产者
+    pub fn build(mut self) -> io::Result<KafkaSink> {
+        match self.delivery {
+            DeliveryGuarantee::AtMostOnce => {
+                self.config.set("acks", "0");
+            }
+            DeliveryGuarantee::AtLeastOnce => {
+                self.config.set("acks", "all");
+            }
+            DeliveryGuarantee::Idempotent => {
+                self.config.set("enable.idempotence", "true");
+            }
+        }
+        let producer = self
+            .config
+            .create_with_context(DeliveryCounters::default())
+            .map_err(to_io)?;
+        Ok(KafkaSink {
+            producer,
+            topic: self.topic,
+            key: self.key,
+            shutdown_timeout: self.shutdown_timeout,
+            rejected: AtomicU64::new(0),
+        })
+    }
+}
+
+/// Kafka 输出目标
+pub struct KafkaSink {
+    producer: BaseProducer<DeliveryCounters>,
+    topic: String,
+    key: KafkaKey,
+    shutdown_timeout: Duration,
+    /// 未能进入发送队列的记录数
+    rejected: AtomicU64,
+}
+
+impl KafkaSink {
+    /// 创建构建器，`brokers` 为逗号分隔的 `host:port` 列表
+    pub fn builder(brokers: &str, topic: &str) -> KafkaSinkBuilder {
+        let mut config = ClientConfig::new();
+        config.set("bootstrap.servers", brokers);
+        KafkaSinkBuilder {
+            config,
+            topic: topic.to_string(),
+            key: KafkaKey::None,
+            delivery: DeliveryGuarantee::default(),
+            shutdown_timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// 以默认配置连接并发布到 `topic`
+    pub fn new(brokers: &str, topic: &str) -> io::Result<Self> {
+        Self::builder(brokers, topic).build()
+    }
+
+    /// 已确认投递的消息数
+    pub fn delivered(&self) -> u64 {
+        self.producer.context().delivered.load(Ordering::Relaxed)
+    }
+
+    /// 投递失败的消息数（含重试耗尽与超时）
+    pub fn failed(&self) -> u64 {
+        self.producer.context().failed.load(Ordering::Relaxed)
+            + self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// 已提交但尚未得到投递回报的消息数
+    pub fn in_flight(&self) -> u64 {
+        self.producer.in_flight_count().max(0) as u64
+    }
+
+    /// 记录对应的消息键（无元数据时目标与级别键不可用）
+    fn key_for<'a>(&'a self, meta: Option<&RecordMeta>) -> Option<&'a str> {
+        match (&self.key, meta) {
+            (KafkaKey::None, _) => None,
+            (KafkaKey::Fixed(key), _) => Some(key),
+            (KafkaKey::Target, Some(meta)) => Some(meta.target),
+            (KafkaKey::Level, Some(meta)) => Some(meta.level.as_str()),
+            (_, None) => None,
+        }
+    }
+
+    /// 发送一条消息；本地队列满时处理投递回报腾出空间后重试
+    fn send(&self, key: Option<&str>, data: &[u8]) -> io::Result<()> {
+        let payload = trim_newline(data);
+        let mut record = BaseRecord::<str, [u8]>::to(&self.topic).payload(payload);
+        if let Some(key) = key {
+            record = record.key(key);
+        }
+        loop {
+            match self.producer.send(record) {
+                Ok(()) => return Ok(()),
+                Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned)) => {
+                    record = returned;
+                    self.producer.poll(QUEUE_FULL_BACKOFF);
+                }
+                Err((err, _)) => {
+                    self.rejected.fetch_add(1, Ordering::Relaxed);
+                    return Err(to_io(err));
+                }
+            }
+        }
+    }
+}
+
+impl Sink for KafkaSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        self.send(self.key_for(None), data)
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        for record in data {
+            self.send(self.key_for(None), record)?;
+        }
+        Ok(())
+    }
+
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        for (meta, record) in meta.iter().zip(data) {
+            self.send(self.key_for(Some(meta)), record)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        // 批尾只处理已到达的投递回报，不等待确认
+        self.producer.poll(Duration::ZERO);
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.producer.flush(self.shutdown_timeout).map_err(to_io)
+    }
+
+    fn health_check(&self) -> io::Result<()> {
+        self.producer
+            .client()
+            .fetch_metadata(Some(&self.topic), self.shutdown_timeout)
+            .map(|_| ())
+            .map_err(to_io)
+    }
+}
+
+fn trim_newline(data: &[u8]) -> &[u8] {
+    let data = data.strip_suffix(b"\n").unwrap_or(data);
+    data.strip_suffix(b"\r").unwrap_or(data)
+}
+
+fn to_io(err: KafkaError) -> io::Error {
+    io::Error::other(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanolog_core::{Level, Record};
+
+    fn sink(key: KafkaKey) -> KafkaSink {
+        // 创建生产者不连接代理
+        KafkaSink::builder("127.0.0.1:1", "logs")
+            .key(key)
+            .set("message.timeout.ms", "100")
+            .set("log_level", "0")
+            .shutdown_timeout(Duration::from_secs(5))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_key_selection() {
+        let record = Record::new(Level::Warn, "app::db", file!(), 1, String::new());
+        let meta = RecordMeta::of(&record);
+        assert_eq!(sink(KafkaKey::None).key_for(Some(&meta)), None);
+        assert_eq!(sink(KafkaKey::Target).key_for(Some(&meta)), Some("app::db"));
+        assert_eq!(sink(KafkaKey::Level).key_for(Some(&meta)), Some("WARN"));
+        assert_eq!(sink(KafkaKey::Target).key_for(None), None);
+        let fixed = sink(KafkaKey::Fixed("svc".to_string()));
+        assert_eq!(fixed.key_for(None), Some("svc"));
+    }
+
+    #[test]
+    fn test_undeliverable_records_counted_on_shutdown() {
+        let sink = sink(KafkaKey::Target);
+        let record = Record::new(Level::Info, "app", file!(), 1, String::new());
+        assert!(
+            sink.write_records(&[RecordMeta::of(&record)], &[b"x\n".to_vec()])
+                .is_ok()
+        );
+        assert!(sink.in_flight() >= 1);
+        let _ = sink.shutdown();
+        assert_eq!(sink.failed(), 1);
+        assert_eq!(sink.delivered(), 0);
+    }
+}
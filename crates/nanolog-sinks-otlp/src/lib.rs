@@ -0,0 +1,415 @@
+/*!
+OpenTelemetry 日志导出（OTLP/HTTP protobuf）。
+
+[`OtlpFormatter`] 将记录编码为 `ExportLogsServiceRequest`：级别映射为 `severity_number` /
+`severity_text`，时间戳映射为 `time_unix_nano`，消息为字符串 `body`，目标、文件与行号映射为
+`code.namespace`、`code.filepath`、`code.lineno` 属性，捕获的线程映射为 `thread.id`、`thread.name`
+属性，追踪上下文映射为 `trace_id`、`span_id` 字段，结构化字段按类型映射为属性值。
+作为 [`BatchFormatter`] 时每批编码为一个请求；作为 [`Formatter`] 时每条记录编码为一个完整请求。
+
+同类型的 protobuf 消息拼接即合并，因此编码结果可直接交给
+[`HttpPayload::Protobuf`](nanolog_core::http::HttpPayload::Protobuf) 模式的 [`HttpSink`](nanolog_core::http::HttpSink)
+发送到 Collector 的 `/v1/logs` 接口：
+
+```no_run
+use nanolog_rs::http::{HttpPayload, HttpSink};
+use nanolog_rs::otlp::OtlpFormatter;
+use nanolog_rs::AsyncLoggerBuilder;
+use std::sync::Arc;
+
+let sink = HttpSink::new("http://localhost:4318/v1/logs")?.with_payload(HttpPayload::Protobuf);
+let logger = AsyncLoggerBuilder::new()
+    .sink(Arc::new(sink))
+    .batch_formatter(Arc::new(OtlpFormatter::new("checkout")))
+    .build()?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+
+#![warn(missing_docs)]
+
+use nanolog_core::field::Value;
+use nanolog_core::format::{BatchFormatter, Formatter};
+use nanolog_core::{Level, Record};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 嵌套数组/映射的最大编码深度，超出部分以字符串 `"..."` 代替
+const MAX_DEPTH: usize = 8;
+
+/// protobuf 线格式类型
+const VARINT: u32 = 0;
+const FIXED64: u32 = 1;
+const LEN: u32 = 2;
+
+/// OTLP 日志格式化器
+pub struct OtlpFormatter {
+    /// 资源属性（含 `service.name`）
+    resource: Vec<(String, Value)>,
+}
+
+impl OtlpFormatter {
+    /// 创建格式化器，`service_name` 作为资源属性 `service.name`
+    pub fn new(service_name: impl Into<String>) -> Self {
+        Self {
+            resource: vec![(
+                "service.name".to_string(),
+                Value::Str(service_name.into().into()),
+            )],
+        }
+    }
+
+    /// 添加资源属性（如 `service.version`、`deployment.environment`）
+    pub fn with_resource_attribute(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<Value>,
+    ) -> Self {
+        self.resource.push((key.into(), value.into()));
+        self
+    }
+
+    /// 编码包含 `records` 的 `ExportLogsServiceRequest`
+    fn encode<'a>(&self, records: impl IntoIterator<Item = &'a Record>, out: &mut Vec<u8>) {
+        let observed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        // ExportLogsServiceRequest.resource_logs
+        message(out, 1, |out| {
+            // ResourceLogs.resource
+            message(out, 1, |out| {
+                for (key, value) in &self.resource {
+                    key_value(out, 1, key, value);
+                }
+            });
+            // ResourceLogs.scope_logs
+            message(out, 2, |out| {
+                // ScopeLogs.scope：以门面 crate 名上报，版本随工作区一致
+                message(out, 1, |out| {
+                    string(out, 1, "nanolog-rs");
+                    string(out, 2, env!("CARGO_PKG_VERSION"));
+                });
+                for record in records {
+                    // ScopeLogs.log_records
+                    message(out, 2, |out| log_record(out, record, observed));
+                }
+            });
+        });
+    }
+}
+
+impl BatchFormatter for OtlpFormatter {
+    fn format_batch(&self, records: &[Record], out: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        self.encode(records, out);
+        Ok(())
+    }
+}
+
+impl Formatter for OtlpFormatter {
+    fn format(&self, record: &Record) -> Result<Vec<u8>, fmt::Error> {
+        let mut out = Vec::new();
+        self.format_into(record, &mut out)?;
+        Ok(out)
+    }
+
+    fn format_into(&self, record: &Record, out: &mut Vec<u8>) -> Result<(), fmt::Error> {
+        self.encode(std::iter::once(record), out);
+        Ok(())
+    }
+}
+
+/// OTLP `SeverityNumber`
+fn severity_number(level: Level) -> u64 {
+    match level {
+        Level::Trace => 1,
+        Level::Debug => 5,
+        Level::Info => 9,
+        Level::Warn => 13,
+        Level::Error => 17,
+    }
+}
+
+/// 编码 `LogRecord` 的字段
+fn log_record(out: &mut Vec<u8>, record: &Record, observed: u64) {
+    fixed64(out, 1, record.timestamp().min(u64::MAX as u128) as u64);
+    tag(out, 2, VARINT);
+    varint(out, severity_number(record.level()));
+    string(out, 3, record.level().as_str());
+    // body: AnyValue.string_value
+    message(out, 5, |out| string(out, 1, record.message()));
+    key_value(
+        out,
+        6,
+        "code.namespace",
+        &Value::Str(record.target().into()),
+    );
+    if !record.file().is_empty() {
+        key_value(out, 6, "code.filepath", &Value::Str(record.file().into()));
+        key_value(out, 6, "code.lineno", &Value::U64(u64::from(record.line())));
+    }
+    if let Some(thread) = record.thread() {
+        key_value(out, 6, "thread.id", &Value::U64(thread.id()));
+        if let Some(name) = thread.name() {
+            message(out, 6, |out| {
+                string(out, 1, "thread.name");
+                message(out, 2, |out| string(out, 1, name));
+            });
+        }
+    }
+    for field in record.fields() {
+        key_value(out, 6, field.key(), field.value());
+    }
+    if let Some(trace) = record.trace_context() {
+        bytes(out, 9, &trace.trace_id());
+        bytes(out, 10, &trace.span_id());
+    }
+    fixed64(out, 11, observed);
+}
+
+/// 编码 `KeyValue`
+fn key_value(out: &mut Vec<u8>, field: u32, key: &str, value: &Value) {
+    message(out, field, |out| {
+        string(out, 1, key);
+        message(out, 2, |out| any_value(out, value, 0));
+    });
+}
+
+/// 编码 `AnyValue` 的字段
+fn any_value(out: &mut Vec<u8>, value: &Value, depth: usize) {
+    match value {
+        Value::Array(_) | Value::Map(_) if depth >= MAX_DEPTH => string(out, 1, "..."),
+        Value::Str(v) => string(out, 1, v),
+        Value::Bool(v) => {
+            tag(out, 2, VARINT);
+            varint(out, u64::from(*v));
+        }
+        Value::I64(v) => int(out, *v),
+        Value::U64(v) if i64::try_from(*v).is_ok() => int(out, *v as i64),
+        Value::Duration(v) if i64::try_from(v.as_nanos()).is_ok() => int(out, v.as_nanos() as i64),
+        Value::F64(v) => {
+            tag(out, 4, FIXED64);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Value::Array(items) => message(out, 5, |out| {
+            for item in items {
+                message(out, 1, |out| any_value(out, item, depth + 1));
+            }
+        }),
+        Value::Map(entries) => message(out, 6, |out| {
+            for (key, value) in entries {
+                message(out, 1, |out| {
+                    string(out, 1, key);
+                    message(out, 2, |out| any_value(out, value, depth + 1));
+                });
+            }
+        }),
+        _ => {
+            let mut text = Vec::new();
+            value.write_plain(&mut text);
+            bytes(out, 1, &text);
+        }
+    }
+}
+
+/// `AnyValue.int_value`
+fn int(out: &mut Vec<u8>, value: i64) {
+    tag(out, 3, VARINT);
+    varint(out, value as u64);
+}
+
+fn varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn tag(out: &mut Vec<u8>, field: u32, wire_type: u32) {
+    varint(out, u64::from(field << 3 | wire_type));
+}
+
+fn fixed64(out: &mut Vec<u8>, field: u32, value: u64) {
+    tag(out, field, FIXED64);
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn bytes(out: &mut Vec<u8>, field: u32, value: &[u8]) {
+    tag(out, field, LEN);
+    varint(out, value.len() as u64);
+    out.extend_from_slice(value);
+}
+
+fn string(out: &mut Vec<u8>, field: u32, value: &str) {
+    bytes(out, field, value.as_bytes());
+}
+
+/// 编码嵌套消息：先写入消息体，再将长度前缀移到消息体之前，无需临时缓冲区
+fn message(out: &mut Vec<u8>, field: u32, body: impl FnOnce(&mut Vec<u8>)) {
+    tag(out, field, LEN);
+    let start = out.len();
+    body(out);
+    let len = out.len() - start;
+    varint(out, len as u64);
+    let prefix = out.len() - start - len;
+    out[start..].rotate_right(prefix);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 解析出的字段：(字段号, 线格式类型, 值)
+    type Fields = Vec<(u32, u32, Vec<u8>)>;
+
+    /// 解析一层 protobuf 字段：(字段号, 线格式类型, 值或长度前缀内容)
+    fn parse(mut data: &[u8]) -> Fields {
+        fn read_varint(data: &mut &[u8]) -> u64 {
+            let mut value = 0u64;
+            for shift in (0..64).step_by(7) {
+                let byte = data[0];
+                *data = &data[1..];
+                value |= u64::from(byte & 0x7F) << shift;
+                if byte < 0x80 {
+                    break;
+                }
+            }
+            value
+        }
+        let mut fields = Vec::new();
+        while !data.is_empty() {
+            let key = read_varint(&mut data);
+            let (field, wire_type) = ((key >> 3) as u32, (key & 7) as u32);
+            let value = match wire_type {
+                VARINT => read_varint(&mut data).to_le_bytes().to_vec(),
+                FIXED64 => {
+                    let (value, rest) = data.split_at(8);
+                    data = rest;
+                    value.to_vec()
+                }
+                LEN => {
+                    let len = read_varint(&mut data) as usize;
+                    let (value, rest) = data.split_at(len);
+                    data = rest;
+                    value.to_vec()
+                }
+                _ => break,
+            };
+            fields.push((field, wire_type, value));
+        }
+        fields
+    }
+
+    fn get(fields: &Fields, field: u32) -> Vec<Vec<u8>> {
+        fields
+            .iter()
+            .filter(|(f, _, _)| *f == field)
+            .map(|(_, _, v)| v.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_message_length_prefix() {
+        let mut out = Vec::new();
+        message(&mut out, 1, |out| out.extend_from_slice(&[7u8; 200]));
+        assert_eq!(&out[..3], &[0x0A, 0xC8, 0x01]);
+        assert_eq!(out.len(), 203);
+    }
+
+    #[test]
+    fn test_encodes_log_records() {
+        let record = Record::new(
+            Level::Warn,
+            "app::db",
+            "db.rs",
+            42,
+            "slow query".to_string(),
+        )
+        .with_field("rows", 3u64)
+        .with_field("ratio", 0.5)
+        .with_field("tags", vec!["a", "b"])
+        .with_thread()
+        .with_trace_context(nanolog_core::trace::TraceContext::new([7; 16], [9; 8]));
+        let thread = record.thread().cloned().unwrap();
+        let mut out = Vec::new();
+        OtlpFormatter::new("svc")
+            .format_batch(&[record.clone(), record], &mut out)
+            .unwrap();
+
+        let request = parse(&out);
+        let resource_logs = parse(&get(&request, 1)[0]);
+        let resource = parse(&get(&resource_logs, 1)[0]);
+        let service = parse(&get(&resource, 1)[0]);
+        assert_eq!(get(&service, 1), vec![b"service.name".to_vec()]);
+        assert_eq!(parse(&get(&service, 2)[0]), vec![(1, LEN, b"svc".to_vec())]);
+
+        let scope_logs = parse(&get(&resource_logs, 2)[0]);
+        let scope = parse(&get(&scope_logs, 1)[0]);
+        assert_eq!(get(&scope, 1), vec![b"nanolog-rs".to_vec()]);
+        let records = get(&scope_logs, 2);
+        assert_eq!(records.len(), 2);
+
+        let log = parse(&records[0]);
+        assert_eq!(get(&log, 2), vec![13u64.to_le_bytes().to_vec()]);
+        assert_eq!(get(&log, 3), vec![b"WARN".to_vec()]);
+        assert_eq!(
+            parse(&get(&log, 5)[0]),
+            vec![(1, LEN, b"slow query".to_vec())]
+        );
+        assert_eq!(get(&log, 9), vec![vec![7u8; 16]]);
+        assert_eq!(get(&log, 10), vec![vec![9u8; 8]]);
+        let attributes: Vec<(Vec<u8>, Fields)> = get(&log, 6)
+            .iter()
+            .map(|kv| {
+                let kv = parse(kv);
+                (get(&kv, 1)[0].clone(), parse(&get(&kv, 2)[0]))
+            })
+            .collect();
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|(k, _)| k == key.as_bytes())
+                .map(|(_, v)| v.clone())
+                .unwrap()
+        };
+        assert_eq!(
+            attribute("code.namespace"),
+            vec![(1, LEN, b"app::db".to_vec())]
+        );
+        assert_eq!(
+            attribute("code.lineno"),
+            vec![(3, VARINT, 42u64.to_le_bytes().to_vec())]
+        );
+        assert_eq!(
+            attribute("rows"),
+            vec![(3, VARINT, 3u64.to_le_bytes().to_vec())]
+        );
+        assert_eq!(
+            attribute("ratio"),
+            vec![(4, FIXED64, 0.5f64.to_le_bytes().to_vec())]
+        );
+        assert_eq!(
+            attribute("thread.id"),
+            vec![(3, VARINT, thread.id().to_le_bytes().to_vec())]
+        );
+        if let Some(name) = thread.name() {
+            assert_eq!(
+                attribute("thread.name"),
+                vec![(1, LEN, name.as_bytes().to_vec())]
+            );
+        }
+        let tags = parse(&attribute("tags")[0].2);
+        assert_eq!(tags.len(), 2);
+        assert_eq!(parse(&tags[1].2), vec![(1, LEN, b"b".to_vec())]);
+    }
+
+    #[test]
+    fn test_per_record_requests_merge_when_concatenated() {
+        let formatter = OtlpFormatter::new("svc");
+        let record = Record::new(Level::Info, "app", "", 0, "m".to_string());
+        let mut out = formatter.format(&record).unwrap();
+        out.extend(formatter.format(&record).unwrap());
+        // 拼接后是包含两个 ResourceLogs 的同一请求
+        assert_eq!(get(&parse(&out), 1).len(), 2);
+    }
+}
@@ -0,0 +1,324 @@
+/*!
+动态加载的输出目标插件。
+
+插件是导出 C ABI 函数表的动态库（`.so` / `.dylib` / `.dll`），可按路径在运行时加载，
+无需重新编译服务即可接入专有的输出目标。插件导出名为 [`ENTRY_SYMBOL`] 的入口函数
+（签名见 [`PluginEntry`]），宿主传入自己支持的 ABI 版本范围，插件返回所选版本的
+[`PluginVTable`]，不支持时返回空指针。
+
+```no_run
+use nanolog_rs::AsyncLogger;
+use nanolog_rs::plugin::PluginSink;
+use std::sync::Arc;
+
+let sink = PluginSink::load("/opt/plugins/libacme_sink.so", "endpoint=acme:9000")?;
+let logger = AsyncLogger::builder().sink(Arc::new(sink)).build()?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+宿主对同一插件状态的调用是串行的；关闭时先刷新并销毁插件状态，之后才卸载动态库。
+*/
+
+#![warn(missing_docs)]
+
+use nanolog_core::sink::Sink;
+use std::ffi::{CStr, c_char, c_void};
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// 宿主支持的最高 ABI 版本
+pub const ABI_VERSION: u32 = 1;
+
+/// 宿主支持的最低 ABI 版本
+pub const MIN_ABI_VERSION: u32 = 1;
+
+/// 插件导出的入口函数名
+pub const ENTRY_SYMBOL: &str = "nanolog_plugin_entry";
+
+/// 插件入口：参数为宿主支持的 ABI 版本范围（闭区间），返回函数表或空指针
+pub type PluginEntry =
+    unsafe extern "C" fn(min_version: u32, max_version: u32) -> *const PluginVTable;
+
+/// 插件函数表（ABI 版本 1）
+///
+/// 函数表须位于插件的静态内存中，在库卸载前保持有效。
+/// 返回 `i32` 的函数以 0 表示成功，其他值作为错误码上报。
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PluginVTable {
+    /// 插件选择的 ABI 版本
+    pub abi_version: u32,
+    /// 插件名称（以 NUL 结尾，可为空指针）
+    pub name: *const c_char,
+    /// 以配置字符串（UTF-8，不以 NUL 结尾）创建插件状态，失败时返回空指针
+    pub create: unsafe extern "C" fn(config: *const u8, config_len: usize) -> *mut c_void,
+    /// 写入一段已格式化的日志数据
+    pub write: unsafe extern "C" fn(state: *mut c_void, data: *const u8, len: usize) -> i32,
+    /// 刷新插件缓冲区
+    pub flush: unsafe extern "C" fn(state: *mut c_void) -> i32,
+    /// 销毁插件状态，之后不再使用该状态
+    pub destroy: unsafe extern "C" fn(state: *mut c_void),
+}
+
+// SAFETY: 函数表只读，名称指向插件内的静态字符串
+unsafe impl Sync for PluginVTable {}
+
+/// 插件状态指针（关闭后为空）
+struct State(*mut c_void);
+
+/// 动态加载的插件输出目标
+pub struct PluginSink {
+    name: String,
+    vtable: PluginVTable,
+    state: Mutex<State>,
+    /// 最后释放：插件状态销毁后才卸载动态库
+    _library: Option<libloading::Library>,
+}
+
+// SAFETY: 插件状态只在互斥锁内访问，宿主对插件的调用是串行的
+unsafe impl Send for PluginSink {}
+unsafe impl Sync for PluginSink {}
+
+impl PluginSink {
+    /// 加载指定路径的插件，以 `config` 创建插件状态
+    ///
+    /// 加载会执行动态库的初始化代码，只应加载受信任的插件。
+    pub fn load(path: impl AsRef<Path>, config: &str) -> io::Result<Self> {
+        // SAFETY: 插件须遵守本模块约定的 ABI；入口签名由 ENTRY_SYMBOL 约定
+        let library =
+            unsafe { libloading::Library::new(path.as_ref()) }.map_err(io::Error::other)?;
+        let entry: PluginEntry = unsafe {
+            *library
+                .get::<PluginEntry>(ENTRY_SYMBOL.as_bytes())
+                .map_err(io::Error::other)?
+        };
+        Self::from_entry(entry, Some(library), config)
+    }
+
+    /// 协商 ABI 版本并创建插件状态
+    fn from_entry(
+        entry: PluginEntry,
+        library: Option<libloading::Library>,
+        config: &str,
+    ) -> io::Result<Self> {
+        // SAFETY: 入口与函数表由插件提供，库在返回的输出目标存活期间保持加载
+        let vtable = unsafe { entry(MIN_ABI_VERSION, ABI_VERSION).as_ref() }
+            .copied()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "plugin supports none of ABI versions {}..={}",
+                        MIN_ABI_VERSION, ABI_VERSION
+                    ),
+                )
+            })?;
+        if !(MIN_ABI_VERSION..=ABI_VERSION).contains(&vtable.abi_version) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "plugin chose unsupported ABI version {}",
+                    vtable.abi_version
+                ),
+            ));
+        }
+        let name = if vtable.name.is_null() {
+            String::from("plugin")
+        } else {
+            // SAFETY: 非空名称为插件内以 NUL 结尾的静态字符串
+            unsafe { CStr::from_ptr(vtable.name) }
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        // SAFETY: 配置在调用期间有效，插件须自行复制需要保留的内容
+        let state = unsafe { (vtable.create)(config.as_ptr(), config.len()) };
+        if state.is_null() {
+            return Err(io::Error::other(format!(
+                "plugin {} failed to initialize",
+                name
+            )));
+        }
+        Ok(Self {
+            name,
+            vtable,
+            state: Mutex::new(State(state)),
+            _library: library,
+        })
+    }
+
+    /// 插件名称
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 在互斥锁内以插件状态调用 `call`，关闭后返回错误
+    fn with_state(&self, call: impl FnOnce(*mut c_void) -> i32, op: &str) -> io::Result<()> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.0.is_null() {
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                format!("plugin {} is shut down", self.name),
+            ));
+        }
+        match call(state.0) {
+            0 => Ok(()),
+            code => Err(io::Error::other(format!(
+                "plugin {} {} failed with code {}",
+                self.name, op, code
+            ))),
+        }
+    }
+}
+
+impl Sink for PluginSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        // SAFETY: 状态非空且调用串行，数据在调用期间有效
+        self.with_state(
+            |state| unsafe { (self.vtable.write)(state, data.as_ptr(), data.len()) },
+            "write",
+        )
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        for item in data {
+            self.write(item)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        // SAFETY: 状态非空且调用串行
+        self.with_state(|state| unsafe { (self.vtable.flush)(state) }, "flush")
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.0.is_null() {
+            return Ok(());
+        }
+        // SAFETY: 状态非空；销毁后置空，不再传给插件
+        let code = unsafe { (self.vtable.flush)(state.0) };
+        unsafe { (self.vtable.destroy)(state.0) };
+        state.0 = std::ptr::null_mut();
+        match code {
+            0 => Ok(()),
+            code => Err(io::Error::other(format!(
+                "plugin {} flush failed with code {}",
+                self.name, code
+            ))),
+        }
+    }
+}
+
+impl Drop for PluginSink {
+    fn drop(&mut self) {
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        if !state.0.is_null() {
+            // SAFETY: 未关闭的状态在库卸载前销毁
+            unsafe { (self.vtable.destroy)(state.0) };
+            state.0 = std::ptr::null_mut();
+        }
+    }
+}
+
+impl std::fmt::Debug for PluginSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginSink")
+            .field("name", &self.name)
+            .field("abi_version", &self.vtable.abi_version)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 进程内模拟插件：状态为收集写入数据的 Vec
+    static DESTROYED: AtomicUsize = AtomicUsize::new(0);
+
+    unsafe extern "C" fn create(config: *const u8, len: usize) -> *mut c_void {
+        let config = unsafe { std::slice::from_raw_parts(config, len) };
+        if config == b"fail" {
+            return std::ptr::null_mut();
+        }
+        Box::into_raw(Box::new(Vec::<u8>::new())).cast()
+    }
+
+    unsafe extern "C" fn write(state: *mut c_void, data: *const u8, len: usize) -> i32 {
+        let buf = unsafe { &mut *state.cast::<Vec<u8>>() };
+        if buf.len() > 8 {
+            return 28;
+        }
+        buf.extend_from_slice(unsafe { std::slice::from_raw_parts(data, len) });
+        0
+    }
+
+    unsafe extern "C" fn flush(_state: *mut c_void) -> i32 {
+        0
+    }
+
+    unsafe extern "C" fn destroy(state: *mut c_void) {
+        drop(unsafe { Box::from_raw(state.cast::<Vec<u8>>()) });
+        DESTROYED.fetch_add(1, Ordering::SeqCst);
+    }
+
+    static VTABLE: PluginVTable = PluginVTable {
+        abi_version: 1,
+        name: c"mock".as_ptr(),
+        create,
+        write,
+        flush,
+        destroy,
+    };
+
+    unsafe extern "C" fn entry(min: u32, max: u32) -> *const PluginVTable {
+        if (min..=max).contains(&1) {
+            &VTABLE
+        } else {
+            std::ptr::null()
+        }
+    }
+
+    unsafe extern "C" fn future_entry(_min: u32, _max: u32) -> *const PluginVTable {
+        static FUTURE: PluginVTable = PluginVTable {
+            abi_version: 2,
+            ..VTABLE
+        };
+        &FUTURE
+    }
+
+    #[test]
+    fn test_plugin_lifecycle() {
+        let sink = PluginSink::from_entry(entry, None, "").unwrap();
+        assert_eq!(sink.name(), "mock");
+        sink.write(b"hello").unwrap();
+        sink.write_batch(&[b"big".to_vec(), b"ger".to_vec()])
+            .unwrap();
+        let err = sink.write(b"!").unwrap_err();
+        assert_eq!(err.to_string(), "plugin mock write failed with code 28");
+
+        let destroyed = DESTROYED.load(Ordering::SeqCst);
+        sink.shutdown().unwrap();
+        assert_eq!(DESTROYED.load(Ordering::SeqCst), destroyed + 1);
+        assert_eq!(
+            sink.write(b"late").unwrap_err().kind(),
+            io::ErrorKind::BrokenPipe
+        );
+        drop(sink);
+        // 关闭后丢弃不会重复销毁
+        assert_eq!(DESTROYED.load(Ordering::SeqCst), destroyed + 1);
+    }
+
+    #[test]
+    fn test_plugin_negotiation_and_init_failures() {
+        let err = PluginSink::from_entry(future_entry, None, "").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        let err = PluginSink::from_entry(entry, None, "fail").unwrap_err();
+        assert_eq!(err.to_string(), "plugin mock failed to initialize");
+        assert!(PluginSink::load("/nonexistent/libplugin.so", "").is_err());
+    }
+}
@@ -1,7 +1,5 @@
-use nanolog_rs::{AsyncLoggerBuilder, ConsoleSink, DefaultFormatter, Level, global_logger, init_global_logger, info};
-use std::sync::atomic::{AtomicBool, Ordering};
+use nanolog_rs::{AsyncLoggerBuilder, Level, global_logger, init_global_logger};
 use std::sync::Arc;
-use std::time::Duration;
 
 fn main() -> Result<(), nanolog_rs::error::Error> {
     let logger = AsyncLoggerBuilder::new()
@@ -0,0 +1,324 @@
+//! 吞吐量浸泡测试
+//!
+//! N 个线程持续写日志 M 分钟，主输出为校验输出目标：逐条解析记录，确认每个线程的序号
+//! 严格递增（无重复、无乱序），并累加序号校验和。额外输出为按大小轮转的文件，
+//! 队列容量较小，使溢出与轮转路径在持续负载下反复触发。
+//!
+//! 结束时校验：
+//! - `block` 策略下每条已发布记录恰好到达一次（条数与校验和均与发布端一致）；
+//! - 丢弃策略下缺失的记录数与日志器的丢失统计一致；
+//! - 任何重复、乱序或无法解析的记录均视为失败。
+//!
+//! 运行：`cargo run --release --example soak_test -- --threads 8 --minutes 10 --overflow drop-oldest`
+//! （快速验证可用 `--seconds 10` 代替 `--minutes`）
+use nanolog_rs::{
+    AsyncLoggerBuilder, FileSink, Level, OverflowPolicy, Record, SimpleFormatter, Sink,
+};
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 队列容量（刻意偏小，使溢出路径持续触发）
+const QUEUE_CAPACITY: usize = 1024;
+
+/// 轮转文件大小上限
+const ROTATE_BYTES: usize = 4 << 20;
+
+/// 保留的轮转文件数
+const MAX_FILES: usize = 4;
+
+/// 进度报告间隔
+const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 命令行参数
+struct Options {
+    threads: usize,
+    duration: Duration,
+    overflow: OverflowPolicy,
+}
+
+impl Options {
+    fn parse() -> io::Result<Self> {
+        let mut options = Options {
+            threads: 4,
+            duration: Duration::from_secs(60),
+            overflow: OverflowPolicy::Block,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| invalid(format!("missing value for {}", arg)))?;
+            let number = || {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| invalid(format!("invalid value for {}: {}", arg, value)))
+            };
+            match arg.as_str() {
+                "--threads" => options.threads = number()?.max(1) as usize,
+                "--minutes" => options.duration = Duration::from_secs(number()? * 60),
+                "--seconds" => options.duration = Duration::from_secs(number()?),
+                "--overflow" => {
+                    options.overflow = match value.as_str() {
+                        "block" => OverflowPolicy::Block,
+                        "drop-newest" => OverflowPolicy::DropNewest,
+                        "drop-oldest" => OverflowPolicy::DropOldest,
+                        _ => return Err(invalid(format!("unknown overflow policy: {}", value))),
+                    }
+                }
+                _ => return Err(invalid(format!("unknown argument: {}", arg))),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// 单个线程的到达情况
+#[derive(Default, Clone)]
+struct Stream {
+    /// 最近到达的序号
+    last: Option<u64>,
+    /// 到达条数
+    received: u64,
+    /// 到达序号之和
+    checksum: u64,
+}
+
+#[derive(Default)]
+struct VerifyState {
+    streams: Vec<Stream>,
+    /// 重复或乱序的记录数
+    out_of_order: u64,
+    /// 无法解析的记录数
+    malformed: u64,
+}
+
+/// 校验输出目标：解析 `soak t=<线程> n=<序号>` 记录并检查每线程顺序
+struct VerifyingSink {
+    state: Mutex<VerifyState>,
+}
+
+impl VerifyingSink {
+    fn new(threads: usize) -> Self {
+        Self {
+            state: Mutex::new(VerifyState {
+                streams: vec![Stream::default(); threads],
+                ..VerifyState::default()
+            }),
+        }
+    }
+
+    fn check(state: &mut VerifyState, line: &[u8]) {
+        let Some((thread, seq)) = parse(line) else {
+            state.malformed += 1;
+            return;
+        };
+        let Some(stream) = state.streams.get_mut(thread) else {
+            state.malformed += 1;
+            return;
+        };
+        if stream.last.is_some_and(|last| seq <= last) {
+            state.out_of_order += 1;
+            return;
+        }
+        stream.last = Some(seq);
+        stream.received += 1;
+        stream.checksum = stream.checksum.wrapping_add(seq);
+    }
+
+    fn snapshot(&self) -> (Vec<Stream>, u64, u64) {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        (state.streams.clone(), state.out_of_order, state.malformed)
+    }
+}
+
+/// 解析 `[INFO] soak t=<线程> n=<序号>\n`
+fn parse(line: &[u8]) -> Option<(usize, u64)> {
+    let line = std::str::from_utf8(line).ok()?.trim_end();
+    let rest = line.split_once("soak t=")?.1;
+    let (thread, seq) = rest.split_once(" n=")?;
+    Some((thread.parse().ok()?, seq.parse().ok()?))
+}
+
+impl Sink for VerifyingSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| io::Error::other("lock poisoned"))?;
+        Self::check(&mut state, data);
+        Ok(())
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| io::Error::other("lock poisoned"))?;
+        for line in data {
+            Self::check(&mut state, line);
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 统计目录下的轮转文件数
+fn rotated_files(dir: &Path) -> io::Result<usize> {
+    let mut count = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        if name.to_string_lossy().starts_with("soak.log.") {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let options = Options::parse()?;
+    let dir = std::env::temp_dir().join(format!("nanolog-soak-{}", std::process::id()));
+    let file = FileSink::new(dir.join("soak.log"))?
+        .with_max_size(ROTATE_BYTES)
+        .with_max_files(MAX_FILES);
+    let verifier = Arc::new(VerifyingSink::new(options.threads));
+
+    let logger = Arc::new(
+        AsyncLoggerBuilder::new()
+            .level(Level::Info)
+            .formatter(Arc::new(SimpleFormatter::new()))
+            .sink(verifier.clone())
+            .output(Arc::new(SimpleFormatter::new()), Arc::new(file))
+            .queue_capacity(QUEUE_CAPACITY)
+            .overflow_policy(options.overflow)
+            .build()?,
+    );
+
+    println!(
+        "soak: {} threads, {:?}, overflow {:?}, rotating into {}",
+        options.threads,
+        options.duration,
+        options.overflow,
+        dir.display()
+    );
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let producers: Vec<_> = (0..options.threads)
+        .map(|t| {
+            let logger = logger.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                let mut published = 0u64;
+                while !stop.load(Ordering::Relaxed) {
+                    let message = format!("soak t={} n={}", t, published);
+                    if logger
+                        .log(Record::new(Level::Info, "soak", file!(), line!(), message))
+                        .is_err()
+                    {
+                        break;
+                    }
+                    published += 1;
+                }
+                published
+            })
+        })
+        .collect();
+
+    let started = Instant::now();
+    while started.elapsed() < options.duration {
+        std::thread::sleep(REPORT_INTERVAL.min(options.duration.saturating_sub(started.elapsed())));
+        let (sent, written, _) = logger.get_loss_stats();
+        println!(
+            "  {:>6.0}s sent {} written {} ({:.0} rec/s)",
+            started.elapsed().as_secs_f64(),
+            sent,
+            written,
+            written as f64 / started.elapsed().as_secs_f64()
+        );
+    }
+    stop.store(true, Ordering::Relaxed);
+
+    let mut published = Vec::with_capacity(options.threads);
+    for producer in producers {
+        published.push(
+            producer
+                .join()
+                .map_err(|_| io::Error::other("producer thread panicked"))?,
+        );
+    }
+    logger.flush()?;
+    let (_, _, lost) = logger.get_loss_stats();
+    logger.shutdown()?;
+
+    let (streams, out_of_order, malformed) = verifier.snapshot();
+    let mut failures = Vec::new();
+    let mut total_published = 0u64;
+    let mut total_received = 0u64;
+    for (t, (stream, &published)) in streams.iter().zip(&published).enumerate() {
+        total_published += published;
+        total_received += stream.received;
+        let expected_checksum = published.saturating_sub(1) * published / 2;
+        if stream.received > published || stream.last.is_some_and(|last| last >= published) {
+            failures.push(format!("thread {}: received records never published", t));
+        }
+        if options.overflow == OverflowPolicy::Block
+            && (stream.received != published || stream.checksum != expected_checksum)
+        {
+            failures.push(format!(
+                "thread {}: published {} received {} checksum {} expected {}",
+                t, published, stream.received, stream.checksum, expected_checksum
+            ));
+        }
+    }
+    let missing = total_published - total_received.min(total_published);
+    if missing != lost as u64 {
+        failures.push(format!(
+            "{} records missing but loss stats report {}",
+            missing, lost
+        ));
+    }
+    if out_of_order > 0 {
+        failures.push(format!(
+            "{} duplicate or out-of-order records",
+            out_of_order
+        ));
+    }
+    if malformed > 0 {
+        failures.push(format!("{} malformed records", malformed));
+    }
+
+    let rotated = rotated_files(&dir)?;
+    let elapsed = started.elapsed().as_secs_f64();
+    println!(
+        "published {} received {} lost {} rotated files {} ({:.0} rec/s)",
+        total_published,
+        total_received,
+        lost,
+        rotated,
+        total_received as f64 / elapsed
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+
+    if failures.is_empty() {
+        println!("soak passed");
+        Ok(())
+    } else {
+        for failure in &failures {
+            eprintln!("FAIL: {}", failure);
+        }
+        Err(io::Error::other(format!("{} verification failures", failures.len())).into())
+    }
+}
@@ -0,0 +1,126 @@
+use nanolog_rs::allocation::{self, CountingAllocator};
+use nanolog_rs::error::Error;
+use nanolog_rs::{AsyncLogger, Level, NullSink, OverflowPolicy, Record, SimpleFormatter};
+use std::sync::Arc;
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator::system();
+
+const CALLS: u64 = 1000;
+
+fn logger(policy: OverflowPolicy) -> Result<Arc<AsyncLogger>, Error> {
+    Ok(Arc::new(
+        AsyncLogger::builder()
+            .level(Level::Info)
+            .formatter(Arc::new(SimpleFormatter::new()))
+            .sink(Arc::new(NullSink::new()))
+            .queue_capacity(64)
+            .overflow_policy(policy)
+            .build()?,
+    ))
+}
+
+fn records(level: Level) -> Vec<Record> {
+    (0..CALLS)
+        .map(|i| {
+            Record::new(level, "app", file!(), line!(), format!("request {}", i))
+                .with_field("id", i)
+        })
+        .collect()
+}
+
+#[test]
+fn test_log_call_does_not_allocate() {
+    assert!(allocation::is_installed());
+    // 小队列使各溢出策略的满队列路径也被覆盖
+    for policy in [
+        OverflowPolicy::Block,
+        OverflowPolicy::DropNewest,
+        OverflowPolicy::DropOldest,
+    ] {
+        let logger = logger(policy).unwrap();
+        let records = records(Level::Info);
+        // 预热：线程本地状态在首次调用时初始化
+        let _ = logger.log(Record::new(
+            Level::Info,
+            "app",
+            file!(),
+            line!(),
+            String::new(),
+        ));
+        let (_, stats) = allocation::measure(|| {
+            for record in records {
+                let _ = logger.log(record);
+            }
+        });
+        assert_eq!(stats.allocations + stats.reallocations, 0, "{:?}", policy);
+        assert!(logger.shutdown().is_ok());
+    }
+}
+
+#[test]
+fn test_disabled_and_macro_calls_allocate_at_most_the_message() {
+    let logger = logger(OverflowPolicy::Block).unwrap();
+    let debug = records(Level::Debug);
+    let (_, stats) = allocation::measure(|| {
+        for record in debug {
+            let _ = logger.log(record);
+        }
+    });
+    assert_eq!(stats.allocations + stats.reallocations, 0);
+
+    nanolog_rs::init_global_logger(logger.clone()).unwrap();
+    let (_, stats) = allocation::measure(|| {
+        for i in 0..CALLS {
+            nanolog_rs::debug!("skipped {}", i);
+        }
+    });
+    assert_eq!(stats.allocations + stats.reallocations, 0);
+    // 调用点在首次调用时注册，预热后测量；字面量消息不分配
+    let handled = || nanolog_rs::info!("request handled");
+    handled();
+    let (_, stats) = allocation::measure(|| {
+        for _ in 0..CALLS {
+            handled();
+        }
+    });
+    assert_eq!(stats.allocations + stats.reallocations, 0, "{:?}", stats);
+
+    // 格式化的消息写入队列槽位归还的缓冲区：队列轮转一圈后不再分配
+    let formatted = |i: u64| nanolog_rs::info!("request {}", i);
+    for i in 0..2 * 64 {
+        formatted(i);
+    }
+    let (_, stats) = allocation::measure(|| {
+        for i in 0..CALLS {
+            formatted(i);
+        }
+    });
+    assert_eq!(stats.allocations + stats.reallocations, 0, "{:?}", stats);
+
+    // `write_with!` 复用已写出记录的消息缓冲区：队列轮转一圈后不再分配
+    let streamed =
+        |i: u64| nanolog_rs::write_with!(Level::Info, |buf| write!(buf, "request {}", i));
+    for i in 0..2 * 64 {
+        streamed(i);
+    }
+    let (_, stats) = allocation::measure(|| {
+        for i in 0..CALLS {
+            streamed(i);
+        }
+    });
+    assert_eq!(stats.allocations + stats.reallocations, 0, "{:?}", stats);
+
+    // 预取的槽位在自身与队列之间轮转消息缓冲区
+    let mut slot = nanolog_rs::record_slot!(&logger, Level::Info);
+    for i in 0..2 * 64 {
+        let _ = slot.log(|r| write!(r, "tick {}", i));
+    }
+    let (_, stats) = allocation::measure(|| {
+        for i in 0..CALLS {
+            let _ = slot.log(|r| write!(r, "tick {}", i));
+        }
+    });
+    assert_eq!(stats.allocations + stats.reallocations, 0, "{:?}", stats);
+    assert!(logger.flush().is_ok());
+}
@@ -35,4 +35,10 @@ fn test_macros_with_memory_sink() {
     assert!(s.contains("[INFO] i1"));
     assert!(s.contains("[DEBUG] d1"));
     assert!(s.contains("[TRACE] t1"));
+
+    // 同步记录在宏返回时已写入
+    nanolog_rs::info!(sync: "audit {}", 7);
+    assert!(String::from_utf8_lossy(&mem_sink.get_content()).contains("[INFO] audit 7"));
+    nanolog_rs::warn!(target: "audit", sync: "w2");
+    assert!(String::from_utf8_lossy(&mem_sink.get_content()).contains("[WARN] w2"));
 }
@@ -0,0 +1,11 @@
+//! 日志宏的编译期诊断测试
+//!
+//! `tests/ui/pass` 下的用法必须能编译，`tests/ui/fail` 下的误用必须以对应 `.stderr` 中的错误信息失败。
+//! 修改宏后如错误信息有意变化，以 `TRYBUILD=overwrite cargo test --test macro_ui` 更新快照。
+
+#[test]
+fn macro_ui() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/pass/*.rs");
+    cases.compile_fail("tests/ui/fail/*.rs");
+}
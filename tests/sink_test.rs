@@ -0,0 +1,1176 @@
+use nanolog_rs::diagnostics::Diagnostic;
+use nanolog_rs::http::{HttpPayload, HttpSink};
+use nanolog_rs::sink::{FallbackSink, MemorySink, RecordMeta, Sink, TcpSink, TimeoutSink};
+use nanolog_rs::{AsyncLoggerBuilder, Level, Record, SimpleFormatter, Watchdog};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 写入时挂起指定时长的输出目标
+struct HangingSink {
+    inner: MemorySink,
+    hang: Duration,
+}
+
+impl Sink for HangingSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        std::thread::sleep(self.hang);
+        self.inner.write(data)
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        std::thread::sleep(self.hang);
+        self.inner.write_batch(data)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 可切换故障状态的输出目标
+#[derive(Default)]
+struct FlakySink {
+    inner: MemorySink,
+    down: AtomicBool,
+}
+
+impl FlakySink {
+    fn check(&self) -> io::Result<()> {
+        if self.down.load(Ordering::Acquire) {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "down"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Sink for FlakySink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        self.check()?;
+        self.inner.write(data)
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        self.check()?;
+        self.inner.write_batch(data)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn health_check(&self) -> io::Result<()> {
+        self.check()
+    }
+}
+
+#[test]
+fn test_timeout_sink_turns_hang_into_error() {
+    let sink = TimeoutSink::new(
+        HangingSink {
+            inner: MemorySink::new(),
+            hang: Duration::from_millis(300),
+        },
+        Duration::from_millis(30),
+    )
+    .expect("spawn worker");
+
+    let started = Instant::now();
+    let err = sink.write(b"a\n").expect_err("write should time out");
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert!(started.elapsed() < Duration::from_millis(250));
+
+    // 前一次写入仍挂起时立即失败
+    let err = sink.write(b"b\n").expect_err("sink still blocked");
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+    std::thread::sleep(Duration::from_millis(400));
+    assert_eq!(sink.inner().inner.get_content(), b"a\n");
+    assert!(sink.flush().is_ok());
+}
+
+#[test]
+fn test_sink_timeout_reported_through_diagnostics() {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let reports_c = reports.clone();
+    let sink = TimeoutSink::new(
+        HangingSink {
+            inner: MemorySink::new(),
+            hang: Duration::from_millis(200),
+        },
+        Duration::from_millis(20),
+    )
+    .expect("spawn worker");
+
+    let logger = AsyncLoggerBuilder::new()
+        .level(Level::Info)
+        .formatter(Arc::new(SimpleFormatter::new()))
+        .sink(Arc::new(sink))
+        .diagnostics(Arc::new(move |d: &Diagnostic| {
+            reports_c.lock().expect("lock").push(d.clone())
+        }))
+        .build()
+        .expect("build logger");
+
+    let _ = logger.log(Record::new(
+        Level::Info,
+        "t",
+        file!(),
+        line!(),
+        "x".to_string(),
+    ));
+    let _ = logger.flush();
+
+    let reports = reports.lock().expect("lock");
+    assert!(matches!(
+        reports.first(),
+        Some(Diagnostic::SinkError {
+            operation: "write",
+            ..
+        })
+    ));
+    drop(reports);
+    let _ = logger.shutdown();
+}
+
+#[test]
+fn test_watchdog_reports_stall_and_switches_to_fallback() {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let reports_c = reports.clone();
+    let fallback = Arc::new(MemorySink::new());
+
+    let logger = AsyncLoggerBuilder::new()
+        .level(Level::Info)
+        .formatter(Arc::new(SimpleFormatter::new()))
+        .sink(Arc::new(HangingSink {
+            inner: MemorySink::new(),
+            hang: Duration::from_millis(400),
+        }))
+        .watchdog(Watchdog::new(Duration::from_millis(50)).with_fallback(fallback.clone()))
+        .diagnostics(Arc::new(move |d: &Diagnostic| {
+            reports_c.lock().expect("lock").push(d.clone())
+        }))
+        .build()
+        .expect("build logger");
+
+    let record =
+        |message: &str| Record::new(Level::Info, "t", file!(), line!(), message.to_string());
+    let _ = logger.log(record("first"));
+    std::thread::sleep(Duration::from_millis(200));
+    let _ = logger.log(record("second"));
+    let _ = logger.flush();
+
+    let stalled = reports.lock().expect("lock").iter().any(|d| {
+        matches!(
+            d,
+            Diagnostic::ConsumerStalled {
+                switched_to_fallback: true,
+                ..
+            }
+        )
+    });
+    assert!(stalled);
+    let content = String::from_utf8_lossy(&fallback.get_content()).into_owned();
+    assert!(content.contains("second"));
+    assert!(!content.contains("first"));
+    let _ = logger.shutdown();
+}
+
+#[test]
+fn test_fallback_sink_demotes_and_promotes() {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let reports_c = reports.clone();
+    let primary = Arc::new(FlakySink::default());
+    let secondary = Arc::new(MemorySink::new());
+    let chain = FallbackSink::new(vec![primary.clone(), secondary.clone()])
+        .with_failure_threshold(2)
+        .with_probe_interval(Duration::from_millis(20))
+        .with_diagnostics(Arc::new(move |d: &Diagnostic| {
+            reports_c.lock().expect("lock").push(d.clone())
+        }));
+
+    primary.down.store(true, Ordering::Release);
+    for line in ["a\n", "b\n", "c\n"] {
+        assert!(chain.write(line.as_bytes()).is_ok());
+    }
+    assert_eq!(chain.active(), 1);
+    assert_eq!(secondary.get_content(), b"a\nb\nc\n");
+
+    primary.down.store(false, Ordering::Release);
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(chain.write(b"d\n").is_ok());
+    assert_eq!(chain.active(), 0);
+    assert_eq!(primary.inner.get_content(), b"d\n");
+
+    let reports = reports.lock().expect("lock");
+    assert!(matches!(
+        reports.as_slice(),
+        [
+            Diagnostic::SinkDemoted { from: 0, to: 1, .. },
+            Diagnostic::SinkPromoted { from: 1, to: 0 }
+        ]
+    ));
+}
+
+#[test]
+fn test_tcp_sink_spills_and_reconnects() {
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    // 先占用端口再释放，得到一个当前无人监听的地址
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.local_addr())
+        .expect("bind");
+    let sink = TcpSink::new(addr.to_string())
+        .with_backoff(Duration::from_millis(10), Duration::from_millis(20))
+        .with_connect_timeout(Duration::from_millis(200));
+
+    assert!(sink.write(b"first\n").is_ok());
+    assert!(!sink.is_connected());
+    assert_eq!(sink.spilled_bytes(), 6);
+
+    let listener = TcpListener::bind(addr).expect("rebind");
+    let reader = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("accept");
+        let mut received = String::new();
+        let _ = stream.read_to_string(&mut received);
+        received
+    });
+
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(sink.write(b"second\n").is_ok());
+    assert!(sink.is_connected());
+    assert_eq!(sink.spilled_bytes(), 0);
+    assert!(sink.shutdown().is_ok());
+
+    assert_eq!(reader.join().expect("reader"), "first\nsecond\n");
+}
+
+#[test]
+fn test_tcp_sink_spills_records_when_peer_dies_before_flush() {
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let sink = TcpSink::new(addr.to_string())
+        .with_backoff(Duration::from_millis(1), Duration::from_millis(1))
+        .with_connect_timeout(Duration::from_millis(200));
+
+    assert!(sink.write(b"first\n").is_ok());
+    assert!(sink.flush().is_ok());
+    let (peer, _) = listener.accept().expect("accept");
+    drop(listener);
+
+    // 写入只进入缓冲区；对端带着未读数据关闭连接（发送 RST），随后的刷新必然失败
+    assert!(sink.write(b"second\n").is_ok());
+    drop(peer);
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(sink.flush().is_err());
+    assert!(!sink.is_connected());
+    assert_eq!(sink.spilled_bytes(), 7);
+    assert_eq!(sink.dropped_bytes(), 0);
+
+    let listener = TcpListener::bind(addr).expect("rebind");
+    let reader = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("accept");
+        let mut received = String::new();
+        let _ = stream.read_to_string(&mut received);
+        received
+    });
+    std::thread::sleep(Duration::from_millis(5));
+    assert!(sink.flush().is_ok());
+    assert_eq!(sink.spilled_bytes(), 0);
+    assert!(sink.shutdown().is_ok());
+    assert_eq!(reader.join().expect("reader"), "second\n");
+}
+
+#[test]
+fn test_tcp_sink_spill_limit_drops_oldest() {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.local_addr())
+        .expect("bind");
+    let sink = TcpSink::new(addr.to_string())
+        .with_backoff(Duration::from_secs(60), Duration::from_secs(60))
+        .with_spill_limit(10);
+
+    assert!(sink.write(b"123456\n").is_ok());
+    assert!(sink.write(b"abcdef\n").is_err());
+    assert_eq!(sink.spilled_bytes(), 7);
+    assert_eq!(sink.dropped_bytes(), 7);
+}
+
+/// 极简 HTTP 服务端：按给定状态码依次应答，返回收到的请求体
+fn http_server(
+    statuses: Vec<u16>,
+) -> io::Result<(String, std::thread::JoinHandle<io::Result<Vec<String>>>)> {
+    use std::io::{BufRead, BufReader, Read, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let url = format!("http://{}/ingest", listener.local_addr()?);
+    let handle = std::thread::spawn(move || {
+        let mut bodies = Vec::new();
+        for status in statuses {
+            let (stream, _) = listener.accept()?;
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line)?;
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap_or(0);
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body)?;
+            bodies.push(String::from_utf8_lossy(&body).into_owned());
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {} X\r\nContent-Length: 0\r\n\r\n",
+                status
+            )?;
+        }
+        Ok(bodies)
+    });
+    Ok((url, handle))
+}
+
+#[test]
+fn test_http_sink_batches_and_retries() {
+    let (url, server) = http_server(vec![503, 200, 200]).expect("server");
+    let sink = HttpSink::new(&url)
+        .expect("url")
+        .with_payload(HttpPayload::JsonArray)
+        .with_max_batch_bytes(24)
+        .with_retries(2, Duration::from_millis(5));
+
+    assert!(
+        sink.write_batch(&[b"{\"n\":1}\n".to_vec(), b"{\"n\":2}\n".to_vec()])
+            .is_ok()
+    );
+    // 第三条将使请求体超出上限，先发送前两条（首次 503 后重试成功）
+    assert!(sink.write(b"{\"n\":3}\n").is_ok());
+    assert!(sink.flush().is_ok());
+    assert_eq!(sink.dropped_records(), 0);
+
+    let bodies = server.join().expect("server").expect("io");
+    assert_eq!(
+        bodies,
+        vec![
+            r#"[{"n":1},{"n":2}]"#.to_string(),
+            r#"[{"n":1},{"n":2}]"#.to_string(),
+            r#"[{"n":3}]"#.to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_http_sink_drops_batch_on_client_error() {
+    let (url, server) = http_server(vec![400]).expect("server");
+    let sink = HttpSink::new(&url)
+        .expect("url")
+        .with_retries(3, Duration::from_millis(5));
+
+    assert!(sink.write(b"a").is_ok());
+    assert!(sink.flush().is_err());
+    assert_eq!(sink.dropped_records(), 1);
+    assert_eq!(
+        server.join().expect("server").expect("io"),
+        vec!["a\n".to_string()]
+    );
+}
+
+/// 以 `Arc` 持有的输出目标（供按值包装的装饰器使用）
+struct ArcSink(Arc<dyn Sink>);
+
+impl Sink for ArcSink {
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        self.0.write(data)
+    }
+
+    fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+        self.0.write_batch(data)
+    }
+
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        self.0.write_records(meta, data)
+    }
+
+    fn stage_record(&self, record: &Record) {
+        self.0.stage_record(record)
+    }
+
+    fn batch_compression(&self) -> Option<nanolog_rs::BatchCompression> {
+        self.0.batch_compression()
+    }
+
+    fn encode_batch(&self, data: &[Vec<u8>], out: &mut Vec<u8>) {
+        self.0.encode_batch(data, out)
+    }
+
+    fn write_compressed(&self, frame: &[u8], records: usize) -> io::Result<()> {
+        self.0.write_compressed(frame, records)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.0.flush()
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.0.shutdown()
+    }
+
+    fn health_check(&self) -> io::Result<()> {
+        self.0.health_check()
+    }
+}
+
+/// 记录每条记录元数据的输出目标
+#[derive(Default)]
+struct MetaRecordingSink {
+    seen: Mutex<Vec<(Level, &'static str, String)>>,
+}
+
+impl Sink for MetaRecordingSink {
+    fn write(&self, _data: &[u8]) -> io::Result<()> {
+        Err(io::Error::other("expected write_records"))
+    }
+
+    fn write_batch(&self, _data: &[Vec<u8>]) -> io::Result<()> {
+        Err(io::Error::other("expected write_records"))
+    }
+
+    fn write_records(&self, meta: &[RecordMeta], data: &[Vec<u8>]) -> io::Result<()> {
+        let mut seen = self.seen.lock().map_err(|_| io::Error::other("poisoned"))?;
+        for (meta, data) in meta.iter().zip(data) {
+            let line = String::from_utf8_lossy(data).trim_end().to_string();
+            seen.push((meta.level, meta.target, line));
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sink_receives_record_metadata() {
+    let sink = Arc::new(MetaRecordingSink::default());
+    let logger = AsyncLoggerBuilder::new()
+        .level(Level::Info)
+        .formatter(Arc::new(SimpleFormatter::new()))
+        .sink(sink.clone())
+        .build()
+        .expect("build logger");
+
+    for (level, target) in [(Level::Info, "app::http"), (Level::Error, "app::db")] {
+        let _ = logger.log(Record::new(level, target, file!(), 1, "m".to_string()));
+    }
+    let _ = logger.shutdown();
+
+    assert_eq!(
+        *sink.seen.lock().expect("lock"),
+        vec![
+            (Level::Info, "app::http", "[INFO] m".to_string()),
+            (Level::Error, "app::db", "[ERROR] m".to_string()),
+        ]
+    );
+}
+
+/// 持续写入直到输出目标完成 `rotations` 次轮转，返回写入的记录
+fn write_until_rotated(sink: &nanolog_rs::FileSink, rotations: u64) -> io::Result<Vec<String>> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut written = Vec::new();
+    while sink.rotations() < rotations {
+        if Instant::now() >= deadline {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "rotation stalled"));
+        }
+        let line = format!("record {:03}\n", written.len());
+        sink.write(line.as_bytes())?;
+        written.push(line);
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    Ok(written)
+}
+
+#[test]
+fn test_file_sink_rotates_with_template_and_retention() {
+    use nanolog_rs::rotation::{FileNameTemplate, RotationPolicy};
+
+    let dir = std::env::temp_dir().join(format!("nanolog-rotate-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("notes.txt"), b"keep").unwrap();
+
+    let policy = RotationPolicy::new()
+        .max_size(10)
+        .interval(Duration::from_secs(3600))
+        .template(FileNameTemplate::parse("app-%Y-%m-%d.%N.log").unwrap())
+        .max_files(2);
+    let sink = nanolog_rs::FileSink::new(dir.join("app.log"))
+        .unwrap()
+        .with_rotation(policy);
+    let written = write_until_rotated(&sink, 4).unwrap();
+    sink.shutdown().unwrap();
+
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert!(names.contains(&"notes.txt".to_string()));
+    names.retain(|n| n.starts_with("app-"));
+    assert_eq!(names.len(), 2, "{:?}", names);
+
+    // 清理后序号会被复用，按记录内容排序；保留的轮转文件与当前文件拼接为写入记录的连续后缀
+    let mut rotated: Vec<String> = names
+        .iter()
+        .map(|name| std::fs::read_to_string(dir.join(name)).unwrap())
+        .collect();
+    rotated.sort();
+    let content = rotated.concat() + &std::fs::read_to_string(dir.join("app.log")).unwrap();
+    assert!(!content.is_empty());
+    assert!(written.concat().ends_with(&content), "{}", content);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_file_sink_daily_rotation_rolls_stale_file() {
+    use nanolog_rs::rotation::{FileNameTemplate, Period, RotationPolicy};
+    use std::time::SystemTime;
+
+    let dir = std::env::temp_dir().join(format!("nanolog-daily-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("app.log");
+    std::fs::write(&path, b"yesterday\n").unwrap();
+    // 文件最后写入于两天前，属于已结束的自然日
+    std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(2 * 86_400))
+        .unwrap();
+
+    let policy = RotationPolicy::new()
+        .period(Period::Daily)
+        .template(FileNameTemplate::parse("app-%Y-%m-%d.log").unwrap());
+    let sink = nanolog_rs::FileSink::new(&path)
+        .unwrap()
+        .with_rotation(policy);
+    let written = write_until_rotated(&sink, 1).unwrap();
+    sink.write(b"still today\n").unwrap();
+    sink.shutdown().unwrap();
+    assert_eq!(sink.rotations(), 1);
+
+    let rotated: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p != &path)
+        .collect();
+    assert_eq!(rotated.len(), 1);
+    let old = std::fs::read_to_string(&rotated[0]).unwrap();
+    let current = std::fs::read_to_string(&path).unwrap();
+    // 轮转完成前写入的记录可能仍进入旧文件
+    assert!(old.starts_with("yesterday\n"));
+    assert!(current.ends_with("still today\n"));
+    assert_eq!(
+        old + &current,
+        format!("yesterday\n{}still today\n", written.concat())
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_file_sink_compresses_rotated_files() {
+    use nanolog_rs::rotation::{Compression, FileNameTemplate, RotationPolicy};
+
+    let dir = std::env::temp_dir().join(format!("nanolog-compress-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let policy = RotationPolicy::new()
+        .max_size(10)
+        .template(FileNameTemplate::parse("app.%N.log").unwrap())
+        .compress(Compression::Gzip)
+        .max_files(2);
+    let sink = nanolog_rs::FileSink::new(dir.join("app.log"))
+        .unwrap()
+        .with_rotation(policy);
+    write_until_rotated(&sink, 3).unwrap();
+    // 关闭时等待后台压缩完成
+    sink.shutdown().unwrap();
+
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|n| n != "app.log")
+        .collect();
+    names.sort();
+    assert!(!names.is_empty() && names.len() <= 3, "{:?}", names);
+    assert!(names.iter().all(|n| n.ends_with(".log.gz")), "{:?}", names);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_file_sink_durability_policies() {
+    use nanolog_rs::{Durability, FileSink};
+
+    let dir = std::env::temp_dir().join(format!("nanolog-durability-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // 每写入 16 字节同步一次
+    let sink = FileSink::new(dir.join("bytes.log"))
+        .unwrap()
+        .with_durability(Durability::EveryBytes(16));
+    sink.write(b"0123456789\n").unwrap();
+    assert_eq!(sink.syncs(), 0);
+    sink.write(b"0123456789\n").unwrap();
+    assert_eq!(sink.syncs(), 1);
+    // 同步前先写出缓冲区
+    assert_eq!(std::fs::read(dir.join("bytes.log")).unwrap().len(), 22);
+
+    // 每次刷新同步，无新数据时不重复同步
+    let sink = FileSink::new(dir.join("flush.log"))
+        .unwrap()
+        .with_durability(Durability::EveryFlush);
+    sink.write(b"a\n").unwrap();
+    sink.flush().unwrap();
+    sink.flush().unwrap();
+    assert_eq!(sink.syncs(), 1);
+
+    // 默认不同步，错误级别批次按 sync_on_error 立即落盘
+    let sink = FileSink::new(dir.join("error.log"))
+        .unwrap()
+        .with_sync_on_error(true);
+    let info = Record::new(Level::Info, "app", file!(), line!(), String::new());
+    let error = Record::new(Level::Error, "app", file!(), line!(), String::new());
+    sink.write_records(&[RecordMeta::of(&info)], &[b"info\n".to_vec()])
+        .unwrap();
+    sink.flush().unwrap();
+    assert_eq!(sink.syncs(), 0);
+    sink.write_records(
+        &[RecordMeta::of(&info), RecordMeta::of(&error)],
+        &[b"info\n".to_vec(), b"error\n".to_vec()],
+    )
+    .unwrap();
+    assert_eq!(sink.syncs(), 1);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("error.log")).unwrap(),
+        "info\ninfo\nerror\n"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_level_routing_sink_splits_by_level() {
+    use nanolog_rs::LevelRoutingSink;
+
+    let errors = Arc::new(MemorySink::new());
+    let rest = Arc::new(MemorySink::new());
+    let traces = Arc::new(MemorySink::new());
+    let sink = LevelRoutingSink::split(Level::Warn, errors.clone(), rest.clone())
+        .route(Level::Trace..=Level::Trace, traces.clone());
+    let logger = AsyncLoggerBuilder::new()
+        .level(Level::Trace)
+        .formatter(Arc::new(SimpleFormatter::new()))
+        .sink(Arc::new(sink))
+        .build()
+        .unwrap();
+
+    for (level, message) in [
+        (Level::Info, "started"),
+        (Level::Error, "failed"),
+        (Level::Debug, "retrying"),
+        (Level::Trace, "detail"),
+        (Level::Warn, "slow"),
+        (Level::Info, "done"),
+    ] {
+        let _ = logger.log(Record::new(level, "app", file!(), line!(), message));
+    }
+    logger.shutdown().unwrap();
+
+    assert_eq!(errors.get_content(), b"[ERROR] failed\n[WARN] slow\n");
+    assert_eq!(
+        rest.get_content(),
+        b"[INFO] started\n[DEBUG] retrying\n[INFO] done\n"
+    );
+    assert_eq!(traces.get_content(), b"[TRACE] detail\n");
+}
+
+#[test]
+fn test_file_sink_removes_expired_files_by_sweep() {
+    use nanolog_rs::rotation::{FileNameTemplate, RotationPolicy};
+    use std::time::SystemTime;
+
+    let dir = std::env::temp_dir().join(format!("nanolog-max-age-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let age = |name: &str, days: u64| {
+        std::fs::write(dir.join(name), b"old\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(dir.join(name))
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(days * 86_400))
+            .unwrap();
+    };
+    age("app-2024-01-01.log", 40);
+    age("app-2024-01-02.log.gz", 35);
+    age("app-2024-02-01.log", 2);
+    age("notes.txt", 40);
+
+    // 无需轮转，维护线程定期清理过期文件
+    let policy = RotationPolicy::new()
+        .template(FileNameTemplate::parse("app-%Y-%m-%d.log").unwrap())
+        .max_age(Duration::from_secs(30 * 86_400))
+        .sweep_interval(Duration::from_millis(10));
+    let sink = nanolog_rs::FileSink::new(dir.join("app.log"))
+        .unwrap()
+        .with_rotation(policy);
+    sink.write(b"today\n").unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while dir.join("app-2024-01-01.log").exists() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    sink.shutdown().unwrap();
+
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["app-2024-02-01.log", "app.log", "notes.txt"]);
+    assert_eq!(sink.rotations(), 0);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_router_sink_dispatches_by_target_prefix() {
+    use nanolog_rs::RouterSink;
+
+    let audit = Arc::new(MemorySink::new());
+    let db = Arc::new(MemorySink::new());
+    let rest = Arc::new(MemorySink::new());
+    let sink = RouterSink::new(rest.clone())
+        .route("audit::*", audit.clone())
+        .route("app::db", db.clone())
+        // 更长的前缀优先
+        .route("app::db::audit", audit.clone());
+    let logger = AsyncLoggerBuilder::new()
+        .formatter(Arc::new(SimpleFormatter::new()))
+        .sink(Arc::new(sink))
+        .build()
+        .unwrap();
+
+    for (target, message) in [
+        ("audit", "login"),
+        ("audit::payments", "refund"),
+        ("auditor", "not audit"),
+        ("app::db", "query"),
+        ("app::db::pool", "checkout"),
+        ("app::db::audit", "schema change"),
+        ("app", "ready"),
+    ] {
+        let _ = logger.log(Record::new(Level::Info, target, file!(), line!(), message));
+    }
+    logger.shutdown().unwrap();
+
+    assert_eq!(
+        audit.get_content(),
+        b"[INFO] login\n[INFO] refund\n[INFO] schema change\n"
+    );
+    assert_eq!(db.get_content(), b"[INFO] query\n[INFO] checkout\n");
+    assert_eq!(rest.get_content(), b"[INFO] not audit\n[INFO] ready\n");
+}
+
+#[test]
+fn test_wrapped_composite_members_receive_staged_records() {
+    use nanolog_rs::{CompositeSink, FailoverSink, JsonFormatter, LevelRoutingSink, RouterSink};
+
+    type Wrap = fn(Arc<dyn Sink>) -> Arc<dyn Sink>;
+    let wrappers: Vec<(&str, Wrap)> = vec![
+        ("composite", |sink| sink),
+        ("level routing", |sink| {
+            Arc::new(LevelRoutingSink::new(sink))
+        }),
+        ("router", |sink| {
+            Arc::new(RouterSink::new(Arc::new(MemorySink::new())).route("app", sink))
+        }),
+        ("fallback", |sink| Arc::new(FallbackSink::new(vec![sink]))),
+        ("failover", |sink| {
+            Arc::new(FailoverSink::new(sink, Arc::new(MemorySink::new())))
+        }),
+        ("timeout", |sink| {
+            Arc::new(TimeoutSink::new(ArcSink(sink), Duration::from_secs(5)).unwrap())
+        }),
+    ];
+    for (name, wrap) in wrappers {
+        let json = Arc::new(MemorySink::new());
+        let mut composite = CompositeSink::new();
+        composite.add_sink_with(
+            json.clone(),
+            Level::Info,
+            Some(Arc::new(JsonFormatter::new())),
+        );
+        // 配置了后备目标的看门狗会再包装一层可切换目标
+        let logger = AsyncLoggerBuilder::new()
+            .formatter(Arc::new(SimpleFormatter::new()))
+            .sink(wrap(Arc::new(composite)))
+            .watchdog(
+                Watchdog::new(Duration::from_secs(60)).with_fallback(Arc::new(MemorySink::new())),
+            )
+            .build()
+            .unwrap();
+        for message in ["started", "done"] {
+            let _ = logger.log(Record::new(Level::Info, "app", file!(), line!(), message));
+        }
+        logger.shutdown().unwrap();
+
+        let json = String::from_utf8(json.get_content()).unwrap();
+        assert_eq!(json.lines().count(), 2, "{}: {}", name, json);
+        assert!(json.contains("\"message\":\"done\""), "{}: {}", name, json);
+    }
+}
+
+#[test]
+fn test_timeout_sink_forwards_records_and_health_check() {
+    use nanolog_rs::RouterSink;
+
+    let audit = Arc::new(MemorySink::new());
+    let rest = Arc::new(MemorySink::new());
+    let router = RouterSink::new(rest.clone()).route("audit", audit.clone());
+    let sink = TimeoutSink::new(router, Duration::from_secs(5)).unwrap();
+    let logger = AsyncLoggerBuilder::new()
+        .formatter(Arc::new(SimpleFormatter::new()))
+        .sink(Arc::new(sink))
+        .build()
+        .unwrap();
+    let _ = logger.log(Record::new(Level::Info, "audit", file!(), line!(), "login"));
+    let _ = logger.log(Record::new(Level::Info, "app", file!(), line!(), "ready"));
+    logger.shutdown().unwrap();
+    assert_eq!(audit.get_content(), b"[INFO] login\n");
+    assert_eq!(rest.get_content(), b"[INFO] ready\n");
+
+    // 健康检查在工作线程上执行并返回内部目标的结果
+    let flaky = Arc::new(FlakySink::default());
+    let sink = TimeoutSink::new(ArcSink(flaky.clone()), Duration::from_secs(5)).unwrap();
+    assert!(sink.health_check().is_ok());
+    flaky.down.store(true, Ordering::Release);
+    assert!(sink.health_check().is_err());
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn test_decorators_forward_batch_compression() {
+    use nanolog_rs::{BatchCompression, FailoverSink};
+    use std::io::Read;
+
+    /// 声明 lz4 压缩并记录收到的压缩帧
+    #[derive(Default)]
+    struct FrameSink {
+        frames: Mutex<Vec<u8>>,
+        plain: Mutex<Vec<u8>>,
+    }
+
+    impl Sink for FrameSink {
+        fn write(&self, data: &[u8]) -> io::Result<()> {
+            self.plain.lock().expect("lock").extend_from_slice(data);
+            Ok(())
+        }
+
+        fn write_batch(&self, data: &[Vec<u8>]) -> io::Result<()> {
+            data.iter().try_for_each(|record| self.write(record))
+        }
+
+        fn batch_compression(&self) -> Option<BatchCompression> {
+            Some(BatchCompression::Lz4)
+        }
+
+        fn write_compressed(&self, frame: &[u8], _records: usize) -> io::Result<()> {
+            self.frames.lock().expect("lock").extend_from_slice(frame);
+            Ok(())
+        }
+
+        fn flush(&self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn shutdown(&self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    type Wrap = fn(Arc<dyn Sink>) -> Arc<dyn Sink>;
+    let wrappers: Vec<(&str, Wrap)> = vec![
+        ("timeout", |sink| {
+            Arc::new(TimeoutSink::new(ArcSink(sink), Duration::from_secs(5)).unwrap())
+        }),
+        ("fallback", |sink| Arc::new(FallbackSink::new(vec![sink]))),
+        ("failover", |sink| {
+            Arc::new(FailoverSink::new(sink, Arc::new(MemorySink::new())))
+        }),
+    ];
+    for (name, wrap) in wrappers {
+        let target = Arc::new(FrameSink::default());
+        assert_eq!(
+            wrap(target.clone()).batch_compression(),
+            Some(BatchCompression::Lz4),
+            "{}",
+            name
+        );
+        // 配置了后备目标的看门狗会再包装一层可切换目标
+        let logger = AsyncLoggerBuilder::new()
+            .formatter(Arc::new(SimpleFormatter::new()))
+            .sink(wrap(target.clone()))
+            .watchdog(
+                Watchdog::new(Duration::from_secs(60)).with_fallback(Arc::new(MemorySink::new())),
+            )
+            .build()
+            .unwrap();
+        for message in ["started", "done"] {
+            let _ = logger.log(Record::new(Level::Info, "app", file!(), line!(), message));
+        }
+        logger.shutdown().unwrap();
+
+        assert!(target.plain.lock().expect("lock").is_empty(), "{}", name);
+        let frames = target.frames.lock().expect("lock").clone();
+        let mut decoded = Vec::new();
+        let mut input = frames.as_slice();
+        while !input.is_empty() {
+            lz4_flex::frame::FrameDecoder::new(&mut input)
+                .read_to_end(&mut decoded)
+                .unwrap();
+        }
+        assert_eq!(decoded, b"[INFO] started\n[INFO] done\n", "{}", name);
+    }
+}
+
+#[test]
+fn test_composite_sink_per_sink_level_and_formatter() {
+    use nanolog_rs::{CompositeSink, JsonFormatter};
+
+    let console = Arc::new(MemorySink::new());
+    let json = Arc::new(MemorySink::new());
+    let errors = Arc::new(MemorySink::new());
+    let mut sink = CompositeSink::new();
+    sink.add_sink(console.clone());
+    sink.add_sink_with(
+        json.clone(),
+        Level::Info,
+        Some(Arc::new(JsonFormatter::new())),
+    );
+    sink.add_sink_with(errors.clone(), Level::Error, None);
+    let logger = AsyncLoggerBuilder::new()
+        .level(Level::Debug)
+        .formatter(Arc::new(SimpleFormatter::new()))
+        .sink(Arc::new(sink))
+        .build()
+        .unwrap();
+
+    for (level, message) in [
+        (Level::Debug, "probe"),
+        (Level::Info, "started"),
+        (Level::Error, "failed"),
+        (Level::Info, "done"),
+    ] {
+        let _ = logger.log(Record::new(level, "app", file!(), line!(), message));
+    }
+    logger.shutdown().unwrap();
+
+    assert_eq!(
+        console.get_content(),
+        b"[DEBUG] probe\n[INFO] started\n[ERROR] failed\n[INFO] done\n"
+    );
+    assert_eq!(errors.get_content(), b"[ERROR] failed\n");
+    let json = String::from_utf8(json.get_content()).unwrap();
+    let messages: Vec<_> = json
+        .lines()
+        .map(|line| line.split("\"message\":\"").nth(1).unwrap_or(""))
+        .collect();
+    assert_eq!(
+        messages,
+        ["started\"}", "failed\"}", "done\"}"],
+        "unexpected JSON output: {}",
+        json
+    );
+}
+
+#[test]
+fn test_failover_sink_switches_and_recovers() {
+    use nanolog_rs::FailoverSink;
+
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let reports_c = reports.clone();
+    let primary = Arc::new(FlakySink::default());
+    let fallback = Arc::new(MemorySink::new());
+    let sink = FailoverSink::new(primary.clone(), fallback.clone())
+        .with_probe_interval(Duration::from_millis(20))
+        .with_diagnostics(Arc::new(move |d: &Diagnostic| {
+            reports_c.lock().expect("lock").push(d.clone())
+        }));
+
+    assert!(sink.write(b"a\n").is_ok());
+    primary.down.store(true, Ordering::Release);
+    assert!(sink.write(b"b\n").is_ok());
+    assert!(sink.is_failed_over());
+    std::thread::sleep(Duration::from_millis(30));
+    // 探测时主目标仍故障，继续写入后备目标且不重复计数
+    assert!(sink.write(b"c\n").is_ok());
+    assert_eq!(sink.failovers(), 1);
+    assert_eq!(fallback.get_content(), b"b\nc\n");
+
+    primary.down.store(false, Ordering::Release);
+    assert!(sink.write(b"d\n").is_ok());
+    assert_eq!(fallback.get_content(), b"b\nc\nd\n");
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(sink.write(b"e\n").is_ok());
+    assert!(!sink.is_failed_over());
+    assert_eq!(sink.recoveries(), 1);
+    assert_eq!(primary.inner.get_content(), b"a\ne\n");
+
+    let reports = reports.lock().expect("lock");
+    assert!(matches!(
+        reports.as_slice(),
+        [
+            Diagnostic::SinkDemoted { from: 0, to: 1, .. },
+            Diagnostic::SinkPromoted { from: 1, to: 0 }
+        ]
+    ));
+}
+
+#[test]
+fn test_sink_error_policy_retries_and_reports() {
+    use nanolog_rs::{SinkErrorPolicy, SinkFailure};
+
+    let failures = Arc::new(Mutex::new(Vec::new()));
+    let failures_c = failures.clone();
+    let sink = Arc::new(FlakySink::default());
+    let logger = AsyncLoggerBuilder::new()
+        .formatter(Arc::new(SimpleFormatter::new()))
+        .sink(sink.clone())
+        .sink_error_policy(SinkErrorPolicy::Retry(2))
+        .on_error(Arc::new(move |failure: &SinkFailure<'_>| {
+            failures_c.lock().expect("lock").push((
+                failure.output,
+                failure.operation,
+                failure.attempts,
+                failure.records,
+            ))
+        }))
+        .diagnostics(Arc::new(|_: &Diagnostic| {}))
+        .build()
+        .unwrap();
+
+    sink.down.store(true, Ordering::Release);
+    let _ = logger.log(Record::new(Level::Info, "app", file!(), line!(), "lost"));
+    logger.flush().unwrap();
+    sink.down.store(false, Ordering::Release);
+    let _ = logger.log(Record::new(Level::Info, "app", file!(), line!(), "kept"));
+    logger.shutdown().unwrap();
+
+    assert_eq!(*failures.lock().expect("lock"), [(0, "write", 3, 1)]);
+    assert_eq!(sink.inner.get_content(), b"[INFO] kept\n");
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn test_tcp_sink_compresses_batches_on_consumer() {
+    use nanolog_rs::BatchCompression;
+    use std::io::Read;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || -> io::Result<Vec<u8>> {
+        let (mut stream, _) = listener.accept()?;
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received)?;
+        Ok(received)
+    });
+
+    let sink = TcpSink::new(addr.to_string()).with_compression(BatchCompression::Lz4);
+    let logger = AsyncLoggerBuilder::new()
+        .formatter(Arc::new(SimpleFormatter::new()))
+        .sink(Arc::new(sink))
+        .build()
+        .unwrap();
+    let mut expected = Vec::new();
+    for i in 0..200 {
+        let message = format!("request {} handled by worker", i);
+        expected.extend_from_slice(format!("[INFO] {}\n", message).as_bytes());
+        let _ = logger.log(Record::new(Level::Info, "app", file!(), line!(), message));
+    }
+    logger.flush().unwrap();
+    let stats = logger.flush_stats().compression;
+    logger.shutdown().unwrap();
+    drop(logger);
+
+    let received = server.join().unwrap().unwrap();
+    // 每个批次一帧，逐帧解码
+    let mut decoded = Vec::new();
+    let mut input = received.as_slice();
+    while !input.is_empty() {
+        lz4_flex::frame::FrameDecoder::new(&mut input)
+            .read_to_end(&mut decoded)
+            .unwrap();
+    }
+    assert_eq!(decoded, expected);
+    assert!(stats.batches >= 1);
+    assert_eq!(stats.input_bytes, expected.len() as u64);
+    assert_eq!(stats.output_bytes, received.len() as u64);
+    assert!(stats.ratio() > 1.0, "{:?}", stats);
+}
+
+#[test]
+fn test_file_sink_rotation_suffix_generator() {
+    use nanolog_rs::naming::Counter;
+    use nanolog_rs::rotation::RotationPolicy;
+
+    let dir = std::env::temp_dir().join(format!("nanolog-suffix-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    // 已占用的名称跳到下一个序号
+    std::fs::write(dir.join("app.log.002"), b"taken\n").unwrap();
+
+    let policy = RotationPolicy::new()
+        .max_size(10)
+        .suffix(Arc::new(Counter::zero_padded(3)));
+    let sink = nanolog_rs::FileSink::new(dir.join("app.log"))
+        .unwrap()
+        .with_rotation(policy);
+    write_until_rotated(&sink, 2).unwrap();
+    sink.shutdown().unwrap();
+
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        ["app.log", "app.log.001", "app.log.002", "app.log.003"]
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("app.log.002")).unwrap(),
+        "taken\n"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,61 @@
+use nanolog_rs::sink::MemorySink;
+use nanolog_rs::{AsyncLoggerBuilder, JsonFormatter, Level, init_global_logger};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 带原因的错误
+#[derive(Debug)]
+struct Wrapped(std::io::Error);
+
+impl std::fmt::Display for Wrapped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("connect failed")
+    }
+}
+
+impl std::error::Error for Wrapped {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[test]
+fn test_macro_fields_in_json_output() {
+    let mem_sink = Arc::new(MemorySink::new());
+
+    let logger = AsyncLoggerBuilder::new()
+        .level(Level::Info)
+        .formatter(Arc::new(JsonFormatter::new()))
+        .sink(mem_sink.clone())
+        .queue_capacity(1024)
+        .flush_interval(Duration::from_millis(20))
+        .build()
+        .expect("build logger");
+
+    let logger = Arc::new(logger);
+    let _ = init_global_logger(logger.clone());
+
+    let user = "alice";
+    nanolog_rs::info!(user = user, latency_us = 12u64, "login {}", 1);
+    let err = std::io::Error::other(Wrapped(std::io::Error::from(
+        std::io::ErrorKind::ConnectionRefused,
+    )));
+    let err: &dyn std::error::Error = &err;
+    nanolog_rs::error!(err = &err, attempt = 3u64, "operation failed");
+    #[cfg(feature = "serde")]
+    {
+        let mut payload = std::collections::BTreeMap::new();
+        payload.insert("qty", 3);
+        nanolog_rs::warn!(payload = serde(&payload), "order");
+    }
+
+    let _ = logger.flush();
+    let s = String::from_utf8(mem_sink.get_content()).expect("utf8");
+    assert!(s.contains(r#""message":"login 1","fields":{"user":"alice","latency_us":12}}"#));
+    assert!(s.contains(
+        r#""message":"operation failed","fields":{"error.message":"connect failed","error.cause[0]":"connection refused","attempt":3}}"#
+    ));
+    #[cfg(feature = "serde")]
+    assert!(s.contains(r#""message":"order","fields":{"payload":{"qty":3}}}"#));
+    let _ = logger.shutdown();
+}
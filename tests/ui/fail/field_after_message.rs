@@ -0,0 +1,5 @@
+use nanolog_rs::warn;
+
+fn main() {
+    warn!("login", user = "alice");
+}
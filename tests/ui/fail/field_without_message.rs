@@ -0,0 +1,6 @@
+use nanolog_rs::info;
+
+fn main() {
+    let user = "alice";
+    info!(user = user);
+}
@@ -0,0 +1,7 @@
+use nanolog_rs::info;
+use std::rc::Rc;
+
+fn main() {
+    let shared = Rc::new(1);
+    info!(lazy: "value {}", shared);
+}
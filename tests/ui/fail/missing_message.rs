@@ -0,0 +1,5 @@
+use nanolog_rs::error;
+
+fn main() {
+    error!();
+}
@@ -0,0 +1,11 @@
+use nanolog_rs::info;
+
+struct Order {
+    id: u64,
+}
+
+fn main() {
+    let order = Order { id: 1 };
+    info!("order {}", order);
+    let _ = order.id;
+}
@@ -0,0 +1,5 @@
+use nanolog_rs::{Level, log};
+
+fn main() {
+    log!(Level::Fatal, "unreachable");
+}
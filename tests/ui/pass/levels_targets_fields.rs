@@ -0,0 +1,14 @@
+use nanolog_rs::{Level, debug, error, info, log, trace, warn};
+
+fn main() {
+    let user = "alice";
+    let latency_us = 12u64;
+    error!("failed: {}", 1);
+    warn!(target: "db", "slow query");
+    info!(user = user, latency_us = latency_us, "login {}", user);
+    debug!(target: "net", peer = "10.0.0.1", "connected");
+    trace!(lazy: "deferred {} {}", user, latency_us);
+    info!(target: "audit", sync: "exported by {user}");
+    log!(Level::Warn, "dynamic level {}", 3);
+    log!(target: "custom", Level::Info, "dynamic target");
+}